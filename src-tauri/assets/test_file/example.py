@@ -0,0 +1,103 @@
+"""A small HTTP client used by the sync service."""
+
+import json
+import logging
+import time
+import urllib.error
+import urllib.request
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_TIMEOUT = 10
+MAX_RETRIES = 3
+
+
+class HttpError(Exception):
+    """Raised when the server answers with an unexpected status code."""
+
+    def __init__(self, status, body):
+        super().__init__(f"HTTP {status}: {body[:200]}")
+        self.status = status
+        self.body = body
+
+
+class HttpClient:
+    """Thin wrapper around urllib with JSON helpers, authentication and retries."""
+
+    def __init__(self, base_url, token=None, timeout=DEFAULT_TIMEOUT):
+        self.base_url = base_url.rstrip("/")
+        self.token = token
+        self.timeout = timeout
+        self.session_started = time.time()
+
+    def _headers(self, extra=None):
+        headers = {
+            "Accept": "application/json",
+            "Content-Type": "application/json",
+            "User-Agent": "sync-service/1.0",
+        }
+        if self.token:
+            headers["Authorization"] = f"Bearer {self.token}"
+        if extra:
+            headers.update(extra)
+        return headers
+
+    def _build_request(self, method, path, payload=None, headers=None):
+        url = f"{self.base_url}/{path.lstrip('/')}"
+        data = None
+        if payload is not None:
+            data = json.dumps(payload).encode("utf-8")
+        return urllib.request.Request(
+            url, data=data, method=method, headers=self._headers(headers)
+        )
+
+    def request_with_retry(self, method, path, payload=None, retries=MAX_RETRIES):
+        """Send the request again with exponential backoff when the server is
+        unavailable or the connection drops."""
+        delay = 0.5
+        for attempt in range(1, retries + 1):
+            request = self._build_request(method, path, payload)
+            try:
+                with urllib.request.urlopen(request, timeout=self.timeout) as response:
+                    return json.loads(response.read() or b"null")
+            except urllib.error.HTTPError as error:
+                if error.code < 500 or attempt == retries:
+                    raise HttpError(error.code, error.read().decode("utf-8", "replace"))
+                logger.warning("server error %s, retry %s/%s", error.code, attempt, retries)
+            except urllib.error.URLError as error:
+                if attempt == retries:
+                    raise
+                logger.warning("connection failed: %s, retry %s/%s", error, attempt, retries)
+            time.sleep(delay)
+            delay *= 2
+
+    def get(self, path):
+        return self.request_with_retry("GET", path)
+
+    def post(self, path, payload):
+        return self.request_with_retry("POST", path, payload)
+
+    def delete(self, path):
+        return self.request_with_retry("DELETE", path)
+
+
+def paginate(client, path, page_size=100):
+    """Yield every item of a paginated collection."""
+    page = 1
+    while True:
+        result = client.get(f"{path}?page={page}&size={page_size}")
+        items = result.get("items", [])
+        if not items:
+            return
+        yield from items
+        page += 1
+
+
+def main():
+    client = HttpClient("https://api.example.com", token="secret")
+    for item in paginate(client, "/files"):
+        print(item["name"])
+
+
+if __name__ == "__main__":
+    main()
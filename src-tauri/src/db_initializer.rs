@@ -1,5 +1,6 @@
 use crate::global::DB_VERSION;
 use crate::utils::app_util::get_db_path;
+use crate::utils::secret_util;
 use anyhow::Result;
 use log::{error, info};
 use rusqlite::Connection;
@@ -39,6 +40,30 @@ pub fn init() -> Result<()> {
                 exec_ddl_v1()?;
                 init_data_v1()?;
             }
+            2 => {
+                exec_ddl_v2()?;
+            }
+            3 => {
+                exec_ddl_v3()?;
+            }
+            4 => {
+                exec_data_v4()?;
+            }
+            5 => {
+                exec_ddl_v5()?;
+            }
+            6 => {
+                exec_ddl_v6()?;
+            }
+            7 => {
+                exec_ddl_v7()?;
+            }
+            8 => {
+                exec_ddl_v8()?;
+            }
+            9 => {
+                exec_ddl_v9()?;
+            }
             _ => {}
         }
     }
@@ -261,6 +286,256 @@ fn exec_ddl_v1() -> Result<()> {
     Ok(())
 }
 
+/// DB_VERSION = 2
+fn exec_ddl_v2() -> Result<()> {
+    info!("exec_ddl_v2");
+    let conn: Connection = Connection::open(get_db_path())?;
+    // Chunk locators, vec0 virtual table can not be altered so they are kept in a separate table
+    conn.execute_batch(
+        r#"
+        create table if not exists file_content_chunk(
+            id integer primary key autoincrement,
+            embedding_id integer not null default 0 unique,  -- foreign key to file_content_embedding.id
+            file_id integer not null default 0,              -- foreign key to file_info.id
+            chunk_index integer not null default 0,
+            locator text not null default '{}',              -- json format, e.g., {"language":"rust","symbol":"retry","start_line":10,"end_line":42}
+            create_time text not null default '',
+            update_time text not null default ''
+        );
+        CREATE INDEX IF NOT EXISTS idx_file_content_chunk_file_id ON file_content_chunk(file_id);
+        create trigger if not exists file_content_chunk_create_time
+        after insert on file_content_chunk
+        for each row
+        begin
+            update file_content_chunk
+            set create_time = datetime('now', 'localtime'),
+                update_time = datetime('now', 'localtime')
+            where id = new.id;
+        end;
+        create trigger if not exists file_content_chunk_update_time
+        after update on file_content_chunk
+        for each row
+        begin
+            update file_content_chunk set update_time = datetime('now', 'localtime')
+            where id = new.id;
+        end;
+        "#,
+    )?;
+    update_db_version(&conn, 2)?;
+    Ok(())
+}
+
+/// DB_VERSION = 3
+fn exec_ddl_v3() -> Result<()> {
+    info!("exec_ddl_v3");
+    let conn: Connection = Connection::open(get_db_path())?;
+    // Custom platforms(is_openai_api_compatible = 1) can be disabled without removing them
+    conn.execute_batch(
+        r#"
+        alter table model_platform add column is_enable integer default 1 not null;
+        "#,
+    )?;
+    update_db_version(&conn, 3)?;
+    Ok(())
+}
+
+/// DB_VERSION = 4
+fn exec_data_v4() -> Result<()> {
+    info!("exec_data_v4");
+    let mut conn: Connection = Connection::open(get_db_path())?;
+    // Encrypt the API keys saved in plaintext by the earlier versions
+    let tx = conn.transaction()?;
+    let plain_keys = {
+        let mut stmt =
+            tx.prepare("select name, api_key from model_platform where api_key != ''")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>("name")?,
+                row.get::<_, String>("api_key")?,
+            ))
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    for (name, api_key) in plain_keys {
+        tx.execute(
+            "update model_platform set api_key = ?1 where name = ?2",
+            [secret_util::encrypt_secret(&api_key)?, name],
+        )?;
+    }
+    update_db_version(&tx, 4)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// DB_VERSION = 5
+fn exec_ddl_v5() -> Result<()> {
+    info!("exec_ddl_v5");
+    let mut conn: Connection = Connection::open(get_db_path())?;
+    // Limits of the remote calls, a local Ollama server handles one request at a time
+    let tx = conn.transaction()?;
+    tx.execute_batch(
+        r#"
+        alter table model_platform add column max_concurrency integer default 2 not null;
+        alter table model_platform add column requests_per_minute integer default 0 not null; -- 0: unlimited
+        update model_platform set max_concurrency = 1 where name = 'ollama';
+        "#,
+    )?;
+    update_db_version(&tx, 5)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// DB_VERSION = 6
+fn exec_ddl_v6() -> Result<()> {
+    info!("exec_ddl_v6");
+    let conn: Connection = Connection::open(get_db_path())?;
+    // Usage of every remote model call, the cost is calculated by the pricing in ai_model.properties at the time of the call
+    conn.execute_batch(
+        r#"
+        create table if not exists model_usage(
+            id integer primary key autoincrement,
+            task_id integer not null default 0,              -- foreign key to indexing_task.id, 0: not called by an indexing task, e.g., chat
+            platform text not null default '',
+            model text not null default '',
+            operation text not null default '',              -- image, audio, chat
+            prompt_tokens integer not null default 0,
+            completion_tokens integer not null default 0,
+            audio_seconds real not null default 0,
+            image_count integer not null default 0,
+            cost real not null default 0,
+            create_time text not null default ''
+        );
+        CREATE INDEX IF NOT EXISTS idx_model_usage_task_id ON model_usage(task_id);
+        CREATE INDEX IF NOT EXISTS idx_model_usage_create_time ON model_usage(create_time);
+        create trigger if not exists model_usage_create_time
+        after insert on model_usage
+        for each row
+        begin
+            update model_usage set create_time = datetime('now', 'localtime')
+            where id = new.id;
+        end;
+        "#,
+    )?;
+    update_db_version(&conn, 6)?;
+    Ok(())
+}
+
+/// DB_VERSION = 7
+fn exec_ddl_v7() -> Result<()> {
+    info!("exec_ddl_v7");
+    let conn: Connection = Connection::open(get_db_path())?;
+    // Results of the remote analysis of images and audios, a copied or re-indexed file with the same content is not analyzed again
+    conn.execute_batch(
+        r#"
+        create table if not exists analysis_cache(
+            id integer primary key autoincrement,
+            md5 text not null,                               -- md5 of the file content
+            platform text not null default '',
+            model text not null default '',
+            prompt_version text not null default '',         -- changed with the prompt(or its language), older results are not used
+            content text not null default '',
+            create_time text not null default '',
+            hit_time text not null default ''                -- last time the result was used, for eviction
+        );
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_analysis_cache_key ON analysis_cache(md5, platform, model, prompt_version);
+        CREATE INDEX IF NOT EXISTS idx_analysis_cache_hit_time ON analysis_cache(hit_time);
+        "#,
+    )?;
+    update_db_version(&conn, 7)?;
+    Ok(())
+}
+
+/// DB_VERSION = 8
+fn exec_ddl_v8() -> Result<()> {
+    info!("exec_ddl_v8");
+    let conn: Connection = Connection::open(get_db_path())?;
+    // Tags managed by the user, linked by file id so a renamed or moved file keeps its tags
+    conn.execute_batch(
+        r#"
+        create table if not exists tag(
+            id integer primary key autoincrement,
+            name varchar(50) collate nocase unique not null,  -- no whitespace, to be written as `tag:name` in a query
+            create_time text not null default ''
+        );
+        create table if not exists file_tag(
+            file_id integer not null,                         -- foreign key to file_info.id
+            tag_id integer not null,                          -- foreign key to tag.id
+            create_time text not null default '',
+            primary key(file_id, tag_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_file_tag_tag_id ON file_tag(tag_id);
+        create trigger if not exists tag_create_time
+        after insert on tag
+        for each row
+        begin
+            update tag set create_time = datetime('now', 'localtime')
+            where id = new.id;
+        end;
+        create trigger if not exists file_info_delete_tags
+        after delete on file_info
+        for each row
+        begin
+            delete from file_tag where file_id = old.id;
+        end;
+        create trigger if not exists tag_delete_links
+        after delete on tag
+        for each row
+        begin
+            delete from file_tag where tag_id = old.id;
+        end;
+        "#,
+    )?;
+    update_db_version(&conn, 8)?;
+    Ok(())
+}
+
+/// DB_VERSION = 9
+fn exec_ddl_v9() -> Result<()> {
+    info!("exec_ddl_v9");
+    let conn: Connection = Connection::open(get_db_path())?;
+    // Searches of the user, the saved ones marked as smart folder have their result count refreshed by the watcher
+    conn.execute_batch(
+        r#"
+        create table if not exists search_history(
+            id integer primary key autoincrement,
+            query text not null,                              -- as typed, with its inline filters
+            mode varchar(20) not null default 'intent',       -- intent, path, semantic
+            result_count integer not null default 0,
+            create_time text not null default ''
+        );
+        CREATE INDEX IF NOT EXISTS idx_search_history_create_time ON search_history(create_time);
+        create table if not exists saved_search(
+            id integer primary key autoincrement,
+            name varchar(50) collate nocase unique not null,
+            query text not null default '',
+            filters text not null default '',                 -- inline filters added to the query, e.g. `type:image tag:trip`
+            mode varchar(20) not null default 'intent',
+            is_smart_folder integer not null default 0,
+            result_count integer not null default 0,          -- of the last run or refresh
+            refresh_time text not null default '',
+            create_time text not null default ''
+        );
+        create trigger if not exists saved_search_create_time
+        after insert on saved_search
+        for each row
+        begin
+            update saved_search set create_time = datetime('now', 'localtime')
+            where id = new.id;
+        end;
+        "#,
+    )?;
+    update_db_version(&conn, 9)?;
+    Ok(())
+}
+
+fn update_db_version(conn: &Connection, version: i32) -> Result<()> {
+    conn.execute(
+        "update config set value = ?1 where name = 'db_version'",
+        [version.to_string()],
+    )?;
+    Ok(())
+}
+
 /// DB_VERSION = 1
 pub fn init_data_v1() -> Result<()> {
     println!("init_data_v1");
@@ -1,8 +1,22 @@
+pub mod archive;
+pub mod code;
+pub mod csv;
+pub mod doc;
 pub mod docx;
+pub mod email;
+pub mod epub;
 pub mod excel;
+pub mod html;
+pub mod markdown;
+pub mod mbox;
+pub mod notebook;
 pub mod odp;
 pub mod odt;
 pub mod open_doc;
 pub mod pdf;
 pub mod plain_text;
+pub mod ppt;
 pub mod pptx;
+pub mod rtf;
+pub mod structured;
+pub mod subtitle;
@@ -0,0 +1,515 @@
+use crate::global::{
+    ARCHIVE_EXTS, ARCHIVE_MAX_DEPTH, ARCHIVE_MAX_MEMBER_SIZE, ARCHIVE_MAX_MEMBERS,
+    ARCHIVE_MAX_RATIO, ARCHIVE_MAX_TOTAL_SIZE, EXT_TO_DOC_LOADER,
+};
+use crate::structs::file_metadata::FileMetadata;
+use crate::structs::virtual_member::VirtualMember;
+use crate::traits::document_loader::DocumentLoader;
+use crate::utils::{app_util, datetime_util, path_util};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use zip::ZipArchive;
+
+const TAR_MAGIC_OFFSET: usize = 257;
+
+/// Loader for zip and tar(.tar, .tar.gz, .tgz) archives, a single gzip file is an archive with one member.
+///
+/// Members are extracted to temp files and loaded by the loader of their own extension,
+/// nested archives are expanded up to `ARCHIVE_MAX_DEPTH` levels.
+#[derive(Debug)]
+pub struct ArchiveLoader {
+    exts: Vec<String>,
+}
+
+impl Default for ArchiveLoader {
+    fn default() -> Self {
+        Self {
+            exts: ARCHIVE_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for ArchiveLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    /// Names of the members
+    fn load_file_max(&self, file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut txt = String::new();
+        walk_entries(file, "", &mut |entry, _| {
+            txt.push_str(&entry.name);
+            txt.push('\n');
+            Ok(max_load_chars == 0 || txt.chars().count() <= max_load_chars)
+        })?;
+        Ok(txt)
+    }
+
+    fn load_members(
+        &self,
+        path: &Path,
+        max_load_chars: usize,
+    ) -> io::Result<Option<Vec<VirtualMember>>> {
+        let modified = std::fs::metadata(path)?
+            .modified()
+            .map(datetime_util::systemtime_to_datetime)
+            .unwrap_or_default();
+        let mut expander = Expander {
+            max_load_chars,
+            modified,
+            members: Vec::new(),
+            total_size: 0,
+        };
+        expander.expand(path, &gzip_name(path), "", 1)?;
+        Ok(Some(expander.members))
+    }
+}
+
+struct ArchiveEntry {
+    name: String, // Path inside the archive, separated by '/'
+    size: u64,
+    compressed_size: Option<u64>,
+    modified: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    Gzip,
+}
+
+impl ArchiveKind {
+    /// Detect by the magic bytes, the extension of `.tar.gz` and `.log.gz` are the same
+    fn detect(mut file: &File) -> io::Result<Self> {
+        let mut head = Vec::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.take(TAR_MAGIC_OFFSET as u64 + 8)
+            .read_to_end(&mut head)?;
+        file.seek(SeekFrom::Start(0))?;
+        if head.starts_with(b"PK") {
+            return Ok(ArchiveKind::Zip);
+        }
+        if head.starts_with(&[0x1f, 0x8b]) {
+            let mut inner = Vec::new();
+            GzDecoder::new(file)
+                .take(TAR_MAGIC_OFFSET as u64 + 8)
+                .read_to_end(&mut inner)?;
+            file.seek(SeekFrom::Start(0))?;
+            return Ok(match is_tar(&inner) {
+                true => ArchiveKind::TarGz,
+                false => ArchiveKind::Gzip,
+            });
+        }
+        if is_tar(&head) {
+            return Ok(ArchiveKind::Tar);
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Not a zip or tar archive",
+        ))
+    }
+}
+
+fn is_tar(head: &[u8]) -> bool {
+    head.get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + 5) == Some(b"ustar")
+}
+
+/// Visit the files of an archive, the visitor returns false to stop.
+/// `gzip_name` is the member name of a single gzip file if the name is not stored in its header.
+fn walk_entries(
+    file: &File,
+    gzip_name: &str,
+    visit: &mut dyn FnMut(&ArchiveEntry, &mut dyn Read) -> io::Result<bool>,
+) -> io::Result<()> {
+    match ArchiveKind::detect(file)? {
+        ArchiveKind::Zip => {
+            let mut archive = ZipArchive::new(file)?;
+            for i in 0..archive.len() {
+                let mut zip_file = match archive.by_index(i) {
+                    Ok(zip_file) => zip_file,
+                    Err(error) => {
+                        // e.g. encrypted members
+                        println!("Skip zip member {}: {}", i, error);
+                        continue;
+                    }
+                };
+                if !zip_file.is_file() {
+                    continue;
+                }
+                // Skip names escaping the archive, e.g. "../../x"
+                let Some(name) = zip_file.enclosed_name() else {
+                    continue;
+                };
+                let entry = ArchiveEntry {
+                    name: name.to_string_lossy().replace('\\', "/"),
+                    size: zip_file.size(),
+                    compressed_size: Some(zip_file.compressed_size()),
+                    modified: zip_file.last_modified().and_then(|dt| {
+                        NaiveDate::from_ymd_opt(
+                            dt.year() as i32,
+                            dt.month() as u32,
+                            dt.day() as u32,
+                        )
+                        .and_then(|date| {
+                            date.and_hms_opt(
+                                dt.hour() as u32,
+                                dt.minute() as u32,
+                                dt.second() as u32,
+                            )
+                        })
+                        .and_then(|naive| Local.from_local_datetime(&naive).single())
+                    }),
+                };
+                if !visit(&entry, &mut zip_file)? {
+                    break;
+                }
+            }
+        }
+        ArchiveKind::Tar => walk_tar(file, visit)?,
+        ArchiveKind::TarGz => walk_tar(GzDecoder::new(file), visit)?,
+        ArchiveKind::Gzip => {
+            let mut decoder = GzDecoder::new(file);
+            let name = decoder
+                .header()
+                .and_then(|header| header.filename())
+                .map(|name| String::from_utf8_lossy(name).to_string())
+                .unwrap_or(gzip_name.to_string());
+            if name.is_empty() {
+                return Ok(());
+            }
+            let entry = ArchiveEntry {
+                name,
+                size: 0, // Unknown until decompressed
+                compressed_size: None,
+                modified: None,
+            };
+            visit(&entry, &mut decoder)?;
+        }
+    }
+    Ok(())
+}
+
+fn walk_tar<R: Read>(
+    reader: R,
+    visit: &mut dyn FnMut(&ArchiveEntry, &mut dyn Read) -> io::Result<bool>,
+) -> io::Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for tar_entry in archive.entries()? {
+        let mut tar_entry = tar_entry?;
+        if !tar_entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = tar_entry.path()?.to_string_lossy().to_string();
+        let entry = ArchiveEntry {
+            name: name.trim_start_matches("./").to_string(),
+            size: tar_entry.size(),
+            compressed_size: None,
+            modified: tar_entry
+                .header()
+                .mtime()
+                .ok()
+                .and_then(|mtime| Local.timestamp_opt(mtime as i64, 0).single()),
+        };
+        if !visit(&entry, &mut tar_entry)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The bytes of a member, the id is the one of its virtual path so a member of a nested archive can be read too
+pub fn read_member(path: &Path, member_id: &str) -> io::Result<Vec<u8>> {
+    let file = File::open(path)?;
+    read_nested_member(&file, &gzip_name(path), member_id, 0)
+}
+
+fn read_nested_member(
+    file: &File,
+    gzip_name: &str,
+    member_id: &str,
+    depth: usize,
+) -> io::Result<Vec<u8>> {
+    let mut data = None;
+    walk_entries(file, gzip_name, &mut |entry, reader| {
+        let nested_prefix = path_util::to_virtual_path(&entry.name, "");
+        let nested_id = member_id.strip_prefix(&nested_prefix);
+        if entry.name != member_id && (nested_id.is_none() || depth >= ARCHIVE_MAX_DEPTH) {
+            return Ok(true);
+        }
+        // The size in header may be fake, limit the bytes actually read
+        let mut temp_file = tempfile::tempfile_in(temp_dir())?;
+        let size = io::copy(
+            &mut reader.take(ARCHIVE_MAX_MEMBER_SIZE + 1),
+            &mut temp_file,
+        )?;
+        if size > ARCHIVE_MAX_MEMBER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Archive member is too big: {}", entry.name),
+            ));
+        }
+        data = Some(match nested_id {
+            Some(nested_id) if entry.name != member_id => read_nested_member(
+                &temp_file,
+                &gzip_name(Path::new(&entry.name)),
+                nested_id,
+                depth + 1,
+            )?,
+            _ => {
+                let mut bytes = Vec::with_capacity(size as usize);
+                temp_file.seek(SeekFrom::Start(0))?;
+                temp_file.read_to_end(&mut bytes)?;
+                bytes
+            }
+        });
+        Ok(false)
+    })?;
+    data.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Archive member not found: {}", member_id),
+        )
+    })
+}
+
+/// The name of the only member of a plain gzip file: `notes.txt` of `notes.txt.gz`
+fn gzip_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Expands an archive and its nested archives into members
+struct Expander {
+    max_load_chars: usize,
+    modified: DateTime<Local>, // Modified time of the container, used if a member has none
+    members: Vec<VirtualMember>,
+    total_size: u64,
+}
+
+impl Expander {
+    /// The `gzip_name` is the member name of a plain gzip file, without its `.gz`
+    fn expand(
+        &mut self,
+        path: &Path,
+        gzip_name: &str,
+        prefix: &str,
+        depth: usize,
+    ) -> io::Result<()> {
+        let file = File::open(path)?;
+        walk_entries(&file, gzip_name, &mut |entry, reader| {
+            self.visit(entry, reader, prefix, depth)
+        })
+    }
+
+    fn visit(
+        &mut self,
+        entry: &ArchiveEntry,
+        reader: &mut dyn Read,
+        prefix: &str,
+        depth: usize,
+    ) -> io::Result<bool> {
+        if self.members.len() >= ARCHIVE_MAX_MEMBERS {
+            println!("Too many archive members, the rest are skipped");
+            return Ok(false);
+        }
+        let ext = Path::new(&entry.name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let is_archive = ARCHIVE_EXTS.contains(&ext.as_str());
+        let loader = get_loader(&ext);
+        if (is_archive && depth >= ARCHIVE_MAX_DEPTH) || (!is_archive && loader.is_none()) {
+            return Ok(true);
+        }
+        if entry.size > ARCHIVE_MAX_MEMBER_SIZE {
+            println!("Skip big archive member: {}", entry.name);
+            return Ok(true);
+        }
+        let ratio_exceeded = entry
+            .compressed_size
+            .is_some_and(|compressed_size| entry.size / compressed_size.max(1) > ARCHIVE_MAX_RATIO);
+        if ratio_exceeded {
+            println!("Skip suspicious compression ratio: {}", entry.name);
+            return Ok(true);
+        }
+
+        // The size in header may be fake, limit the bytes actually read
+        let mut temp_file = tempfile::Builder::new()
+            .suffix(&format!(".{}", ext))
+            .tempfile_in(temp_dir())?;
+        let size = io::copy(
+            &mut reader.take(ARCHIVE_MAX_MEMBER_SIZE + 1),
+            temp_file.as_file_mut(),
+        )?;
+        if size > ARCHIVE_MAX_MEMBER_SIZE {
+            println!("Skip big archive member: {}", entry.name);
+            return Ok(true);
+        }
+        self.total_size += size;
+        if self.total_size > ARCHIVE_MAX_TOTAL_SIZE {
+            println!("Archive is too big after decompressed, the rest are skipped");
+            return Ok(false);
+        }
+
+        let id = format!("{}{}", prefix, entry.name);
+        if is_archive {
+            let nested_prefix = path_util::to_virtual_path(&id, "");
+            // Named after the member rather than the random temp file, to keep the member ids stable
+            let gzip_name = gzip_name(Path::new(&entry.name));
+            if let Err(error) = self.expand(temp_file.path(), &gzip_name, &nested_prefix, depth + 1)
+            {
+                println!("Expand nested archive error: {}, member: {}", error, id);
+            }
+            return Ok(true);
+        }
+        let Some(loader) = loader else {
+            return Ok(true);
+        };
+        let content = match loader.load_max(temp_file.path(), self.max_load_chars) {
+            Ok(content) => content,
+            Err(error) => {
+                println!("Load archive member error: {}, member: {}", error, id);
+                return Ok(true);
+            }
+        };
+        let mut metadata = FileMetadata::default();
+        metadata.name = entry.name.rsplit('/').next().unwrap_or("").to_string();
+        metadata.extension = ext;
+        metadata.size = size as i64;
+        metadata.created = entry.modified.unwrap_or(self.modified);
+        metadata.modified = entry.modified.unwrap_or(self.modified);
+        self.members.push(VirtualMember {
+            id,
+            ext: metadata.extension.clone(),
+            content,
+            metadata,
+        });
+        Ok(true)
+    }
+}
+
+fn get_loader(ext: &str) -> Option<Arc<dyn DocumentLoader + Send + Sync>> {
+    // Members are loaded on a blocking thread, waits if the loaders are being re-registered
+    EXT_TO_DOC_LOADER.blocking_read().get(ext).cloned()
+}
+
+fn temp_dir() -> PathBuf {
+    let tmp_path = app_util::get_assets_tmp_path();
+    if tmp_path.is_empty() {
+        std::env::temp_dir()
+    } else {
+        PathBuf::from(tmp_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::global::MAX_DOCUMENT_LOAD_CHARS;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn read_members() {
+        let path = Path::new("assets/test_file/example.zip");
+        let names = ArchiveLoader::default().load(path).unwrap();
+        println!("names: {}", names);
+        assert!(names.contains("docs/spec.txt"));
+
+        let members = ArchiveLoader::default()
+            .load_members(path, MAX_DOCUMENT_LOAD_CHARS)
+            .unwrap()
+            .unwrap();
+        let ids: Vec<&str> = members.iter().map(|m| m.id.as_str()).collect();
+        println!("ids: {:?}", ids);
+        // Images have no document loader
+        assert!(!ids.contains(&"images/logo.png"));
+        let spec = members.iter().find(|m| m.id == "docs/spec.txt").unwrap();
+        assert_eq!(spec.metadata.name, "spec.txt");
+        assert_eq!(spec.metadata.extension, "txt");
+        assert!(spec.content.contains("Mango Desk archive spec"));
+        // Nested tar.gz
+        let nested = members
+            .iter()
+            .find(|m| m.id == "bundle.tar.gz!/notes/readme.md")
+            .unwrap();
+        assert!(nested.content.contains("芒果"));
+    }
+
+    #[test]
+    fn read_member_bytes() {
+        let path = Path::new("assets/test_file/example.zip");
+        let spec = read_member(path, "docs/spec.txt").unwrap();
+        assert!(String::from_utf8_lossy(&spec).contains("Mango Desk archive spec"));
+        let nested = read_member(path, "bundle.tar.gz!/notes/readme.md").unwrap();
+        assert!(String::from_utf8_lossy(&nested).contains("芒果"));
+        let error = read_member(path, "docs/missing.txt").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn skip_zip_bomb() {
+        let mut temp_file = tempfile::Builder::new().suffix(".zip").tempfile().unwrap();
+        {
+            let mut writer = zip::ZipWriter::new(temp_file.as_file_mut());
+            writer
+                .start_file("zeros.txt", SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(&vec![b'0'; 8 * 1024 * 1024]).unwrap();
+            writer
+                .start_file("small.txt", SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(b"small file").unwrap();
+            writer.finish().unwrap();
+        }
+        let members = ArchiveLoader::default()
+            .load_members(temp_file.path(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].id, "small.txt");
+    }
+
+    #[test]
+    fn nested_gzip_member_id() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(b"service started").unwrap();
+        let gzip = gzip.finish().unwrap();
+        let mut temp_file = tempfile::Builder::new().suffix(".zip").tempfile().unwrap();
+        {
+            let mut writer = zip::ZipWriter::new(temp_file.as_file_mut());
+            writer
+                .start_file("logs/app.log.gz", SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(&gzip).unwrap();
+            writer.finish().unwrap();
+        }
+        // Named after the member, not after the temp file it is extracted to
+        for _ in 0..2 {
+            let members = ArchiveLoader::default()
+                .load_members(temp_file.path(), 0)
+                .unwrap()
+                .unwrap();
+            assert_eq!(members.len(), 1);
+            assert_eq!(members[0].id, "logs/app.log.gz!/app.log");
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::enums::CodeLanguage;
+use crate::global::{CODE_CHUNK_SIZE, CODE_EXTS};
+use crate::structs::document_chunk::DocumentChunk;
+use crate::traits::document_loader::DocumentLoader;
+use crate::utils::{code_util, text_util};
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use tokenizers::Tokenizer;
+
+/// Loader for source code files, the content is split at function/class boundaries
+#[derive(Debug)]
+pub struct CodeLoader {
+    exts: Vec<String>,
+}
+
+impl Default for CodeLoader {
+    fn default() -> Self {
+        Self {
+            exts: CODE_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for CodeLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        return self.load_file_max(&file, max_load_chars);
+    }
+
+    fn load_file_max(&self, file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut bytes = Vec::new();
+        if max_load_chars > 0 {
+            file.take((max_load_chars * 4) as u64)
+                .read_to_end(&mut bytes)?;
+        } else {
+            let mut file = file;
+            file.read_to_end(&mut bytes)?;
+        }
+        let contents = String::from_utf8_lossy(&bytes);
+        if max_load_chars < 1 || contents.chars().count() <= max_load_chars {
+            return Ok(contents.to_string());
+        }
+        // Cut at a line end so that the last line is complete
+        let contents: String = contents.chars().take(max_load_chars).collect();
+        let end = contents.rfind('\n').unwrap_or(contents.len());
+        return Ok(contents[..end].to_string());
+    }
+
+    fn split(
+        &self,
+        path: &Path,
+        content: &str,
+        tokenizer: &Tokenizer,
+    ) -> Result<Vec<DocumentChunk>, Box<dyn Error>> {
+        let language = path
+            .extension()
+            .and_then(|ext| CodeLanguage::from_ext(&ext.to_string_lossy().to_lowercase()));
+        match language {
+            Some(language) => Ok(code_util::split_code(
+                content,
+                language,
+                tokenizer,
+                CODE_CHUNK_SIZE,
+            )),
+            // Extensions added by user
+            None => {
+                let chunks = text_util::split_text(content, tokenizer)?;
+                Ok(chunks.into_iter().map(DocumentChunk::from_text).collect())
+            }
+        }
+    }
+
+    fn keep_layout(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split() {
+        let path = Path::new("assets/test_file/example.py");
+        let tokenizer =
+            Tokenizer::from_file("assets/model/all-minilm-l6-v2-tokenizer.json").unwrap();
+        let loader = CodeLoader::default();
+        let content = loader.load(path).unwrap();
+        let chunks = loader.split(path, &content, &tokenizer).unwrap();
+        for chunk in &chunks {
+            println!("{:?}", chunk.locator);
+        }
+        let retry = chunks
+            .iter()
+            .find(|chunk| chunk.text.contains("def request_with_retry"))
+            .unwrap();
+        assert_eq!(retry.locator.language.as_deref(), Some("python"));
+        assert!(
+            retry
+                .locator
+                .symbol
+                .as_deref()
+                .unwrap()
+                .contains("HttpClient.request_with_retry")
+        );
+        let start = retry.locator.start_line.unwrap();
+        let end = retry.locator.end_line.unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[start - 1..end].join("\n"), retry.text);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::global::{CSV_EXTS, TABLE_CHUNK_SIZE};
+use crate::structs::document_chunk::DocumentChunk;
+use crate::structs::table::Table;
+use crate::traits::document_loader::DocumentLoader;
+use crate::utils::table_util;
+use csv::ReaderBuilder;
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use tokenizers::Tokenizer;
+
+/// Loader for comma/tab separated values(.csv/.tsv).
+///
+/// The delimiter is detected from the first line, rows are rendered as `header: value` pairs.
+#[derive(Debug)]
+pub struct CsvLoader {
+    exts: Vec<String>,
+}
+
+impl Default for CsvLoader {
+    fn default() -> Self {
+        Self {
+            exts: CSV_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl CsvLoader {
+    fn read_table(&self, file: &File) -> io::Result<Option<Table>> {
+        let mut reader = BufReader::new(file);
+        // Skip UTF-8 BOM
+        if reader.fill_buf()?.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            reader.consume(3);
+        }
+        let delimiter = detect_delimiter(reader.fill_buf()?);
+        let mut csv_reader = ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(delimiter)
+            .from_reader(reader);
+        // Rows are read lazily, reading stops once the table reaches the max rows
+        let rows = csv_reader
+            .byte_records()
+            .enumerate()
+            // A read error would be returned again and again
+            .take_while(|(_, record)| !record.as_ref().is_err_and(|e| e.is_io_error()))
+            .filter_map(|(index, record)| match record {
+                Ok(record) => {
+                    let cells = record
+                        .iter()
+                        .map(|field| String::from_utf8_lossy(field).to_string())
+                        .collect();
+                    Some((index + 1, cells))
+                }
+                // A malformed record is skipped, the rows after it are kept
+                Err(e) => {
+                    println!("Skip malformed csv record {}: {}", index + 1, e);
+                    None
+                }
+            });
+        Ok(table_util::build_table(None, rows))
+    }
+}
+
+impl DocumentLoader for CsvLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, file: &File, max_load_chars: usize) -> io::Result<String> {
+        let tables: Vec<Table> = self.read_table(file)?.into_iter().collect();
+        Ok(table_util::render_tables(&tables, max_load_chars))
+    }
+
+    fn split(
+        &self,
+        _path: &Path,
+        content: &str,
+        tokenizer: &Tokenizer,
+    ) -> Result<Vec<DocumentChunk>, Box<dyn Error>> {
+        Ok(table_util::split_rendered_tables(
+            content,
+            tokenizer,
+            TABLE_CHUNK_SIZE,
+        ))
+    }
+}
+
+/// The most frequent one of `,` `;` `\t` `|` in the first line, `,` by default
+fn detect_delimiter(head: &[u8]) -> u8 {
+    let first_line = head.split(|b| *b == b'\n').next().unwrap_or_default();
+    let mut best = (b',', 0);
+    for delimiter in [b',', b';', b'\t', b'|'] {
+        let count = first_line.iter().filter(|b| **b == delimiter).count();
+        if count > best.1 {
+            best = (delimiter, count);
+        }
+    }
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let data = CsvLoader::default()
+            .load(Path::new("assets/test_file/example.csv"))
+            .unwrap();
+        println!("len: {}, data: {}", data.len(), data);
+        assert!(data.starts_with("Columns: id | name | city | joined"));
+        assert!(
+            data.contains("Row 3: id: 2; name: 芒果; city: Shanghai, China; joined: 2024-03-15")
+        );
+        // Empty lines are skipped
+        assert!(data.contains("Row 4: id: 3; name: Bob; joined: 2024-07-01"));
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(detect_delimiter(b"a;b;c\n1;2,5;3"), b';');
+        assert_eq!(detect_delimiter(b"a\tb\tc"), b'\t');
+        assert_eq!(detect_delimiter(b"single"), b',');
+    }
+}
@@ -0,0 +1,242 @@
+use crate::global::DOC_EXTS;
+use crate::traits::document_loader::DocumentLoader;
+use cfb::CompoundFile;
+use encoding_rs::WINDOWS_1252;
+use std::fs::File;
+use std::io;
+use std::io::{Error, ErrorKind, Read, Seek};
+use std::path::Path;
+
+const WORD_IDENT: u16 = 0xA5EC;
+// Offset of fcClx/lcbClx in FibRgFcLcb97(in u32 units)
+const FC_CLX_INDEX: usize = 66;
+// Offset of ccpText in FibRgLw97(in u32 units)
+const CCP_TEXT_INDEX: usize = 3;
+
+/// Loader for Word 97-2003 binary documents(.doc).
+///
+/// The text is located through the piece table(Clx) stored in the table stream,
+/// only the main document text is extracted, headers/footnotes/comments are skipped.
+#[derive(Debug)]
+pub struct DocLoader {
+    exts: Vec<String>,
+}
+
+impl Default for DocLoader {
+    fn default() -> Self {
+        Self {
+            exts: DOC_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for DocLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut comp = CompoundFile::open(file)?;
+        let word_doc = read_stream(&mut comp, "/WordDocument")?;
+
+        let fib = Fib::parse(&word_doc)?;
+        if fib.encrypted {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Encrypted doc file is not supported",
+            ));
+        }
+        let table_name = if fib.use_1table { "/1Table" } else { "/0Table" };
+        let table = read_stream(&mut comp, table_name)?;
+        let clx = table
+            .get(fib.fc_clx..fib.fc_clx + fib.lcb_clx)
+            .ok_or_else(|| invalid_data("Clx is out of table stream"))?;
+
+        let text = read_pieces(&word_doc, clx, fib.ccp_text, max_load_chars)?;
+        Ok(clean_text(&text))
+    }
+}
+
+struct Fib {
+    encrypted: bool,
+    use_1table: bool,
+    ccp_text: usize,
+    fc_clx: usize,
+    lcb_clx: usize,
+}
+
+impl Fib {
+    fn parse(data: &[u8]) -> io::Result<Self> {
+        if read_u16(data, 0)? != WORD_IDENT {
+            return Err(invalid_data("Not a Word binary document"));
+        }
+        let flags = read_u16(data, 0x0A)?;
+
+        // FibBase(32 bytes) -> csw -> fibRgW -> cslw -> fibRgLw -> cbRgFcLcb -> fibRgFcLcbBlob
+        let csw = read_u16(data, 32)? as usize;
+        let rg_lw_start = 32 + 2 + csw * 2 + 2;
+        let cslw = read_u16(data, rg_lw_start - 2)? as usize;
+        let rg_fc_lcb_start = rg_lw_start + cslw * 4 + 2;
+
+        let ccp_text = read_u32(data, rg_lw_start + CCP_TEXT_INDEX * 4)? as usize;
+        let fc_clx = read_u32(data, rg_fc_lcb_start + FC_CLX_INDEX * 4)? as usize;
+        let lcb_clx = read_u32(data, rg_fc_lcb_start + (FC_CLX_INDEX + 1) * 4)? as usize;
+        Ok(Self {
+            encrypted: flags & 0x0100 != 0,
+            use_1table: flags & 0x0200 != 0,
+            ccp_text,
+            fc_clx,
+            lcb_clx,
+        })
+    }
+}
+
+/// Walk the piece table(PlcPcd) in Clx and concat the text of each piece
+fn read_pieces(
+    word_doc: &[u8],
+    clx: &[u8],
+    ccp_text: usize,
+    max_load_chars: usize,
+) -> io::Result<String> {
+    let mut pos = 0;
+    // Skip Prc(grpprl) entries
+    while clx.get(pos) == Some(&0x01) {
+        let cb_grpprl = read_u16(clx, pos + 1)? as usize;
+        pos += 3 + cb_grpprl;
+    }
+    if clx.get(pos) != Some(&0x02) {
+        return Err(invalid_data("Pcdt not found in Clx"));
+    }
+    let lcb = read_u32(clx, pos + 1)? as usize;
+    let plc_pcd = clx
+        .get(pos + 5..pos + 5 + lcb)
+        .ok_or_else(|| invalid_data("PlcPcd is out of Clx"))?;
+    // PlcPcd = (n + 1) * CP(4 bytes) + n * Pcd(8 bytes)
+    let count = (plc_pcd.len().saturating_sub(4)) / 12;
+
+    let mut txt = String::new();
+    let mut cp_loaded = 0;
+    for i in 0..count {
+        let cp_start = read_u32(plc_pcd, i * 4)? as usize;
+        let cp_end = read_u32(plc_pcd, (i + 1) * 4)? as usize;
+        if cp_end <= cp_start || cp_start >= ccp_text {
+            continue;
+        }
+        let char_count = cp_end.min(ccp_text) - cp_start;
+        let fc_compressed = read_u32(plc_pcd, (count + 1) * 4 + i * 8 + 2)?;
+        let compressed = fc_compressed & 0x4000_0000 != 0;
+        let fc = (fc_compressed & 0x3FFF_FFFF) as usize;
+
+        if compressed {
+            let offset = fc / 2;
+            let bytes = word_doc
+                .get(offset..offset + char_count)
+                .ok_or_else(|| invalid_data("Piece is out of WordDocument stream"))?;
+            let (decoded, _, _) = WINDOWS_1252.decode(bytes);
+            txt.push_str(&decoded);
+        } else {
+            let bytes = word_doc
+                .get(fc..fc + char_count * 2)
+                .ok_or_else(|| invalid_data("Piece is out of WordDocument stream"))?;
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .collect();
+            txt.push_str(&String::from_utf16_lossy(&units));
+        }
+
+        cp_loaded += char_count;
+        if cp_loaded >= ccp_text {
+            break;
+        }
+        if max_load_chars > 0 && txt.chars().count() > max_load_chars {
+            break;
+        }
+    }
+    Ok(txt)
+}
+
+/// Replace Word control characters, drop field instructions and keep field results
+fn clean_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    // Field nesting, true if currently inside the instruction part of a field
+    let mut fields: Vec<bool> = Vec::new();
+    for c in text.chars() {
+        match c {
+            '\u{13}' => fields.push(true),
+            '\u{14}' => {
+                if let Some(in_instruction) = fields.last_mut() {
+                    *in_instruction = false;
+                }
+            }
+            '\u{15}' => {
+                fields.pop();
+            }
+            _ if fields.last() == Some(&true) => (),
+            '\r' | '\u{0B}' | '\u{0C}' => result.push('\n'),
+            '\u{07}' => result.push('\t'),
+            '\u{1E}' => result.push('-'),
+            '\t' => result.push('\t'),
+            _ if c.is_control() => (),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn read_stream<F: Read + Seek>(comp: &mut CompoundFile<F>, name: &str) -> io::Result<Vec<u8>> {
+    let mut stream = comp.open_stream(name)?;
+    let mut data = Vec::new();
+    stream.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> io::Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid_data("Unexpected end of data"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> io::Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid_data("Unexpected end of data"))
+}
+
+fn invalid_data(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let data = DocLoader::default()
+            .load(Path::new("assets/test_file/example.doc"))
+            .unwrap();
+        println!("len: {}, data: {}", data.len(), data);
+        assert!(data.contains("Mango Desk legacy document"));
+        assert!(data.contains("芒果桌面"));
+        assert!(!data.contains("HYPERLINK"));
+        assert!(data.contains("project page"));
+    }
+
+    #[test]
+    fn read_max() {
+        let data = DocLoader::default()
+            .load_max(Path::new("assets/test_file/example.doc"), 10)
+            .unwrap();
+        assert!(!data.contains("芒果桌面"));
+    }
+}
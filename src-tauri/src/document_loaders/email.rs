@@ -0,0 +1,161 @@
+use crate::global::{DEFAULT_DATETIME_FORMAT, EMAIL_EXTS};
+use crate::structs::file_metadata::{EmailMetadata, FileMetadata};
+use crate::traits::document_loader::DocumentLoader;
+use chrono::{Local, TimeZone};
+use mail_parser::{Address, Message, MessageParser, MimeHeaders};
+use std::fs::File;
+use std::io;
+use std::io::{Error, ErrorKind, Read};
+use std::path::Path;
+
+/// Loader for email messages(.eml).
+///
+/// MIME parts are decoded(quoted-printable/base64 and charsets), the text bodies are indexed
+/// together with the main headers, html bodies are converted to text.
+#[derive(Debug)]
+pub struct EmailLoader {
+    exts: Vec<String>,
+}
+
+impl Default for EmailLoader {
+    fn default() -> Self {
+        Self {
+            exts: EMAIL_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for EmailLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, mut file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        let message = parse_message(&data)?;
+        Ok(message_text(&message, max_load_chars))
+    }
+
+    fn load_metadata(&self, path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+        let data = std::fs::read(path)?;
+        let message = parse_message(&data)?;
+        let email = email_metadata(&message);
+        if metadata.author.is_empty() {
+            metadata.author = email.from.clone();
+        }
+        metadata.email = Some(email);
+        Ok(())
+    }
+}
+
+pub fn parse_message(data: &[u8]) -> io::Result<Message<'_>> {
+    MessageParser::default()
+        .parse(data)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Not an email message"))
+}
+
+/// Main headers followed by the text bodies and the attachment names
+pub fn message_text(message: &Message, max_load_chars: usize) -> String {
+    let email = email_metadata(message);
+    let mut txt = format!("Subject: {}\nFrom: {}\n", email.subject, email.from);
+    if !email.to.is_empty() {
+        txt.push_str(&format!("To: {}\n", email.to.join("; ")));
+    }
+    if !email.cc.is_empty() {
+        txt.push_str(&format!("Cc: {}\n", email.cc.join("; ")));
+    }
+    if !email.date.is_empty() {
+        txt.push_str(&format!("Date: {}\n", email.date));
+    }
+    for i in 0..message.text_body_count() {
+        if let Some(body) = message.body_text(i) {
+            txt.push('\n');
+            txt.push_str(body.trim());
+            txt.push('\n');
+        }
+        if max_load_chars > 0 && txt.chars().count() > max_load_chars {
+            return txt.chars().take(max_load_chars).collect();
+        }
+    }
+    let attachments: Vec<&str> = message
+        .attachments()
+        .filter_map(|part| part.attachment_name())
+        .collect();
+    if !attachments.is_empty() {
+        txt.push_str(&format!("\nAttachments: {}\n", attachments.join(", ")));
+    }
+    txt
+}
+
+pub fn email_metadata(message: &Message) -> EmailMetadata {
+    EmailMetadata {
+        subject: message.subject().unwrap_or_default().trim().to_string(),
+        from: message
+            .from()
+            .map(|address| format_address(address).join("; "))
+            .unwrap_or_default(),
+        to: message.to().map(format_address).unwrap_or_default(),
+        cc: message.cc().map(format_address).unwrap_or_default(),
+        date: message
+            .date()
+            .and_then(|date| Local.timestamp_opt(date.to_timestamp(), 0).single())
+            .map(|date| date.format(DEFAULT_DATETIME_FORMAT).to_string())
+            .unwrap_or_default(),
+        message_id: message.message_id().unwrap_or_default().to_string(),
+    }
+}
+
+/// `Name <address>` or `address` of every mailbox
+fn format_address(address: &Address) -> Vec<String> {
+    address
+        .iter()
+        .map(|addr| match (addr.name(), addr.address()) {
+            (Some(name), Some(address)) => format!("{} <{}>", name, address),
+            (Some(name), None) => name.to_string(),
+            (None, Some(address)) => address.to_string(),
+            (None, None) => String::new(),
+        })
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let path = Path::new("assets/test_file/example.eml");
+        let data = EmailLoader::default().load(path).unwrap();
+        println!("len: {}, data: {}", data.len(), data);
+        assert!(data.contains("Subject: Quarterly budget 芒果"));
+        assert!(data.contains("From: Alice Chen <alice@example.com>"));
+        // quoted-printable body
+        assert!(data.contains("The café budget is approved."));
+        // base64 html body is not indexed when there is a text alternative
+        assert!(!data.contains("<p>"));
+        assert!(data.contains("Attachments: budget.xlsx"));
+
+        let mut metadata = FileMetadata::default();
+        EmailLoader::default()
+            .load_metadata(path, &mut metadata)
+            .unwrap();
+        let email = metadata.email.unwrap();
+        assert_eq!(
+            email.to,
+            vec!["bob@example.com", "Carol <carol@example.com>"]
+        );
+        assert_eq!(email.message_id, "budget-1@example.com");
+        assert!(!email.date.is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::global::EPUB_EXTS;
+use crate::structs::file_metadata::FileMetadata;
+use crate::traits::document_loader::DocumentLoader;
+use crate::utils::doc_props_util;
+use mail_parser::decoders::html::html_to_text;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::{Error, ErrorKind, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Loader for EPUB e-books(.epub).
+///
+/// The chapters listed in the spine of the package document(OPF) are read in order,
+/// the Dublin Core metadata of the package is loaded as properties.
+#[derive(Debug)]
+pub struct EpubLoader {
+    exts: Vec<String>,
+}
+
+impl Default for EpubLoader {
+    fn default() -> Self {
+        Self {
+            exts: EPUB_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for EpubLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut archive = ZipArchive::new(file)?;
+        let (opf_path, opf) = read_package(&mut archive)?;
+        let opf_dir = match opf_path.rfind('/') {
+            Some(index) => &opf_path[..index + 1],
+            None => "",
+        };
+
+        let mut txt = Vec::new();
+        let mut total_chars = 0;
+        for href in spine_hrefs(&opf) {
+            let name = format!("{}{}", opf_dir, href);
+            let Some(xhtml) = doc_props_util::read_entry(&mut archive, &name)? else {
+                continue;
+            };
+            let text = html_to_text(&xhtml);
+            total_chars += text.chars().count();
+            txt.push(text);
+            if max_load_chars > 0 && total_chars > max_load_chars {
+                break;
+            }
+        }
+        let txt = txt.join("\n");
+        if max_load_chars > 0 {
+            return Ok(txt.chars().take(max_load_chars).collect());
+        }
+        Ok(txt)
+    }
+
+    fn load_metadata(&self, path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let (_, opf) = read_package(&mut archive)?;
+        doc_props_util::apply_xml_properties(&opf, metadata);
+        Ok(())
+    }
+}
+
+/// Locate the package document through `META-INF/container.xml`, return its path and content
+fn read_package<R: Read + Seek>(archive: &mut ZipArchive<R>) -> io::Result<(String, String)> {
+    let container = doc_props_util::read_entry(archive, "META-INF/container.xml")?
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "container.xml not found"))?;
+
+    let mut reader = Reader::from_str(&container);
+    let mut opf_path = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"rootfile" => {
+                opf_path = e
+                    .attributes()
+                    .flatten()
+                    .find(|attr| attr.key.local_name().as_ref() == b"full-path")
+                    .map(|attr| String::from_utf8_lossy(&attr.value).to_string());
+                break;
+            }
+            Ok(Event::Eof) => break,
+            Err(error) => return Err(Error::new(ErrorKind::InvalidData, error)),
+            _ => (),
+        }
+    }
+    let opf_path =
+        opf_path.ok_or_else(|| Error::new(ErrorKind::InvalidData, "rootfile not found"))?;
+    let opf = doc_props_util::read_entry(archive, &opf_path)?
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "package document not found"))?;
+    Ok((opf_path, opf))
+}
+
+/// Hrefs of the manifest items referenced by the spine, in reading order
+fn spine_hrefs(opf: &str) -> Vec<String> {
+    let mut reader = Reader::from_str(opf);
+    let mut manifest: HashMap<String, String> = HashMap::new();
+    let mut spine: Vec<String> = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                let attr = |name: &[u8]| {
+                    e.attributes()
+                        .flatten()
+                        .find(|attr| attr.key.local_name().as_ref() == name)
+                        .map(|attr| String::from_utf8_lossy(&attr.value).to_string())
+                };
+                match e.local_name().as_ref() {
+                    b"item" => {
+                        if let (Some(id), Some(href)) = (attr(b"id"), attr(b"href")) {
+                            manifest.insert(id, href);
+                        }
+                    }
+                    b"itemref" => {
+                        if let Some(idref) = attr(b"idref") {
+                            spine.push(idref);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(error) => {
+                println!("Read epub spine error: {}", error);
+                break;
+            }
+            _ => (),
+        }
+    }
+    spine
+        .iter()
+        .filter_map(|idref| manifest.get(idref).cloned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let path = Path::new("assets/test_file/example.epub");
+        let loader = EpubLoader::default();
+        let data = loader.load(path).unwrap();
+        println!("len: {}, data: {}", data.len(), data);
+        let first = data.find("Chapter one").unwrap();
+        let second = data.find("Chapter two").unwrap();
+        assert!(first < second);
+
+        let mut metadata = FileMetadata::default();
+        loader.load_metadata(path, &mut metadata).unwrap();
+        assert_eq!(metadata.title, "Mango Desk Handbook");
+        assert_eq!(metadata.author, "Alice");
+    }
+}
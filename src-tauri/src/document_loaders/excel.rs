@@ -1,9 +1,15 @@
-use crate::global::EXCEL_EXTS;
+use crate::global::{EXCEL_EXTS, TABLE_CHUNK_SIZE};
+use crate::structs::document_chunk::DocumentChunk;
+use crate::structs::file_metadata::FileMetadata;
+use crate::structs::table::Table;
 use crate::traits::document_loader::DocumentLoader;
+use crate::utils::{doc_props_util, table_util};
 use calamine::Reader;
 use calamine::open_workbook_auto;
+use std::error::Error;
 use std::io;
 use std::path::Path;
+use tokenizers::Tokenizer;
 
 #[derive(Debug)]
 
@@ -30,6 +36,48 @@ impl DocumentLoader for ExcelLoader {
         self.load_max(path, 0)
     }
     fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let tables = self.read_tables(path)?;
+        Ok(table_util::render_tables(&tables, max_load_chars))
+    }
+    fn load_file_max(&self, file: &std::fs::File, max_load_chars: usize) -> io::Result<String> {
+        unimplemented!(
+            "load_file_max with File is not supported for Excel files, use load_max() with Path instead"
+        )
+    }
+
+    fn split(
+        &self,
+        _path: &Path,
+        content: &str,
+        tokenizer: &Tokenizer,
+    ) -> Result<Vec<DocumentChunk>, Box<dyn Error>> {
+        Ok(table_util::split_rendered_tables(
+            content,
+            tokenizer,
+            TABLE_CHUNK_SIZE,
+        ))
+    }
+
+    fn load_metadata(&self, path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match ext.as_str() {
+            "xlsx" | "xlsm" | "xlsb" | "xlam" => {
+                doc_props_util::load_ooxml_properties(path, metadata)
+            }
+            "ods" => doc_props_util::load_odf_properties(path, metadata),
+            // Properties of legacy xls files are not supported
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ExcelLoader {
+    /// Read every non-empty sheet as a table
+    fn read_tables(&self, path: &Path) -> io::Result<Vec<Table>> {
         let mut workbook = open_workbook_auto(path).map_err(|e| {
             io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -37,34 +85,25 @@ impl DocumentLoader for ExcelLoader {
             )
         })?;
         let sheet_names = workbook.sheet_names();
-        let mut txt = String::new();
-        'outer: for sheet_name in sheet_names {
+        let mut tables = Vec::new();
+        for sheet_name in sheet_names {
             let range = workbook.worksheet_range(&sheet_name).map_err(|e| {
                 io::Error::new(
                     io::ErrorKind::InvalidInput,
                     format!("Failed to open Excel file: {}", e),
                 )
             })?;
-            if range.rows().len() == 0 {
-                continue;
-            } else {
-                for row in range.rows() {
-                    for cell in row {
-                        txt.push_str(&cell.to_string());
-                        txt.push_str("\t");
-                        if max_load_chars > 0 && txt.chars().count() > max_load_chars {
-                            break 'outer;
-                        }
-                    }
-                }
+            // The range starts at the first used cell, not always at A1
+            let first_row = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+            let rows = range.rows().enumerate().map(|(i, row)| {
+                let cells = row.iter().map(|cell| cell.to_string()).collect();
+                (first_row + i + 1, cells)
+            });
+            if let Some(table) = table_util::build_table(Some(sheet_name.clone()), rows) {
+                tables.push(table);
             }
         }
-        Ok(txt)
-    }
-    fn load_file_max(&self, file: &std::fs::File, max_load_chars: usize) -> io::Result<String> {
-        unimplemented!(
-            "load_file_max with File is not supported for Excel files, use load_max() with Path instead"
-        )
+        Ok(tables)
     }
 }
 
@@ -0,0 +1,137 @@
+use crate::global::HTML_EXTS;
+use crate::structs::file_metadata::FileMetadata;
+use crate::traits::document_loader::DocumentLoader;
+use mail_parser::decoders::html::html_to_text;
+use regex::Regex;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use std::sync::LazyLock;
+
+static TITLE_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap());
+static META_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<meta\s[^>]*>").unwrap());
+static ATTR_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?is)([a-z-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// Loader for web pages(.html/.htm/.xhtml).
+///
+/// Tags, scripts and styles are stripped from the text, the `<title>` and
+/// `<meta name="author|description|keywords">` are loaded as properties.
+#[derive(Debug)]
+pub struct HtmlLoader {
+    exts: Vec<String>,
+}
+
+impl Default for HtmlLoader {
+    fn default() -> Self {
+        Self {
+            exts: HTML_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for HtmlLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, file: &File, max_load_chars: usize) -> io::Result<String> {
+        let text = html_to_text(&read_html(file)?);
+        if max_load_chars > 0 {
+            return Ok(text.chars().take(max_load_chars).collect());
+        }
+        Ok(text)
+    }
+
+    fn load_metadata(&self, path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+        let html = read_html(&File::open(path)?)?;
+        apply_html_properties(&html, metadata);
+        Ok(())
+    }
+}
+
+fn read_html(mut file: &File) -> io::Result<String> {
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(String::from_utf8_lossy(&data).to_string())
+}
+
+/// Fill in the title, author, subject(description) and keywords from the head of a page,
+/// properties already set are kept
+pub fn apply_html_properties(html: &str, metadata: &mut FileMetadata) {
+    if metadata.title.is_empty() {
+        if let Some(title) = TITLE_PATTERN.captures(html) {
+            metadata.title = decode_value(&title[1]);
+        }
+    }
+    for tag in META_PATTERN.find_iter(html) {
+        let mut name = String::new();
+        let mut content = String::new();
+        for attr in ATTR_PATTERN.captures_iter(tag.as_str()) {
+            let value = attr.get(2).or(attr.get(3)).map_or("", |v| v.as_str());
+            match attr[1].to_ascii_lowercase().as_str() {
+                "name" | "property" => name = value.to_ascii_lowercase(),
+                "content" => content = decode_value(value),
+                _ => (),
+            }
+        }
+        if content.is_empty() {
+            continue;
+        }
+        match name.as_str() {
+            "author" | "dc.creator" if metadata.author.is_empty() => metadata.author = content,
+            "description" | "og:description" if metadata.subject.is_empty() => {
+                metadata.subject = content
+            }
+            "og:title" if metadata.title.is_empty() => metadata.title = content,
+            "keywords" => metadata.keywords.extend(
+                content
+                    .split([',', ';'])
+                    .map(|keyword| keyword.trim().to_string())
+                    .filter(|keyword| !keyword.is_empty()),
+            ),
+            _ => (),
+        }
+    }
+}
+
+/// Resolve the entities and collapse the whitespaces
+fn decode_value(value: &str) -> String {
+    html_to_text(value)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let path = Path::new("assets/test_file/example.html");
+        let loader = HtmlLoader::default();
+        let data = loader.load(path).unwrap();
+        println!("len: {}, data: {}", data.len(), data);
+        assert!(data.contains("Mango Desk web page"));
+        assert!(!data.contains("console.log"));
+
+        let mut metadata = FileMetadata::default();
+        loader.load_metadata(path, &mut metadata).unwrap();
+        assert_eq!(metadata.title, "Mango Desk & friends");
+        assert_eq!(metadata.author, "Alice");
+        assert_eq!(metadata.subject, "Local semantic search");
+        assert_eq!(metadata.keywords, vec!["search", "desktop"]);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::global::{DOCUMENT_CHUNK_SIZE, MARKDOWN_EXTS};
+use crate::structs::document_chunk::DocumentChunk;
+use crate::traits::document_loader::DocumentLoader;
+use crate::utils::markdown_util;
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use tokenizers::Tokenizer;
+
+/// Loader for markdown documents(.md/.mdx).
+///
+/// The content is chunked by the markdown structure, see `markdown_util::split_markdown`.
+#[derive(Debug)]
+pub struct MarkdownLoader {
+    exts: Vec<String>,
+}
+
+impl Default for MarkdownLoader {
+    fn default() -> Self {
+        Self {
+            exts: MARKDOWN_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for MarkdownLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, mut file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut contents = String::new();
+        if max_load_chars > 0 {
+            file.take((max_load_chars * 4) as u64)
+                .read_to_string(&mut contents)?;
+            return Ok(contents.chars().take(max_load_chars).collect());
+        }
+        file.read_to_string(&mut contents)?;
+        Ok(contents)
+    }
+
+    fn split(
+        &self,
+        _path: &Path,
+        content: &str,
+        tokenizer: &Tokenizer,
+    ) -> Result<Vec<DocumentChunk>, Box<dyn Error>> {
+        Ok(markdown_util::split_markdown(
+            content,
+            tokenizer,
+            DOCUMENT_CHUNK_SIZE,
+        ))
+    }
+
+    /// Blank lines separate the paragraphs
+    fn keep_layout(&self) -> bool {
+        true
+    }
+}
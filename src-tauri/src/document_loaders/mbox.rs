@@ -0,0 +1,147 @@
+use crate::document_loaders::email::{email_metadata, message_text, parse_message};
+use crate::global::{EMAIL_EXTS, MBOX_EXTS, MBOX_MAX_MESSAGES};
+use crate::structs::file_metadata::FileMetadata;
+use crate::structs::virtual_member::VirtualMember;
+use crate::traits::document_loader::DocumentLoader;
+use chrono::{Local, TimeZone};
+use mail_parser::mailbox::mbox::MessageIterator;
+use md5::{Digest, Md5};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Loader for mailbox archives(.mbox).
+///
+/// Every message of the mailbox is indexed as a separate member, see `load_members`.
+#[derive(Debug)]
+pub struct MboxLoader {
+    exts: Vec<String>,
+}
+
+impl Default for MboxLoader {
+    fn default() -> Self {
+        Self {
+            exts: MBOX_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for MboxLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut txt = String::new();
+        for message in MessageIterator::new(BufReader::new(file)) {
+            let message = message?;
+            let Ok(parsed) = parse_message(message.contents()) else {
+                continue;
+            };
+            txt.push_str(&message_text(&parsed, max_load_chars));
+            txt.push('\n');
+            if max_load_chars > 0 && txt.chars().count() > max_load_chars {
+                return Ok(txt.chars().take(max_load_chars).collect());
+            }
+        }
+        Ok(txt)
+    }
+
+    fn load_members(
+        &self,
+        path: &Path,
+        max_load_chars: usize,
+    ) -> io::Result<Option<Vec<VirtualMember>>> {
+        let file = File::open(path)?;
+        let mut members = Vec::new();
+        let mut ids = HashSet::new();
+        for message in MessageIterator::new(BufReader::new(file)) {
+            if members.len() >= MBOX_MAX_MESSAGES {
+                println!(
+                    "Only the first {} messages are indexed: {}",
+                    MBOX_MAX_MESSAGES,
+                    path.display()
+                );
+                break;
+            }
+            let message = message?;
+            let contents = message.contents();
+            let Ok(parsed) = parse_message(contents) else {
+                continue;
+            };
+            let email = email_metadata(&parsed);
+            let base_id = match email.message_id.is_empty() {
+                true => format!("{:x}", Md5::digest(contents)),
+                false => email.message_id.clone(),
+            };
+            // Message ids are not always unique in a mailbox(e.g. duplicated messages)
+            let mut id = base_id.clone();
+            let mut n = 1;
+            while !ids.insert(id.clone()) {
+                n += 1;
+                id = format!("{}-{}", base_id, n);
+            }
+
+            let mut metadata = FileMetadata::default();
+            metadata.name = match email.subject.is_empty() {
+                true => id.clone(),
+                false => email.subject.clone(),
+            };
+            metadata.extension = EMAIL_EXTS[0].to_string();
+            metadata.size = contents.len() as i64;
+            if let Some(date) = parsed
+                .date()
+                .and_then(|date| Local.timestamp_opt(date.to_timestamp(), 0).single())
+            {
+                metadata.created = date;
+                metadata.modified = date;
+            }
+            metadata.author = email.from.clone();
+            metadata.email = Some(email);
+
+            members.push(VirtualMember {
+                id,
+                ext: EMAIL_EXTS[0].to_string(),
+                content: message_text(&parsed, max_load_chars),
+                metadata,
+            });
+        }
+        Ok(Some(members))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_members() {
+        let members = MboxLoader::default()
+            .load_members(Path::new("assets/test_file/example.mbox"), 0)
+            .unwrap()
+            .unwrap();
+        for member in &members {
+            println!("id: {}, content: {}", member.id, member.content);
+        }
+        assert_eq!(members.len(), 3);
+        assert_eq!(members[0].id, "launch-1@example.com");
+        assert_eq!(members[0].metadata.name, "Launch plan");
+        assert!(members[0].content.contains("From the start of May"));
+        // Duplicated message id
+        assert_eq!(members[1].id, "launch-1@example.com-2");
+        // Message without id
+        assert_eq!(members[2].id.len(), 32);
+        assert!(members[2].content.contains("芒果"));
+    }
+}
@@ -0,0 +1,364 @@
+use crate::global::{DOCUMENT_CHUNK_SIZE, NOTEBOOK_EXTS, NOTEBOOK_MAX_OUTPUT_CHARS};
+use crate::structs::chunk_locator::ChunkLocator;
+use crate::structs::document_chunk::DocumentChunk;
+use crate::traits::document_loader::DocumentLoader;
+use serde_json::Value;
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, ErrorKind};
+use std::path::Path;
+use text_splitter::{ChunkConfig, ChunkSizer, TextSplitter};
+use tokenizers::Tokenizer;
+
+/// Loader for Jupyter notebooks(.ipynb).
+///
+/// Markdown and code cells are extracted in order, text outputs of code cells are included
+/// if `notebook_include_outputs` of the indexer setting is set. Chunks are made of whole cells
+/// with the cell numbers as locator.
+#[derive(Debug)]
+pub struct NotebookLoader {
+    exts: Vec<String>,
+    include_outputs: bool,
+}
+
+impl Default for NotebookLoader {
+    fn default() -> Self {
+        Self {
+            exts: NOTEBOOK_EXTS.iter().map(|s| s.to_string()).collect(),
+            include_outputs: false,
+        }
+    }
+}
+
+/// A rendered cell, `number` is the 1-based position in the notebook
+struct Cell {
+    number: usize,
+    text: String,
+}
+
+struct Notebook {
+    language: Option<String>,
+    cells: Vec<Cell>,
+}
+
+impl NotebookLoader {
+    pub fn new(include_outputs: bool) -> Self {
+        Self {
+            include_outputs,
+            ..Default::default()
+        }
+    }
+
+    fn read_notebook(&self, file: &File) -> io::Result<Notebook> {
+        let value: Value = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+        let language = value
+            .pointer("/metadata/kernelspec/language")
+            .or_else(|| value.pointer("/metadata/language_info/name"))
+            .and_then(Value::as_str)
+            .map(|s| s.to_string());
+        let mut cells = Vec::new();
+        let empty = Vec::new();
+        let raw_cells = value["cells"].as_array().unwrap_or(&empty);
+        for (i, raw_cell) in raw_cells.iter().enumerate() {
+            let cell_type = raw_cell["cell_type"].as_str().unwrap_or("");
+            if cell_type != "markdown" && cell_type != "code" {
+                continue;
+            }
+            let source = join_text(&raw_cell["source"]);
+            if source.trim().is_empty() {
+                continue;
+            }
+            let mut text = format!("Cell {} ({}):\n{}", i + 1, cell_type, source.trim_end());
+            if self.include_outputs && cell_type == "code" {
+                let outputs = raw_cell["outputs"].as_array().unwrap_or(&empty);
+                let output = outputs
+                    .iter()
+                    .map(output_text)
+                    .filter(|s| !s.trim().is_empty())
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                if !output.is_empty() {
+                    let output: String = output.chars().take(NOTEBOOK_MAX_OUTPUT_CHARS).collect();
+                    text.push_str(&format!("\nOutput:\n{}", output.trim_end()));
+                }
+            }
+            cells.push(Cell {
+                number: i + 1,
+                text,
+            });
+        }
+        Ok(Notebook { language, cells })
+    }
+}
+
+impl DocumentLoader for NotebookLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, file: &File, max_load_chars: usize) -> io::Result<String> {
+        let notebook = cap_cells(self.read_notebook(file)?, max_load_chars);
+        let mut txt = String::new();
+        if let Some(language) = &notebook.language {
+            txt.push_str(&format!("{}{}\n\n", LANGUAGE_PREFIX, language));
+        }
+        for cell in notebook.cells {
+            txt.push_str(&cell.text);
+            txt.push_str("\n\n");
+        }
+        Ok(txt)
+    }
+
+    fn split(
+        &self,
+        _path: &Path,
+        content: &str,
+        tokenizer: &Tokenizer,
+    ) -> Result<Vec<DocumentChunk>, Box<dyn Error>> {
+        let notebook = parse_loaded(content);
+        Ok(split_cells(&notebook, tokenizer, DOCUMENT_CHUNK_SIZE))
+    }
+}
+
+/// The first line of the loaded text if the kernel language is known
+const LANGUAGE_PREFIX: &str = "Language: ";
+
+/// Cell number of a `Cell <number> (<type>):` header line
+fn cell_header(line: &str) -> Option<usize> {
+    let rest = line.strip_prefix("Cell ")?;
+    let (number, cell_type) = rest.split_once(' ')?;
+    if cell_type != "(markdown):" && cell_type != "(code):" {
+        return None;
+    }
+    number.parse().ok()
+}
+
+/// The cells of the loaded text, blank lines may have been collapsed
+fn parse_loaded(text: &str) -> Notebook {
+    let mut language = None;
+    let mut cells: Vec<Cell> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if i == 0 {
+            if let Some(name) = line.strip_prefix(LANGUAGE_PREFIX) {
+                language = Some(name.to_string());
+                continue;
+            }
+        }
+        match (cell_header(line), cells.last_mut()) {
+            (Some(number), _) => cells.push(Cell {
+                number,
+                text: line.to_string(),
+            }),
+            (None, Some(cell)) => {
+                cell.text.push('\n');
+                cell.text.push_str(line);
+            }
+            (None, None) => {}
+        }
+    }
+    for cell in &mut cells {
+        cell.text.truncate(cell.text.trim_end().len());
+    }
+    Notebook { language, cells }
+}
+
+/// The cells loaded within the max load chars(0 for no limit), the cell reaching the limit is cut
+fn cap_cells(mut notebook: Notebook, max_load_chars: usize) -> Notebook {
+    if max_load_chars == 0 {
+        return notebook;
+    }
+    let mut remaining = max_load_chars;
+    let mut cells = Vec::new();
+    for mut cell in notebook.cells {
+        if remaining == 0 {
+            break;
+        }
+        // A cell is followed by a blank line in the loaded text
+        let cell_chars = cell.text.chars().count() + 2;
+        if cell_chars > remaining {
+            cell.text = cell.text.chars().take(remaining).collect();
+            remaining = 0;
+        } else {
+            remaining -= cell_chars;
+        }
+        cells.push(cell);
+    }
+    notebook.cells = cells;
+    notebook
+}
+
+/// `source` and output `text` are either a string or a list of lines
+fn join_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn output_text(output: &Value) -> String {
+    match output["output_type"].as_str().unwrap_or("") {
+        "stream" => join_text(&output["text"]),
+        "execute_result" | "display_data" => join_text(&output["data"]["text/plain"]),
+        "error" => format!(
+            "{}: {}",
+            output["ename"].as_str().unwrap_or(""),
+            output["evalue"].as_str().unwrap_or("")
+        ),
+        _ => String::new(),
+    }
+}
+
+/// Pack consecutive cells into chunks, a cell bigger than the capacity is split alone
+fn split_cells<S: ChunkSizer>(
+    notebook: &Notebook,
+    sizer: &S,
+    capacity: usize,
+) -> Vec<DocumentChunk> {
+    let mut chunks = Vec::new();
+    let mut texts: Vec<&str> = Vec::new();
+    let mut size = 0;
+    let mut cells: Option<(usize, usize)> = None;
+    for cell in &notebook.cells {
+        let cell_size = sizer.size(&cell.text) + 2;
+        if cell_size > capacity {
+            if !texts.is_empty() {
+                chunks.push(cell_chunk(notebook, texts.join("\n\n"), cells));
+                texts.clear();
+                size = 0;
+                cells = None;
+            }
+            let splitter = TextSplitter::new(ChunkConfig::new(capacity).with_sizer(sizer));
+            for part in splitter.chunks(&cell.text) {
+                chunks.push(cell_chunk(
+                    notebook,
+                    part.to_string(),
+                    Some((cell.number, cell.number)),
+                ));
+            }
+            continue;
+        }
+        if !texts.is_empty() && size + cell_size > capacity {
+            chunks.push(cell_chunk(notebook, texts.join("\n\n"), cells));
+            texts.clear();
+            size = 0;
+            cells = None;
+        }
+        size += cell_size;
+        texts.push(&cell.text);
+        cells = Some(match cells {
+            Some((start, _)) => (start, cell.number),
+            None => (cell.number, cell.number),
+        });
+    }
+    if !texts.is_empty() {
+        chunks.push(cell_chunk(notebook, texts.join("\n\n"), cells));
+    }
+    chunks
+}
+
+fn cell_chunk(notebook: &Notebook, text: String, cells: Option<(usize, usize)>) -> DocumentChunk {
+    DocumentChunk {
+        text,
+        locator: ChunkLocator {
+            language: notebook.language.clone(),
+            start_cell: cells.map(|(start, _)| start),
+            end_cell: cells.map(|(_, end)| end),
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text_splitter::Characters;
+
+    #[test]
+    fn read() {
+        let path = Path::new("assets/test_file/example.ipynb");
+        let loader = NotebookLoader::new(false);
+        let data = loader.load(path).unwrap();
+        println!("len: {}, data: {}", data.len(), data);
+        assert!(data.starts_with("Language: python\n\nCell 1 (markdown)"));
+        assert!(data.contains("Cell 1 (markdown):\n# Sales analysis"));
+        assert!(data.contains("Cell 2 (code):\nimport pandas as pd"));
+        assert!(!data.contains("Output:"));
+        // Raw cells are skipped
+        assert!(!data.contains("raw cell"));
+
+        let loader = NotebookLoader::new(true);
+        let data = loader.load(path).unwrap();
+        assert!(data.contains("Output:\ntotal: 42"));
+    }
+
+    #[test]
+    fn parse_loaded_text() {
+        let path = Path::new("assets/test_file/example.ipynb");
+        let loader = NotebookLoader::new(true);
+        let file = File::open(path).unwrap();
+        let notebook = loader.read_notebook(&file).unwrap();
+        let data = loader.load(path).unwrap();
+        // The cells are found again even after the blank lines are collapsed
+        for text in [data.clone(), data.replace("\n\n", "\n")] {
+            let parsed = parse_loaded(&text);
+            assert_eq!(parsed.language, notebook.language);
+            assert_eq!(parsed.cells.len(), notebook.cells.len());
+            for (parsed, cell) in parsed.cells.iter().zip(&notebook.cells) {
+                assert_eq!(parsed.number, cell.number);
+                assert_eq!(
+                    parsed.text.replace("\n\n", "\n"),
+                    cell.text.replace("\n\n", "\n")
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn split() {
+        let file = File::open("assets/test_file/example.ipynb").unwrap();
+        let notebook = NotebookLoader::default().read_notebook(&file).unwrap();
+        let chunks = split_cells(&notebook, &Characters, 60);
+        for chunk in &chunks {
+            println!("{:?}: {}", chunk.locator, chunk.text);
+            assert!(chunk.text.chars().count() <= 60);
+            assert_eq!(chunk.locator.language.as_deref(), Some("python"));
+        }
+        assert_eq!(chunks[0].locator.start_cell, Some(1));
+        assert_eq!(chunks.last().unwrap().locator.end_cell, Some(5));
+    }
+
+    #[test]
+    fn split_capped_cells() {
+        let path = Path::new("assets/test_file/example.ipynb");
+        let loader = NotebookLoader::new(true);
+        let file = File::open(path).unwrap();
+        let notebook = loader.read_notebook(&file).unwrap();
+        let cell_count = notebook.cells.len();
+        assert_eq!(cap_cells(notebook, 0).cells.len(), cell_count);
+
+        // Cells beyond the max load chars are neither loaded nor chunked
+        let file = File::open(path).unwrap();
+        let capped = cap_cells(loader.read_notebook(&file).unwrap(), 60);
+        assert_eq!(capped.cells.len(), 1);
+        assert!(capped.cells[0].text.chars().count() <= 60);
+        assert_eq!(
+            loader.load_max(path, 60).unwrap(),
+            format!("Language: python\n\n{}\n\n", capped.cells[0].text)
+        );
+        let chunks = split_cells(&capped, &Characters, 1000);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].locator.end_cell, Some(1));
+    }
+}
@@ -1,6 +1,8 @@
 use crate::document_loaders::open_doc;
 use crate::global::ODP_EXTS;
+use crate::structs::file_metadata::FileMetadata;
 use crate::traits::document_loader::DocumentLoader;
+use crate::utils::doc_props_util;
 use std::io;
 use std::path::Path;
 
@@ -42,6 +44,10 @@ impl DocumentLoader for OdpLoader {
             "load_file_max with File is not supported for odp files, use load_max() with Path instead"
         )
     }
+
+    fn load_metadata(&self, path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+        doc_props_util::load_odf_properties(path, metadata)
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 use crate::global::PDF_EXTS;
+use crate::structs::file_metadata::FileMetadata;
 use crate::traits::document_loader::DocumentLoader;
-use lopdf::{Document, Object};
+use lopdf::{Document, Object, decode_text_string};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt::Debug;
@@ -19,6 +20,16 @@ pub struct PdfLoader {
     exts: Vec<String>,
 }
 
+/// Properties in the Info dictionary and the page count
+#[derive(Debug, Default)]
+struct PdfProperties {
+    author: String,
+    title: String,
+    subject: String,
+    keywords: Vec<String>,
+    page_count: u32,
+}
+
 static IGNORE: &[&[u8]] = &[
     b"Length",
     b"BBox",
@@ -69,6 +80,36 @@ fn load_pdf<P: AsRef<Path>>(path: P) -> Result<Document, Error> {
         .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))
 }
 
+fn read_properties(doc: &Document) -> PdfProperties {
+    let mut properties = PdfProperties {
+        page_count: doc.get_pages().len() as u32,
+        ..Default::default()
+    };
+    let info = match doc.trailer.get(b"Info") {
+        Ok(Object::Reference(id)) => doc.get_dictionary(*id),
+        Ok(object) => object.as_dict(),
+        Err(error) => Err(error),
+    };
+    let Ok(info) = info else {
+        return properties;
+    };
+    let text = |key: &[u8]| {
+        info.get(key)
+            .and_then(decode_text_string)
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    properties.author = text(b"Author");
+    properties.title = text(b"Title");
+    properties.subject = text(b"Subject");
+    properties.keywords = text(b"Keywords")
+        .split([',', ';'])
+        .map(|keyword| keyword.trim().to_string())
+        .filter(|keyword| !keyword.is_empty())
+        .collect();
+    properties
+}
+
 fn load_form(file: &std::fs::File) -> Result<Document, Error> {
     Document::load_from(file).map_err(|e| Error::new(ErrorKind::Other, e.to_string()))
 }
@@ -135,49 +176,13 @@ impl DocumentLoader for PdfLoader {
     }
 
     fn load(&self, path: &Path) -> io::Result<String> {
-        println!("Load:{} ", path.display());
-        let mut doc = load_pdf(&path)?;
-        if doc.is_encrypted() {
-            doc.decrypt(&"")
-                .map_err(|_err| Error::new(ErrorKind::InvalidInput, "Failed to decrypt"))?;
-        }
-        let text = get_pdf_text(&doc, usize::MAX)?;
-        if !text.errors.is_empty() {
-            eprintln!("{} has {} errors:", path.display(), text.errors.len());
-            for error in &text.errors[..10] {
-                eprintln!("{error:?}");
-            }
-        }
-        let contents = text
-            .text
-            .values()
-            .flat_map(|lines| lines.iter())
-            .cloned()
-            .collect::<String>();
-        Ok(contents)
+        self.load_max(path, usize::MAX)
     }
 
     fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
         println!("Load:{} ", path.display());
-        let mut doc = load_pdf(&path)?;
-        if doc.is_encrypted() {
-            doc.decrypt(&"")
-                .map_err(|_err| Error::new(ErrorKind::InvalidInput, "Failed to decrypt"))?;
-        }
-        let pdf_text = get_pdf_text(&doc, max_load_chars)?;
-        if !pdf_text.errors.is_empty() {
-            eprintln!("{} has {} errors:", path.display(), pdf_text.errors.len());
-            for error in &pdf_text.errors[..10] {
-                eprintln!("{error:?}");
-            }
-        }
-        let contents = pdf_text
-            .text
-            .values()
-            .flat_map(|lines| lines.iter())
-            .cloned()
-            .collect::<String>();
-        Ok(contents)
+        let doc = open(path)?;
+        doc_text(path, &doc, max_load_chars)
     }
 
     fn load_file_max(&self, _file: &std::fs::File, _max_load_chars: usize) -> io::Result<String> {
@@ -186,4 +191,69 @@ impl DocumentLoader for PdfLoader {
             "Loading from file is not supported for PDF",
         ))
     }
+
+    fn load_metadata(&self, path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+        fill_metadata(read_properties(&open(path)?), metadata);
+        Ok(())
+    }
+
+    /// The document is parsed once for the content and the properties
+    fn load_with_metadata(
+        &self,
+        path: &Path,
+        max_load_chars: usize,
+        metadata: &mut FileMetadata,
+    ) -> io::Result<String> {
+        println!("Load:{} ", path.display());
+        let doc = open(path)?;
+        fill_metadata(read_properties(&doc), metadata);
+        doc_text(path, &doc, max_load_chars)
+    }
+}
+
+/// Load and decrypt the document
+fn open(path: &Path) -> io::Result<Document> {
+    let mut doc = load_pdf(path)?;
+    if doc.is_encrypted() {
+        doc.decrypt(&"")
+            .map_err(|_err| Error::new(ErrorKind::InvalidInput, "Failed to decrypt"))?;
+    }
+    Ok(doc)
+}
+
+/// Text of the pages within the max load chars, the page errors are logged
+fn doc_text(path: &Path, doc: &Document, max_load_chars: usize) -> io::Result<String> {
+    let pdf_text = get_pdf_text(doc, max_load_chars)?;
+    if !pdf_text.errors.is_empty() {
+        eprintln!("{} has {} errors:", path.display(), pdf_text.errors.len());
+        for error in pdf_text.errors.iter().take(10) {
+            eprintln!("{error:?}");
+        }
+    }
+    let contents = pdf_text
+        .text
+        .values()
+        .flat_map(|lines| lines.iter())
+        .cloned()
+        .collect::<String>();
+    Ok(contents)
+}
+
+/// Properties in the Info dictionary and the page count, the ones already set are kept
+fn fill_metadata(properties: PdfProperties, metadata: &mut FileMetadata) {
+    if metadata.author.is_empty() {
+        metadata.author = properties.author;
+    }
+    if metadata.title.is_empty() {
+        metadata.title = properties.title;
+    }
+    if metadata.subject.is_empty() {
+        metadata.subject = properties.subject;
+    }
+    if metadata.keywords.is_empty() {
+        metadata.keywords = properties.keywords;
+    }
+    if metadata.page_count.is_none() {
+        metadata.page_count = Some(properties.page_count);
+    }
 }
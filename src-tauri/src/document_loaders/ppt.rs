@@ -0,0 +1,151 @@
+use crate::global::PPT_EXTS;
+use crate::traits::document_loader::DocumentLoader;
+use cfb::CompoundFile;
+use std::fs::File;
+use std::io;
+use std::io::{Error, ErrorKind, Read};
+use std::path::Path;
+
+const RECORD_HEADER_LEN: usize = 8;
+// Containers are nested a few levels in valid files, deeper ones of a corrupt file are skipped
+const MAX_RECORD_DEPTH: usize = 16;
+const RT_MAIN_MASTER: u16 = 0x03F8;
+const RT_HANDOUT: u16 = 0x0FC9;
+const RT_TEXT_CHARS_ATOM: u16 = 0x0FA0;
+const RT_TEXT_BYTES_ATOM: u16 = 0x0FA8;
+
+/// Loader for PowerPoint 97-2003 binary presentations(.ppt).
+///
+/// Text is collected from TextCharsAtom/TextBytesAtom records of the "PowerPoint Document" stream,
+/// records under master slides and handouts are skipped.
+#[derive(Debug)]
+pub struct PptLoader {
+    exts: Vec<String>,
+}
+
+impl Default for PptLoader {
+    fn default() -> Self {
+        Self {
+            exts: PPT_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for PptLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut comp = CompoundFile::open(file)?;
+        if comp.is_stream("/EncryptedSummary") {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Encrypted ppt file is not supported",
+            ));
+        }
+        let mut data = Vec::new();
+        comp.open_stream("/PowerPoint Document")?
+            .read_to_end(&mut data)?;
+
+        let mut txt = Vec::new();
+        let mut total_chars = 0;
+        collect_text(&data, &mut txt, &mut total_chars, max_load_chars, 0);
+        Ok(txt.join("\n"))
+    }
+}
+
+/// Walk the records recursively, container records(recVer = 0xF) are descended into
+/// up to `MAX_RECORD_DEPTH` levels. Return false if max_load_chars is reached.
+fn collect_text(
+    data: &[u8],
+    txt: &mut Vec<String>,
+    total_chars: &mut usize,
+    max_load_chars: usize,
+    depth: usize,
+) -> bool {
+    let mut pos = 0;
+    while pos + RECORD_HEADER_LEN <= data.len() {
+        let ver_instance = u16::from_le_bytes([data[pos], data[pos + 1]]);
+        let rec_type = u16::from_le_bytes([data[pos + 2], data[pos + 3]]);
+        let rec_len =
+            u32::from_le_bytes([data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]])
+                as usize;
+        let body_start = pos + RECORD_HEADER_LEN;
+        let body_end = body_start.saturating_add(rec_len).min(data.len());
+        let body = &data[body_start..body_end];
+        pos = body_end;
+
+        if ver_instance & 0x000F == 0x000F {
+            if rec_type == RT_MAIN_MASTER || rec_type == RT_HANDOUT || depth >= MAX_RECORD_DEPTH {
+                continue;
+            }
+            if !collect_text(body, txt, total_chars, max_load_chars, depth + 1) {
+                return false;
+            }
+            continue;
+        }
+
+        let text = match rec_type {
+            RT_TEXT_CHARS_ATOM => {
+                let units: Vec<u16> = body
+                    .chunks_exact(2)
+                    .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                    .collect();
+                String::from_utf16_lossy(&units)
+            }
+            // Each byte is the low byte of a UTF-16 code unit whose high byte is 0x00
+            RT_TEXT_BYTES_ATOM => body.iter().map(|b| *b as char).collect(),
+            _ => continue,
+        };
+        // '\r' is paragraph end and '\u{0B}' is vertical tab in slide text
+        let text = text.replace(['\r', '\u{0B}'], "\n");
+        *total_chars += text.chars().count();
+        txt.push(text);
+        if max_load_chars > 0 && *total_chars > max_load_chars {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let data = PptLoader::default()
+            .load(Path::new("assets/test_file/example.ppt"))
+            .unwrap();
+        println!("len: {}, data: {}", data.len(), data);
+        assert!(data.contains("Quarterly review"));
+        assert!(data.contains("芒果桌面"));
+        assert!(!data.contains("Click to edit Master title style"));
+    }
+
+    #[test]
+    fn deeply_nested_records() {
+        // A container holding itself far deeper than the max depth, as a crafted file could
+        let mut data = Vec::new();
+        for level in 0..100_000u32 {
+            let body_len = (100_000 - level - 1) * RECORD_HEADER_LEN as u32;
+            data.extend_from_slice(&0x000Fu16.to_le_bytes());
+            data.extend_from_slice(&0x03E8u16.to_le_bytes());
+            data.extend_from_slice(&body_len.to_le_bytes());
+        }
+        let mut txt = Vec::new();
+        let mut total_chars = 0;
+        assert!(collect_text(&data, &mut txt, &mut total_chars, 0, 0));
+        assert!(txt.is_empty());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::global::PPTX_EXTS;
+use crate::structs::file_metadata::FileMetadata;
 use crate::traits::document_loader::DocumentLoader;
+use crate::utils::doc_props_util;
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 use std::fs::File;
@@ -107,6 +109,10 @@ impl DocumentLoader for PptxLoader {
 
         Ok(txt.join(""))
     }
+
+    fn load_metadata(&self, path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+        doc_props_util::load_ooxml_properties(path, metadata)
+    }
 }
 
 mod tests {
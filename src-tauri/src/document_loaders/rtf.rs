@@ -0,0 +1,360 @@
+use crate::global::RTF_EXTS;
+use crate::traits::document_loader::DocumentLoader;
+use encoding_rs::{Encoding, WINDOWS_1252};
+use std::fs::File;
+use std::io;
+use std::io::{Error, ErrorKind, Read};
+use std::path::Path;
+
+// Destinations whose content is not part of the document text
+static SKIP_DESTINATIONS: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "object",
+    "fldinst",
+    "themedata",
+    "colorschememapping",
+    "datastore",
+    "latentstyles",
+    "listtable",
+    "listoverridetable",
+    "rsidtbl",
+    "generator",
+    "xmlnstbl",
+    "mmathPr",
+    "filetbl",
+    "revtbl",
+    "header",
+    "headerl",
+    "headerr",
+    "headerf",
+    "footer",
+    "footerl",
+    "footerr",
+    "footerf",
+];
+
+/// Loader for Rich Text Format(.rtf) documents.
+///
+/// Control words are stripped, `\'hh` escapes are decoded with the `\ansicpg` code page
+/// and `\uN` unicode escapes are supported.
+#[derive(Debug)]
+pub struct RtfLoader {
+    exts: Vec<String>,
+}
+
+impl Default for RtfLoader {
+    fn default() -> Self {
+        Self {
+            exts: RTF_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for RtfLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let file = File::open(path)?;
+        self.load_file_max(&file, max_load_chars)
+    }
+    fn load_file_max(&self, mut file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        if !data.starts_with(b"{\\rtf") {
+            return Err(Error::new(ErrorKind::InvalidData, "Not a rtf file"));
+        }
+        Ok(RtfParser::new(&data).parse(max_load_chars))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct GroupState {
+    skip: bool,
+    // Number of fallback chars to skip after \uN
+    uc: usize,
+}
+
+struct RtfParser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    encoding: &'static Encoding,
+    // Bytes of \'hh escapes waiting to be decoded together(multi-byte code pages)
+    pending_bytes: Vec<u8>,
+    // UTF-16 units of \uN escapes waiting to be decoded together(surrogate pairs)
+    pending_units: Vec<u16>,
+    txt: String,
+    // Chars of txt, counted as text is appended to check the max load chars cheaply
+    txt_chars: usize,
+}
+
+impl<'a> RtfParser<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            encoding: WINDOWS_1252,
+            pending_bytes: Vec::new(),
+            pending_units: Vec::new(),
+            txt: String::new(),
+            txt_chars: 0,
+        }
+    }
+
+    fn parse(mut self, max_load_chars: usize) -> String {
+        let mut stack: Vec<GroupState> = Vec::new();
+        let mut state = GroupState { skip: false, uc: 1 };
+        // Fallback chars left to skip after \uN
+        let mut skip_chars = 0;
+
+        while self.pos < self.data.len() {
+            if max_load_chars > 0 && self.txt_chars > max_load_chars {
+                break;
+            }
+            let b = self.data[self.pos];
+            match b {
+                b'{' => {
+                    self.pos += 1;
+                    stack.push(state);
+                    skip_chars = 0;
+                }
+                b'}' => {
+                    self.pos += 1;
+                    self.flush();
+                    state = stack.pop().unwrap_or(state);
+                    skip_chars = 0;
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    let Some(&next) = self.data.get(self.pos) else {
+                        break;
+                    };
+                    if next == b'\'' {
+                        let hex = self
+                            .data
+                            .get(self.pos + 1..self.pos + 3)
+                            .unwrap_or_default();
+                        self.pos += 3;
+                        if skip_chars > 0 {
+                            skip_chars -= 1;
+                            continue;
+                        }
+                        let byte = std::str::from_utf8(hex)
+                            .ok()
+                            .and_then(|h| u8::from_str_radix(h, 16).ok());
+                        if let (Some(byte), false) = (byte, state.skip) {
+                            self.flush_units();
+                            self.pending_bytes.push(byte);
+                        }
+                    } else if next.is_ascii_alphabetic() {
+                        let (word, param) = self.read_control_word();
+                        if skip_chars > 0 {
+                            skip_chars -= 1;
+                            continue;
+                        }
+                        match word.as_str() {
+                            "ansicpg" => {
+                                if let Some(encoding) =
+                                    param.and_then(|p| codepage::to_encoding(p as u16))
+                                {
+                                    self.encoding = encoding;
+                                }
+                            }
+                            "uc" => state.uc = param.unwrap_or(1).max(0) as usize,
+                            "u" => {
+                                if let (Some(p), false) = (param, state.skip) {
+                                    self.flush_bytes();
+                                    self.pending_units.push(p as i16 as u16);
+                                }
+                                skip_chars = state.uc;
+                            }
+                            "bin" => {
+                                self.pos += param.unwrap_or(0).max(0) as usize;
+                            }
+                            "par" | "line" | "row" | "sect" | "page" => self.push_str("\n"),
+                            "tab" | "cell" => self.push_str("\t"),
+                            "emdash" => self.push_str("—"),
+                            "endash" => self.push_str("–"),
+                            "lquote" => self.push_str("‘"),
+                            "rquote" => self.push_str("’"),
+                            "ldblquote" => self.push_str("“"),
+                            "rdblquote" => self.push_str("”"),
+                            "bullet" => self.push_str("•"),
+                            _ if SKIP_DESTINATIONS.contains(&word.as_str()) => state.skip = true,
+                            _ => (),
+                        }
+                        if state.skip {
+                            self.pending_bytes.clear();
+                            self.pending_units.clear();
+                        }
+                    } else {
+                        self.pos += 1;
+                        match next {
+                            // Ignorable destination
+                            b'*' => state.skip = true,
+                            b'~' => self.push_str("\u{00A0}"),
+                            b'_' => self.push_str("-"),
+                            b'\n' | b'\r' => self.push_str("\n"),
+                            b'\\' | b'{' | b'}' => {
+                                let text = (next as char).to_string();
+                                self.push_str(&text);
+                            }
+                            _ => (),
+                        }
+                        if state.skip {
+                            self.pending_bytes.clear();
+                            self.pending_units.clear();
+                        }
+                    }
+                    if state.skip {
+                        // Check again, state may be changed by current control word
+                        self.skip_group(&mut stack, &mut state);
+                    }
+                }
+                b'\r' | b'\n' => self.pos += 1,
+                _ => {
+                    self.pos += 1;
+                    if skip_chars > 0 {
+                        skip_chars -= 1;
+                        continue;
+                    }
+                    if state.skip {
+                        continue;
+                    }
+                    if b.is_ascii() {
+                        self.flush();
+                        self.txt.push(b as char);
+                        self.txt_chars += 1;
+                    } else {
+                        // Raw 8-bit text is in the code page like the \'hh escapes
+                        self.flush_units();
+                        self.pending_bytes.push(b);
+                    }
+                }
+            }
+        }
+        self.flush();
+        self.txt
+    }
+
+    /// Read control word and optional numeric parameter, the delimiter space is consumed
+    fn read_control_word(&mut self) -> (String, Option<i32>) {
+        let start = self.pos;
+        while self.pos < self.data.len() && self.data[self.pos].is_ascii_alphabetic() {
+            self.pos += 1;
+        }
+        let word = String::from_utf8_lossy(&self.data[start..self.pos]).to_string();
+
+        let param_start = self.pos;
+        if self.data.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        while self.pos < self.data.len() && self.data[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        let param = std::str::from_utf8(&self.data[param_start..self.pos])
+            .ok()
+            .and_then(|p| p.parse::<i32>().ok());
+
+        if self.data.get(self.pos) == Some(&b' ') {
+            self.pos += 1;
+        }
+        (word, param)
+    }
+
+    /// Skip the rest of current group, the closing brace is consumed
+    fn skip_group(&mut self, stack: &mut Vec<GroupState>, state: &mut GroupState) {
+        let mut depth = 1;
+        while self.pos < self.data.len() {
+            match self.data[self.pos] {
+                b'\\' => self.pos += 1,
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.pos += 1;
+                        *state = stack.pop().unwrap_or(*state);
+                        return;
+                    }
+                }
+                _ => (),
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.flush();
+        self.append(s);
+    }
+
+    fn append(&mut self, s: &str) {
+        self.txt.push_str(s);
+        self.txt_chars += s.chars().count();
+    }
+
+    fn flush(&mut self) {
+        self.flush_bytes();
+        self.flush_units();
+    }
+
+    fn flush_bytes(&mut self) {
+        if !self.pending_bytes.is_empty() {
+            let (decoded, _, _) = self.encoding.decode(&self.pending_bytes);
+            let decoded = decoded.into_owned();
+            self.append(&decoded);
+            self.pending_bytes.clear();
+        }
+    }
+
+    fn flush_units(&mut self) {
+        if !self.pending_units.is_empty() {
+            let decoded = String::from_utf16_lossy(&self.pending_units);
+            self.append(&decoded);
+            self.pending_units.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let data = RtfLoader::default()
+            .load(Path::new("assets/test_file/example.rtf"))
+            .unwrap();
+        println!("len: {}, data: {}", data.len(), data);
+        assert!(data.contains("Mango Desk rich text"));
+        assert!(data.contains("芒果桌面"));
+        assert!(data.contains("café"));
+        assert!(!data.contains("Times New Roman"));
+        assert!(!data.contains("HYPERLINK"));
+        assert!(data.contains("project page"));
+    }
+
+    #[test]
+    fn read_ansi_code_page() {
+        let data = RtfParser::new(b"{\\rtf1\\ansi\\ansicpg936 \\'c3\\'a2\\'b9\\'fb\\par}").parse(0);
+        assert_eq!(data, "芒果\n");
+    }
+
+    #[test]
+    fn read_raw_high_bytes() {
+        let data = RtfParser::new(b"{\\rtf1\\ansi caf\xe9\\par}").parse(0);
+        assert_eq!(data, "café\n");
+        let data = RtfParser::new(b"{\\rtf1\\ansi\\ansicpg936 \xc3\xa2\\'b9\xfb\\par}").parse(0);
+        assert_eq!(data, "芒果\n");
+    }
+}
@@ -0,0 +1,322 @@
+use crate::global::{JSON_EXTS, TOML_EXTS, YAML_EXTS};
+use crate::traits::document_loader::DocumentLoader;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{Error, ErrorKind, Read};
+use std::path::Path;
+
+/// Loader for JSON documents(.json) and JSON lines(.jsonl/.ndjson).
+///
+/// Every value is flattened into a `key.path: value` line, see `flatten`.
+#[derive(Debug)]
+pub struct JsonLoader {
+    exts: Vec<String>,
+}
+
+/// Loader for YAML documents(.yaml/.yml), multiple documents in a file are supported.
+#[derive(Debug)]
+pub struct YamlLoader {
+    exts: Vec<String>,
+}
+
+/// Loader for TOML documents(.toml).
+#[derive(Debug)]
+pub struct TomlLoader {
+    exts: Vec<String>,
+}
+
+impl Default for JsonLoader {
+    fn default() -> Self {
+        Self {
+            exts: JSON_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl Default for YamlLoader {
+    fn default() -> Self {
+        Self {
+            exts: YAML_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl Default for TomlLoader {
+    fn default() -> Self {
+        Self {
+            exts: TOML_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+macro_rules! impl_structured_loader {
+    ($loader:ty, $parse:ident) => {
+        impl DocumentLoader for $loader {
+            fn get_exts(&self) -> &[String] {
+                &self.exts
+            }
+            fn add_ext(&mut self, ext: String) {
+                self.exts.push(ext);
+            }
+            fn load(&self, path: &Path) -> io::Result<String> {
+                self.load_max(path, 0)
+            }
+            fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+                let file = File::open(path)?;
+                self.load_file_max(&file, max_load_chars)
+            }
+            fn load_file_max(&self, mut file: &File, max_load_chars: usize) -> io::Result<String> {
+                let mut data = String::new();
+                file.read_to_string(&mut data)?;
+                let value = $parse(&data)?;
+                Ok(flatten(&value, max_load_chars))
+            }
+        }
+    };
+}
+
+impl_structured_loader!(JsonLoader, parse_json);
+impl_structured_loader!(YamlLoader, parse_yaml);
+impl_structured_loader!(TomlLoader, parse_toml);
+
+/// A parsed document, the keys of a map keep their order in the document
+enum Node {
+    Scalar(String),
+    List(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+/// Builds a node from JSON, unlike `serde_json::Value` without sorting the keys
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+    fn visit_bool<E>(self, v: bool) -> Result<Node, E> {
+        Ok(Node::Scalar(v.to_string()))
+    }
+    fn visit_i64<E>(self, v: i64) -> Result<Node, E> {
+        Ok(Node::Scalar(v.to_string()))
+    }
+    fn visit_u64<E>(self, v: u64) -> Result<Node, E> {
+        Ok(Node::Scalar(v.to_string()))
+    }
+    fn visit_f64<E>(self, v: f64) -> Result<Node, E> {
+        Ok(Node::Scalar(Value::from(v).to_string()))
+    }
+    fn visit_str<E>(self, v: &str) -> Result<Node, E> {
+        Ok(Node::from(v))
+    }
+    fn visit_unit<E>(self) -> Result<Node, E> {
+        Ok(Node::Scalar("null".to_string()))
+    }
+    fn visit_none<E>(self) -> Result<Node, E> {
+        Ok(Node::Scalar("null".to_string()))
+    }
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Node, D::Error> {
+        Node::deserialize(deserializer)
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Node::List(items))
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Node::Map(entries))
+    }
+}
+
+/// Keep every value on its own line
+impl From<&str> for Node {
+    fn from(s: &str) -> Self {
+        Node::Scalar(s.replace(['\r', '\n'], " "))
+    }
+}
+
+/// A JSON document, or JSON lines as a list of values
+fn parse_json(data: &str) -> io::Result<Node> {
+    if let Ok(node) = serde_json::from_str(data) {
+        return Ok(node);
+    }
+    let lines = data
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<Node>, _>>()
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    Ok(Node::List(lines))
+}
+
+/// One document as it is, multiple documents as a list
+fn parse_yaml(data: &str) -> io::Result<Node> {
+    let mut documents = serde_yaml::Deserializer::from_str(data)
+        .map(|document| {
+            let value = serde_yaml::Value::deserialize(document)?;
+            Ok(yaml_to_node(value))
+        })
+        .collect::<Result<Vec<Node>, serde_yaml::Error>>()
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    match documents.len() {
+        1 => Ok(documents.remove(0)),
+        _ => Ok(Node::List(documents)),
+    }
+}
+
+fn parse_toml(data: &str) -> io::Result<Node> {
+    let table: toml::Table =
+        toml::from_str(data).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    Ok(toml_to_node(toml::Value::Table(table)))
+}
+
+/// YAML allows non-string keys and tags, keys are converted to strings
+fn yaml_to_node(value: serde_yaml::Value) -> Node {
+    match value {
+        serde_yaml::Value::Null => Node::Scalar("null".to_string()),
+        serde_yaml::Value::Bool(b) => Node::Scalar(b.to_string()),
+        serde_yaml::Value::Number(n) => Node::Scalar(n.to_string()),
+        serde_yaml::Value::String(s) => Node::from(s.as_str()),
+        serde_yaml::Value::Sequence(items) => {
+            Node::List(items.into_iter().map(yaml_to_node).collect())
+        }
+        serde_yaml::Value::Mapping(mapping) => Node::Map(
+            mapping
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = match yaml_to_node(key) {
+                        Node::Scalar(s) => s,
+                        _ => String::new(),
+                    };
+                    (key, yaml_to_node(value))
+                })
+                .collect(),
+        ),
+        serde_yaml::Value::Tagged(tagged) => yaml_to_node(tagged.value),
+    }
+}
+
+fn toml_to_node(value: toml::Value) -> Node {
+    match value {
+        toml::Value::String(s) => Node::from(s.as_str()),
+        toml::Value::Integer(i) => Node::Scalar(i.to_string()),
+        toml::Value::Float(f) => Node::Scalar(Value::from(f).to_string()),
+        toml::Value::Boolean(b) => Node::Scalar(b.to_string()),
+        toml::Value::Datetime(dt) => Node::Scalar(dt.to_string()),
+        toml::Value::Array(items) => Node::List(items.into_iter().map(toml_to_node).collect()),
+        toml::Value::Table(table) => Node::Map(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_node(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// One `key.path: value` line for every scalar, e.g. `servers[0].host: localhost`,
+/// so that a chunk keeps the context of its values.
+fn flatten(node: &Node, max_load_chars: usize) -> String {
+    let mut lines = Vec::new();
+    let mut total_chars = 0;
+    flatten_into(node, "", &mut lines, &mut total_chars, max_load_chars);
+    lines.join("\n")
+}
+
+/// Return false if max_load_chars is reached
+fn flatten_into(
+    node: &Node,
+    path: &str,
+    lines: &mut Vec<String>,
+    total_chars: &mut usize,
+    max_load_chars: usize,
+) -> bool {
+    let scalar = match node {
+        Node::Map(entries) if !entries.is_empty() => {
+            for (key, child) in entries {
+                let child_path = match path.is_empty() {
+                    true => key.clone(),
+                    false => format!("{}.{}", path, key),
+                };
+                if !flatten_into(child, &child_path, lines, total_chars, max_load_chars) {
+                    return false;
+                }
+            }
+            return true;
+        }
+        Node::List(items) if !items.is_empty() => {
+            for (i, child) in items.iter().enumerate() {
+                let child_path = format!("{}[{}]", path, i);
+                if !flatten_into(child, &child_path, lines, total_chars, max_load_chars) {
+                    return false;
+                }
+            }
+            return true;
+        }
+        Node::Map(_) => "{}",
+        Node::List(_) => "[]",
+        Node::Scalar(s) => s.as_str(),
+    };
+    let line = match path.is_empty() {
+        true => scalar.to_string(),
+        false => format!("{}: {}", path, scalar),
+    };
+    *total_chars += line.chars().count() + 1;
+    lines.push(line);
+    max_load_chars == 0 || *total_chars <= max_load_chars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flatten_json() {
+        let value = parse_json(
+            r#"{"name": "mango", "servers": [{"host": "a", "port": 80}], "tags": [], "note": "a\nb"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            flatten(&value, 0),
+            "name: mango\nservers[0].host: a\nservers[0].port: 80\ntags: []\nnote: a b"
+        );
+        // Keys keep their order in the document
+        let value = parse_json(r#"{"zone": 1, "app": {"z": null, "a": 1.5}}"#).unwrap();
+        assert_eq!(flatten(&value, 0), "zone: 1\napp.z: null\napp.a: 1.5");
+        let lines = parse_json("{\"id\": 1}\n{\"id\": 2}\n").unwrap();
+        assert_eq!(flatten(&lines, 0), "[0].id: 1\n[1].id: 2");
+    }
+
+    #[test]
+    fn flatten_yaml_toml() {
+        let value =
+            parse_yaml("name: mango\nlimits:\n  cpu: 2\n  1: one\n---\nname: desk\n").unwrap();
+        assert_eq!(
+            flatten(&value, 0),
+            "[0].name: mango\n[0].limits.cpu: 2\n[0].limits.1: one\n[1].name: desk"
+        );
+        let value = parse_toml(
+            "title = \"mango\"\n[database]\nports = [8000, 8001]\ncreated = 2024-01-02T03:04:05Z\n",
+        )
+        .unwrap();
+        assert_eq!(
+            flatten(&value, 0),
+            "title: mango\ndatabase.ports[0]: 8000\ndatabase.ports[1]: 8001\ndatabase.created: 2024-01-02T03:04:05Z"
+        );
+    }
+}
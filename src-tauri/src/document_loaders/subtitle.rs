@@ -0,0 +1,109 @@
+use crate::global::{DOCUMENT_CHUNK_SIZE, SUBTITLE_EXTS};
+use crate::structs::document_chunk::DocumentChunk;
+use crate::structs::file_metadata::FileMetadata;
+use crate::traits::document_loader::DocumentLoader;
+use crate::utils::{subtitle_util, text_util};
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use tokenizers::Tokenizer;
+
+/// Loader for subtitle files(.srt/.vtt/.ass/.ssa).
+///
+/// Formatting tags are stripped and consecutive cues are merged into chunks,
+/// the start time of the first cue is the chunk locator. The video sharing the stem
+/// of the subtitle file is recorded in the metadata.
+#[derive(Debug)]
+pub struct SubtitleLoader {
+    exts: Vec<String>,
+}
+
+impl Default for SubtitleLoader {
+    fn default() -> Self {
+        Self {
+            exts: SUBTITLE_EXTS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DocumentLoader for SubtitleLoader {
+    fn get_exts(&self) -> &[String] {
+        &self.exts
+    }
+    fn add_ext(&mut self, ext: String) {
+        self.exts.push(ext);
+    }
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.load_max(path, 0)
+    }
+    fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String> {
+        let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let cues = subtitle_util::parse_subtitle(&subtitle_util::read_subtitle(path)?, ext);
+        Ok(truncate(subtitle_util::cues_to_text(&cues), max_load_chars))
+    }
+    fn load_file_max(&self, mut file: &File, max_load_chars: usize) -> io::Result<String> {
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        // The format is detected by the content without the extension
+        let cues = subtitle_util::parse_subtitle(&subtitle_util::decode_subtitle(&data), "");
+        Ok(truncate(subtitle_util::cues_to_text(&cues), max_load_chars))
+    }
+
+    fn split(
+        &self,
+        path: &Path,
+        content: &str,
+        tokenizer: &Tokenizer,
+    ) -> Result<Vec<DocumentChunk>, Box<dyn Error>> {
+        // Archive members have no file to parse again, the loaded text is split as is
+        if !path.is_file() {
+            let chunks = text_util::split_text(content, tokenizer)?;
+            return Ok(chunks.into_iter().map(DocumentChunk::from_text).collect());
+        }
+        // Parse the cues again, the loaded text has lost the timestamps
+        let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let cues = subtitle_util::parse_subtitle(&subtitle_util::read_subtitle(path)?, ext);
+        Ok(subtitle_util::split_cues(
+            &cues,
+            tokenizer,
+            DOCUMENT_CHUNK_SIZE,
+        ))
+    }
+
+    fn load_metadata(&self, path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+        if let Some(video_path) = subtitle_util::find_sibling_video(path) {
+            metadata.video_path = video_path.to_string_lossy().to_string();
+        }
+        Ok(())
+    }
+}
+
+fn truncate(text: String, max_load_chars: usize) -> String {
+    if max_load_chars > 0 {
+        return text.chars().take(max_load_chars).collect();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text_splitter::Characters;
+
+    #[test]
+    fn split_with_timestamps() {
+        let srt = "1\n00:14:32,000 --> 00:14:35,000\nWelcome back\n\n2\n00:14:35,500 --> 00:14:38,000\nToday: borrow checker\n\n3\n00:14:38,000 --> 00:14:40,000\nLet us begin\n";
+        let cues = subtitle_util::parse_subtitle(srt, "srt");
+        let chunks = subtitle_util::split_cues(&cues, &Characters, 40);
+        for chunk in &chunks {
+            println!("{}", chunk.embedding_text());
+        }
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].text, "Welcome back\nToday: borrow checker");
+        assert_eq!(chunks[0].locator.start_time, Some(872.0));
+        assert_eq!(chunks[1].locator.start_time, Some(878.0));
+        assert!(chunks[0].embedding_text().starts_with("time:00:14:32"));
+    }
+}
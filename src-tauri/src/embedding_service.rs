@@ -1,9 +1,13 @@
 use crate::enums::FileContentLanguage;
+use crate::errors::AppError;
+use crate::global::{
+    EN_EMBEDDING_MODEL_ID, INDEXER_SETTING, MULTI_LANG_MODEL_ID, OLLAMA_EMBEDDING_MODEL_PREFIX,
+};
+use crate::structs::indexer_setting::IndexerSetting;
 use crate::utils::app_util::{
     get_english_embedding_path, get_english_tokenizer_path, get_multilingual_embedding_path,
     get_multilingual_tokenizer_path,
 };
-use crate::{errors::AppError, global::INDEXER_SETTING};
 use log::{error, info};
 use ort::{
     session::{Session, builder::GraphOptimizationLevel},
@@ -59,13 +63,21 @@ impl EmbeddingService {
         })
     }
 
-    pub async fn model_name() -> &'static str {
-        let content_language: FileContentLanguage =
-            { INDEXER_SETTING.read().await.file_content_language.clone() };
-        if content_language != FileContentLanguage::English {
-            return "paraphrase-multilingual-MiniLM-L12-v2";
+    pub async fn model_name() -> String {
+        Self::model_name_of(&INDEXER_SETTING.read().await)
+    }
+
+    /// The Ollama embedding model if set, otherwise the local model of the content language
+    pub fn model_name_of(setting: &IndexerSetting) -> String {
+        if !setting.ollama_embedding_model.is_empty() {
+            format!(
+                "{}{}",
+                OLLAMA_EMBEDDING_MODEL_PREFIX, setting.ollama_embedding_model
+            )
+        } else if setting.file_content_language != FileContentLanguage::English {
+            MULTI_LANG_MODEL_ID.to_string()
         } else {
-            return "all-minilm-l6-v2";
+            EN_EMBEDDING_MODEL_ID.to_string()
         }
     }
 
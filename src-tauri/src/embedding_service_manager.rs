@@ -1,5 +1,7 @@
 use crate::embedding_service::EmbeddingService;
 use crate::errors::AppError;
+use crate::global::INDEXER_SETTING;
+use crate::model_platform_services::ollama::Ollama;
 use std::sync::OnceLock;
 use std::time::Instant;
 use tokio::sync::RwLock as AsyncRwLock;
@@ -60,7 +62,13 @@ impl EmbeddingServiceManager {
         self.service = None;
     }
 
+    /// Embedded by the Ollama embedding model of the indexer setting if set, otherwise by the local model
     pub async fn embed(&mut self, text: &str) -> Result<Vec<f32>, AppError> {
+        let ollama_model = INDEXER_SETTING.read().await.ollama_embedding_model.clone();
+        if !ollama_model.is_empty() {
+            let mut embeddings = Ollama::new().await.embed(&ollama_model, &[text]).await?;
+            return Ok(embeddings.remove(0));
+        }
         let service = self.service().await?;
         let result = service.embed(text);
         self.update_last_used();
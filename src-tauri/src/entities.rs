@@ -1,4 +1,4 @@
-use crate::enums::{FileIndexStatus, IndexingTaskStatus};
+use crate::enums::{FileIndexStatus, IndexingTaskStatus, ModelPlatformName, SearchMode};
 use crate::structs::file_metadata::FileMetadata;
 use crate::utils::datetime_util;
 use chrono::{DateTime, Local};
@@ -12,6 +12,7 @@ pub struct Config {
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct ModelPlatform {
     pub id: i64,
     pub name: String,
@@ -22,6 +23,9 @@ pub struct ModelPlatform {
     pub remark: String,
     pub is_proxy_enable: bool,
     pub is_openai_api_compatible: bool,
+    pub is_enable: bool,
+    pub max_concurrency: i32,     // Requests in flight at the same time
+    pub requests_per_minute: i32, // 0: unlimited
     #[serde(with = "datetime_util")]
     pub create_time: DateTime<Local>,
     #[serde(with = "datetime_util")]
@@ -40,6 +44,9 @@ impl Default for ModelPlatform {
             remark: "".to_string(),
             is_proxy_enable: false,
             is_openai_api_compatible: false,
+            is_enable: true,
+            max_concurrency: 2,
+            requests_per_minute: 0,
             create_time: Local::now(),
             update_time: Local::now(),
         }
@@ -47,12 +54,18 @@ impl Default for ModelPlatform {
 }
 
 impl ModelPlatform {
-    pub fn is_enable(&self) -> bool {
-        !self.api_key.is_empty()
+    /// Enabled and configured, e.g. the API key is set for the platforms requiring it.
+    /// Custom OpenAI compatible platforms may be local servers(LM Studio, vLLM) used without a key
+    pub fn is_available(&self) -> bool {
+        self.is_enable
+            && (!self.api_key.is_empty()
+                || self.is_openai_api_compatible
+                || !ModelPlatformName::from(&self.name).requires_api_key())
     }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct AiModel {
     pub id: i64,
     pub name: String,
@@ -76,6 +89,84 @@ pub struct AiModel {
     pub update_time: DateTime<Local>,
 }
 
+impl Default for AiModel {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            name: "".to_string(),
+            title: "".to_string(),
+            remark: "".to_string(),
+            model_types: "text".to_string(),
+            setting: "".to_string(),
+            platform: "".to_string(),
+            context_window: 0,
+            max_input_tokens: 0,
+            max_output_tokens: 0,
+            input_types: "text".to_string(),
+            properties: "{}".to_string(),
+            is_reasoner: false,
+            is_thinking_closable: false,
+            is_free: false,
+            is_enable: true,
+            create_time: Local::now(),
+            update_time: Local::now(),
+        }
+    }
+}
+
+/// Usage of one remote model call
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ModelUsage {
+    pub id: i64,
+    pub task_id: i64,
+    pub platform: String,
+    pub model: String,
+    pub operation: String, // image, audio, chat
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub audio_seconds: f64,
+    pub image_count: i64,
+    pub cost: f64,
+}
+
+/// A tag managed by the user, files of a tag make a collection
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub file_count: i64, // Files linked to the tag, counted when listed
+    pub create_time: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SearchHistory {
+    pub id: i64,
+    pub query: String,
+    pub mode: SearchMode,
+    pub result_count: i64,
+    pub create_time: String,
+}
+
+/// A named search to run again, a smart folder has its result count refreshed as files are indexed
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SavedSearch {
+    pub id: i64, // 0: not saved yet
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub filters: String, // Inline filters, e.g. `type:image tag:trip`
+    #[serde(default)]
+    pub mode: SearchMode,
+    #[serde(default)]
+    pub is_smart_folder: bool,
+    #[serde(default)]
+    pub result_count: i64,
+    #[serde(default)]
+    pub refresh_time: String,
+    #[serde(default)]
+    pub create_time: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FileInfo {
     pub id: i64,
@@ -35,18 +35,20 @@ pub enum ModelPlatformName {
     SiliconFlow,
     DashScope,
     DeepSeek,
+    Ollama,
     OpenAiCompatable,
 }
 
 impl ModelPlatformName {
+    /// Names other than the built-in ones belong to user-defined OpenAI compatible platforms
     pub fn from(value: &str) -> Self {
         match value {
             "openai" => ModelPlatformName::OpenAi,
             "siliconflow" => ModelPlatformName::SiliconFlow,
             "dashscope" => ModelPlatformName::DashScope,
             "deepseek" => ModelPlatformName::DeepSeek,
-            "opeai_compatible" => ModelPlatformName::OpenAiCompatable,
-            _ => ModelPlatformName::OpenAi,
+            "ollama" => ModelPlatformName::Ollama,
+            _ => ModelPlatformName::OpenAiCompatable,
         }
     }
     pub fn text(self) -> &'static str {
@@ -55,9 +57,18 @@ impl ModelPlatformName {
             ModelPlatformName::SiliconFlow => "siliconflow",
             ModelPlatformName::DashScope => "dashscope",
             ModelPlatformName::DeepSeek => "deepseek",
+            ModelPlatformName::Ollama => "ollama",
             ModelPlatformName::OpenAiCompatable => "opeai_compatible",
         }
     }
+    /// Names reserved by the built-in platforms
+    pub fn is_builtin(value: &str) -> bool {
+        !matches!(Self::from(value), ModelPlatformName::OpenAiCompatable)
+    }
+    /// Local platforms such as Ollama are used without an API key
+    pub fn requires_api_key(self) -> bool {
+        !matches!(self, ModelPlatformName::Ollama)
+    }
 }
 
 // Model type: text, image, vision, embedding, rerank, asr, tts
@@ -249,6 +260,43 @@ impl From<FileContentLanguage> for &'static str {
     }
 }
 
+/// Kind of a remote model call recorded in the usage
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ModelOperation {
+    Image,
+    Audio,
+    Chat,
+}
+
+impl ModelOperation {
+    pub fn text(self) -> &'static str {
+        match self {
+            ModelOperation::Image => "image",
+            ModelOperation::Audio => "audio",
+            ModelOperation::Chat => "chat",
+        }
+    }
+}
+
+/// The period a usage budget applies to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Daily,
+    #[default]
+    Monthly,
+}
+
+/// What a local model of the registry is used for
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LocalModelKind {
+    Embedding,
+    Reranker,
+    Ocr,
+    Asr,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum IndexingTaskStatus {
@@ -308,6 +356,48 @@ impl Display for IndexingTaskStatus {
     }
 }
 
+/// The search a query went through, recorded with the search history and saved searches
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    #[default]
+    Intent, // Path, semantic or both by the intent detected from the query
+    Path,
+    Semantic,
+}
+
+impl From<&str> for SearchMode {
+    fn from(s: &str) -> Self {
+        match s {
+            "path" => SearchMode::Path,
+            "semantic" => SearchMode::Semantic,
+            _ => SearchMode::Intent,
+        }
+    }
+}
+
+impl From<SearchMode> for &'static str {
+    fn from(mode: SearchMode) -> Self {
+        match mode {
+            SearchMode::Intent => "intent",
+            SearchMode::Path => "path",
+            SearchMode::Semantic => "semantic",
+        }
+    }
+}
+
+impl ToSql for SearchMode {
+    fn to_sql(&self) -> SqlResult<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(<&'static str>::from(*self)))
+    }
+}
+
+impl FromSql for SearchMode {
+    fn column_result(value: rusqlite::types::ValueRef) -> SqlResult<Self, FromSqlError> {
+        Ok(SearchMode::from(value.as_str()?))
+    }
+}
+
 // Communication events with front-end
 
 #[derive(Clone, Serialize)]
@@ -339,6 +429,20 @@ pub enum IndexingEvent {
     Finish { task_id: i64, msg: String },
 }
 
+#[derive(Clone, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "event",
+    content = "data"
+)]
+pub enum SmartFolderEvent {
+    Refresh {
+        saved_search_id: i64,
+        result_count: i64,
+    },
+}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 pub enum CommandResultCode {
     ERROR,
@@ -408,3 +512,94 @@ pub enum SearchSource {
     Path,
     Semantic,
 }
+
+// Programming language of source code files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Go,
+    Java,
+    Kotlin,
+    C,
+    Cpp,
+    CSharp,
+    Swift,
+    Php,
+    Sql,
+    Shell,
+}
+
+impl CodeLanguage {
+    pub const ALL: [CodeLanguage; 14] = [
+        CodeLanguage::Rust,
+        CodeLanguage::Python,
+        CodeLanguage::JavaScript,
+        CodeLanguage::TypeScript,
+        CodeLanguage::Go,
+        CodeLanguage::Java,
+        CodeLanguage::Kotlin,
+        CodeLanguage::C,
+        CodeLanguage::Cpp,
+        CodeLanguage::CSharp,
+        CodeLanguage::Swift,
+        CodeLanguage::Php,
+        CodeLanguage::Sql,
+        CodeLanguage::Shell,
+    ];
+
+    pub fn from_ext(ext: &str) -> Option<Self> {
+        CodeLanguage::ALL
+            .into_iter()
+            .find(|language| language.exts().contains(&ext))
+    }
+
+    /// Accept both language name and extension, e.g. `python` or `py`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        CodeLanguage::ALL
+            .into_iter()
+            .find(|language| language.text() == name)
+            .or_else(|| CodeLanguage::from_ext(&name))
+    }
+
+    pub fn text(self) -> &'static str {
+        match self {
+            CodeLanguage::Rust => "rust",
+            CodeLanguage::Python => "python",
+            CodeLanguage::JavaScript => "javascript",
+            CodeLanguage::TypeScript => "typescript",
+            CodeLanguage::Go => "go",
+            CodeLanguage::Java => "java",
+            CodeLanguage::Kotlin => "kotlin",
+            CodeLanguage::C => "c",
+            CodeLanguage::Cpp => "cpp",
+            CodeLanguage::CSharp => "csharp",
+            CodeLanguage::Swift => "swift",
+            CodeLanguage::Php => "php",
+            CodeLanguage::Sql => "sql",
+            CodeLanguage::Shell => "shell",
+        }
+    }
+
+    pub fn exts(self) -> &'static [&'static str] {
+        match self {
+            CodeLanguage::Rust => &["rs"],
+            CodeLanguage::Python => &["py", "pyw"],
+            CodeLanguage::JavaScript => &["js", "jsx", "mjs", "cjs"],
+            CodeLanguage::TypeScript => &["ts", "tsx", "mts", "cts"],
+            CodeLanguage::Go => &["go"],
+            CodeLanguage::Java => &["java"],
+            CodeLanguage::Kotlin => &["kt", "kts"],
+            CodeLanguage::C => &["c", "h"],
+            CodeLanguage::Cpp => &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+            CodeLanguage::CSharp => &["cs"],
+            CodeLanguage::Swift => &["swift"],
+            CodeLanguage::Php => &["php"],
+            CodeLanguage::Sql => &["sql"],
+            CodeLanguage::Shell => &["sh", "bash", "zsh"],
+        }
+    }
+}
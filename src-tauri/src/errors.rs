@@ -45,6 +45,8 @@ pub enum AppError {
     OpenAiError(#[from] async_openai::error::OpenAIError),
     #[error("HTTP request failed: {0}")]
     RequestError(#[from] reqwest::Error),
+    #[error("Remote call failed: {0}")]
+    RemoteCallError(String),
     #[error("Model platform not found: {0}")]
     ModelPlatformNotFound(String),
     #[error("Image analysis not supported by: {0}")]
@@ -55,10 +57,20 @@ pub enum AppError {
     UnsupportedVideoAnalyze(String),
     #[error("AI model not found: {0}")]
     AiModelNotFound(String),
+    #[error("Local model not found: {0}")]
+    LocalModelNotFound(String),
+    #[error("Download failed: {0}")]
+    DownloadError(String),
+    #[error("Workspace not found: {0}")]
+    WorkspaceNotFound(String),
+    #[error("Index bundle error: {0}")]
+    IndexBundleError(String),
     #[error("Unsupported audio format: {0}")]
     UnsupportedAudioFormat(String),
     #[error("Operation failed: {0}")]
     OperationFailed(String),
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
     #[error("Repository error: {0}")]
     RepositoryError(#[from] RepositoryError),
     #[error("Send error: {0}")]
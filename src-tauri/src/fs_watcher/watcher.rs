@@ -4,17 +4,26 @@ use crate::global::{CONFIG_NAME_WATCHER_SETTING, EXIT_APP_SIGNAL, FS_WATCHER_SET
 use crate::indexer_service;
 use crate::repositories::{config_repo, file_info_repo};
 use crate::searcher::path_search_engine;
+use crate::utils::saved_search_util;
 use anyhow::Result;
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use std::path::Path;
-use std::sync::OnceLock;
-use std::sync::atomic::Ordering;
-use std::{collections::HashMap, path::PathBuf, time::Duration};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex as StdMutex, OnceLock};
+use std::{collections::HashMap, future::Future, path::PathBuf, time::Duration};
 use tokio::sync::RwLock as AsyncRwLock;
-use tokio::sync::mpsc;
 use tokio::sync::mpsc::Receiver;
+use tokio::sync::{Mutex as AsyncMutex, mpsc};
+use tokio::task::JoinHandle;
 
 pub static WATCHER: OnceLock<AsyncRwLock<RecommendedWatcher>> = OnceLock::new();
+// Set while the workspace is switched, the received events are dropped
+static PAUSED: AtomicBool = AtomicBool::new(false);
+// Held while the pending events are processed
+static FLUSH_LOCK: AsyncMutex<()> = AsyncMutex::const_new(());
+// Tasks spawned for the events, e.g. indexing a new file
+static EVENT_TASKS: LazyLock<StdMutex<Vec<JoinHandle<()>>>> =
+    LazyLock::new(|| StdMutex::new(Vec::new()));
 
 pub async fn init_after_ui_mounted() -> Result<()> {
     log::info!("init file watcher");
@@ -33,12 +42,7 @@ pub async fn init_after_ui_mounted() -> Result<()> {
     WATCHER.set(AsyncRwLock::new(watcher)).map_err(|_| {
         anyhow::anyhow!("Failed to initialize watcher - it may already be initialized")
     })?;
-    for path in FS_WATCHER_SETTING.read().await.directories.iter() {
-        watch_path(path).await?;
-    }
-    for path in FS_WATCHER_SETTING.read().await.files.iter() {
-        watch_path(path).await?;
-    }
+    watch_all().await?;
     let mut fs_event_normalizer = FsEventNormalizer::new();
     // debounce duration, adjust as needed
     tokio::spawn(async move {
@@ -81,6 +85,68 @@ pub async fn remove_path(path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Watch the directories and files of the watcher setting
+pub async fn watch_all() -> Result<()> {
+    for path in FS_WATCHER_SETTING.read().await.directories.iter() {
+        watch_path(path).await?;
+    }
+    for path in FS_WATCHER_SETTING.read().await.files.iter() {
+        watch_path(path).await?;
+    }
+    Ok(())
+}
+
+/// Stop watching the directories and files of the watcher setting, which is kept as is
+pub async fn unwatch_all() -> Result<()> {
+    for path in FS_WATCHER_SETTING.read().await.directories.iter() {
+        unwatch_path(path).await?;
+    }
+    for path in FS_WATCHER_SETTING.read().await.files.iter() {
+        unwatch_path(path).await?;
+    }
+    Ok(())
+}
+
+/// Stop watching and drop the events not processed yet, the tasks of the processed ones are cancelled and awaited.
+/// Nothing is written into the database until `resume`, e.g. the one of another workspace
+pub async fn pause() -> Result<()> {
+    PAUSED.store(true, Ordering::SeqCst);
+    let result = unwatch_all().await;
+    // Waits for the events being processed
+    drop(FLUSH_LOCK.lock().await);
+    let tasks: Vec<JoinHandle<()>> = match EVENT_TASKS.lock() {
+        Ok(mut tasks) => tasks.drain(..).collect(),
+        Err(_) => Vec::new(),
+    };
+    for task in tasks {
+        task.abort();
+        if let Err(e) = task.await {
+            if !e.is_cancelled() {
+                log::error!("File watcher task failed: {}", e);
+            }
+        }
+    }
+    result
+}
+
+/// Watch the paths of the watcher setting again after `pause`
+pub async fn resume() -> Result<()> {
+    let result = watch_all().await;
+    PAUSED.store(false, Ordering::SeqCst);
+    result
+}
+
+fn spawn_event_task(task: impl Future<Output = ()> + Send + 'static) {
+    let handle = tokio::task::spawn(task);
+    match EVENT_TASKS.lock() {
+        Ok(mut tasks) => {
+            tasks.retain(|task| !task.is_finished());
+            tasks.push(handle);
+        }
+        Err(e) => log::error!("Failed to track file watcher task: {}", e),
+    }
+}
+
 async fn watch_path(path: &str) -> Result<()> {
     log::info!("Watching {}", path.to_string());
     if let Some(watcher) = WATCHER.get() {
@@ -124,6 +190,7 @@ async fn fs_event_loop(mut rx: Receiver<Event>, fs_event_normalizer: &mut FsEven
         tokio::select! {
             maybe_ev = rx.recv() => {
                 match maybe_ev {
+                    Some(_) if PAUSED.load(Ordering::SeqCst) => {}
                     Some(ev) => {
                         let fs_event = fs_event_normalizer.handle(ev);
                         aggregate_event(&mut pending, fs_event);
@@ -137,7 +204,12 @@ async fn fs_event_loop(mut rx: Receiver<Event>, fs_event_normalizer: &mut FsEven
                 }
             }
             _ = tokio::time::sleep(debounce_duration) => {
-                flush_pending(&mut pending);
+                let _guard = FLUSH_LOCK.lock().await;
+                if PAUSED.load(Ordering::SeqCst) {
+                    pending.clear();
+                } else {
+                    flush_pending(&mut pending);
+                }
             }
         }
     }
@@ -233,14 +305,14 @@ fn flush_pending(pending: &mut HashMap<PathBuf, FsEvent>) {
                         log::error!("Failed to remove directory index: {}", error);
                     })
                 }
-                tokio::task::spawn(async move {
+                spawn_event_task(async move {
                     path_search_engine::remove_from_index(&path, is_file).await;
                 });
             }
             FsEvent::Rename { from, to } => {
                 let from_path = from.to_string_lossy().to_string();
                 let is_file = to.is_file();
-                tokio::task::spawn({
+                spawn_event_task({
                     let delete_path = from_path.clone();
                     async move {
                         path_search_engine::remove_from_index(&delete_path, is_file).await;
@@ -262,14 +334,20 @@ fn flush_pending(pending: &mut HashMap<PathBuf, FsEvent>) {
                                     log::error!("Failed to rename file: {}", error);
                                     0
                                 });
+                            file_info_repo::rename_members(&from_path, &target_path)
+                                .unwrap_or_else(|error| {
+                                    log::error!("Failed to rename file members: {}", error);
+                                    0
+                                });
                         }
                         None => {
-                            tokio::task::spawn(async move {
+                            spawn_event_task(async move {
                                 indexer_service::index_file(&target_path)
                                     .await
                                     .unwrap_or_else(|error| {
                                         log::error!("Failed to index new file: {}", error);
                                     });
+                                saved_search_util::refresh_smart_folders_later();
                             });
                         }
                     }
@@ -277,13 +355,14 @@ fn flush_pending(pending: &mut HashMap<PathBuf, FsEvent>) {
                     log::info!("Rename directory: {} -> {}", from_path, target_path);
                     let count = file_info_repo::count_by_prefix_path(&from_path).unwrap_or(0);
                     if count == 0 {
-                        tokio::task::spawn(async move {
+                        spawn_event_task(async move {
                             indexer_service::background_indexing(target_path.as_str())
                                 .await
                                 .unwrap_or_else(|error: String| {
                                     log::error!("Failed to index new directory: {}", error);
                                     false
                                 });
+                            saved_search_util::refresh_smart_folders_later();
                         });
                     } else {
                         file_info_repo::replace_directory_prefix_path(&from_path, &target_path)
@@ -296,12 +375,13 @@ fn flush_pending(pending: &mut HashMap<PathBuf, FsEvent>) {
             }
             FsEvent::Create(create_path) => {
                 if create_path.is_file() {
-                    tokio::task::spawn(async move {
+                    spawn_event_task(async move {
                         indexer_service::index_file(&path)
                             .await
                             .unwrap_or_else(|error| {
                                 log::error!("Failed to index new file: {}", error);
                             });
+                        saved_search_util::refresh_smart_folders_later();
                     });
                 } else {
                     log::info!("Create directory: {}", path);
@@ -309,12 +389,13 @@ fn flush_pending(pending: &mut HashMap<PathBuf, FsEvent>) {
             }
             FsEvent::Modify(modify_path) => {
                 if modify_path.is_file() {
-                    tokio::task::spawn(async move {
+                    spawn_event_task(async move {
                         indexer_service::index_file(&path)
                             .await
                             .unwrap_or_else(|error| {
                                 log::error!("Failed to index modified file: {}", error);
-                            })
+                            });
+                        saved_search_util::refresh_smart_folders_later();
                     });
                 }
             }
@@ -323,5 +404,7 @@ fn flush_pending(pending: &mut HashMap<PathBuf, FsEvent>) {
             }
         }
     }
+    // Removed and renamed files change the results as well
+    saved_search_util::refresh_smart_folders_later();
     println!("--------------------------");
 }
@@ -1,32 +1,54 @@
+use crate::document_loaders::archive::ArchiveLoader;
+use crate::document_loaders::code::CodeLoader;
+use crate::document_loaders::csv::CsvLoader;
+use crate::document_loaders::doc::DocLoader;
 use crate::document_loaders::docx::DocxLoader;
+use crate::document_loaders::email::EmailLoader;
+use crate::document_loaders::epub::EpubLoader;
 use crate::document_loaders::excel::ExcelLoader;
+use crate::document_loaders::html::HtmlLoader;
+use crate::document_loaders::markdown::MarkdownLoader;
+use crate::document_loaders::mbox::MboxLoader;
+use crate::document_loaders::notebook::NotebookLoader;
 use crate::document_loaders::odp::OdpLoader;
 use crate::document_loaders::odt::OdtLoader;
 use crate::document_loaders::pdf::PdfLoader;
 use crate::document_loaders::plain_text::PlainTextLoader;
+use crate::document_loaders::ppt::PptLoader;
 use crate::document_loaders::pptx::PptxLoader;
+use crate::document_loaders::rtf::RtfLoader;
+use crate::document_loaders::structured::{JsonLoader, TomlLoader, YamlLoader};
+use crate::document_loaders::subtitle::SubtitleLoader;
 use crate::entities::ModelPlatform;
+use crate::enums::SmartFolderEvent;
 use crate::structs::fs_watcher_setting::FsWatcherSetting;
 use crate::structs::indexer_setting::IndexerSetting;
 use crate::structs::indexing_summary::IndexingSummary;
 use crate::structs::proxy_setting::ProxyInfo;
+use crate::structs::search_history_setting::SearchHistorySetting;
 use crate::traits::document_loader::DocumentLoader;
 use chrono::{DateTime, Local};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, AtomicUsize};
-use std::sync::{Arc, LazyLock, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize};
+use std::sync::{Arc, LazyLock, OnceLock, RwLock as StdRwLock};
+use tauri::ipc::Channel;
 use tokio::sync::RwLock as AsyncRwLock;
 
-pub const DB_VERSION: i32 = 1;
+pub const DB_VERSION: i32 = 9;
 
 pub const HUGGINFACE_WEBSITE: &str = "https://huggingface.co";
 pub const HUGGINFACE_MIRROR: &str = "https://hf-mirror.com";
-// multi-language embedding model(384 dimensions): https://huggingface.co/sentence-transformers/paraphrase-multilingual-MiniLM-L12-v2/resolve/main/onnx/model.onnx
-// web url: https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2/blob/main/onnx/model.onnx
-// multi-language tokenzier: https://huggingface.co/sentence-transformers/paraphrase-multilingual-MiniLM-L12-v2/resolve/main/tokenizer.json
-// web url: https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2/blob/main/tokenizer.json
-pub const MULTI_LANG_MODEL_URL: &str = "https://huggingface.co/sentence-transformers/paraphrase-multilingual-MiniLM-L12-v2/resolve/main/onnx/model.onnx";
-pub const MULTI_LANG_TOKENIZER_URL: &str = "https://huggingface.co/sentence-transformers/paraphrase-multilingual-MiniLM-L12-v2/resolve/main/tokenizer.json";
+// Local models(embedding, reranker, OCR, ASR) are listed in assets/local_models.json,
+// more can be added by a manifest with the same name in the model directory.
+// The built-in files pin their size and sha256, except the multilingual model which is still to be pinned
+pub const LOCAL_MODEL_MANIFEST_NAME: &str = "local_models.json";
+// multi-language embedding model(384 dimensions) of the registry, downloaded into the model directory
+pub const MULTI_LANG_MODEL_ID: &str = "paraphrase-multilingual-MiniLM-L12-v2";
+// built-in English embedding model(384 dimensions)
+pub const EN_EMBEDDING_MODEL_ID: &str = "all-minilm-l6-v2";
+// Dimension of the vector tables, an Ollama embedding model must match it(e.g. all-minilm, snowflake-arctic-embed:33m)
+pub const EMBEDDING_DIMENSION: usize = 384;
+pub const OLLAMA_EMBEDDING_MODEL_PREFIX: &str = "ollama:";
 // assets/model/model.onnx
 pub static MULTI_LANG_EMBEDDING_PATH: OnceLock<String> = OnceLock::new();
 // assets/model/tokenizer.json
@@ -42,25 +64,37 @@ pub const CONFIG_NAME_PROXY: &'static str = "proxy";
 pub const CONFIG_NAME_INDEXER_SETTING: &'static str = "indexer_setting";
 pub const CONFIG_NAME_WATCHER_SETTING: &'static str = "fs_watcher_setting";
 pub const CONFIG_NAME_ACTIVE_LOCALE: &'static str = "active_locale";
+pub const CONFIG_NAME_SEARCH_HISTORY_SETTING: &str = "search_history_setting";
+
+// Workspaces have their own database(indexer setting, watched paths and embedding model included),
+// the default one is storage/mango-desk.db, the others are storage/workspaces/{name}/mango-desk.db
+pub const DB_FILE_NAME: &str = "mango-desk.db";
+pub const DEFAULT_WORKSPACE: &str = "default";
+pub const WORKSPACE_DIR_NAME: &str = "workspaces";
+pub const WORKSPACE_RECORD_NAME: &str = ".workspace"; // Name of the active workspace, in the storage directory
+pub const WORKSPACE_NAME_MAX_CHARS: usize = 50;
+pub static ACTIVE_WORKSPACE: LazyLock<AsyncRwLock<String>> =
+    LazyLock::new(|| AsyncRwLock::new(DEFAULT_WORKSPACE.to_string()));
 
 pub static APP_DATA_PATH: LazyLock<AsyncRwLock<String>> =
     LazyLock::new(|| AsyncRwLock::new("".to_string()));
 pub static ONNX_EXEC_PROVIDERS_INITIALIZED: OnceLock<bool> = OnceLock::new();
 pub static HOME_PATH: OnceLock<String> = OnceLock::new();
 pub static STORAGE_PATH: OnceLock<String> = OnceLock::new();
-pub static DB_PATH: OnceLock<String> = OnceLock::new();
+// The database of the active workspace, replaced when another workspace is switched to
+pub static DB_PATH: LazyLock<StdRwLock<String>> = LazyLock::new(|| StdRwLock::new(String::new()));
+tokio::task_local! {
+    // Overrides DB_PATH for the repositories called within its scope, e.g. to search another workspace
+    pub static WORKSPACE_DB_PATH: String;
+}
+pub static SECRET_KEY_PATH: OnceLock<String> = OnceLock::new(); // Encrypts the API keys, kept out of the database
 pub static TMP_PATH: OnceLock<String> = OnceLock::new();
 pub static EXIT_APP_SIGNAL: AtomicBool = AtomicBool::new(false);
 pub static ACTIVE_MODEL_PLATFORM: LazyLock<AsyncRwLock<ModelPlatform>> =
     LazyLock::new(|| AsyncRwLock::new(ModelPlatform::default()));
 
-pub static PROXY: LazyLock<AsyncRwLock<ProxyInfo>> = LazyLock::new(|| {
-    AsyncRwLock::new(ProxyInfo {
-        protocal: "".to_string(),
-        host: "".to_string(),
-        port: 0,
-    })
-});
+pub static PROXY: LazyLock<AsyncRwLock<ProxyInfo>> =
+    LazyLock::new(|| AsyncRwLock::new(ProxyInfo::default()));
 pub const DEFAULT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 pub const DEFAULT_DATETIME_MICRO_FORMAT: &str = "%Y-%m-%d %H:%M:%S.%f";
 
@@ -71,6 +105,8 @@ pub static ACTIVE_LOCALE: LazyLock<AsyncRwLock<String>> =
     LazyLock::new(|| AsyncRwLock::new("en-US".to_string()));
 
 pub static DOWNLOADING: AtomicBool = AtomicBool::new(false);
+pub static DOWNLOAD_CANCEL_SIGNAL: AtomicBool = AtomicBool::new(false);
+pub static MODEL_PATH: OnceLock<String> = OnceLock::new(); // Directory of the downloaded local models
 
 // Scanning related
 pub static SCANNING: AtomicBool = AtomicBool::new(false);
@@ -85,15 +121,47 @@ macro_rules! define_document_exts {
     }
 }
 define_document_exts! {
+    ARCHIVE_EXTS: ["zip", "tar", "gz", "tgz"];
+    CODE_EXTS: [
+        "rs", "py", "pyw", "js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "go", "java", "kt",
+        "kts", "c", "h", "cpp", "cc", "cxx", "hpp", "hh", "hxx", "cs", "swift", "php", "sql", "sh",
+        "bash", "zsh"
+    ];
+    CSV_EXTS: ["csv", "tsv"];
+    DOC_EXTS: ["doc"];
     DOCX_EXTS: ["docx"];
+    EMAIL_EXTS: ["eml"];
+    EPUB_EXTS: ["epub"];
     EXCEL_EXTS: ["xlsx", "xls", "xlsm", "xlsb", "xla", "xlam", "ods"];
+    HTML_EXTS: ["html", "htm", "xhtml"];
+    JSON_EXTS: ["json", "jsonl", "ndjson"];
+    MARKDOWN_EXTS: ["md", "mdx", "markdown"];
+    MBOX_EXTS: ["mbox"];
+    NOTEBOOK_EXTS: ["ipynb"];
     ODP_EXTS: ["odp"];
     ODT_EXTS: ["odt"];
     PDF_EXTS: ["pdf"];
+    PPT_EXTS: ["ppt"];
     PPTX_EXTS: ["pptx"];
-    PLAIN_TEXT_EXTS: ["txt", "log", "md", "mdx", "ini"];
+    RTF_EXTS: ["rtf"];
+    SUBTITLE_EXTS: ["srt", "vtt", "ass", "ssa"];
+    TOML_EXTS: ["toml"];
+    YAML_EXTS: ["yaml", "yml"];
+    PLAIN_TEXT_EXTS: ["txt", "log", "ini"];
 }
-pub const SUPPORTED_IMAGE_EXTS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "webp"];
+// Files containing members that are indexed as separate items, see `path_util::split_virtual_path`.
+// A member path joins the container path and the member id by the separator of the container:
+// `archive.mbox#<message-id>` for a mailbox, `bundle.zip!/<member path>` for an archive
+pub const VIRTUAL_CONTAINER_EXTS: &[&str] = &["mbox", "zip", "tar", "gz", "tgz"];
+pub const MAILBOX_MEMBER_SEPARATOR: &str = "#";
+pub const ARCHIVE_MEMBER_SEPARATOR: &str = "!/";
+// The messages of a mailbox are held in memory until they are indexed, the ones beyond the max are skipped
+pub const MBOX_MAX_MESSAGES: usize = 1000;
+pub const SUPPORTED_IMAGE_EXTS: [&str; 9] = [
+    "jpg", "jpeg", "png", "gif", "webp", "heic", "heif", "tif", "tiff",
+];
+// Images accepted by the vision models, the others are only indexed by EXIF/XMP metadata
+pub const VISION_IMAGE_EXTS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "webp"];
 pub const SUPPORTED_AUDIO_EXTS: [&str; 8] =
     ["mp3", "wav", "aac", "flac", "ogg", "m4a", "wma", "amr"];
 pub const SUPPORTED_VIDEO_EXTS: [&str; 4] = ["mp4", "avi", "mov", "mkv"];
@@ -103,12 +171,28 @@ type DocHandler = Arc<dyn DocumentLoader + Send + Sync>;
 pub static EXT_TO_DOC_LOADER: LazyLock<AsyncRwLock<HashMap<String, DocHandler>>> =
     LazyLock::new(|| {
         let loaders: Vec<Arc<dyn DocumentLoader + Send + Sync>> = vec![
+            Arc::new(ArchiveLoader::default()) as DocHandler,
+            Arc::new(CodeLoader::default()) as DocHandler,
+            Arc::new(CsvLoader::default()) as DocHandler,
+            Arc::new(DocLoader::default()) as DocHandler,
             Arc::new(DocxLoader::default()) as DocHandler,
+            Arc::new(EmailLoader::default()) as DocHandler,
+            Arc::new(EpubLoader::default()) as DocHandler,
             Arc::new(ExcelLoader::default()) as DocHandler,
+            Arc::new(HtmlLoader::default()) as DocHandler,
+            Arc::new(JsonLoader::default()) as DocHandler,
+            Arc::new(MarkdownLoader::default()) as DocHandler,
+            Arc::new(MboxLoader::default()) as DocHandler,
+            Arc::new(NotebookLoader::default()) as DocHandler,
             Arc::new(OdpLoader::default()) as DocHandler,
             Arc::new(OdtLoader::default()) as DocHandler,
             Arc::new(PdfLoader::default()) as DocHandler,
+            Arc::new(PptLoader::default()) as DocHandler,
             Arc::new(PptxLoader::default()) as DocHandler,
+            Arc::new(RtfLoader::default()) as DocHandler,
+            Arc::new(SubtitleLoader::default()) as DocHandler,
+            Arc::new(TomlLoader::default()) as DocHandler,
+            Arc::new(YamlLoader::default()) as DocHandler,
             Arc::new(PlainTextLoader::default()) as DocHandler,
         ];
         // key: extension, value: document loader
@@ -125,15 +209,69 @@ pub static EXT_TO_DOC_LOADER: LazyLock<AsyncRwLock<HashMap<String, DocHandler>>>
 pub const DOCUMENT_CHUNK_SIZE: usize = 1024;
 pub const DOCUMENT_CHUNK_OVERLAP: usize = 20;
 pub const MAX_DOCUMENT_LOAD_CHARS: usize = 30000;
+// Source code is chunked by function/class, smaller chunks keep one function per chunk
+pub const CODE_CHUNK_SIZE: usize = 512;
+// Tables are chunked by rows with the header repeated, very big tables are truncated
+pub const TABLE_CHUNK_SIZE: usize = 512;
+pub const TABLE_MAX_ROWS: usize = 5000;
+pub const TABLE_MAX_COLUMNS: usize = 64;
+pub const TABLE_MAX_CELL_CHARS: usize = 200;
+// Outputs of notebook code cells are indexed if enabled in the indexer setting, cut to the max chars
+pub const NOTEBOOK_MAX_OUTPUT_CHARS: usize = 2000;
+// Long audio files are cut into segments below the upload limit of the transcription APIs(25 MB),
+// a 10 minutes segment is about 19 MB as 16 kHz mono 16-bit WAV
+pub const AUDIO_UPLOAD_MAX_SIZE: u64 = 24 * 1024 * 1024;
+pub const AUDIO_SEGMENT_SECONDS: usize = 600;
+pub const AUDIO_SEGMENT_SAMPLE_RATE: u32 = 16000;
+// Remote calls(image analysis, transcription) are retried on 429/5xx with exponential backoff,
+// a Retry-After header sent by the platform wins over the computed delay
+pub const REMOTE_CALL_RETRIES: u32 = 3;
+pub const REMOTE_CALL_BASE_DELAY_MS: u64 = 1000;
+pub const REMOTE_CALL_MAX_DELAY_SECS: u64 = 60;
+// Requested by the connection test of the proxy settings, any HTTP response means the proxy works
+pub const PROXY_TEST_URL: &str = "https://huggingface.co";
+pub const PROXY_TEST_TIMEOUT_SECS: u64 = 10;
+// Remote analysis results are cached by file md5, a bump of the prompt version invalidates the older results.
+// Entries not used for the max days are evicted after an indexing task, the least recently used beyond the max entries as well
+pub const IMAGE_ANALYSIS_PROMPT_VERSION: &str = "image-v1";
+pub const AUDIO_ANALYSIS_PROMPT_VERSION: &str = "audio-v1";
+pub const ANALYSIS_CACHE_MAX_DAYS: i64 = 180;
+pub const ANALYSIS_CACHE_MAX_ENTRIES: i64 = 100_000;
+// Tags are written as `tag:name` in a query, their names have no whitespace
+pub const TAG_NAME_MAX_CHARS: usize = 50;
+// Searches typed within the merge window that refine the previous query replace it in the history,
+// the search box searches as the user types
+pub const SEARCH_HISTORY_MERGE_SECS: i64 = 30;
+pub const SAVED_SEARCH_NAME_MAX_CHARS: usize = 50;
+// Smart folders are refreshed once the watcher has been idle for the delay
+pub const SMART_FOLDER_REFRESH_DELAY_SECS: u64 = 5;
+// An index bundle is a zip of the manifest and a snapshot of the database without secrets
+pub const INDEX_BUNDLE_FORMAT_VERSION: i32 = 1;
+pub const INDEX_BUNDLE_MANIFEST_NAME: &str = "manifest.json";
+pub const INDEX_BUNDLE_DB_NAME: &str = "mango-desk.db";
+// Archive members are extracted with limits to protect against zip bombs
+pub const ARCHIVE_MAX_DEPTH: usize = 3;
+pub const ARCHIVE_MAX_MEMBERS: usize = 1000;
+pub const ARCHIVE_MAX_MEMBER_SIZE: u64 = 64 * 1024 * 1024;
+pub const ARCHIVE_MAX_TOTAL_SIZE: u64 = 1024 * 1024 * 1024;
+pub const ARCHIVE_MAX_RATIO: u64 = 100;
 
 // Indexing related
 pub static INDEXER_SETTING: LazyLock<AsyncRwLock<IndexerSetting>> =
     LazyLock::new(|| AsyncRwLock::new(IndexerSetting::default()));
 pub static INDEXING: AtomicBool = AtomicBool::new(false);
+pub static INDEXING_TASK_ID: AtomicI64 = AtomicI64::new(0); // Model usage is linked to it, 0: no running task
 pub static STOP_INDEX_SIGNAL: AtomicBool = AtomicBool::new(false);
 pub static FS_WATCHER_SETTING: LazyLock<AsyncRwLock<FsWatcherSetting>> =
     LazyLock::new(|| AsyncRwLock::new(FsWatcherSetting::default()));
 
+// Search history and smart folders
+pub static SEARCH_HISTORY_SETTING: LazyLock<AsyncRwLock<SearchHistorySetting>> =
+    LazyLock::new(|| AsyncRwLock::new(SearchHistorySetting::default()));
+pub static SMART_FOLDER_REFRESH_GENERATION: AtomicU64 = AtomicU64::new(0); // Bumped by each refresh request, only the latest one runs
+pub static SMART_FOLDER_CHANNEL: LazyLock<StdRwLock<Option<Channel<SmartFolderEvent>>>> =
+    LazyLock::new(|| StdRwLock::new(None));
+
 // Running indexing task summary
 pub static INDEXING_SUMMARY: LazyLock<AsyncRwLock<IndexingSummary>> =
     LazyLock::new(|| AsyncRwLock::new(IndexingSummary::default()));
@@ -6,14 +6,15 @@ use crate::global::{
     SCANNING_TOTAL, STOP_INDEX_SIGNAL,
 };
 use crate::initializer;
+use crate::model_platform_services::ollama::Ollama;
 use crate::repositories::{
-    config_repo, file_content_embedding_repo, file_info_repo, file_metadata_embedding_repo,
-    indexing_task_repo,
+    config_repo, file_content_chunk_repo, file_content_embedding_repo, file_info_repo,
+    file_metadata_embedding_repo, indexing_task_repo,
 };
 use crate::scanner;
 use crate::structs::indexer_setting::IndexerSetting;
-use crate::traits::indexing_template::IndexingTemplate;
-use crate::utils::{frontend_util, indexing_task_util};
+use crate::traits::indexing_template::{IndexingTemplate, remove_members_index};
+use crate::utils::{analysis_cache_util, frontend_util, indexing_task_util};
 use crate::{embedding_service_manager, indexers};
 use rust_i18n::t;
 use std::path::PathBuf;
@@ -21,9 +22,20 @@ use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use tauri::ipc::Channel;
 
-pub async fn update_indexer_setting(indexer_setting: IndexerSetting) -> Result<usize, String> {
+pub async fn update_indexer_setting(mut indexer_setting: IndexerSetting) -> Result<usize, String> {
     let content_language_changed =
         indexer_setting.file_content_language != INDEXER_SETTING.read().await.file_content_language;
+    indexer_setting.ollama_embedding_model =
+        indexer_setting.ollama_embedding_model.trim().to_string();
+    let ollama_model = &indexer_setting.ollama_embedding_model;
+    if !ollama_model.is_empty()
+        && *ollama_model != INDEXER_SETTING.read().await.ollama_embedding_model
+    {
+        Ollama::new()
+            .await
+            .check_embedding_model(ollama_model)
+            .await?;
+    }
     let json = serde_json::to_string(&indexer_setting).map_err(|e| AppError::SerializeError(e))?;
     let result = config_repo::update_by_name(CONFIG_NAME_INDEXER_SETTING, &json)?;
     initializer::init_setting(
@@ -32,6 +44,7 @@ pub async fn update_indexer_setting(indexer_setting: IndexerSetting) -> Result<u
         &INDEXER_SETTING,
     )
     .await;
+    initializer::init_doc_loaders().await;
     if content_language_changed {
         embedding_service_manager::get_manager()
             .write()
@@ -55,7 +68,7 @@ pub async fn is_embedding_model_changed() -> Result<bool, String> {
                 .first()
                 .map(|item| item.embedding_model.clone())
                 .unwrap_or_default();
-            let embedding_name: &'static str = EmbeddingService::model_name().await;
+            let embedding_name = EmbeddingService::model_name().await;
             if latest != embedding_name {
                 return Ok(true);
             } else {
@@ -98,7 +111,7 @@ pub async fn start_indexing(
     }
     STOP_INDEX_SIGNAL.store(false, Ordering::SeqCst);
     let embedding_model = EmbeddingService::model_name().await;
-    let task = indexing_task_util::task_new(&paths, embedding_model).await?;
+    let task = indexing_task_util::task_new(&paths, &embedding_model).await?;
 
     let task = Arc::new(task);
     let event = Arc::new(on_event);
@@ -134,7 +147,25 @@ pub async fn start_indexing(
     indexing_task_util::summary_to_db().await;
 
     if INDEXER_SETTING.read().await.is_private {
-        println!("--- private mode, skip indexing image and audio ---");
+        println!("--- private mode, index image, audio and video metadata only ---");
+        let mut image_indexer = indexers::image_indexer::ImageIndexer::metadata_only();
+        let _ = image_indexer
+            .process(task.clone(), Some(event.clone()))
+            .await
+            .unwrap_or_else(|e| println!("image metadata indexing error,{}", e));
+        indexing_task_util::summary_to_db().await;
+        let mut audio_indexer = indexers::audio_indexer::AudioIndexer::metadata_only();
+        let _ = audio_indexer
+            .process(task.clone(), Some(event.clone()))
+            .await
+            .unwrap_or_else(|e| println!("audio metadata indexing error,{}", e));
+        indexing_task_util::summary_to_db().await;
+        let mut video_indexer = indexers::video_indexer::VideoIndexer::metadata_only();
+        let _ = video_indexer
+            .process(task.clone(), Some(event.clone()))
+            .await
+            .unwrap_or_else(|e| println!("video metadata indexing error,{}", e));
+        indexing_task_util::summary_to_db().await;
         indexing_finish(
             task.id,
             t!("message.indexing-skip-by-privacy").to_string().as_str(),
@@ -146,7 +177,7 @@ pub async fn start_indexing(
 
     let (enabled, platform_name) = {
         let platform = ACTIVE_MODEL_PLATFORM.read().await;
-        (platform.is_enable(), platform.name.clone())
+        (platform.is_available(), platform.name.clone())
     };
     if !enabled {
         println!(
@@ -184,6 +215,17 @@ pub async fn start_indexing(
         indexing_task_util::summary_to_db().await;
     }
 
+    // Subtitles and container metadata are indexed even without a text model
+    let mut video_indexer = indexers::video_indexer::VideoIndexer::new()
+        .await
+        .unwrap_or_else(|_| indexers::video_indexer::VideoIndexer::metadata_only());
+    println!("video indexing...");
+    let _ = video_indexer
+        .process(task.clone(), Some(event.clone()))
+        .await
+        .unwrap_or_else(|e| println!("video indexing error,{}", e));
+    indexing_task_util::summary_to_db().await;
+
     indexing_finish(task.id, "done", Some(event.clone())).await?;
 
     return Ok(true);
@@ -196,7 +238,7 @@ pub async fn background_indexing(path: &str) -> Result<bool, String> {
     }
     let paths = vec![path.to_string()];
     let embedding_model = EmbeddingService::model_name().await;
-    let task = indexing_task_util::task_new(&paths, embedding_model).await?;
+    let task = indexing_task_util::task_new(&paths, &embedding_model).await?;
     let task = Arc::new(task);
 
     // Scan specified paths and store file metadata in database
@@ -210,13 +252,28 @@ pub async fn background_indexing(path: &str) -> Result<bool, String> {
         .unwrap_or_else(|e| log::error!("Document indexing error,{}", e));
 
     if INDEXER_SETTING.read().await.is_private {
-        log::info!("--- private mode, skip indexing image and audio ---");
+        log::info!("--- private mode, index image, audio and video metadata only ---");
+        let mut image_indexer = indexers::image_indexer::ImageIndexer::metadata_only();
+        let _ = image_indexer
+            .process(task.clone(), None)
+            .await
+            .unwrap_or_else(|e| log::error!("image metadata indexing error,{}", e));
+        let mut audio_indexer = indexers::audio_indexer::AudioIndexer::metadata_only();
+        let _ = audio_indexer
+            .process(task.clone(), None)
+            .await
+            .unwrap_or_else(|e| log::error!("audio metadata indexing error,{}", e));
+        let mut video_indexer = indexers::video_indexer::VideoIndexer::metadata_only();
+        let _ = video_indexer
+            .process(task.clone(), None)
+            .await
+            .unwrap_or_else(|e| log::error!("video metadata indexing error,{}", e));
         return Ok(true);
     }
 
     let (enabled, platform_name) = {
         let platform = ACTIVE_MODEL_PLATFORM.read().await;
-        (platform.is_enable(), platform.name.clone())
+        (platform.is_available(), platform.name.clone())
     };
     if !enabled {
         log::info!(
@@ -250,6 +307,14 @@ pub async fn background_indexing(path: &str) -> Result<bool, String> {
             .unwrap_or_else(|e| log::error!("audio indexing error,{}", e));
     }
 
+    let mut video_indexer = indexers::video_indexer::VideoIndexer::new()
+        .await
+        .unwrap_or_else(|_| indexers::video_indexer::VideoIndexer::metadata_only());
+    let _ = video_indexer
+        .process(task.clone(), None)
+        .await
+        .unwrap_or_else(|e| log::error!("video indexing error,{}", e));
+
     indexing_finish(task.id, "done", None).await?;
     return Ok(true);
 }
@@ -279,13 +344,31 @@ pub async fn index_file(path: &str) -> Result<(), String> {
             document_indexer.embedding_one_file(&file_info).await?;
         }
         FileCategory::Image => {
-            let image_indexer = indexers::image_indexer::ImageIndexer::new().await?;
+            let image_indexer = if INDEXER_SETTING.read().await.is_private {
+                indexers::image_indexer::ImageIndexer::metadata_only()
+            } else {
+                indexers::image_indexer::ImageIndexer::new().await?
+            };
             image_indexer.embedding_one_file(&file_info).await?;
         }
         FileCategory::Audio => {
-            let audio_indexer = indexers::audio_indexer::AudioIndexer::new().await?;
+            let audio_indexer = if INDEXER_SETTING.read().await.is_private {
+                indexers::audio_indexer::AudioIndexer::metadata_only()
+            } else {
+                indexers::audio_indexer::AudioIndexer::new().await?
+            };
             audio_indexer.embedding_one_file(&file_info).await?;
         }
+        FileCategory::Video => {
+            let video_indexer = if INDEXER_SETTING.read().await.is_private {
+                indexers::video_indexer::VideoIndexer::metadata_only()
+            } else {
+                indexers::video_indexer::VideoIndexer::new()
+                    .await
+                    .unwrap_or_else(|_| indexers::video_indexer::VideoIndexer::metadata_only())
+            };
+            video_indexer.embedding_one_file(&file_info).await?;
+        }
         _ => {}
     }
     Ok(())
@@ -299,7 +382,9 @@ pub fn remove_file_index(path: &str) -> Result<(), String> {
     if let Some(file_info) = file_info {
         file_info_repo::delete_by_id(file_info.id)?;
         file_content_embedding_repo::delete_by_file_id(file_info.id)?;
+        file_content_chunk_repo::delete_by_file_id(file_info.id)?;
         file_metadata_embedding_repo::delete_by_file_id(file_info.id)?;
+        remove_members_index(path)?;
     }
     Ok(())
 }
@@ -309,6 +394,7 @@ pub fn remove_directory_index(path: &str) -> Result<(), String> {
         return Ok(());
     }
     file_content_embedding_repo::delete_by_file_prefix_path(path)?;
+    file_content_chunk_repo::delete_by_file_prefix_path(path)?;
     file_metadata_embedding_repo::delete_by_file_prefix_path(path)?;
     file_info_repo::delete_by_prefix_path(path)?;
     Ok(())
@@ -325,6 +411,7 @@ async fn indexing_finish(
     STOP_INDEX_SIGNAL.store(false, Ordering::SeqCst);
 
     indexing_task_util::task_done().await?;
+    analysis_cache_util::evict();
 
     if let Some(event) = event {
         frontend_util::send_to_frontend(
@@ -1,3 +1,4 @@
 pub mod audio_indexer;
 pub mod document_indexer;
 pub mod image_indexer;
+pub mod video_indexer;
@@ -1,18 +1,23 @@
+use crate::embedding_service_manager::get_manager;
 use crate::entities::{AiModel, FileInfo};
 use crate::enums::{FileCategory, ModelPlatformName, ModelType};
-use crate::errors::AppError;
-use crate::global::ACTIVE_MODEL_PLATFORM;
+use crate::errors::{AppError, IndexingError};
+use crate::global::{ACTIVE_MODEL_PLATFORM, AUDIO_ANALYSIS_PROMPT_VERSION, DOCUMENT_CHUNK_SIZE};
 use crate::model_platform_services::openai::OpenAi;
 use crate::model_platform_services::openai_compatible_service::OpenAiCompatibleService;
 use crate::model_platform_services::siliconflow::SiliconFlow;
 use crate::repositories::ai_model_repo;
+use crate::structs::document_chunk::DocumentChunk;
+use crate::structs::file_metadata::FileMetadata;
 use crate::traits::audio_analyzer::AudioAnalyzer;
-use crate::traits::indexing_template::IndexingTemplate;
+use crate::traits::indexing_template::{IndexingTemplate, split_text_content};
+use crate::utils::{analysis_cache_util, audio_util, subtitle_util, usage_util};
+use std::path::Path;
 
 pub struct AudioIndexer {
     category: FileCategory,
-    ai_model: AiModel,
-    model_platform_service: Box<dyn AudioAnalyzer>,
+    // None if audio files are only indexed by their tags
+    analyzer: Option<(AiModel, Box<dyn AudioAnalyzer>)>,
 }
 
 impl<'a> AudioIndexer {
@@ -31,10 +36,14 @@ impl<'a> AudioIndexer {
                 match ModelPlatformName::from(platform_name.as_str()) {
                     ModelPlatformName::OpenAi => Box::new(OpenAi::new().await),
                     ModelPlatformName::SiliconFlow => Box::new(SiliconFlow::new().await),
-                    ModelPlatformName::DashScope | ModelPlatformName::DeepSeek => {
-                        println!("DeepSeek and DashScope do not support audio analysis yet.");
+                    ModelPlatformName::DashScope
+                    | ModelPlatformName::DeepSeek
+                    | ModelPlatformName::Ollama => {
+                        println!(
+                            "DeepSeek, DashScope and Ollama do not support audio analysis yet."
+                        );
                         return Err(AppError::UnsupportedAudioAnalyze(
-                            "Deepseek and Dashscope".to_string(),
+                            "Deepseek, Dashscope and Ollama".to_string(),
                         ));
                     }
                     _ => Box::new(OpenAiCompatibleService::new(&platform_name, &base_url).await),
@@ -42,8 +51,7 @@ impl<'a> AudioIndexer {
 
             return Ok(Self {
                 category: FileCategory::Audio,
-                ai_model,
-                model_platform_service: platform_service,
+                analyzer: Some((ai_model, platform_service)),
             });
         }
         Err(AppError::AiModelNotFound(format!(
@@ -52,6 +60,14 @@ impl<'a> AudioIndexer {
             <&'static str>::from(ModelType::Vision)
         )))
     }
+
+    /// Index the tags only, nothing is sent to the model platform(e.g. in private mode)
+    pub fn metadata_only() -> AudioIndexer {
+        Self {
+            category: FileCategory::Audio,
+            analyzer: None,
+        }
+    }
 }
 
 impl IndexingTemplate for AudioIndexer {
@@ -59,11 +75,34 @@ impl IndexingTemplate for AudioIndexer {
         &self.category
     }
 
+    fn concurrency(&self) -> usize {
+        self.analyzer.as_ref().map_or(1, |(_, platform_service)| {
+            platform_service.platform().max_concurrency.max(1) as usize
+        })
+    }
+
+    async fn is_paused(&self) -> bool {
+        match self.analyzer.as_ref() {
+            Some((_, platform_service)) => usage_util::is_paused(platform_service.platform()).await,
+            None => false,
+        }
+    }
+
     async fn load_content(&self, file_info: &FileInfo) -> String {
-        match self
-            .model_platform_service
-            .analyze_audio(&self.ai_model, &file_info.path)
-            .await
+        let Some((ai_model, platform_service)) = self.analyzer.as_ref() else {
+            // Embedded lyrics are the only local content
+            return audio_util::load_audio_metadata(Path::new(&file_info.path))
+                .map(|audio| audio.lyrics)
+                .unwrap_or_default();
+        };
+        match analysis_cache_util::load_or_analyze(
+            file_info,
+            platform_service.platform(),
+            ai_model,
+            AUDIO_ANALYSIS_PROMPT_VERSION,
+            || platform_service.analyze_audio(ai_model, &file_info.path),
+        )
+        .await
         {
             Ok(content) => content,
             Err(e) => {
@@ -72,4 +111,31 @@ impl IndexingTemplate for AudioIndexer {
             }
         }
     }
+
+    async fn load_metadata(&self, file_info: &FileInfo, file_meta: &mut FileMetadata) {
+        match audio_util::load_audio_metadata(Path::new(&file_info.path)) {
+            Ok(audio) if !audio.is_empty() => file_meta.audio = Some(audio),
+            Ok(_) => (),
+            Err(error) => println!("Load audio metadata error: {}", error),
+        }
+    }
+
+    async fn split_content(
+        &self,
+        _file_info: &FileInfo,
+        content: &str,
+    ) -> Result<Vec<DocumentChunk>, IndexingError> {
+        // Transcripts keep the start time of every segment as the chunk locator, lyrics are plain text
+        let cues = subtitle_util::parse_transcript(content);
+        if cues.is_empty() {
+            return split_text_content(content).await;
+        }
+        let mut manager = get_manager().write().await;
+        let embedding_service = manager.service().await?;
+        Ok(subtitle_util::split_cues(
+            &cues,
+            &embedding_service.tokenizer,
+            DOCUMENT_CHUNK_SIZE,
+        ))
+    }
 }
@@ -1,9 +1,16 @@
+use crate::embedding_service_manager::get_manager;
 use crate::entities::FileInfo;
 use crate::enums::FileCategory;
+use crate::errors::{AppError, IndexingError};
 use crate::global::{EXT_TO_DOC_LOADER, MAX_DOCUMENT_LOAD_CHARS};
+use crate::structs::document_chunk::DocumentChunk;
 use crate::structs::embedding_progress::EmbeddingProgress;
-use crate::traits::indexing_template::IndexingTemplate;
+use crate::structs::file_metadata::FileMetadata;
+use crate::structs::virtual_member::VirtualMember;
+use crate::traits::document_loader::DocumentLoader;
+use crate::traits::indexing_template::{IndexingTemplate, split_text_content};
 use std::path::Path;
+use std::sync::Arc;
 
 pub struct DocumentIndexer {
     pub category: FileCategory,
@@ -17,6 +24,10 @@ impl DocumentIndexer {
             status: EmbeddingProgress::default(),
         }
     }
+
+    async fn get_loader(&self, ext: &str) -> Option<Arc<dyn DocumentLoader + Send + Sync>> {
+        EXT_TO_DOC_LOADER.read().await.get(ext).cloned()
+    }
 }
 
 impl IndexingTemplate for DocumentIndexer {
@@ -24,11 +35,7 @@ impl IndexingTemplate for DocumentIndexer {
         &self.category
     }
     async fn load_content(&self, file_info: &FileInfo) -> String {
-        let loader = EXT_TO_DOC_LOADER
-            .read()
-            .await
-            .get(&file_info.file_ext)
-            .cloned();
+        let loader = self.get_loader(&file_info.file_ext).await;
         match loader {
             Some(doc_loader) => doc_loader
                 .load_max(Path::new(&file_info.path), MAX_DOCUMENT_LOAD_CHARS)
@@ -42,4 +49,85 @@ impl IndexingTemplate for DocumentIndexer {
             }
         }
     }
+
+    async fn keep_layout(&self, file_info: &FileInfo) -> bool {
+        self.get_loader(&file_info.file_ext)
+            .await
+            .map(|loader| loader.keep_layout())
+            .unwrap_or(false)
+    }
+
+    async fn load_metadata(&self, file_info: &FileInfo, file_meta: &mut FileMetadata) {
+        let Some(loader) = self.get_loader(&file_info.file_ext).await else {
+            return;
+        };
+        if let Err(error) = loader.load_metadata(Path::new(&file_info.path), file_meta) {
+            println!("Load metadata error: {}, path: {}", error, &file_info.path);
+        }
+    }
+
+    async fn load_content_metadata(
+        &self,
+        file_info: &FileInfo,
+        file_meta: &mut FileMetadata,
+    ) -> String {
+        let Some(loader) = self.get_loader(&file_info.file_ext).await else {
+            println!(
+                "No document loader found for extension: {}",
+                &file_info.file_ext
+            );
+            return "".to_string();
+        };
+        loader
+            .load_with_metadata(
+                Path::new(&file_info.path),
+                MAX_DOCUMENT_LOAD_CHARS,
+                file_meta,
+            )
+            .unwrap_or("".to_string())
+    }
+
+    async fn load_members(&self, file_info: &FileInfo) -> Option<Vec<VirtualMember>> {
+        let loader = self.get_loader(&file_info.file_ext).await?;
+        // Archives extract and load their members, on a blocking thread
+        let path = file_info.path.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            loader.load_members(Path::new(&path), MAX_DOCUMENT_LOAD_CHARS)
+        })
+        .await;
+        match result {
+            Ok(Ok(members)) => members,
+            Ok(Err(error)) => {
+                println!("Load members error: {}, path: {}", error, &file_info.path);
+                None
+            }
+            Err(error) => {
+                println!(
+                    "Load members task error: {}, path: {}",
+                    error, &file_info.path
+                );
+                None
+            }
+        }
+    }
+
+    async fn split_content(
+        &self,
+        file_info: &FileInfo,
+        content: &str,
+    ) -> Result<Vec<DocumentChunk>, IndexingError> {
+        let Some(loader) = self.get_loader(&file_info.file_ext).await else {
+            return split_text_content(content).await;
+        };
+        let mut manager = get_manager().write().await;
+        let embedding_service = manager.service().await?;
+        let chunks = loader
+            .split(
+                Path::new(&file_info.path),
+                content,
+                &embedding_service.tokenizer,
+            )
+            .map_err(|op| AppError::DocumentSplitterError(op.to_string()))?;
+        Ok(chunks)
+    }
 }
@@ -1,19 +1,23 @@
 use crate::entities::{AiModel, FileInfo};
 use crate::enums::{FileCategory, ModelPlatformName, ModelType};
 use crate::errors::AppError;
-use crate::global::ACTIVE_MODEL_PLATFORM;
+use crate::global::{ACTIVE_MODEL_PLATFORM, IMAGE_ANALYSIS_PROMPT_VERSION};
 use crate::model_platform_services::dashscope::DashScope;
+use crate::model_platform_services::ollama::Ollama;
 use crate::model_platform_services::openai::OpenAi;
 use crate::model_platform_services::openai_compatible_service::OpenAiCompatibleService;
 use crate::model_platform_services::siliconflow::SiliconFlow;
 use crate::repositories::ai_model_repo;
+use crate::structs::file_metadata::FileMetadata;
 use crate::traits::image_analyzer::ImageAnalyzer;
 use crate::traits::indexing_template::IndexingTemplate;
+use crate::utils::{analysis_cache_util, exif_util, usage_util};
+use std::path::Path;
 
 pub struct ImageIndexer {
     category: FileCategory,
-    ai_model: AiModel,
-    platform_service: Box<dyn ImageAnalyzer>,
+    // None if images are only indexed by their EXIF/XMP metadata
+    analyzer: Option<(AiModel, Box<dyn ImageAnalyzer>)>,
 }
 
 impl ImageIndexer {
@@ -33,6 +37,7 @@ impl ImageIndexer {
                     ModelPlatformName::OpenAi => Box::new(OpenAi::new().await),
                     ModelPlatformName::SiliconFlow => Box::new(SiliconFlow::new().await),
                     ModelPlatformName::DashScope => Box::new(DashScope::new().await),
+                    ModelPlatformName::Ollama => Box::new(Ollama::new().await),
                     ModelPlatformName::DeepSeek => {
                         println!("DeepSeek do not support image analysis yet.");
                         return Err(AppError::UnsupportedImageAnalyze(
@@ -44,24 +49,57 @@ impl ImageIndexer {
 
             return Ok(Self {
                 category: FileCategory::Image,
-                ai_model,
-                platform_service,
+                analyzer: Some((ai_model, platform_service)),
             });
         }
         let vision: &str = ModelType::Vision.into();
         Err(AppError::AiModelNotFound(format!("model type:{}", vision)))
     }
+
+    /// Index the EXIF/XMP metadata only, nothing is sent to the model platform(e.g. in private mode)
+    pub fn metadata_only() -> ImageIndexer {
+        Self {
+            category: FileCategory::Image,
+            analyzer: None,
+        }
+    }
 }
 
 impl IndexingTemplate for ImageIndexer {
     fn category(&self) -> &FileCategory {
         &self.category
     }
+
+    fn concurrency(&self) -> usize {
+        self.analyzer.as_ref().map_or(1, |(_, platform_service)| {
+            platform_service.platform().max_concurrency.max(1) as usize
+        })
+    }
+
+    async fn is_paused(&self) -> bool {
+        match self.analyzer.as_ref() {
+            Some((_, platform_service)) => usage_util::is_paused(platform_service.platform()).await,
+            None => false,
+        }
+    }
     async fn load_content(&self, file_info: &FileInfo) -> String {
-        match self
-            .platform_service
-            .analyze_image(&self.ai_model, &file_info.path)
-            .await
+        let Some((ai_model, platform_service)) = self.analyzer.as_ref() else {
+            return String::new();
+        };
+        // The prompt is localized, a result in another language is not reused
+        let prompt_version = format!(
+            "{}-{}",
+            IMAGE_ANALYSIS_PROMPT_VERSION,
+            &*rust_i18n::locale()
+        );
+        match analysis_cache_util::load_or_analyze(
+            file_info,
+            platform_service.platform(),
+            ai_model,
+            &prompt_version,
+            || platform_service.analyze_image(ai_model, &file_info.path),
+        )
+        .await
         {
             Ok(content) => content,
             Err(e) => {
@@ -70,4 +108,10 @@ impl IndexingTemplate for ImageIndexer {
             }
         }
     }
+
+    async fn load_metadata(&self, file_info: &FileInfo, file_meta: &mut FileMetadata) {
+        if let Err(error) = exif_util::load_image_metadata(Path::new(&file_info.path), file_meta) {
+            println!("Load image metadata error: {}", error);
+        }
+    }
 }
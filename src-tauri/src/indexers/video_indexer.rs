@@ -0,0 +1,147 @@
+use crate::entities::{AiModel, FileInfo};
+use crate::enums::{FileCategory, ModelPlatformName, ModelType};
+use crate::errors::AppError;
+use crate::global::{ACTIVE_MODEL_PLATFORM, INDEXER_SETTING};
+use crate::model_platform_services::dashscope::DashScope;
+use crate::model_platform_services::deepseek::DeepSeek;
+use crate::model_platform_services::ollama::Ollama;
+use crate::model_platform_services::openai::OpenAi;
+use crate::model_platform_services::openai_compatible_service::OpenAiCompatibleService;
+use crate::model_platform_services::siliconflow::SiliconFlow;
+use crate::repositories::ai_model_repo;
+use crate::structs::file_metadata::{FileMetadata, VideoMetadata};
+use crate::traits::indexing_template::IndexingTemplate;
+use crate::traits::video_describer::VideoDescriber;
+use crate::utils::{subtitle_util, usage_util, video_util};
+use std::path::Path;
+
+pub struct VideoIndexer {
+    category: FileCategory,
+    // None if videos are only indexed by their container metadata and sidecar subtitles
+    describer: Option<(AiModel, Box<dyn VideoDescriber>)>,
+}
+
+impl VideoIndexer {
+    /// The text model describes the videos without subtitles only if enabled in the indexer setting
+    pub async fn new() -> Result<VideoIndexer, AppError> {
+        if !INDEXER_SETTING.read().await.video_describe_by_title {
+            return Ok(Self::metadata_only());
+        }
+        let (platform_name, base_url) = {
+            let active_platform = ACTIVE_MODEL_PLATFORM.read().await;
+            (
+                active_platform.name.clone(),
+                active_platform.base_url.clone(),
+            )
+        };
+        if let Ok(Some(ai_model)) =
+            ai_model_repo::get_one_by_type(platform_name.as_str(), ModelType::Text.into())
+        {
+            let platform_service: Box<dyn VideoDescriber> =
+                match ModelPlatformName::from(platform_name.as_str()) {
+                    ModelPlatformName::OpenAi => Box::new(OpenAi::new().await),
+                    ModelPlatformName::SiliconFlow => Box::new(SiliconFlow::new().await),
+                    ModelPlatformName::DashScope => Box::new(DashScope::new().await),
+                    ModelPlatformName::DeepSeek => Box::new(DeepSeek::new().await),
+                    ModelPlatformName::Ollama => Box::new(Ollama::new().await),
+                    _ => Box::new(OpenAiCompatibleService::new(&platform_name, &base_url).await),
+                };
+
+            return Ok(Self {
+                category: FileCategory::Video,
+                describer: Some((ai_model, platform_service)),
+            });
+        }
+        let text: &str = ModelType::Text.into();
+        Err(AppError::AiModelNotFound(format!("model type:{}", text)))
+    }
+
+    /// Index the container metadata and sidecar subtitles only, nothing is sent to the model platform(e.g. in private mode)
+    pub fn metadata_only() -> VideoIndexer {
+        Self {
+            category: FileCategory::Video,
+            describer: None,
+        }
+    }
+}
+
+impl IndexingTemplate for VideoIndexer {
+    fn category(&self) -> &FileCategory {
+        &self.category
+    }
+
+    async fn is_paused(&self) -> bool {
+        match self.describer.as_ref() {
+            Some((_, platform_service)) => usage_util::is_paused(platform_service.platform()).await,
+            None => false,
+        }
+    }
+
+    async fn load_content(&self, file_info: &FileInfo) -> String {
+        let path = Path::new(&file_info.path);
+        let subtitles: Vec<String> = subtitle_util::find_sidecar_subtitles(path)
+            .iter()
+            .filter_map(|subtitle_path| {
+                let ext = subtitle_path.extension()?.to_str()?;
+                let content = subtitle_util::read_subtitle(subtitle_path)
+                    .inspect_err(|error| println!("Read subtitle error: {}", error))
+                    .ok()?;
+                Some(subtitle_util::cues_to_text(&subtitle_util::parse_subtitle(
+                    &content, ext,
+                )))
+            })
+            .filter(|text| !text.is_empty())
+            .collect();
+        subtitles.join("\n")
+    }
+
+    async fn load_metadata(&self, file_info: &FileInfo, file_meta: &mut FileMetadata) {
+        match video_util::load_video_metadata(Path::new(&file_info.path)) {
+            Ok((video, title)) => {
+                if file_meta.title.is_empty() {
+                    file_meta.title = title;
+                }
+                if !video.is_empty() {
+                    file_meta.video = Some(video);
+                }
+            }
+            Err(error) => println!("Load video metadata error: {}", error),
+        }
+    }
+
+    /// A video without subtitles may be described from its title, the description is a guess
+    /// so it is kept in the metadata rather than indexed as content
+    async fn load_content_metadata(
+        &self,
+        file_info: &FileInfo,
+        file_meta: &mut FileMetadata,
+    ) -> String {
+        let content = self.load_content(file_info).await;
+        self.load_metadata(file_info, file_meta).await;
+        let Some((ai_model, platform_service)) =
+            self.describer.as_ref().filter(|_| content.is_empty())
+        else {
+            return content;
+        };
+        // The title in the container is usually better than a file name like `VID_20240501.mp4`
+        let title = if file_meta.title.is_empty() {
+            Path::new(&file_info.path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        } else {
+            file_meta.title.clone()
+        };
+        match platform_service.describe_video(ai_model, &title).await {
+            Ok(description) if !description.is_empty() => {
+                file_meta
+                    .video
+                    .get_or_insert_with(VideoMetadata::default)
+                    .description = description;
+            }
+            Ok(_) => (),
+            Err(e) => eprintln!("Error describing video: {}", e),
+        }
+        content
+    }
+}
@@ -1,20 +1,24 @@
 use crate::db_initializer;
+use crate::document_loaders::notebook::NotebookLoader;
 use crate::global::{
     ACTIVE_LOCALE, ACTIVE_MODEL_PLATFORM, CLIENT_ID, CONFIG_NAME_ACTIVE_LOCALE,
     CONFIG_NAME_CLIENT_ID, CONFIG_NAME_INDEXER_SETTING, CONFIG_NAME_PROXY,
-    CONFIG_NAME_WATCHER_SETTING, FS_WATCHER_SETTING, INDEXER_SETTING,
-    ONNX_EXEC_PROVIDERS_INITIALIZED, PROXY,
+    CONFIG_NAME_SEARCH_HISTORY_SETTING, CONFIG_NAME_WATCHER_SETTING, EXT_TO_DOC_LOADER,
+    FS_WATCHER_SETTING, INDEXER_SETTING, ONNX_EXEC_PROVIDERS_INITIALIZED, PROXY,
+    SEARCH_HISTORY_SETTING,
 };
 use crate::repositories::{config_repo, model_platform_repo};
 use crate::structs::fs_watcher_setting::FsWatcherSetting;
 use crate::structs::indexer_setting::IndexerSetting;
 use crate::structs::proxy_setting::ProxyInfo;
+use crate::structs::search_history_setting::SearchHistorySetting;
+use crate::traits::document_loader::DocumentLoader;
 use anyhow::Context;
 use log::{error, info};
 use ort::execution_providers::{CPUExecutionProvider, CUDAExecutionProvider};
 use serde::Deserialize;
 use serde_json;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 use tokio::sync::{RwLock as AsyncRwLock, RwLockWriteGuard as AsyncRwLockWriteGuard};
 
 pub async fn process() {
@@ -37,12 +41,19 @@ pub async fn process() {
         &INDEXER_SETTING,
     )
     .await;
+    init_doc_loaders().await;
     init_setting(
         CONFIG_NAME_WATCHER_SETTING,
         || serde_json::to_string(&FsWatcherSetting::default()).unwrap_or_default(),
         &FS_WATCHER_SETTING,
     )
     .await;
+    init_setting(
+        CONFIG_NAME_SEARCH_HISTORY_SETTING,
+        || serde_json::to_string(&SearchHistorySetting::default()).unwrap_or_default(),
+        &SEARCH_HISTORY_SETTING,
+    )
+    .await;
     init_string_setting(CONFIG_NAME_ACTIVE_LOCALE, &ACTIVE_LOCALE).await;
 
     //Onnx Runtime initialization
@@ -65,7 +76,11 @@ pub async fn process() {
         }
     }
 
-    // Initialize the default model platform
+    init_active_platform().await;
+}
+
+/// Load the active model platform of the config table
+pub async fn init_active_platform() {
     let config = config_repo::get_one("active_model_platform");
     if let Ok(Some(config)) = config {
         let model_platform = model_platform_repo::get_one(&config.value);
@@ -79,6 +94,17 @@ pub async fn process() {
     }
 }
 
+/// Register the document loaders that depend on the indexer setting, called after it is loaded
+pub async fn init_doc_loaders() {
+    let include_outputs = INDEXER_SETTING.read().await.notebook_include_outputs;
+    let loader: Arc<dyn DocumentLoader + Send + Sync> =
+        Arc::new(NotebookLoader::new(include_outputs));
+    let mut ext_to_loader = EXT_TO_DOC_LOADER.write().await;
+    for ext in loader.get_exts() {
+        ext_to_loader.insert(ext.clone(), Arc::clone(&loader));
+    }
+}
+
 pub trait ConfigLock<T: 'static> {
     async fn write(&self) -> AsyncRwLockWriteGuard<'_, T>;
 }
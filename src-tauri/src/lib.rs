@@ -23,14 +23,24 @@ mod utils;
 
 use crate::global::UI_MOUNTED;
 use crate::lib_commands::{
-    add_watch_path, check_path_type, clear_index, count_files, count_indexing_tasks,
-    delete_index_item, delete_indexing_task, download_multilingual_model, get_client_id,
-    get_data_path, is_embedding_model_changed, load_active_locale, load_active_platform,
-    load_chunks, load_config_value, load_embedding_models, load_file_detail, load_files,
-    load_indexer_setting, load_indexing_tasks, load_model_by_type, load_model_platforms,
-    load_proxy_info, path_search, quick_search, read_file_data, remove_watch_path, reset_data_path,
-    search, semantic_search, set_active_locale, set_active_platform, set_data_path, start_indexing,
-    stop_indexing, ui_mounted, update_indexer_setting, update_model_platform, update_proxy_info,
+    add_ai_model, add_model_platform, add_watch_path, cancel_local_model_download, check_path_type,
+    clear_index, clear_search_history, count_files, count_indexing_tasks, create_tag,
+    create_workspace, delete_ai_model, delete_index_item, delete_indexing_task,
+    delete_model_platform, delete_saved_search, delete_search_history, delete_tag,
+    delete_workspace, download_multilingual_model, export_index, get_client_id, get_data_path,
+    import_index, install_local_model, is_embedding_model_changed, load_active_locale,
+    load_active_platform, load_ai_models, load_chunks, load_config_value, load_embedding_models,
+    load_file_detail, load_file_tags, load_files, load_index_bundle_manifest, load_indexer_setting,
+    load_indexing_tasks, load_local_models, load_model_by_type, load_model_platforms,
+    load_ollama_models, load_proxy_info, load_saved_searches, load_search_history,
+    load_search_history_setting, load_tag_files, load_tags, load_usage_report, load_workspaces,
+    path_search, purge_analysis_cache, quick_search, read_file_data, remove_local_model,
+    remove_watch_path, rename_tag, reset_data_path, run_saved_search, save_search, search,
+    search_workspaces, semantic_search, set_active_locale, set_active_platform, set_data_path,
+    set_model_platform_enable, start_indexing, stop_indexing, subscribe_smart_folders,
+    switch_workspace, tag_files, test_proxy_connection, ui_mounted, untag_files, update_ai_model,
+    update_indexer_setting, update_model_platform, update_proxy_info,
+    update_search_history_setting,
 };
 use crate::repositories::file_content_embedding_repo;
 use crate::utils::app_util;
@@ -110,10 +120,22 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             load_active_locale,
             load_model_platforms,
+            load_usage_report,
+            purge_analysis_cache,
+            export_index,
+            load_index_bundle_manifest,
+            import_index,
+            test_proxy_connection,
+            load_local_models,
+            install_local_model,
+            cancel_local_model_download,
+            remove_local_model,
             load_proxy_info,
             load_active_platform,
             load_indexer_setting,
             load_model_by_type,
+            load_ollama_models,
+            load_ai_models,
             load_embedding_models,
             load_indexing_tasks,
             load_files,
@@ -122,9 +144,23 @@ pub fn run() {
             load_chunks,
             count_indexing_tasks,
             count_files,
+            load_tags,
+            create_tag,
+            rename_tag,
+            delete_tag,
+            tag_files,
+            untag_files,
+            load_file_tags,
+            load_tag_files,
             set_active_platform,
             set_active_locale,
             update_model_platform,
+            add_model_platform,
+            set_model_platform_enable,
+            delete_model_platform,
+            add_ai_model,
+            update_ai_model,
+            delete_ai_model,
             update_proxy_info,
             update_indexer_setting,
             start_indexing,
@@ -146,6 +182,21 @@ pub fn run() {
             remove_watch_path,
             path_search,
             semantic_search,
+            search_workspaces,
+            load_workspaces,
+            create_workspace,
+            delete_workspace,
+            switch_workspace,
+            load_search_history,
+            delete_search_history,
+            clear_search_history,
+            load_search_history_setting,
+            update_search_history_setting,
+            load_saved_searches,
+            save_search,
+            run_saved_search,
+            delete_saved_search,
+            subscribe_smart_folders,
             get_client_id,
         ])
         .setup(|app| {
@@ -1,30 +1,52 @@
+use crate::document_loaders::archive;
 use crate::embedding_service_manager::get_manager;
-use crate::entities::{FileInfo, IndexingTask, ModelPlatform};
+use crate::entities::{
+    AiModel, FileInfo, IndexingTask, ModelPlatform, SavedSearch, SearchHistory, Tag,
+};
 use crate::enums::CommandResultCode;
-use crate::enums::{DownloadEvent, IndexingEvent, Locale, ModelPlatformName};
+use crate::enums::{
+    DownloadEvent, FileContentLanguage, IndexingEvent, Locale, ModelPlatformName, SearchMode,
+    SmartFolderEvent,
+};
 use crate::errors::AppError;
 use crate::fs_watcher::watcher;
 use crate::global::{
     ACTIVE_LOCALE, ACTIVE_MODEL_PLATFORM, APP_DATA_PATH, CLIENT_ID, CONFIG_NAME_INDEXER_SETTING,
-    CONFIG_NAME_PROXY, INDEXING, SCANNING, STOP_INDEX_SIGNAL, UI_MOUNTED,
+    CONFIG_NAME_PROXY, DOWNLOAD_CANCEL_SIGNAL, DOWNLOADING, INDEXER_SETTING, INDEXING,
+    MAILBOX_MEMBER_SEPARATOR, MULTI_LANG_MODEL_ID, PROXY, PROXY_TEST_TIMEOUT_SECS, PROXY_TEST_URL,
+    SCANNING, SEARCH_HISTORY_SETTING, STOP_INDEX_SIGNAL, UI_MOUNTED,
 };
 use crate::indexer_service;
+use crate::model_platform_services::ollama::{Ollama, OllamaModel};
 use crate::model_platform_services::siliconflow::SiliconFlow;
 use crate::repositories::{
-    ai_model_repo, config_repo, file_content_embedding_repo, file_info_repo,
-    file_metadata_embedding_repo, indexing_task_repo, model_platform_repo,
+    ai_model_repo, analysis_cache_repo, config_repo, file_content_chunk_repo,
+    file_content_embedding_repo, file_info_repo, file_metadata_embedding_repo, indexing_task_repo,
+    model_platform_repo, model_usage_repo, saved_search_repo, search_history_repo, tag_repo,
 };
 use crate::searcher;
 use crate::structs::command_result::CommandResult;
+use crate::structs::index_bundle::{IndexBundleManifest, PathMapping};
+use crate::structs::local_model::LocalModel;
 use crate::structs::proxy_setting::ProxyInfo;
+use crate::structs::search_history_setting::SearchHistorySetting;
 use crate::structs::search_result::SearchResult;
+use crate::structs::usage_report::UsageReport;
+use crate::structs::workspace::Workspace;
 use crate::traits::chat_capable::ChatCapable;
-use crate::utils::{app_util, download_util};
+use crate::traits::indexing_template::remove_members_index;
+use crate::utils::{
+    app_util, download_util, http_client_util, index_bundle_util, model_platform_util,
+    model_registry_util, path_util, saved_search_util, search_history_util, secret_util, tag_util,
+    usage_util, workspace_util,
+};
+use chrono::Local;
 use rust_i18n::t;
 use serde_json::json;
 use std::fs::read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 use tauri::command;
 use tauri::{AppHandle, ipc::Channel};
 
@@ -34,26 +56,77 @@ pub async fn load_active_locale() -> Result<String, String> {
     Ok(guard.clone())
 }
 
+/// The built-in platforms followed by the user-defined ones, API keys are masked
 #[command]
 pub async fn load_model_platforms() -> Vec<ModelPlatform> {
-    model_platform_repo::list(&vec![
+    let mut platforms = model_platform_repo::list(&vec![
         ModelPlatformName::OpenAi.text().to_string(),
         ModelPlatformName::SiliconFlow.text().to_string(),
+        ModelPlatformName::Ollama.text().to_string(),
     ])
     .unwrap_or_else(|e| {
         println!("Failed to load model platforms: {}", e);
         vec![]
-    })
+    });
+    match model_platform_repo::list_custom() {
+        Ok(custom) => platforms.extend(custom),
+        Err(e) => println!("Failed to load custom model platforms: {}", e),
+    }
+    platforms.into_iter().map(mask_api_key).collect()
 }
 
+/// The proxy password is masked
 #[command]
 pub async fn load_proxy_info() -> Result<ProxyInfo, String> {
-    let result = config_repo::get_one(CONFIG_NAME_PROXY)?
-        .map(|config| serde_json::from_str(&config.value).map_err(|e| e.to_string()))
-        .unwrap_or_else(|| Ok(ProxyInfo::default()))?;
+    let mut result = stored_proxy_info()?;
+    result.password = secret_util::mask_secret(&result.password);
     Ok(result)
 }
 
+fn stored_proxy_info() -> Result<ProxyInfo, String> {
+    config_repo::get_one(CONFIG_NAME_PROXY)?
+        .map(|config| serde_json::from_str(&config.value).map_err(|e| e.to_string()))
+        .unwrap_or_else(|| Ok(ProxyInfo::default()))
+}
+
+/// A password equal to the mask keeps the stored one, the others are encrypted
+fn resolve_proxy_password(mut proxy_info: ProxyInfo) -> Result<ProxyInfo, String> {
+    proxy_info.password = if proxy_info.password == secret_util::API_KEY_MASK {
+        stored_proxy_info()?.password
+    } else {
+        secret_util::encrypt_secret(&proxy_info.password)?
+    };
+    Ok(proxy_info)
+}
+
+/// Request a URL through the proxy settings(saved or not), return the elapsed milliseconds.
+///
+/// Any HTTP response counts, the status of the test URL doesn't matter.
+#[command]
+pub async fn test_proxy_connection(
+    proxy_info: ProxyInfo,
+    url: Option<String>,
+) -> Result<u128, AppError> {
+    let proxy_info = resolve_proxy_password(proxy_info).map_err(AppError::InternalError)?;
+    let client = http_client_util::client_builder(Some(&proxy_info))?
+        .timeout(Duration::from_secs(PROXY_TEST_TIMEOUT_SECS))
+        .build()?;
+    let url = url
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| PROXY_TEST_URL.to_string());
+    let start = Instant::now();
+    let response = client.head(url.trim()).send().await.map_err(|e| {
+        AppError::RemoteCallError(format!("Failed to connect through the proxy: {}", e))
+    })?;
+    println!(
+        "Proxy test {}: {}, {}ms",
+        url,
+        response.status(),
+        start.elapsed().as_millis()
+    );
+    Ok(start.elapsed().as_millis())
+}
+
 #[command]
 pub async fn load_indexer_setting()
 -> Result<crate::structs::indexer_setting::IndexerSetting, String> {
@@ -78,6 +151,12 @@ pub async fn load_embedding_models() -> serde_json::Value {
     })
 }
 
+/// Models pulled into the local Ollama server
+#[command]
+pub async fn load_ollama_models() -> Result<Vec<OllamaModel>, String> {
+    Ok(Ollama::new().await.list_models().await?)
+}
+
 #[command]
 pub async fn load_model_by_type(
     platform: &str,
@@ -101,17 +180,126 @@ pub async fn is_embedding_model_changed() -> Result<bool, String> {
 
 #[command]
 pub async fn update_proxy_info(proxy_info: ProxyInfo) -> Result<usize, String> {
+    let proxy_info = resolve_proxy_password(proxy_info)?;
     let proxy_json = serde_json::to_string(&proxy_info).map_err(|e| AppError::SerializeError(e))?;
-    Ok(config_repo::update_by_name("proxy", &proxy_json)?)
+    let result = config_repo::update_by_name("proxy", &proxy_json)?;
+    *PROXY.write().await = proxy_info;
+    Ok(result)
 }
 
 #[command]
-pub async fn update_model_platform(platform: ModelPlatform) -> Result<usize, AppError> {
+pub async fn update_model_platform(mut platform: ModelPlatform) -> Result<usize, AppError> {
+    if !ModelPlatformName::is_builtin(&platform.name) {
+        model_platform_util::normalize_custom_platform(&mut platform)?;
+    }
+    // The UI got the mask, the repository keeps the stored key
+    if platform.api_key == secret_util::API_KEY_MASK
+        && !model_platform_repo::exists(&platform.name)?
+    {
+        return Err(AppError::ModelPlatformNotFound(platform.name.clone()));
+    }
     let result = model_platform_repo::update_by_name(&platform.name, &platform)?;
-    if platform.name == ACTIVE_MODEL_PLATFORM.read().await.name {
+    reload_active_platform(&platform.name).await?;
+    Ok(result)
+}
+
+/// Add a user-defined OpenAI compatible platform, the name must be unique
+#[command]
+pub async fn add_model_platform(mut platform: ModelPlatform) -> Result<ModelPlatform, AppError> {
+    model_platform_util::normalize_custom_platform(&mut platform)?;
+    if model_platform_repo::exists(&platform.name)? {
+        return Err(AppError::InvalidParameter(format!(
+            "platform {} already exists",
+            platform.name
+        )));
+    }
+    Ok(mask_api_key(model_platform_repo::insert(&platform)?))
+}
+
+#[command]
+pub async fn set_model_platform_enable(name: &str, enable: bool) -> Result<usize, AppError> {
+    let mut platform = model_platform_repo::get_one(name)
+        .map_err(|_| AppError::ModelPlatformNotFound(name.to_string()))?;
+    platform.is_enable = enable;
+    // Keep the stored key rather than encrypting the ciphertext again
+    platform.api_key = secret_util::API_KEY_MASK.to_string();
+    let result = model_platform_repo::update_by_name(name, &platform)?;
+    reload_active_platform(name).await?;
+    Ok(result)
+}
+
+/// Delete a user-defined platform and its models, the active platform can not be deleted
+#[command]
+pub async fn delete_model_platform(name: &str) -> Result<usize, AppError> {
+    if ModelPlatformName::is_builtin(name) {
+        return Err(AppError::InvalidParameter(format!(
+            "built-in platform {} can not be deleted",
+            name
+        )));
+    }
+    if name == ACTIVE_MODEL_PLATFORM.read().await.name {
+        return Err(AppError::InvalidParameter(format!(
+            "platform {} is in use",
+            name
+        )));
+    }
+    Ok(model_platform_repo::delete_by_name(name)?)
+}
+
+#[command]
+pub async fn load_ai_models(platform: &str) -> Result<Vec<AiModel>, String> {
+    Ok(ai_model_repo::list_by_platform(platform)?)
+}
+
+/// Add a model to a platform, the name must be unique within the platform
+#[command]
+pub async fn add_ai_model(mut ai_model: AiModel) -> Result<AiModel, AppError> {
+    model_platform_util::normalize_model(&mut ai_model)?;
+    if !model_platform_repo::exists(&ai_model.platform)? {
+        return Err(AppError::ModelPlatformNotFound(ai_model.platform));
+    }
+    if ai_model_repo::exists(&ai_model.platform, &ai_model.name, 0)? {
+        return Err(AppError::InvalidParameter(format!(
+            "model {} already exists in {}",
+            ai_model.name, ai_model.platform
+        )));
+    }
+    Ok(ai_model_repo::insert(&ai_model)?)
+}
+
+#[command]
+pub async fn update_ai_model(mut ai_model: AiModel) -> Result<usize, AppError> {
+    model_platform_util::normalize_model(&mut ai_model)?;
+    let Some(one) = ai_model_repo::get_by_id(ai_model.id)? else {
+        return Err(AppError::AiModelNotFound(format!("id:{}", ai_model.id)));
+    };
+    // Models do not move between platforms
+    ai_model.platform = one.platform;
+    if ai_model_repo::exists(&ai_model.platform, &ai_model.name, ai_model.id)? {
+        return Err(AppError::InvalidParameter(format!(
+            "model {} already exists in {}",
+            ai_model.name, ai_model.platform
+        )));
+    }
+    Ok(ai_model_repo::update(&ai_model)?)
+}
+
+#[command]
+pub async fn delete_ai_model(id: i64) -> Result<usize, String> {
+    Ok(ai_model_repo::delete(id)?)
+}
+
+fn mask_api_key(mut platform: ModelPlatform) -> ModelPlatform {
+    platform.api_key = secret_util::mask_secret(&platform.api_key);
+    platform
+}
+
+/// Keep the in-memory active platform in sync after it is changed in the database
+async fn reload_active_platform(name: &str) -> Result<(), AppError> {
+    if name == ACTIVE_MODEL_PLATFORM.read().await.name {
         match ACTIVE_MODEL_PLATFORM.try_write() {
             Ok(mut guard) => {
-                let one = model_platform_repo::get_one(&platform.name)?;
+                let one = model_platform_repo::get_one(name)?;
                 *guard = one;
             }
             Err(_) => {
@@ -119,7 +307,7 @@ pub async fn update_model_platform(platform: ModelPlatform) -> Result<usize, App
             }
         }
     }
-    Ok(result)
+    Ok(())
 }
 
 #[command]
@@ -222,12 +410,114 @@ pub async fn count_indexing_tasks() -> Result<i64, String> {
     Ok(count)
 }
 
+/// Delete all the cached analysis results, the files analyzed again are charged by the model platform
+#[command]
+pub async fn purge_analysis_cache() -> Result<usize, String> {
+    let count = analysis_cache_repo::purge()?;
+    println!("Purged {} analysis cache entries", count);
+    Ok(count)
+}
+
+/// Export the index with its settings(without API keys and proxy) into a bundle file
+#[command]
+pub async fn export_index(path: &str) -> Result<IndexBundleManifest, AppError> {
+    index_bundle_util::export(path).await
+}
+
+/// The manifest of a bundle, its root paths are offered to be remapped before the import
+#[command]
+pub async fn load_index_bundle_manifest(path: &str) -> Result<IndexBundleManifest, AppError> {
+    index_bundle_util::read_manifest(path)
+}
+
+/// Replace the index by the one of a bundle
+#[command]
+pub async fn import_index(
+    path: &str,
+    path_mappings: Vec<PathMapping>,
+) -> Result<IndexBundleManifest, AppError> {
+    index_bundle_util::import(path, &path_mappings).await
+}
+
+/// Model usage and cost of an indexing task, or of the current budget period if no task is given
+#[command]
+pub async fn load_usage_report(task_id: Option<i64>) -> Result<UsageReport, String> {
+    let budget = INDEXER_SETTING.read().await.budget.clone();
+    let items = match task_id {
+        Some(task_id) => model_usage_repo::report(task_id, "")?,
+        None => {
+            model_usage_repo::report(0, &usage_util::period_start(budget.period, Local::now()))?
+        }
+    };
+    let budget_used = usage_util::budget_used(&budget);
+    Ok(UsageReport {
+        total_cost: items.iter().map(|item| item.cost).sum(),
+        items,
+        is_budget_exceeded: budget.limit > 0.0 && budget_used >= budget.limit,
+        budget,
+        budget_used,
+    })
+}
+
 #[command]
 pub async fn load_files(page: i64, page_size: i64) -> Result<Vec<FileInfo>, String> {
     let files = file_info_repo::list(page, page_size)?;
     Ok(files)
 }
 
+#[command]
+pub async fn load_tags() -> Result<Vec<Tag>, String> {
+    let tags = tag_repo::list()?;
+    Ok(tags)
+}
+
+#[command]
+pub async fn create_tag(name: &str) -> Result<Tag, AppError> {
+    tag_util::create(name)
+}
+
+#[command]
+pub async fn rename_tag(id: i64, name: &str) -> Result<Tag, AppError> {
+    tag_util::rename(id, name)
+}
+
+#[command]
+pub async fn delete_tag(id: i64) -> Result<(), AppError> {
+    tag_util::delete(id)
+}
+
+/// Tag the indexed files of the paths, e.g. the results of a search, the tags are created if missing
+#[command]
+pub async fn tag_files(paths: Vec<String>, tags: Vec<String>) -> Result<usize, AppError> {
+    tag_util::tag_files(&paths, &tags)
+}
+
+#[command]
+pub async fn untag_files(paths: Vec<String>, tag_id: i64) -> Result<usize, AppError> {
+    tag_util::untag_files(&paths, tag_id)
+}
+
+/// The workspace is the one of the search result, the active one if not set
+#[command]
+pub async fn load_file_tags(file_id: i64, workspace: Option<String>) -> Result<Vec<Tag>, String> {
+    let tags = workspace_util::with_db(workspace.as_deref(), || tag_repo::list_by_file_id(file_id))
+        .await??;
+    Ok(tags)
+}
+
+/// The collection of a tag, the latest tagged files first
+#[command]
+pub async fn load_tag_files(
+    tag_id: i64,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<FileInfo>, String> {
+    let file_ids = tag_repo::list_file_ids(tag_id, page, page_size)?;
+    let mut files = file_info_repo::list_by_ids(&file_ids)?;
+    files.sort_by_key(|file| file_ids.iter().position(|id| *id == file.id));
+    Ok(files)
+}
+
 #[command]
 pub async fn count_files() -> Result<i64, String> {
     let count = file_info_repo::count()?;
@@ -244,6 +534,56 @@ pub async fn download_multilingual_model(proxy: bool, on_event: Channel<Download
     return true;
 }
 
+/// Local models of the registry with their install state
+#[command]
+pub async fn load_local_models() -> Result<Vec<LocalModel>, AppError> {
+    model_registry_util::list_models()
+}
+
+/// Download a local model, from the HuggingFace mirror first if `mirror` is set(or by the locale if not)
+#[command]
+pub async fn install_local_model(
+    id: &str,
+    proxy: bool,
+    mirror: Option<bool>,
+    on_event: Channel<DownloadEvent>,
+) -> Result<(), AppError> {
+    let model = model_registry_util::get_model(id)?;
+    let prefer_mirror = model_registry_util::prefer_mirror(mirror).await;
+    download_util::install_local_model(&model, proxy, prefer_mirror, &on_event)
+        .await
+        .map_err(|e| {
+            eprintln!("install local model error: {e}");
+            AppError::DownloadError(e.to_string())
+        })
+}
+
+/// Stop the running download, the downloaded part is kept to resume later
+#[command]
+pub async fn cancel_local_model_download() {
+    DOWNLOAD_CANCEL_SIGNAL.store(true, Ordering::SeqCst);
+}
+
+#[command]
+pub async fn remove_local_model(id: &str) -> Result<(), AppError> {
+    let model = model_registry_util::get_model(id)?;
+    if DOWNLOADING.load(Ordering::SeqCst) {
+        return Err(AppError::OperationFailed(
+            "Downloading is in progress".to_string(),
+        ));
+    }
+    // The embedding of the indexed content would not match the fallback model
+    if id == MULTI_LANG_MODEL_ID
+        && INDEXER_SETTING.read().await.file_content_language == FileContentLanguage::Multilingual
+    {
+        return Err(AppError::OperationFailed(format!(
+            "{} is the embedding model in use",
+            id
+        )));
+    }
+    model_registry_util::remove_model(&model)
+}
+
 #[command]
 pub async fn check_path_type(path: &str) -> Result<String, String> {
     let path = Path::new(path);
@@ -262,15 +602,24 @@ pub fn delete_indexing_task(task_id: i64) -> Result<(), String> {
     Ok(())
 }
 
+/// The workspace is the one of the search result, the active one if not set
 #[command]
-pub async fn load_file_detail(file_id: i64) -> Result<Option<FileInfo>, String> {
-    let file = file_info_repo::get_by_id(file_id)?;
+pub async fn load_file_detail(
+    file_id: i64,
+    workspace: Option<String>,
+) -> Result<Option<FileInfo>, String> {
+    let file = workspace_util::with_db(workspace.as_deref(), || file_info_repo::get_by_id(file_id))
+        .await??;
     Ok(file)
 }
 
 #[command]
 pub async fn delete_index_item(file_id: i64) -> Result<(), String> {
+    if let Some(file_info) = file_info_repo::get_by_id(file_id)? {
+        remove_members_index(&file_info.path)?;
+    }
     file_content_embedding_repo::delete_by_file_id(file_id)?;
+    file_content_chunk_repo::delete_by_file_id(file_id)?;
     file_metadata_embedding_repo::delete_by_file_id(file_id)?;
     file_info_repo::delete_by_id(file_id)?;
     Ok(())
@@ -279,6 +628,7 @@ pub async fn delete_index_item(file_id: i64) -> Result<(), String> {
 #[command]
 pub async fn clear_index() -> Result<(), String> {
     file_content_embedding_repo::clear()?;
+    file_content_chunk_repo::clear()?;
     file_metadata_embedding_repo::clear()?;
     file_info_repo::clear()?;
     Ok(())
@@ -301,24 +651,125 @@ pub async fn quick_search(query: &str) -> Result<(), String> {
 #[command]
 pub async fn search(query: &str) -> Result<Vec<SearchResult>, String> {
     let results = searcher::search_with_intent(query).await;
+    search_history_util::record(query, SearchMode::Intent, results.len()).await;
     Ok(results)
 }
 
 #[command]
 pub async fn path_search(query: &str) -> Result<Vec<SearchResult>, String> {
     let results = searcher::path_search(query).await;
+    search_history_util::record(query, SearchMode::Path, results.len()).await;
     Ok(results)
 }
 
 #[command]
 pub async fn semantic_search(query: &str) -> Result<Vec<SearchResult>, String> {
     let results = searcher::semantic_search(query).await;
+    search_history_util::record(query, SearchMode::Semantic, results.len()).await;
+    Ok(results)
+}
+
+/// The latest searches first
+#[command]
+pub async fn load_search_history(page: i64, page_size: i64) -> Result<Vec<SearchHistory>, String> {
+    Ok(search_history_repo::list(page, page_size)?)
+}
+
+#[command]
+pub async fn delete_search_history(id: i64) -> Result<usize, String> {
+    Ok(search_history_repo::delete_by_id(id)?)
+}
+
+#[command]
+pub async fn clear_search_history() -> Result<usize, String> {
+    Ok(search_history_repo::clear()?)
+}
+
+#[command]
+pub async fn load_search_history_setting() -> SearchHistorySetting {
+    SEARCH_HISTORY_SETTING.read().await.clone()
+}
+
+#[command]
+pub async fn update_search_history_setting(
+    setting: SearchHistorySetting,
+) -> Result<usize, AppError> {
+    search_history_util::update_setting(setting).await
+}
+
+#[command]
+pub async fn load_saved_searches() -> Result<Vec<SavedSearch>, String> {
+    Ok(saved_search_repo::list()?)
+}
+
+/// Create a saved search if its id is 0, update it otherwise
+#[command]
+pub async fn save_search(saved_search: SavedSearch) -> Result<SavedSearch, AppError> {
+    saved_search_util::save(saved_search).await
+}
+
+#[command]
+pub async fn run_saved_search(id: i64) -> Result<Vec<SearchResult>, AppError> {
+    saved_search_util::run_by_id(id).await
+}
+
+#[command]
+pub async fn delete_saved_search(id: i64) -> Result<(), AppError> {
+    saved_search_util::delete(id)
+}
+
+/// Receive the result counts of the smart folders, refreshed as the watcher indexes files
+#[command]
+pub async fn subscribe_smart_folders(on_event: Channel<SmartFolderEvent>) {
+    saved_search_util::subscribe(on_event);
+}
+
+#[command]
+pub async fn search_workspaces(
+    query: &str,
+    workspaces: Vec<String>,
+) -> Result<Vec<SearchResult>, String> {
+    let results = searcher::search_workspaces(query, &workspaces).await;
     Ok(results)
 }
 
+#[command]
+pub async fn load_workspaces() -> Result<Vec<Workspace>, AppError> {
+    workspace_util::list().await
+}
+
+#[command]
+pub async fn create_workspace(name: &str) -> Result<Workspace, AppError> {
+    workspace_util::create(name)
+}
+
+#[command]
+pub async fn delete_workspace(name: &str) -> Result<(), AppError> {
+    workspace_util::delete(name).await
+}
+
+/// Switch to another workspace, its database is created the first time
+#[command]
+pub async fn switch_workspace(name: &str) -> Result<(), AppError> {
+    workspace_util::switch(name).await
+}
+
 #[command]
 pub async fn read_file_data(path: String) -> Result<Vec<u8>, String> {
-    read(path).map_err(|e| e.to_string())
+    let (container_path, member_id) = path_util::split_virtual_path(&path);
+    let Some(member_id) = member_id else {
+        return read(&path).map_err(|e| e.to_string());
+    };
+    // Members of an archive are extracted, the messages of a mailbox are not files
+    if path_util::virtual_path_separator(container_path) == MAILBOX_MEMBER_SEPARATOR {
+        return Err(format!("Not a file: {}", path));
+    }
+    let container_path = PathBuf::from(container_path);
+    let member_id = member_id.to_string();
+    tokio::task::spawn_blocking(move || archive::read_member(&container_path, &member_id))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
 }
 
 #[command]
@@ -389,11 +840,31 @@ pub async fn ui_mounted(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// The ids are of the workspace of the search result, the active one if not set
 #[command]
-pub async fn load_chunks(ids: Vec<u32>) -> Result<Vec<String>, String> {
-    let segments =
-        file_content_embedding_repo::list_chunks_by_ids(&ids).map_err(|e| e.to_string())?;
-    Ok(segments)
+pub async fn load_chunks(ids: Vec<u32>, workspace: Option<String>) -> Result<Vec<String>, String> {
+    let (segments, locators) = workspace_util::with_db(workspace.as_deref(), || {
+        let segments =
+            file_content_embedding_repo::list_chunks_by_ids(&ids).map_err(|e| e.to_string())?;
+        let embedding_ids: Vec<i64> = segments.iter().map(|(id, _)| *id).collect();
+        let locators = file_content_chunk_repo::map_by_embedding_ids(&embedding_ids)?;
+        Ok::<_, String>((segments, locators))
+    })
+    .await??;
+    // The heading breadcrumb tells which section of the document a chunk belongs to
+    let chunks = segments
+        .into_iter()
+        .map(|(id, text)| {
+            match locators
+                .get(&id)
+                .and_then(|locator| locator.heading.as_ref())
+            {
+                Some(heading) => format!("{}\n{}", heading, text),
+                None => text,
+            }
+        })
+        .collect();
+    Ok(chunks)
 }
 
 async fn chat() {
@@ -1,5 +1,6 @@
 pub mod dashscope;
 pub mod deepseek;
+pub mod ollama;
 pub mod openai;
 pub mod openai_compatible_service;
 pub mod siliconflow;
@@ -1,10 +1,12 @@
 use crate::entities::{AiModel, ModelPlatform};
-use crate::enums::ModelPlatformName;
+use crate::enums::{ModelOperation, ModelPlatformName};
 use crate::structs::proxy_setting::ProxyInfo;
 use crate::traits::chat_capable::ChatCapable;
+use crate::traits::video_describer::VideoDescriber;
 use crate::traits::with_platform_config::WithPlatformConfig;
 use crate::types::LlmStreaming;
 use crate::utils::llm_client_util::{create_client, init_service};
+use crate::utils::usage_util;
 use futures::StreamExt;
 use rusqlite::Result;
 use serde::{Deserialize, Serialize};
@@ -77,9 +79,12 @@ impl DeepSeek {
                             }
                         }
                     } else if !chunk["usage"].is_null() {
-                        let usage = chunk["usage"].clone();
-                        println!("\nUsage:");
-                        println!("{}", usage.to_string());
+                        usage_util::record(
+                            &self.platform.name,
+                            ai_model,
+                            ModelOperation::Chat,
+                            usage_util::usage_from_response(&chunk),
+                        );
                     } else {
                         println!("Unknown chunk: {}", chunk.to_string());
                     }
@@ -104,3 +109,5 @@ impl WithPlatformConfig for DeepSeek {
 }
 
 impl ChatCapable for DeepSeek {}
+
+impl VideoDescriber for DeepSeek {}
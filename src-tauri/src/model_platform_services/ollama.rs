@@ -0,0 +1,499 @@
+use crate::entities::{AiModel, ModelPlatform, ModelUsage};
+use crate::enums::{ModelOperation, ModelPlatformName};
+use crate::errors::AppError;
+use crate::global::EMBEDDING_DIMENSION;
+use crate::structs::proxy_setting::ProxyInfo;
+use crate::traits::chat_capable::ChatCapable;
+use crate::traits::image_analyzer::ImageAnalyzer;
+use crate::traits::video_describer::VideoDescriber;
+use crate::traits::with_platform_config::WithPlatformConfig;
+use crate::utils::image_util::is_supported_image_file;
+use crate::utils::llm_client_util::{create_http_client, init_service};
+use crate::utils::{remote_call_util, usage_util};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rusqlite::Result;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+const DEFAULT_BASE_URL: &str = "http://127.0.0.1:11434/api";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Ollama {
+    platform: ModelPlatform,
+    proxy: ProxyInfo,
+    name: &'static str,
+}
+
+/// A model pulled into the Ollama server, listed by `/api/tags`
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct OllamaModel {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64, // In bytes
+    #[serde(default)]
+    pub details: OllamaModelDetails,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct OllamaModelDetails {
+    #[serde(default)]
+    pub family: String,
+    #[serde(default)]
+    pub families: Option<Vec<String>>, // e.g. ["llama", "clip"] for a vision model
+    #[serde(default)]
+    pub parameter_size: String,
+    #[serde(default)]
+    pub quantization_level: String,
+}
+
+/**
+ * Ollama LLM Service, runs models locally and needs no API key
+ * Documentation:
+ * https://github.com/ollama/ollama/blob/main/docs/api.md
+ */
+impl Ollama {
+    pub async fn new() -> Self {
+        let name = ModelPlatformName::Ollama.text();
+        let (platform, proxy) = init_service(name, Some(DEFAULT_BASE_URL.to_string())).await;
+        return Ollama {
+            platform,
+            proxy,
+            name,
+        };
+    }
+
+    /// The base url is `http://host:port/api`, `http://host:port` is accepted as well
+    fn api_url(&self, path: &str) -> String {
+        let base_url = match self.platform.base_url.trim_end_matches('/') {
+            "" => DEFAULT_BASE_URL,
+            base_url => base_url,
+        };
+        if base_url.ends_with("/api") {
+            format!("{}/{}", base_url, path)
+        } else {
+            format!("{}/api/{}", base_url, path)
+        }
+    }
+
+    fn http_client(&self) -> Result<reqwest::Client, AppError> {
+        create_http_client(&self.platform, &self.proxy)
+    }
+
+    /// Sent through the shared remote-call layer, a busy server is retried
+    async fn post(&self, path: &str, body: &Value) -> Result<reqwest::Response, AppError> {
+        let client = self.http_client()?;
+        let url = self.api_url(path);
+        remote_call_util::send(&self.platform, || Ok(client.post(&url).json(body))).await
+    }
+
+    /// Models pulled into the server
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>, AppError> {
+        let response: Value = self
+            .http_client()?
+            .get(self.api_url("tags"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let models = serde_json::from_value(response["models"].clone())?;
+        Ok(models)
+    }
+
+    /// Embeddings of the texts in order, by an embedding model such as `all-minilm`.
+    /// They are stored in the vector tables, so a model of another dimension is rejected
+    pub async fn embed(&self, model: &str, input: &[&str]) -> Result<Vec<Vec<f32>>, AppError> {
+        let response: Value = self
+            .post("embed", &json!({ "model": model, "input": input }))
+            .await?
+            .json()
+            .await?;
+        if let Some(error) = response["error"].as_str() {
+            return Err(AppError::EmbeddingError(error.to_string()));
+        }
+        let embeddings: Vec<Vec<f32>> = serde_json::from_value(response["embeddings"].clone())?;
+        if embeddings.len() != input.len() {
+            return Err(AppError::EmbeddingError(format!(
+                "expected {} embeddings, got {}",
+                input.len(),
+                embeddings.len()
+            )));
+        }
+        if let Some(embedding) = embeddings
+            .iter()
+            .find(|embedding| embedding.len() != EMBEDDING_DIMENSION)
+        {
+            return Err(AppError::EmbeddingSizeMismatch(format!(
+                "{} has {} dimensions, {} expected",
+                model,
+                embedding.len(),
+                EMBEDDING_DIMENSION
+            )));
+        }
+        Ok(embeddings)
+    }
+
+    /// Dimension of the embeddings of a model, read from `/api/show`
+    pub async fn embedding_length(&self, model: &str) -> Result<usize, AppError> {
+        let response: Value = self
+            .post("show", &json!({ "model": model }))
+            .await?
+            .json()
+            .await?;
+        let model_info = &response["model_info"];
+        let architecture = model_info["general.architecture"]
+            .as_str()
+            .unwrap_or_default();
+        model_info[format!("{}.embedding_length", architecture)]
+            .as_u64()
+            .map(|length| length as usize)
+            .ok_or_else(|| {
+                AppError::EmbeddingError(format!("embedding length of {} not found", model))
+            })
+    }
+
+    /// Check a model before it is set as the embedding model, its dimension must match the vector tables
+    pub async fn check_embedding_model(&self, model: &str) -> Result<(), AppError> {
+        let length = self.embedding_length(model).await?;
+        if length != EMBEDDING_DIMENSION {
+            return Err(AppError::EmbeddingSizeMismatch(format!(
+                "{} has {} dimensions, {} expected",
+                model, length, EMBEDDING_DIMENSION
+            )));
+        }
+        Ok(())
+    }
+
+    /// Non-streaming chat, images are base64 encoded without the data uri prefix
+    pub async fn chat_once(
+        &self,
+        ai_model: &AiModel,
+        operation: ModelOperation,
+        prompt: &str,
+        images: Vec<String>,
+    ) -> Result<String, AppError> {
+        let image_count = images.len() as i64;
+        let mut message = json!({ "role": "user", "content": prompt });
+        if !images.is_empty() {
+            message["images"] = json!(images);
+        }
+        let response: Value = self
+            .post(
+                "chat",
+                &json!({ "model": ai_model.name, "messages": [message], "stream": false }),
+            )
+            .await?
+            .json()
+            .await?;
+        if let Some(error) = response["error"].as_str() {
+            return Err(AppError::InternalError(error.to_string()));
+        }
+        usage_util::record(
+            &self.platform.name,
+            ai_model,
+            operation,
+            ModelUsage {
+                image_count,
+                ..usage_from_response(&response)
+            },
+        );
+        Ok(response["message"]["content"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// Streaming chat, the response is one JSON object per line
+    pub async fn chat_stream_once<F>(
+        &self,
+        ai_model: &AiModel,
+        prompt: &str,
+        callback: &F,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: Fn(&str) -> Result<(), Box<dyn std::error::Error>>,
+    {
+        let mut response = self
+            .post(
+                "chat",
+                &json!({
+                    "model": ai_model.name,
+                    "messages": [{ "role": "user", "content": prompt }],
+                    "stream": true
+                }),
+            )
+            .await?;
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut usage = None;
+        while let Some(bytes) = response.chunk().await? {
+            buffer.extend_from_slice(&bytes);
+            while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                usage = handle_stream_line(&line, callback)?.or(usage);
+            }
+        }
+        usage = handle_stream_line(&buffer, callback)?.or(usage);
+        if let Some(usage) = usage {
+            usage_util::record(&self.platform.name, ai_model, ModelOperation::Chat, usage);
+        }
+        Ok(())
+    }
+}
+
+/// Pass the content of a line to the callback, the usage is in the last line
+fn handle_stream_line<F>(
+    line: &[u8],
+    callback: &F,
+) -> Result<Option<ModelUsage>, Box<dyn std::error::Error>>
+where
+    F: Fn(&str) -> Result<(), Box<dyn std::error::Error>>,
+{
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+    let chunk: Value = serde_json::from_slice(line)?;
+    if let Some(error) = chunk["error"].as_str() {
+        return Err(Box::new(AppError::InternalError(error.to_string())));
+    }
+    if let Some(content) = chunk["message"]["content"]
+        .as_str()
+        .filter(|c| !c.is_empty())
+    {
+        if let Err(e) = callback(content) {
+            eprintln!("Callback error: {}", e);
+        }
+    }
+    if chunk["done"].as_bool() == Some(true) {
+        return Ok(Some(usage_from_response(&chunk)));
+    }
+    Ok(None)
+}
+
+/// Token counts of a finished chat
+fn usage_from_response(response: &Value) -> ModelUsage {
+    ModelUsage {
+        prompt_tokens: response["prompt_eval_count"].as_i64().unwrap_or(0),
+        completion_tokens: response["eval_count"].as_i64().unwrap_or(0),
+        ..Default::default()
+    }
+}
+
+impl WithPlatformConfig for Ollama {
+    fn platform(&self) -> &ModelPlatform {
+        &self.platform
+    }
+    fn proxy(&self) -> &ProxyInfo {
+        &self.proxy
+    }
+}
+
+impl ChatCapable for Ollama {
+    async fn chat(
+        &self,
+        ai_model: &AiModel,
+        prompt: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(self
+            .chat_once(ai_model, ModelOperation::Chat, prompt, Vec::new())
+            .await?)
+    }
+
+    async fn chat_stream<F>(
+        &self,
+        ai_model: &AiModel,
+        prompt: &str,
+        callback: &F,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: Fn(&str) -> Result<(), Box<dyn std::error::Error>>,
+    {
+        self.chat_stream_once(ai_model, prompt, callback).await
+    }
+}
+
+#[async_trait]
+impl ImageAnalyzer for Ollama {
+    fn is_stream(&self) -> bool {
+        false
+    }
+
+    async fn analyze_image(
+        &self,
+        ai_model: &AiModel,
+        image_path: &str,
+    ) -> Result<String, AppError> {
+        if !is_supported_image_file(image_path)? {
+            return Err(AppError::UnsupportedFormat(image_path.to_string()));
+        }
+        let image = STANDARD.encode(std::fs::read(image_path)?);
+        self.chat_once(
+            ai_model,
+            ModelOperation::Image,
+            t!("prompt.image-analyze").as_ref(),
+            vec![image],
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl VideoDescriber for Ollama {
+    async fn describe_video(&self, ai_model: &AiModel, title: &str) -> Result<String, AppError> {
+        let prompt = t!("prompt.video-introduce", title = title);
+        let description = self
+            .chat_once(ai_model, ModelOperation::Chat, prompt.as_ref(), Vec::new())
+            .await?;
+        if description.contains(t!("prompt.video-no-content").as_ref()) {
+            return Ok(String::new());
+        }
+        Ok(description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_server_util;
+    use std::cell::RefCell;
+    use std::time::Duration;
+
+    /// Serve the canned response bodies by request path
+    async fn mock_server(routes: Vec<(&'static str, String)>) -> String {
+        let server = mock_server_util::start(Duration::ZERO, move |_, path| {
+            match routes.iter().find(|(route, _)| *route == path) {
+                Some((_, body)) => ("200 OK".to_string(), body.clone()),
+                None => ("404 Not Found".to_string(), "{}".to_string()),
+            }
+        })
+        .await;
+        server.url
+    }
+
+    fn service(base_url: String) -> Ollama {
+        Ollama {
+            platform: ModelPlatform {
+                name: ModelPlatformName::Ollama.text().to_string(),
+                base_url,
+                ..Default::default()
+            },
+            proxy: ProxyInfo::default(),
+            name: ModelPlatformName::Ollama.text(),
+        }
+    }
+
+    fn model(name: &str) -> AiModel {
+        AiModel {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn list_models() {
+        let base_url = mock_server(vec![(
+            "/api/tags",
+            r#"{"models":[{"name":"llava:7b","size":4733363377,"details":{"family":"llama","families":["llama","clip"],"parameter_size":"7B","quantization_level":"Q4_0"}},{"name":"nomic-embed-text:latest"}]}"#.to_string(),
+        )])
+        .await;
+        // Without the `/api` suffix
+        let ollama = service(base_url);
+        assert!(ollama.platform.is_available());
+
+        let models = ollama.list_models().await.unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].name, "llava:7b");
+        assert_eq!(models[0].details.parameter_size, "7B");
+        assert_eq!(models[1].details.family, "");
+    }
+
+    #[tokio::test]
+    async fn embed_checks_dimension() {
+        let embedding = vec![0.5f32; EMBEDDING_DIMENSION];
+        let base_url = mock_server(vec![
+            (
+                "/api/embed",
+                json!({ "model": "all-minilm", "embeddings": [embedding, embedding] }).to_string(),
+            ),
+            (
+                "/api/show",
+                r#"{"model_info":{"general.architecture":"bert","bert.embedding_length":384}}"#
+                    .to_string(),
+            ),
+        ])
+        .await;
+        let ollama = service(base_url);
+        let embeddings = ollama
+            .embed("all-minilm", &["first", "second"])
+            .await
+            .unwrap();
+        assert_eq!(embeddings, vec![embedding.clone(), embedding]);
+        assert!(ollama.embed("all-minilm", &["only one"]).await.is_err());
+        assert_eq!(ollama.embedding_length("all-minilm").await.unwrap(), 384);
+        assert!(ollama.check_embedding_model("all-minilm").await.is_ok());
+
+        // Vectors of another dimension don't fit the vector tables
+        let base_url = mock_server(vec![
+            (
+                "/api/embed",
+                r#"{"model":"nomic-embed-text","embeddings":[[0.1,0.2]]}"#.to_string(),
+            ),
+            (
+                "/api/show",
+                r#"{"model_info":{"general.architecture":"nomic-bert","nomic-bert.embedding_length":768}}"#
+                    .to_string(),
+            ),
+        ])
+        .await;
+        let ollama = service(base_url);
+        assert!(matches!(
+            ollama.embed("nomic-embed-text", &["text"]).await,
+            Err(AppError::EmbeddingSizeMismatch(_))
+        ));
+        assert!(matches!(
+            ollama.check_embedding_model("nomic-embed-text").await,
+            Err(AppError::EmbeddingSizeMismatch(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn chat_and_stream() {
+        let base_url = mock_server(vec![(
+            "/api/chat",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"Hel\"},\"done\":false}\n{\"message\":{\"role\":\"assistant\",\"content\":\"lo\"},\"done\":false}\n{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"eval_count\":2}\n".to_string(),
+        )])
+        .await;
+        let ollama = service(format!("{}/api/", base_url));
+
+        let received = RefCell::new(String::new());
+        let callback = |content: &str| -> Result<(), Box<dyn std::error::Error>> {
+            received.borrow_mut().push_str(content);
+            Ok(())
+        };
+        ollama
+            .chat_stream_once(&model("llama3.2"), "Say hello", &callback)
+            .await
+            .unwrap();
+        assert_eq!(received.borrow().as_str(), "Hello");
+
+        let base_url = mock_server(vec![(
+            "/api/chat",
+            r#"{"message":{"role":"assistant","content":"A mango on a desk."},"done":true}"#
+                .to_string(),
+        )])
+        .await;
+        let ollama = service(format!("{}/api", base_url));
+        let content = ollama
+            .chat_once(
+                &model("llava"),
+                ModelOperation::Image,
+                "What is in the image?",
+                vec!["aGVsbG8=".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(content, "A mango on a desk.");
+    }
+}
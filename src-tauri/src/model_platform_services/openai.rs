@@ -2,6 +2,7 @@ use crate::enums::ModelPlatformName;
 use crate::structs::proxy_setting::ProxyInfo;
 use crate::traits::chat_capable::ChatCapable;
 use crate::traits::image_analyzer::ImageAnalyzer;
+use crate::traits::video_describer::VideoDescriber;
 use crate::traits::with_platform_config::WithPlatformConfig;
 use crate::utils::llm_client_util::init_service;
 use crate::{entities::ModelPlatform, traits::audio_analyzer::AudioAnalyzer};
@@ -52,3 +53,5 @@ impl ImageAnalyzer for OpenAi {
 }
 
 impl AudioAnalyzer for OpenAi {}
+
+impl VideoDescriber for OpenAi {}
@@ -3,6 +3,7 @@ use crate::structs::proxy_setting::ProxyInfo;
 use crate::traits::audio_analyzer::AudioAnalyzer;
 use crate::traits::chat_capable::ChatCapable;
 use crate::traits::image_analyzer::ImageAnalyzer;
+use crate::traits::video_describer::VideoDescriber;
 use crate::traits::with_platform_config::WithPlatformConfig;
 use crate::utils::llm_client_util::init_service;
 use serde::{Deserialize, Serialize};
@@ -43,3 +44,5 @@ impl ImageAnalyzer for OpenAiCompatibleService {
 }
 
 impl AudioAnalyzer for OpenAiCompatibleService {}
+
+impl VideoDescriber for OpenAiCompatibleService {}
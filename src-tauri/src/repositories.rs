@@ -1,10 +1,17 @@
 pub mod ai_model_repo;
+pub mod analysis_cache_repo;
+pub mod backup_repo;
 pub mod config_repo;
+pub mod file_content_chunk_repo;
 pub mod file_content_embedding_repo;
 pub mod file_info_repo;
 pub mod file_metadata_embedding_repo;
 pub mod indexing_task_repo;
 pub mod model_platform_repo;
+pub mod model_usage_repo;
+pub mod saved_search_repo;
+pub mod search_history_repo;
+pub mod tag_repo;
 
 use crate::errors::AppError;
 use thiserror::Error;
@@ -56,3 +63,17 @@ impl From<RepositoryError> for rusqlite::Error {
         }
     }
 }
+
+/// KNN condition of the embedding tables, `file_id` is a metadata column of the vec0 tables so it can be filtered
+fn file_id_condition(file_ids: Option<&[i64]>) -> String {
+    match file_ids {
+        Some(ids) => format!(
+            " and file_id in ({})",
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        None => String::new(),
+    }
+}
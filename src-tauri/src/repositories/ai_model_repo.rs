@@ -36,6 +36,33 @@ pub fn get_one_by_type(platform: &str, one_type: &str) -> Result<Option<AiModel>
     return Ok(one);
 }
 
+/// All models of the platform, including the disabled ones
+pub fn list_by_platform(platform: &str) -> Result<Vec<AiModel>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare("select * from ai_model where platform = ?1 order by id")?;
+    let rows = stmt.query_map([platform], |row| Ok(build_ai_model(row)?))?;
+    let mut result = Vec::new();
+    for item in rows {
+        result.push(item?);
+    }
+    Ok(result)
+}
+
+pub fn get_by_id(id: i64) -> Result<Option<AiModel>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare("select * from ai_model where id = ?1")?;
+    let mut rows = stmt.query_map([id], |row| Ok(build_ai_model(row)?))?;
+    Ok(rows.next().transpose()?)
+}
+
+/// Whether another model(id other than `exclude_id`) of the platform has the name
+pub fn exists(platform: &str, name: &str, exclude_id: i64) -> Result<bool, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt =
+        conn.prepare("select 1 from ai_model where platform = ?1 and name = ?2 and id != ?3")?;
+    Ok(stmt.exists((platform, name, exclude_id))?)
+}
+
 pub fn insert(ai_model: &AiModel) -> Result<AiModel, RepositoryError> {
     let conn = Connection::open(get_db_path())?;
     let mut stmt = conn.prepare(
@@ -91,6 +118,12 @@ pub fn update(ai_model: &AiModel) -> Result<usize, RepositoryError> {
     Ok(affected)
 }
 
+pub fn delete(id: i64) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute("delete from ai_model where id = ?1", [id])?;
+    Ok(affected)
+}
+
 fn build_ai_model(row: &Row<'_>) -> Result<AiModel, RepositoryError> {
     let create_time_str: String = row.get("create_time")?;
     let update_time_str: String = row.get("update_time")?;
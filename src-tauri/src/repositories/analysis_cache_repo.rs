@@ -0,0 +1,74 @@
+use crate::repositories::RepositoryError;
+use crate::utils::app_util::get_db_path;
+use rusqlite::{Connection, OptionalExtension, Result, named_params};
+
+/// The cached content of a file analyzed by the model with the prompt version, the hit time is refreshed
+pub fn get(
+    md5: &str,
+    platform: &str,
+    model: &str,
+    prompt_version: &str,
+) -> Result<Option<String>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let params = named_params! {
+        ":md5": md5,
+        ":platform": platform,
+        ":model": model,
+        ":prompt_version": prompt_version,
+    };
+    let content: Option<String> = conn
+        .query_row(
+            "select content from analysis_cache where md5 = :md5 and platform = :platform and model = :model and prompt_version = :prompt_version",
+            params,
+            |row| row.get(0),
+        )
+        .optional()?;
+    if content.is_some() {
+        conn.execute(
+            "update analysis_cache set hit_time = datetime('now', 'localtime') where md5 = :md5 and platform = :platform and model = :model and prompt_version = :prompt_version",
+            params,
+        )?;
+    }
+    Ok(content)
+}
+
+pub fn save(
+    md5: &str,
+    platform: &str,
+    model: &str,
+    prompt_version: &str,
+    content: &str,
+) -> Result<(), RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    conn.execute(
+        "insert into analysis_cache(md5,platform,model,prompt_version,content,create_time,hit_time) values (:md5,:platform,:model,:prompt_version,:content,datetime('now', 'localtime'),datetime('now', 'localtime')) on conflict(md5,platform,model,prompt_version) do update set content = excluded.content, hit_time = excluded.hit_time",
+        named_params! {
+            ":md5": md5,
+            ":platform": platform,
+            ":model": model,
+            ":prompt_version": prompt_version,
+            ":content": content,
+        },
+    )?;
+    Ok(())
+}
+
+/// Delete the entries not used for `max_days`, then the least recently used beyond `max_entries`
+pub fn evict(max_days: i64, max_entries: i64) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let expired = conn.execute(
+        "delete from analysis_cache where hit_time < datetime('now', 'localtime', :days)",
+        named_params! {":days": format!("-{} days", max_days)},
+    )?;
+    let overflow = conn.execute(
+        "delete from analysis_cache where id in (select id from analysis_cache order by hit_time desc, id desc limit -1 offset :max_entries)",
+        named_params! {":max_entries": max_entries},
+    )?;
+    Ok(expired + overflow)
+}
+
+pub fn purge() -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let count = conn.execute("delete from analysis_cache", [])?;
+    Ok(count)
+}
@@ -0,0 +1,71 @@
+use crate::global::{CONFIG_NAME_CLIENT_ID, CONFIG_NAME_PROXY};
+use crate::repositories::RepositoryError;
+use crate::utils::app_util::get_db_path;
+use rusqlite::{Connection, MAIN_DB, Result, named_params};
+use std::path::Path;
+
+/// Copy the database to the path by the online backup API, the writes during the copy are not lost
+pub fn backup_to(path: &Path) -> Result<(), RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    conn.backup(MAIN_DB, path, None)?;
+    Ok(())
+}
+
+/// Replace the content of the database by the one of the path
+pub fn restore_from(path: &Path) -> Result<(), RepositoryError> {
+    let mut conn = Connection::open(get_db_path())?;
+    conn.restore(MAIN_DB, path, None::<fn(rusqlite::backup::Progress)>)?;
+    Ok(())
+}
+
+/// Remove the API keys, the proxy(with its password), the client id and the search history from a copy of the database,
+/// the freed pages are zeroed and dropped by the vacuum
+pub fn strip_secrets(path: &Path) -> Result<(), RepositoryError> {
+    let conn = Connection::open(path)?;
+    conn.execute_batch(
+        "pragma secure_delete = on; update model_platform set api_key = ''; delete from search_history;",
+    )?;
+    conn.execute(
+        "delete from config where name in (:client_id, :proxy)",
+        named_params! {
+            ":client_id": CONFIG_NAME_CLIENT_ID,
+            ":proxy": CONFIG_NAME_PROXY,
+        },
+    )?;
+    conn.execute_batch("vacuum")?;
+    Ok(())
+}
+
+pub fn is_intact(path: &Path) -> Result<bool, RepositoryError> {
+    let conn = Connection::open(path)?;
+    let result: String = conn.query_row("pragma quick_check", [], |row| row.get(0))?;
+    Ok(result == "ok")
+}
+
+/// The encrypted API keys by platform name
+pub fn list_api_keys() -> Result<Vec<(String, String)>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare("select name, api_key from model_platform where api_key != ''")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    let mut result = Vec::new();
+    for item in rows {
+        result.push(item?);
+    }
+    Ok(result)
+}
+
+/// Set back the encrypted API keys of the platforms without one
+pub fn restore_api_keys(api_keys: &[(String, String)]) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "update model_platform set api_key = :api_key where name = :name and api_key = ''",
+    )?;
+    let mut affected = 0;
+    for (name, api_key) in api_keys {
+        affected += stmt.execute(named_params! {
+            ":name": name,
+            ":api_key": api_key,
+        })?;
+    }
+    Ok(affected)
+}
@@ -0,0 +1,90 @@
+use crate::repositories::RepositoryError;
+use crate::structs::chunk_locator::ChunkLocator;
+use crate::utils::app_util::get_db_path;
+use rusqlite::{Connection, Result, named_params};
+use std::collections::HashMap;
+
+pub fn insert(
+    embedding_id: i64,
+    file_id: i64,
+    chunk_index: i64,
+    locator: &ChunkLocator,
+) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "insert or replace into file_content_chunk(embedding_id,file_id,chunk_index,locator) values (:embedding_id,:file_id,:chunk_index,:locator)",
+    )?;
+    let affected = stmt.execute(named_params! {
+        ":embedding_id": embedding_id,
+        ":file_id": file_id,
+        ":chunk_index": chunk_index,
+        ":locator": locator.to_json(),
+    })?;
+    Ok(affected)
+}
+
+/// Key: file_content_embedding.id, value: locator of the chunk
+pub fn map_by_embedding_ids(
+    embedding_ids: &[i64],
+) -> Result<HashMap<i64, ChunkLocator>, RepositoryError> {
+    if embedding_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let conn = Connection::open(get_db_path())?;
+    let ids_str = embedding_ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut stmt = conn.prepare(&format!(
+        "select embedding_id, locator from file_content_chunk where embedding_id in ({})",
+        ids_str
+    ))?;
+    let rows = stmt.query_map((), |row| {
+        let embedding_id: i64 = row.get("embedding_id")?;
+        let locator: String = row.get("locator")?;
+        Ok((embedding_id, ChunkLocator::from_json(&locator)))
+    })?;
+    let mut locators = HashMap::new();
+    for row_result in rows {
+        match row_result {
+            Ok((embedding_id, locator)) => {
+                locators.insert(embedding_id, locator);
+            }
+            Err(e) => eprintln!("Error retrieving chunk locator: {}", e),
+        }
+    }
+    Ok(locators)
+}
+
+pub fn delete_by_file_id(file_id: i64) -> Result<usize, RepositoryError> {
+    if file_id < 1 {
+        return Ok(0);
+    }
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare("delete from file_content_chunk where file_id = :file_id")?;
+    let affected = stmt.execute(named_params! {":file_id": file_id})?;
+    Ok(affected)
+}
+
+pub fn delete_by_file_prefix_path(file_prefix_path: &str) -> Result<usize, RepositoryError> {
+    if file_prefix_path.is_empty() {
+        return Ok(0);
+    }
+    let pattern = if file_prefix_path.ends_with(std::path::MAIN_SEPARATOR) {
+        format!("{}%", file_prefix_path)
+    } else {
+        format!("{}{}%", file_prefix_path, std::path::MAIN_SEPARATOR)
+    };
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare("delete from file_content_chunk where file_id in (select id from file_info where path like :prefix_path)")?;
+    let affected = stmt.execute(named_params! {":prefix_path": pattern})?;
+    Ok(affected)
+}
+
+pub fn clear() -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare("delete from file_content_chunk")?;
+    let affected = stmt.execute([])?;
+    Ok(affected)
+}
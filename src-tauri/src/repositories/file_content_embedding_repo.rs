@@ -64,9 +64,12 @@ pub fn update(file_content_embedding: &FileContentEmbedding) -> Result<usize, Re
     Ok(affected)
 }
 
+/// KNN search of the chunks, only in the files of `file_ids` if it is given
 pub fn search(
     embedding: &[f32],
     max_distance: f32,
+    limit: usize,
+    file_ids: Option<&[i64]>,
 ) -> Result<Vec<FileContentEmbedding>, RepositoryError> {
     let embedding_bytes = unsafe {
         std::slice::from_raw_parts(
@@ -76,22 +79,28 @@ pub fn search(
     };
 
     let conn = Connection::open(get_db_path())?;
-    let mut stmt = conn.prepare("select *,distance from file_content_embedding where embedding match :embedding order by distance asc limit 10")?;
-    let rows = stmt.query_map(named_params! {":embedding": embedding_bytes}, |row| {
-        let embedding_bytes: Vec<u8> = row.get("embedding")?;
-        let embedding: [f32; 384] = unsafe {
-            let ptr = embedding_bytes.as_ptr() as *const f32;
-            std::ptr::read(ptr as *const [f32; 384])
-        };
-        Ok(FileContentEmbedding {
-            id: row.get("id")?,
-            file_id: row.get("file_id")?,
-            chunk_index: row.get("chunk_index")?,
-            chunk_text: row.get("chunk_text")?,
-            embedding,
-            distance: row.get("distance")?,
-        })
-    })?;
+    let mut stmt = conn.prepare(&format!(
+        "select *,distance from file_content_embedding where embedding match :embedding{} order by distance asc limit :limit",
+        super::file_id_condition(file_ids)
+    ))?;
+    let rows = stmt.query_map(
+        named_params! {":embedding": embedding_bytes, ":limit": limit as i64},
+        |row| {
+            let embedding_bytes: Vec<u8> = row.get("embedding")?;
+            let embedding: [f32; 384] = unsafe {
+                let ptr = embedding_bytes.as_ptr() as *const f32;
+                std::ptr::read(ptr as *const [f32; 384])
+            };
+            Ok(FileContentEmbedding {
+                id: row.get("id")?,
+                file_id: row.get("file_id")?,
+                chunk_index: row.get("chunk_index")?,
+                chunk_text: row.get("chunk_text")?,
+                embedding,
+                distance: row.get("distance")?,
+            })
+        },
+    )?;
     if max_distance < 0.0 {
         return Ok(rows.collect::<Result<Vec<FileContentEmbedding>, Error>>()?);
     }
@@ -113,7 +122,8 @@ pub fn search(
     return Ok(filtered_result);
 }
 
-pub fn list_chunks_by_ids(ids: &Vec<u32>) -> Result<Vec<String>, RepositoryError> {
+/// (embedding id, chunk text) of the chunks
+pub fn list_chunks_by_ids(ids: &Vec<u32>) -> Result<Vec<(i64, String)>, RepositoryError> {
     if ids.is_empty() {
         return Ok(Vec::new());
     }
@@ -124,17 +134,18 @@ pub fn list_chunks_by_ids(ids: &Vec<u32>) -> Result<Vec<String>, RepositoryError
         .collect::<Vec<_>>()
         .join("','");
     let mut stmt = conn.prepare(&format!(
-        "select chunk_text from file_content_embedding where id in ('{}') order by chunk_index asc",
+        "select id, chunk_text from file_content_embedding where id in ('{}') order by chunk_index asc",
         ids_str
     ))?;
     let rows = stmt.query_map((), |row| {
+        let id: i64 = row.get("id")?;
         let chunk_text: String = row.get("chunk_text")?;
-        Ok(chunk_text)
+        Ok((id, chunk_text))
     })?;
-    let mut segments: Vec<String> = Vec::new();
+    let mut segments: Vec<(i64, String)> = Vec::new();
     for row_result in rows {
         match row_result {
-            Ok(chunk_text) => segments.push(chunk_text),
+            Ok(segment) => segments.push(segment),
             Err(e) => eprintln!("Error retrieving chunk text: {}", e),
         }
     }
@@ -1,9 +1,10 @@
 use crate::entities::FileInfo;
 use crate::repositories::RepositoryError;
+use crate::structs::search_filter::GeoBounds;
 use crate::utils::app_util::get_db_path;
-use crate::utils::datetime_util;
+use crate::utils::{datetime_util, path_util};
 use chrono::{DateTime, Local};
-use rusqlite::{Connection, Result, Row, named_params};
+use rusqlite::{Connection, Result, Row, named_params, params_from_iter};
 
 const ALL_COLUMNS_EXCEPT_CONTENT: &str = "id, name, category, path, file_ext, file_size, content, content_index_status, content_index_status_msg, meta_index_status, meta_index_status_msg, is_invalid, invalid_reason, md5, metadata, file_create_time, file_update_time, create_time, update_time";
 
@@ -199,6 +200,33 @@ pub fn list_paths(page: i64, size: i64, asc: bool) -> Result<Vec<String>, Reposi
     Ok(result)
 }
 
+/// Ids and paths of the files whose path contains one of the keywords, ASCII case insensitive like the path search
+pub fn list_id_paths_by_keywords(keywords: &[&str]) -> Result<Vec<(i64, String)>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    query_id_paths_by_keywords(&conn, keywords)
+}
+
+fn query_id_paths_by_keywords(
+    conn: &Connection,
+    keywords: &[&str],
+) -> Result<Vec<(i64, String)>, RepositoryError> {
+    if keywords.is_empty() {
+        return Ok(Vec::new());
+    }
+    // Not LIKE, which takes `_` and `%` of the keywords as wildcards
+    let conditions = vec!["instr(lower(path), lower(?)) > 0"; keywords.len()].join(" or ");
+    let mut stmt = conn.prepare(&format!(
+        "select id, path from file_info where {} order by id",
+        conditions
+    ))?;
+    let rows = stmt
+        .query_map(params_from_iter(keywords), |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<Result<Vec<(i64, String)>, _>>()?;
+    Ok(rows)
+}
+
 pub fn list_unindexed_files(
     min_id: i64,
     limit: i64,
@@ -409,21 +437,35 @@ pub fn count_by_prefix_path(pre_path: &str) -> Result<i64, RepositoryError> {
     Ok(count)
 }
 
+/// Move the files under a directory to another one, the video paths recorded in the metadata of subtitles included
 pub fn replace_directory_prefix_path(
     old_pre_path: &str,
     new_pre_path: &str,
 ) -> Result<usize, RepositoryError> {
-    let pattern = if old_pre_path.ends_with(std::path::MAIN_SEPARATOR) {
-        format!("{}%", old_pre_path)
-    } else {
-        format!("{}{}%", old_pre_path, std::path::MAIN_SEPARATOR)
-    };
     let conn = Connection::open(get_db_path())?;
+    let affected = replace_prefix_path(&conn, old_pre_path, new_pre_path)?;
+    println!("replace file_info by prefix path affected: {:?}", affected);
+    Ok(affected)
+}
+
+fn replace_prefix_path(
+    conn: &Connection,
+    old_pre_path: &str,
+    new_pre_path: &str,
+) -> Result<usize, RepositoryError> {
+    let old_dir = old_pre_path.trim_end_matches(std::path::MAIN_SEPARATOR);
+    let new_dir = new_pre_path.trim_end_matches(std::path::MAIN_SEPARATOR);
+    // The separator keeps a sibling such as `/a/bc` of `/a/b` out, and only the leading directory is replaced
+    let old_prefix = format!("{}{}", old_dir, std::path::MAIN_SEPARATOR);
     let affected = conn.execute(
-        "UPDATE file_info SET path = REPLACE(path, ?1, ?2) WHERE path LIKE ?3",
-        (old_pre_path, new_pre_path, pattern),
+        "UPDATE file_info SET path = ?2 || substr(path, length(?1) + 1) WHERE substr(path, 1, length(?3)) = ?3",
+        (old_dir, new_dir, &old_prefix),
+    )?;
+    conn.execute(
+        "UPDATE file_info SET metadata = json_set(metadata, '$.video_path', ?2 || substr(json_extract(metadata, '$.video_path'), length(?1) + 1))
+        WHERE substr(json_extract(metadata, '$.video_path'), 1, length(?3)) = ?3",
+        (old_dir, new_dir, &old_prefix),
     )?;
-    println!("replace file_info by prefix path affected: {:?}", affected);
     Ok(affected)
 }
 
@@ -437,6 +479,81 @@ pub fn rename(old_path: &str, new_path: &str, new_name: &str) -> Result<usize, R
     Ok(affected)
 }
 
+/// Ids of the members indexed under a container file, see `path_util::to_virtual_path`
+pub fn list_member_ids(container_path: &str) -> Result<Vec<i64>, RepositoryError> {
+    let prefix = path_util::to_virtual_path(container_path, "");
+    let conn = Connection::open(get_db_path())?;
+    // Not LIKE, which ignores the case and takes `_` and `%` of the path as wildcards
+    let mut stmt =
+        conn.prepare("select id from file_info where substr(path, 1, length(?1)) = ?1")?;
+    let ids = stmt
+        .query_map([prefix], |row| row.get(0))?
+        .collect::<Result<Vec<i64>, _>>()?;
+    Ok(ids)
+}
+
+pub fn rename_members(
+    old_container_path: &str,
+    new_container_path: &str,
+) -> Result<usize, RepositoryError> {
+    let old_prefix = path_util::to_virtual_path(old_container_path, "");
+    let new_prefix = path_util::to_virtual_path(new_container_path, "");
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute(
+        "UPDATE file_info SET path = ?2 || substr(path, length(?1) + 1) WHERE substr(path, 1, length(?1)) = ?1",
+        (&old_prefix, &new_prefix),
+    )?;
+    Ok(affected)
+}
+
+/// Ids and paths of the photos taken in the range and located in the bounds, read from the metadata column.
+/// The range bounds are date prefixes, the whole period of the upper bound is included
+pub fn list_photo_ids(
+    taken_from: Option<&str>,
+    taken_to: Option<&str>,
+    bounds: Option<&GeoBounds>,
+) -> Result<Vec<(i64, String)>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    query_photo_ids(&conn, taken_from, taken_to, bounds)
+}
+
+fn query_photo_ids(
+    conn: &Connection,
+    taken_from: Option<&str>,
+    taken_to: Option<&str>,
+    bounds: Option<&GeoBounds>,
+) -> Result<Vec<(i64, String)>, RepositoryError> {
+    let mut stmt = conn.prepare(
+        r#"
+        select id, path from (
+            select id, path,
+                json_extract(metadata, '$.image.taken') as taken,
+                json_extract(metadata, '$.image.latitude') as latitude,
+                json_extract(metadata, '$.image.longitude') as longitude
+            from file_info where json_extract(metadata, '$.image') is not null
+        )
+        where (:taken_from is null or (taken <> '' and taken >= :taken_from))
+            and (:taken_to is null or (taken <> '' and substr(taken, 1, length(:taken_to)) <= :taken_to))
+            and (:south is null or latitude between :south and :north)
+            and (:west is null or case when :west <= :east
+                then longitude between :west and :east
+                else longitude >= :west or longitude <= :east end)
+        "#,
+    )?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":taken_from": taken_from,
+            ":taken_to": taken_to,
+            ":south": bounds.map(|b| b.south),
+            ":west": bounds.map(|b| b.west),
+            ":north": bounds.map(|b| b.north),
+            ":east": bounds.map(|b| b.east),
+        },
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    Ok(rows.collect::<Result<Vec<(i64, String)>, _>>()?)
+}
+
 fn build_file_info(row: &Row<'_>) -> Result<FileInfo, RepositoryError> {
     let file_create_time: String = row.get("file_create_time").unwrap_or_default();
     let file_update_time: String = row.get("file_update_time").unwrap_or_default();
@@ -466,3 +583,136 @@ fn build_file_info(row: &Row<'_>) -> Result<FileInfo, RepositoryError> {
         update_time: datetime_util::str_to_datetime(update_time_str.as_str())?,
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_id_paths_containing_keywords() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            create table file_info(id integer primary key, path text);
+            insert into file_info values
+                (3, '/docs/Report_2024.pdf'),
+                (5, '/docs/report-2023.pdf'),
+                (8, '/music/100% hits.mp3');
+            "#,
+        )
+        .unwrap();
+        let ids = |keywords: &[&str]| -> Vec<i64> {
+            query_id_paths_by_keywords(&conn, keywords)
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect()
+        };
+        assert_eq!(ids(&["REPORT"]), vec![3, 5]);
+        assert_eq!(ids(&["t_2"]), vec![3]);
+        assert_eq!(ids(&["100%", "2023"]), vec![5, 8]);
+        assert_eq!(ids(&["%"]), vec![8]);
+        assert!(ids(&[]).is_empty());
+    }
+
+    #[test]
+    fn replace_prefix_of_directory_only() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "create table file_info(id integer primary key, path text, metadata text default '{}');",
+        )
+        .unwrap();
+        let sep = std::path::MAIN_SEPARATOR;
+        let paths = [
+            format!("{sep}a{sep}b{sep}1.txt"),
+            format!("{sep}a{sep}b{sep}c{sep}a{sep}b{sep}2.txt"),
+            format!("{sep}a{sep}bc{sep}3.txt"),
+            format!("{sep}a{sep}b_{sep}4.txt"),
+            format!("{sep}x{sep}a{sep}b{sep}5.txt"),
+        ];
+        for path in &paths {
+            conn.execute("insert into file_info(path) values (?1)", [path])
+                .unwrap();
+        }
+        let subtitle_metadata = serde_json::json!({
+            "video_path": format!("{sep}a{sep}b{sep}movie.mkv")
+        });
+        conn.execute(
+            "insert into file_info(path, metadata) values (?1, ?2)",
+            (
+                format!("{sep}a{sep}b{sep}movie.srt"),
+                subtitle_metadata.to_string(),
+            ),
+        )
+        .unwrap();
+
+        let affected =
+            replace_prefix_path(&conn, &format!("{sep}a{sep}b{sep}"), &format!("{sep}d")).unwrap();
+        assert_eq!(affected, 3);
+        let mut stmt = conn
+            .prepare(
+                "select path, json_extract(metadata, '$.video_path') from file_info order by id",
+            )
+            .unwrap();
+        let rows: Vec<(String, Option<String>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let paths: Vec<&str> = rows.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                format!("{sep}d{sep}1.txt"),
+                format!("{sep}d{sep}c{sep}a{sep}b{sep}2.txt"),
+                format!("{sep}a{sep}bc{sep}3.txt"),
+                format!("{sep}a{sep}b_{sep}4.txt"),
+                format!("{sep}x{sep}a{sep}b{sep}5.txt"),
+                format!("{sep}d{sep}movie.srt"),
+            ]
+        );
+        assert_eq!(rows[5].1, Some(format!("{sep}d{sep}movie.mkv")));
+    }
+
+    #[test]
+    fn list_photos_in_range() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            create table file_info(id integer primary key, path text, metadata text);
+            insert into file_info values
+                (1, '/photos/paris.jpg', '{"image":{"taken":"2023-06-30 10:00:00","latitude":48.8566,"longitude":2.3522}}'),
+                (2, '/photos/london.jpg', '{"image":{"taken":"2023-07-01 10:00:00","latitude":51.5072,"longitude":-0.1276}}'),
+                (3, '/photos/fiji.jpg', '{"image":{"taken":"","latitude":-17.7,"longitude":178.0}}'),
+                (4, '/notes/plan.md', '{"name":"plan.md"}');
+            "#,
+        )
+        .unwrap();
+        let list = |taken_from, taken_to, bounds| {
+            let mut ids: Vec<i64> = query_photo_ids(&conn, taken_from, taken_to, bounds)
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(list(Some("2023-01"), Some("2023-06"), None), vec![1]);
+        assert_eq!(list(None, Some("2023"), None), vec![1, 2]);
+        let europe = GeoBounds {
+            south: 40.0,
+            west: -10.0,
+            north: 60.0,
+            east: 10.0,
+        };
+        assert_eq!(list(None, None, Some(&europe)), vec![1, 2]);
+        let antimeridian = GeoBounds {
+            south: -20.0,
+            west: 170.0,
+            north: -10.0,
+            east: -170.0,
+        };
+        assert_eq!(list(None, None, Some(&antimeridian)), vec![3]);
+        assert!(list(Some("2023"), None, Some(&antimeridian)).is_empty());
+    }
+}
@@ -38,6 +38,31 @@ pub fn insert(
     Ok(file_metadata_embedding)
 }
 
+/// Replace the metadata embeddings of a file by the one given, in a transaction
+pub fn replace_by_file_id(file_id: i64, embedding: &[f32; 384]) -> Result<usize, RepositoryError> {
+    let mut conn = Connection::open(get_db_path())?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "delete from file_metadata_embedding where file_id = :file_id",
+        named_params! {":file_id": file_id},
+    )?;
+    let embedding_bytes = unsafe {
+        std::slice::from_raw_parts(
+            embedding.as_ptr() as *const u8,
+            embedding.len() * std::mem::size_of::<f32>(),
+        )
+    };
+    let affected = tx.execute(
+        "insert into file_metadata_embedding(file_id,embedding) values (:file_id,:embedding)",
+        named_params! {
+            ":file_id": file_id,
+            ":embedding": embedding_bytes,
+        },
+    )?;
+    tx.commit()?;
+    Ok(affected)
+}
+
 pub fn update(file_metadata_embedding: &FileMetaEmbedding) -> Result<usize, RepositoryError> {
     let conn = Connection::open(get_db_path())?;
     let mut stmt = conn.prepare(
@@ -61,6 +86,7 @@ pub fn update(file_metadata_embedding: &FileMetaEmbedding) -> Result<usize, Repo
 pub fn search(
     embedding: &[f32],
     max_distance: f32,
+    file_ids: Option<&[i64]>,
 ) -> Result<Vec<FileMetaEmbedding>, RepositoryError> {
     let embedding_bytes = unsafe {
         std::slice::from_raw_parts(
@@ -70,7 +96,10 @@ pub fn search(
     };
 
     let conn = Connection::open(get_db_path())?;
-    let mut stmt = conn.prepare("select *,distance from file_metadata_embedding where embedding match :embedding order by distance limit 10")?;
+    let mut stmt = conn.prepare(&format!(
+        "select *,distance from file_metadata_embedding where embedding match :embedding{} order by distance limit 10",
+        super::file_id_condition(file_ids)
+    ))?;
     let rows = stmt.query_map(named_params! {":embedding": embedding_bytes}, |row| {
         let embedding_bytes: Vec<u8> = row.get("embedding")?;
         let embedding: [f32; 384] = unsafe {
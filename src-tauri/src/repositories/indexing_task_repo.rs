@@ -173,6 +173,57 @@ pub fn count() -> Result<i64, RepositoryError> {
     Ok(count)
 }
 
+/// The distinct comma-joined paths of all tasks
+pub fn list_paths() -> Result<Vec<String>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare("select distinct paths from indexing_task where paths != ''")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    let mut result = Vec::new();
+    for item in rows {
+        result.push(item?);
+    }
+    Ok(result)
+}
+
+/// Move the task paths under a directory to another one, each path of the comma-joined list is checked on its own
+pub fn replace_directory_prefix_path(
+    old_pre_path: &str,
+    new_pre_path: &str,
+) -> Result<usize, RepositoryError> {
+    let old_dir = old_pre_path.trim_end_matches(std::path::MAIN_SEPARATOR);
+    let new_dir = new_pre_path.trim_end_matches(std::path::MAIN_SEPARATOR);
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare("select id, paths from indexing_task where paths != ''")?;
+    let tasks = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<(i64, String)>, _>>()?;
+    let mut update_stmt = conn.prepare("update indexing_task set paths = :paths where id = :id")?;
+    let mut affected = 0;
+    for (id, paths) in tasks {
+        let new_paths = paths
+            .split(',')
+            .map(|path| match path.trim().strip_prefix(old_dir) {
+                Some(rest) if rest.is_empty() || rest.starts_with(std::path::MAIN_SEPARATOR) => {
+                    format!("{}{}", new_dir, rest)
+                }
+                _ => path.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(",");
+        if new_paths != paths {
+            affected += update_stmt.execute(named_params! {
+                ":id": id,
+                ":paths": new_paths,
+            })?;
+        }
+    }
+    println!(
+        "replace indexing_task by prefix path affected: {:?}",
+        affected
+    );
+    Ok(affected)
+}
+
 pub fn delete_by_id(id: i64) -> Result<usize, RepositoryError> {
     let conn = Connection::open(get_db_path())?;
     let mut stmt = conn.prepare("delete from indexing_task where id = :id")?;
@@ -1,7 +1,7 @@
 use crate::entities::ModelPlatform;
 use crate::repositories::RepositoryError;
 use crate::utils::app_util::get_db_path;
-use crate::utils::datetime_util;
+use crate::utils::{datetime_util, secret_util};
 use rusqlite::{Connection, Result, Row, named_params};
 
 pub fn get_one(name: &str) -> Result<ModelPlatform, RepositoryError> {
@@ -33,10 +33,60 @@ pub fn list(names: &Vec<String>) -> Result<Vec<ModelPlatform>, RepositoryError>
     Ok(result)
 }
 
+/// Platforms added by the user, they are served through the OpenAI compatible API
+pub fn list_custom() -> Result<Vec<ModelPlatform>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn
+        .prepare("select * from model_platform where is_openai_api_compatible = 1 order by id")?;
+    let rows = stmt.query_map([], |row| Ok(build_model_platform(row)?))?;
+    let mut result = Vec::new();
+    for item in rows {
+        result.push(item?);
+    }
+    Ok(result)
+}
+
+pub fn exists(name: &str) -> Result<bool, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare("select 1 from model_platform where name = ?1")?;
+    Ok(stmt.exists([name])?)
+}
+
+/// The API key is encrypted before it is saved
+pub fn insert(platform: &ModelPlatform) -> Result<ModelPlatform, RepositoryError> {
+    let api_key = encrypt_api_key(&platform.api_key)?;
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "insert into model_platform(name,title,base_url,api_key,logo,remark,is_proxy_enable,is_openai_api_compatible,is_enable,max_concurrency,requests_per_minute) values (:name,:title,:base_url,:api_key,:logo,:remark,:is_proxy_enable,:is_openai_api_compatible,:is_enable,:max_concurrency,:requests_per_minute)",
+    )?;
+    let last_insert_rowid = stmt.insert(named_params! {
+        ":name": &platform.name,
+        ":title": &platform.title,
+        ":base_url": &platform.base_url,
+        ":api_key": &api_key,
+        ":logo": &platform.logo,
+        ":remark": &platform.remark,
+        ":is_proxy_enable": &platform.is_proxy_enable,
+        ":is_openai_api_compatible": &platform.is_openai_api_compatible,
+        ":is_enable": &platform.is_enable,
+        ":max_concurrency": &platform.max_concurrency,
+        ":requests_per_minute": &platform.requests_per_minute,
+    })?;
+    let mut query_stmt = conn.prepare("select * from model_platform where rowid = ?1")?;
+    let platform =
+        query_stmt.query_row([last_insert_rowid], |row| Ok(build_model_platform(row)?))?;
+    Ok(platform)
+}
+
+/// The API key is encrypted before it is saved, the mask keeps the stored key
 pub fn update_by_name(name: &str, platform: &ModelPlatform) -> Result<usize, RepositoryError> {
+    let api_key = match platform.api_key.as_str() {
+        secret_util::API_KEY_MASK => None,
+        api_key => Some(encrypt_api_key(api_key)?),
+    };
     let conn = Connection::open(get_db_path())?;
     let mut stmt = conn.prepare(
-        "update model_platform set title = :title, base_url= :base_url, api_key= :api_key, logo= :logo, remark= :remark, is_proxy_enable= :is_proxy_enable,is_openai_api_compatible= :is_openai_api_compatible, update_time = datetime('now', 'localtime') where name = :name",
+        "update model_platform set title = :title, base_url= :base_url, api_key= coalesce(:api_key, api_key), logo= :logo, remark= :remark, is_proxy_enable= :is_proxy_enable,is_openai_api_compatible= :is_openai_api_compatible, is_enable= :is_enable, max_concurrency= :max_concurrency, requests_per_minute= :requests_per_minute, update_time = datetime('now', 'localtime') where name = :name",
     )?;
     let affected: usize = stmt.execute(named_params! {
         ":name": name,
@@ -45,12 +95,29 @@ pub fn update_by_name(name: &str, platform: &ModelPlatform) -> Result<usize, Rep
         ":remark": &platform.remark,
         ":is_proxy_enable": &platform.is_proxy_enable,
         ":is_openai_api_compatible": &platform.is_openai_api_compatible,
-        ":api_key": &platform.api_key,
+        ":is_enable": &platform.is_enable,
+        ":max_concurrency": &platform.max_concurrency,
+        ":requests_per_minute": &platform.requests_per_minute,
+        ":api_key": &api_key,
         ":base_url": &platform.base_url,
     })?;
     Ok(affected)
 }
 
+/// Delete the platform and its models
+pub fn delete_by_name(name: &str) -> Result<usize, RepositoryError> {
+    let mut conn = Connection::open(get_db_path())?;
+    let tx = conn.transaction()?;
+    tx.execute("delete from ai_model where platform = ?1", [name])?;
+    let affected = tx.execute("delete from model_platform where name = ?1", [name])?;
+    tx.commit()?;
+    Ok(affected)
+}
+
+fn encrypt_api_key(api_key: &str) -> Result<String, RepositoryError> {
+    secret_util::encrypt_secret(api_key).map_err(|e| RepositoryError::InvalidInput(e.to_string()))
+}
+
 fn build_model_platform(row: &Row<'_>) -> Result<ModelPlatform, RepositoryError> {
     let create_time_str: String = row.get("create_time")?;
     let update_time_str: String = row.get("update_time")?;
@@ -64,6 +131,9 @@ fn build_model_platform(row: &Row<'_>) -> Result<ModelPlatform, RepositoryError>
         remark: row.get("remark")?,
         is_proxy_enable: row.get("is_proxy_enable")?,
         is_openai_api_compatible: row.get("is_openai_api_compatible")?,
+        is_enable: row.get("is_enable")?,
+        max_concurrency: row.get("max_concurrency")?,
+        requests_per_minute: row.get("requests_per_minute")?,
         create_time: datetime_util::str_to_datetime(create_time_str.as_str())?,
         update_time: datetime_util::str_to_datetime(update_time_str.as_str())?,
     });
@@ -0,0 +1,63 @@
+use crate::entities::ModelUsage;
+use crate::repositories::RepositoryError;
+use crate::structs::usage_report::UsageReportItem;
+use crate::utils::app_util::get_db_path;
+use rusqlite::{Connection, Result, named_params};
+
+pub fn insert(usage: &ModelUsage) -> Result<i64, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "insert into model_usage(task_id,platform,model,operation,prompt_tokens,completion_tokens,audio_seconds,image_count,cost) values (:task_id,:platform,:model,:operation,:prompt_tokens,:completion_tokens,:audio_seconds,:image_count,:cost)",
+    )?;
+    let id = stmt.insert(named_params! {
+        ":task_id": &usage.task_id,
+        ":platform": &usage.platform,
+        ":model": &usage.model,
+        ":operation": &usage.operation,
+        ":prompt_tokens": &usage.prompt_tokens,
+        ":completion_tokens": &usage.completion_tokens,
+        ":audio_seconds": &usage.audio_seconds,
+        ":image_count": &usage.image_count,
+        ":cost": &usage.cost,
+    })?;
+    Ok(id)
+}
+
+/// Usage grouped by platform and model, of one indexing task(task_id > 0) or since a local time like `2025-01-01 00:00:00`
+pub fn report(task_id: i64, since: &str) -> Result<Vec<UsageReportItem>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "select platform, model, count(*) as calls, sum(prompt_tokens) as prompt_tokens, sum(completion_tokens) as completion_tokens, sum(audio_seconds) as audio_seconds, sum(image_count) as image_count, sum(cost) as cost from model_usage where (:task_id = 0 or task_id = :task_id) and create_time >= :since group by platform, model order by cost desc, calls desc",
+    )?;
+    let rows = stmt.query_map(
+        named_params! {":task_id": task_id, ":since": since},
+        |row| {
+            Ok(UsageReportItem {
+                platform: row.get("platform")?,
+                model: row.get("model")?,
+                calls: row.get("calls")?,
+                prompt_tokens: row.get("prompt_tokens")?,
+                completion_tokens: row.get("completion_tokens")?,
+                audio_seconds: row.get("audio_seconds")?,
+                image_count: row.get("image_count")?,
+                cost: row.get("cost")?,
+            })
+        },
+    )?;
+    let mut result = Vec::new();
+    for item in rows {
+        result.push(item?);
+    }
+    Ok(result)
+}
+
+/// Total cost since a local time like `2025-01-01 00:00:00`
+pub fn sum_cost_since(since: &str) -> Result<f64, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let cost = conn.query_row(
+        "select coalesce(sum(cost), 0) from model_usage where create_time >= ?1",
+        [since],
+        |row| row.get(0),
+    )?;
+    Ok(cost)
+}
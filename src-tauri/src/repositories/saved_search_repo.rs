@@ -0,0 +1,107 @@
+use crate::entities::SavedSearch;
+use crate::repositories::RepositoryError;
+use crate::utils::app_util::get_db_path;
+use rusqlite::{Connection, OptionalExtension, Result, Row, named_params};
+
+const COLUMNS: &str =
+    "id, name, query, filters, mode, is_smart_folder, result_count, refresh_time, create_time";
+
+/// All saved searches by name
+pub fn list() -> Result<Vec<SavedSearch>, RepositoryError> {
+    query_list(&format!(
+        "select {} from saved_search order by name",
+        COLUMNS
+    ))
+}
+
+pub fn list_smart_folders() -> Result<Vec<SavedSearch>, RepositoryError> {
+    query_list(&format!(
+        "select {} from saved_search where is_smart_folder = 1 order by name",
+        COLUMNS
+    ))
+}
+
+pub fn get_by_id(id: i64) -> Result<Option<SavedSearch>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let saved_search = conn
+        .query_row(
+            &format!("select {} from saved_search where id = ?1", COLUMNS),
+            [id],
+            |row| Ok(build_entity(row)?),
+        )
+        .optional()?;
+    Ok(saved_search)
+}
+
+/// Fails if another saved search has the name already
+pub fn insert(saved_search: &SavedSearch) -> Result<i64, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    conn.execute(
+        "insert into saved_search(name, query, filters, mode, is_smart_folder) values (:name, :query, :filters, :mode, :is_smart_folder)",
+        named_params! {
+            ":name": saved_search.name,
+            ":query": saved_search.query,
+            ":filters": saved_search.filters,
+            ":mode": saved_search.mode,
+            ":is_smart_folder": saved_search.is_smart_folder,
+        },
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Fails if another saved search has the name already
+pub fn update(saved_search: &SavedSearch) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute(
+        "update saved_search set name = :name, query = :query, filters = :filters, mode = :mode, is_smart_folder = :is_smart_folder where id = :id",
+        named_params! {
+            ":id": saved_search.id,
+            ":name": saved_search.name,
+            ":query": saved_search.query,
+            ":filters": saved_search.filters,
+            ":mode": saved_search.mode,
+            ":is_smart_folder": saved_search.is_smart_folder,
+        },
+    )?;
+    Ok(affected)
+}
+
+pub fn update_result_count(id: i64, result_count: i64) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute(
+        "update saved_search set result_count = :result_count, refresh_time = datetime('now', 'localtime') where id = :id",
+        named_params! {":id": id, ":result_count": result_count},
+    )?;
+    Ok(affected)
+}
+
+pub fn delete_by_id(id: i64) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute("delete from saved_search where id = ?1", [id])?;
+    Ok(affected)
+}
+
+fn query_list(sql: &str) -> Result<Vec<SavedSearch>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], |row| Ok(build_entity(row)?))?;
+    let mut result = Vec::new();
+    for item in rows {
+        result.push(item?);
+    }
+    Ok(result)
+}
+
+fn build_entity(row: &Row<'_>) -> Result<SavedSearch, RepositoryError> {
+    Ok(SavedSearch {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        query: row.get("query")?,
+        filters: row.get("filters")?,
+        mode: row.get("mode")?,
+        is_smart_folder: row.get("is_smart_folder")?,
+        result_count: row.get("result_count")?,
+        refresh_time: row.get("refresh_time")?,
+        create_time: row.get("create_time")?,
+    })
+}
@@ -0,0 +1,95 @@
+use crate::entities::SearchHistory;
+use crate::enums::SearchMode;
+use crate::repositories::RepositoryError;
+use crate::utils::app_util::get_db_path;
+use rusqlite::{Connection, OptionalExtension, Result, Row, named_params};
+
+/// The latest searches first
+pub fn list(page: i64, size: i64) -> Result<Vec<SearchHistory>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "select id, query, mode, result_count, create_time from search_history order by create_time desc, id desc limit :limit offset :offset",
+    )?;
+    let rows = stmt.query_map(
+        named_params! {":limit": size, ":offset": (page - 1) * size},
+        |row| Ok(build_entity(row)?),
+    )?;
+    let mut result = Vec::new();
+    for item in rows {
+        result.push(item?);
+    }
+    Ok(result)
+}
+
+/// The latest search if it was made within the seconds
+pub fn get_latest_within(secs: i64) -> Result<Option<SearchHistory>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let history = conn
+        .query_row(
+            "select id, query, mode, result_count, create_time from search_history where create_time >= datetime('now', 'localtime', :secs) order by create_time desc, id desc limit 1",
+            named_params! {":secs": format!("-{} seconds", secs)},
+            |row| Ok(build_entity(row)?),
+        )
+        .optional()?;
+    Ok(history)
+}
+
+pub fn insert(query: &str, mode: SearchMode, result_count: i64) -> Result<i64, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    conn.execute(
+        "insert into search_history(query, mode, result_count, create_time) values (:query, :mode, :result_count, datetime('now', 'localtime'))",
+        named_params! {":query": query, ":mode": mode, ":result_count": result_count},
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Replace the query of an entry, which becomes the latest one
+pub fn update(id: i64, query: &str, result_count: i64) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute(
+        "update search_history set query = :query, result_count = :result_count, create_time = datetime('now', 'localtime') where id = :id",
+        named_params! {":id": id, ":query": query, ":result_count": result_count},
+    )?;
+    Ok(affected)
+}
+
+pub fn delete_by_id(id: i64) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute("delete from search_history where id = ?1", [id])?;
+    Ok(affected)
+}
+
+pub fn clear() -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute("delete from search_history", [])?;
+    Ok(affected)
+}
+
+/// Delete the entries older than `max_days`, then the oldest beyond `max_entries`, 0 disables a limit
+pub fn evict(max_days: i64, max_entries: i64) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut affected = 0;
+    if max_days > 0 {
+        affected += conn.execute(
+            "delete from search_history where create_time < datetime('now', 'localtime', :days)",
+            named_params! {":days": format!("-{} days", max_days)},
+        )?;
+    }
+    if max_entries > 0 {
+        affected += conn.execute(
+            "delete from search_history where id in (select id from search_history order by create_time desc, id desc limit -1 offset :max_entries)",
+            named_params! {":max_entries": max_entries},
+        )?;
+    }
+    Ok(affected)
+}
+
+fn build_entity(row: &Row<'_>) -> Result<SearchHistory, RepositoryError> {
+    Ok(SearchHistory {
+        id: row.get("id")?,
+        query: row.get("query")?,
+        mode: row.get("mode")?,
+        result_count: row.get("result_count")?,
+        create_time: row.get("create_time")?,
+    })
+}
@@ -0,0 +1,177 @@
+use crate::entities::Tag;
+use crate::repositories::RepositoryError;
+use crate::utils::app_util::get_db_path;
+use rusqlite::{Connection, OptionalExtension, Result, Row, named_params, params_from_iter};
+use std::collections::HashSet;
+
+/// All tags by name, with the count of their files
+pub fn list() -> Result<Vec<Tag>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "select t.id, t.name, t.create_time, count(ft.file_id) as file_count from tag t left join file_tag ft on ft.tag_id = t.id group by t.id order by t.name",
+    )?;
+    let rows = stmt.query_map([], |row| Ok(build_entity(row)?))?;
+    let mut result = Vec::new();
+    for item in rows {
+        result.push(item?);
+    }
+    Ok(result)
+}
+
+pub fn get_by_id(id: i64) -> Result<Option<Tag>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let tag = conn
+        .query_row(
+            "select t.id, t.name, t.create_time, (select count(*) from file_tag where tag_id = t.id) as file_count from tag t where t.id = ?1",
+            [id],
+            |row| Ok(build_entity(row)?),
+        )
+        .optional()?;
+    Ok(tag)
+}
+
+/// The tag of the name(case insensitive), created if missing
+pub fn insert_or_get(name: &str) -> Result<Tag, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    conn.execute("insert or ignore into tag(name) values (?1)", [name])?;
+    let tag = conn.query_row(
+        "select t.id, t.name, t.create_time, (select count(*) from file_tag where tag_id = t.id) as file_count from tag t where t.name = ?1",
+        [name],
+        |row| Ok(build_entity(row)?),
+    )?;
+    Ok(tag)
+}
+
+/// Fails if another tag has the name already
+pub fn rename(id: i64, name: &str) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute(
+        "update tag set name = :name where id = :id",
+        named_params! {":id": id, ":name": name},
+    )?;
+    Ok(affected)
+}
+
+/// The links of the tag are deleted by a trigger
+pub fn delete_by_id(id: i64) -> Result<usize, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let affected = conn.execute("delete from tag where id = ?1", [id])?;
+    println!("delete tag affected: {:?}", affected);
+    Ok(affected)
+}
+
+/// Link the files to the tag, the files tagged already are skipped
+pub fn link(file_ids: &[i64], tag_id: i64) -> Result<usize, RepositoryError> {
+    let mut conn = Connection::open(get_db_path())?;
+    let tx = conn.transaction()?;
+    let mut affected = 0;
+    {
+        let mut stmt = tx.prepare(
+            "insert or ignore into file_tag(file_id, tag_id, create_time) values (:file_id, :tag_id, datetime('now', 'localtime'))",
+        )?;
+        for file_id in file_ids {
+            affected += stmt.execute(named_params! {":file_id": file_id, ":tag_id": tag_id})?;
+        }
+    }
+    tx.commit()?;
+    Ok(affected)
+}
+
+pub fn unlink(file_ids: &[i64], tag_id: i64) -> Result<usize, RepositoryError> {
+    let mut conn = Connection::open(get_db_path())?;
+    let tx = conn.transaction()?;
+    let mut affected = 0;
+    {
+        let mut stmt =
+            tx.prepare("delete from file_tag where file_id = :file_id and tag_id = :tag_id")?;
+        for file_id in file_ids {
+            affected += stmt.execute(named_params! {":file_id": file_id, ":tag_id": tag_id})?;
+        }
+    }
+    tx.commit()?;
+    Ok(affected)
+}
+
+/// Ids of the files of the tag, the latest tagged first
+pub fn list_file_ids(tag_id: i64, page: i64, size: i64) -> Result<Vec<i64>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "select file_id from file_tag where tag_id = :tag_id order by create_time desc, file_id desc limit :limit offset :offset",
+    )?;
+    let ids = stmt
+        .query_map(
+            named_params! {":tag_id": tag_id, ":limit": size, ":offset": (page - 1) * size},
+            |row| row.get(0),
+        )?
+        .collect::<Result<Vec<i64>, _>>()?;
+    Ok(ids)
+}
+
+pub fn list_names_by_file_id(file_id: i64) -> Result<Vec<String>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "select t.name from tag t join file_tag ft on ft.tag_id = t.id where ft.file_id = ?1 order by t.name",
+    )?;
+    let names = stmt
+        .query_map([file_id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(names)
+}
+
+pub fn list_by_file_id(file_id: i64) -> Result<Vec<Tag>, RepositoryError> {
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(
+        "select t.id, t.name, t.create_time, (select count(*) from file_tag where tag_id = t.id) as file_count from tag t join file_tag ft on ft.tag_id = t.id where ft.file_id = ?1 order by t.name",
+    )?;
+    let rows = stmt.query_map([file_id], |row| Ok(build_entity(row)?))?;
+    let mut result = Vec::new();
+    for item in rows {
+        result.push(item?);
+    }
+    Ok(result)
+}
+
+/// Paths of the files having all the tags(case insensitive)
+pub fn list_paths_by_names(names: &[String]) -> Result<HashSet<String>, RepositoryError> {
+    if names.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let placeholders = vec!["?"; names.len()].join(",");
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(&format!(
+        "select f.path from file_info f join file_tag ft on ft.file_id = f.id join tag t on t.id = ft.tag_id where t.name in ({}) group by f.id having count(distinct t.id) = {}",
+        placeholders,
+        names.len()
+    ))?;
+    let paths = stmt
+        .query_map(params_from_iter(names), |row| row.get(0))?
+        .collect::<Result<HashSet<String>, _>>()?;
+    Ok(paths)
+}
+
+/// Ids of the files having all the tags(case insensitive)
+pub fn list_file_ids_by_names(names: &[String]) -> Result<Vec<i64>, RepositoryError> {
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders = vec!["?"; names.len()].join(",");
+    let conn = Connection::open(get_db_path())?;
+    let mut stmt = conn.prepare(&format!(
+        "select ft.file_id from file_tag ft join tag t on t.id = ft.tag_id where t.name in ({}) group by ft.file_id having count(distinct t.id) = {}",
+        placeholders,
+        names.len()
+    ))?;
+    let ids = stmt
+        .query_map(params_from_iter(names), |row| row.get(0))?
+        .collect::<Result<Vec<i64>, _>>()?;
+    Ok(ids)
+}
+
+fn build_entity(row: &Row<'_>) -> Result<Tag, RepositoryError> {
+    Ok(Tag {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        file_count: row.get("file_count")?,
+        create_time: row.get("create_time")?,
+    })
+}
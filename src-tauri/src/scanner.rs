@@ -3,7 +3,7 @@ use crate::enums::{FileCategory, FileIndexStatus, IndexingEvent};
 use crate::errors::{AppError, IndexingError};
 use crate::global::{
     IGNORE_HIDDEN_DIRS, IGNORE_HIDDEN_FILES, INDEXER_SETTING, SCANNING, SCANNING_TOTAL,
-    STOP_INDEX_SIGNAL,
+    STOP_INDEX_SIGNAL, VIRTUAL_CONTAINER_EXTS,
 };
 use crate::repositories::file_info_repo;
 use crate::structs::indexer_setting::IndexerSetting;
@@ -268,8 +268,11 @@ pub async fn add_or_update_file_info(input_path: String) -> Result<(), IndexingE
         let modified_time = datetime_util::systemtime_to_datetime(
             (&file_handle).metadata().await?.modified()?.into(),
         );
+        // Expanding a container is expensive, its members are only indexed again when the content changes
+        let unchanged_container =
+            VIRTUAL_CONTAINER_EXTS.contains(&ext.as_str()) && file_record.md5 == md5_hash;
         if file_record.content_index_status == FileIndexStatus::Indexed.value()
-            && file_record.file_update_time.ge(&modified_time)
+            && (file_record.file_update_time.ge(&modified_time) || unchanged_container)
         {
             println!("File is already indexed: {}", path.display());
             return Ok(());
@@ -1,42 +1,256 @@
 pub mod path_search_engine;
 pub mod semantic_search_engine;
 
-use crate::enums::QueryIntent;
+use crate::embedding_service::EmbeddingService;
+use crate::entities::FileInfo;
+use crate::enums::{QueryIntent, SearchMode, SearchSource};
+use crate::global::{ACTIVE_WORKSPACE, WORKSPACE_DB_PATH};
+use crate::repositories::{file_info_repo, tag_repo};
+use crate::structs::search_filter::SearchFilter;
 use crate::structs::search_result::SearchResult;
-use crate::utils::search_util;
+use crate::utils::{search_util, workspace_util};
+use std::collections::HashMap;
+use std::path::Path;
 use tokio::{task, try_join};
 
+const FILTER_LIMIT: usize = 200;
+// Weight of the semantic score in a hybrid search, the path score has the rest
+const SEMANTIC_WEIGHT: f32 = 0.4;
+
 pub async fn path_search(query: &str) -> Vec<SearchResult> {
+    let (query, filter) = search_util::parse_query(query);
     if query.is_empty() {
-        return Vec::new();
+        return filter_search(&filter);
     }
-    let result = path_search_engine::search(query).await;
+    let result = path_search_engine::search(&query, &filter).await;
     result
 }
 
 pub async fn semantic_search(query: &str) -> Vec<SearchResult> {
-    semantic_search_engine::search(query).await
+    let (query, filter) = search_util::parse_query(query);
+    if query.is_empty() {
+        return filter_search(&filter);
+    }
+    semantic_search_engine::search(&query, &filter).await
 }
 
 pub async fn search_with_intent(query: &str) -> Vec<SearchResult> {
-    let intent = search_util::detect_intent(query);
+    let (query, filter) = search_util::parse_query(query);
+    if query.is_empty() {
+        return filter_search(&filter);
+    }
+    let intent = search_util::detect_intent(&query);
     match intent {
-        QueryIntent::PathOnly => path_search_engine::search(query).await,
+        QueryIntent::PathOnly => path_search_engine::search(&query, &filter).await,
+
+        QueryIntent::SemanticOnly => semantic_search_engine::search(&query, &filter).await,
+
+        QueryIntent::Hybrid => parallel_search(query, filter).await,
+    }
+}
+
+/// The number of results of a query without the display limits: every matching path is counted,
+/// the semantic matches are the nearest ones above the similarity threshold
+pub async fn count(query: &str, mode: SearchMode) -> usize {
+    let (query, filter) = search_util::parse_query(query);
+    if query.is_empty() {
+        return filtered_files(&filter).len();
+    }
+    let intent = match mode {
+        SearchMode::Intent => search_util::detect_intent(&query),
+        SearchMode::Path => QueryIntent::PathOnly,
+        SearchMode::Semantic => QueryIntent::SemanticOnly,
+    };
+    let path_count = match intent {
+        QueryIntent::SemanticOnly => 0,
+        _ => path_search_engine::count(&query, &filter).await,
+    };
+    if matches!(intent, QueryIntent::PathOnly) {
+        return path_count;
+    }
+    // The semantic matches whose path matches too are already counted
+    let semantic_count = semantic_search_engine::search(&query, &filter)
+        .await
+        .iter()
+        .filter(|result| {
+            matches!(intent, QueryIntent::SemanticOnly)
+                || !path_search_engine::matches_query(&query, &result.file_info.path)
+        })
+        .count();
+    path_count + semantic_count
+}
 
-        QueryIntent::SemanticOnly => semantic_search_engine::search(query).await,
+/// List the files of a query with only filters: the photos in the `taken:`/`geo:` ranges, or else the tagged files.
+/// The latest taken photos come first, then the latest modified files
+fn filter_search(filter: &SearchFilter) -> Vec<SearchResult> {
+    filtered_files(filter)
+        .into_iter()
+        .take(FILTER_LIMIT)
+        .map(|file_info| SearchResult {
+            file_info,
+            score: 1.0,
+            source: SearchSource::Path,
+            matched_keywords: Vec::new(),
+            matched_chunk_ids: Vec::new(),
+            matched_chunk_locators: HashMap::new(),
+            workspace: String::new(),
+        })
+        .collect()
+}
 
-        QueryIntent::Hybrid => parallel_search(query).await,
+/// All the files matched by the filters alone, in the order of `filter_search`.
+/// Nothing is matched without a `taken:`, `geo:` or `tag:` filter
+fn filtered_files(filter: &SearchFilter) -> Vec<FileInfo> {
+    let ids: Vec<i64> = match &filter.photo_ids {
+        Some(photos) => photos.values().copied().collect(),
+        None if !filter.tags.is_empty() => tag_repo::list_file_ids_by_names(&filter.tags)
+            .unwrap_or_else(|e| {
+                println!("Failed to load the tagged files: {}", e);
+                Vec::new()
+            }),
+        None => Vec::new(),
+    };
+    if ids.is_empty() {
+        return Vec::new();
     }
+    let mut file_infos: Vec<_> = file_info_repo::list_by_ids(&ids)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|info| filter.matches(info))
+        .collect();
+    let taken = |info: &FileInfo| {
+        info.metadata
+            .image
+            .as_ref()
+            .map(|image| image.taken.clone())
+            .unwrap_or_default()
+    };
+    file_infos.sort_by_cached_key(|info| std::cmp::Reverse((taken(info), info.file_update_time)));
+    file_infos
 }
 
-async fn parallel_search(query: &str) -> Vec<SearchResult> {
-    let query = query.to_owned();
+/// Search several workspaces at once, the results are tagged with their workspace and sorted by score.
+/// The scores of each workspace are scaled to 0..=1 first, as path and fused scores are not on the same scale.
+///
+/// The paths of the other workspaces are read from their database, their semantic search is skipped
+/// if they are indexed by another embedding model than the active workspace.
+pub async fn search_workspaces(query: &str, workspaces: &[String]) -> Vec<SearchResult> {
+    let active_workspace = ACTIVE_WORKSPACE.read().await.clone();
+    let active_model = EmbeddingService::model_name().await;
+    // Semantic-only results of a hybrid search keep their fused weight
+    let semantic_weight = match search_util::detect_intent(&search_util::parse_query(query).0) {
+        QueryIntent::Hybrid => SEMANTIC_WEIGHT,
+        _ => 1.0,
+    };
+    let mut workspace_results = Vec::new();
+    for workspace in workspaces {
+        let results = if *workspace == active_workspace {
+            search_with_intent(query).await
+        } else {
+            let db_path = workspace_util::db_path(workspace);
+            if !workspace_util::exists(workspace) || !Path::new(&db_path).exists() {
+                println!("Workspace not found or not initialized: {}", workspace);
+                continue;
+            }
+            WORKSPACE_DB_PATH
+                .scope(db_path, search_other_workspace(query, &active_model))
+                .await
+        };
+        workspace_results.push((workspace.clone(), results));
+    }
+    merge_workspace_results(workspace_results, semantic_weight)
+}
+
+/// The results of all workspaces, the higher the score the better.
+/// Semantic scores are vector distances(the lower the better), they are turned into similarities first.
+fn merge_workspace_results(
+    workspace_results: Vec<(String, Vec<SearchResult>)>,
+    semantic_weight: f32,
+) -> Vec<SearchResult> {
+    let normalize = workspace_results.len() > 1;
+    let mut results = Vec::new();
+    for (workspace, mut workspace_results) in workspace_results {
+        for result in workspace_results.iter_mut() {
+            if matches!(result.source, SearchSource::Semantic) {
+                result.score = semantic_weight - result.score;
+            }
+            result.workspace = workspace.clone();
+        }
+        if normalize {
+            normalize_scores(&mut workspace_results);
+        }
+        results.append(&mut workspace_results);
+    }
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    results
+}
+
+/// Min-max normalization, the results with the same score all get 1
+fn normalize_scores(results: &mut [SearchResult]) {
+    let (min, max) = results.iter().fold((f32::MAX, f32::MIN), |(min, max), r| {
+        (min.min(r.score), max.max(r.score))
+    });
+    for result in results.iter_mut() {
+        result.score = if max > min {
+            (result.score - min) / (max - min)
+        } else {
+            1.0
+        };
+    }
+}
+
+/// Search the workspace of the `WORKSPACE_DB_PATH` scope, sequentially as spawned tasks would leave the scope
+async fn search_other_workspace(query: &str, active_model: &str) -> Vec<SearchResult> {
+    let (query, filter) = search_util::parse_query(query);
+    if query.is_empty() {
+        return filter_search(&filter);
+    }
+    let is_same_model = workspace_util::embedding_model() == active_model;
+    // Only the matching paths are read from the database, the results keep their file ids
+    let path_search = || {
+        let keywords: Vec<&str> = query.split_whitespace().collect();
+        let (ids, paths): (Vec<i64>, Vec<String>) =
+            file_info_repo::list_id_paths_by_keywords(&keywords)
+                .unwrap_or_default()
+                .into_iter()
+                .unzip();
+        let mut results = path_search_engine::search_paths(&paths, &query, &filter);
+        for result in results.iter_mut() {
+            result.file_info.id = ids[result.file_info.id as usize];
+        }
+        results
+    };
+    match search_util::detect_intent(&query) {
+        QueryIntent::PathOnly => path_search(),
+        QueryIntent::SemanticOnly if is_same_model => {
+            semantic_search_engine::search(&query, &filter).await
+        }
+        QueryIntent::Hybrid if is_same_model => {
+            let path_results = path_search();
+            let semantic_results = semantic_search_engine::search(&query, &filter).await;
+            fuse_results(path_results, semantic_results)
+        }
+        _ => {
+            println!(
+                "Semantic search skipped, the workspace is indexed by another embedding model"
+            );
+            path_search()
+        }
+    }
+}
+
+async fn parallel_search(query: String, filter: SearchFilter) -> Vec<SearchResult> {
     let (path_results, semantic_results) = try_join!(
         task::spawn({
             let query = query.clone();
-            async move { Ok(path_search_engine::search(&query).await) }
+            let filter = filter.clone();
+            async move { Ok(path_search_engine::search(&query, &filter).await) }
         }),
-        task::spawn(async move { Ok(semantic_search_engine::search(&query).await) }),
+        task::spawn(async move { Ok(semantic_search_engine::search(&query, &filter).await) }),
     )
     .map(|(path_res, semantic_res)| {
         (
@@ -53,8 +267,6 @@ fn fuse_results(
     path_results: Vec<SearchResult>,
     semantic_results: Vec<SearchResult>,
 ) -> Vec<SearchResult> {
-    use std::collections::HashMap;
-
     let mut map: HashMap<String, SearchResult> = HashMap::new();
 
     for r in path_results {
@@ -64,10 +276,12 @@ fn fuse_results(
     for mut r in semantic_results {
         map.entry(r.file_info.path.clone())
             .and_modify(|e| {
-                e.score = e.score * 0.6 + r.score * 0.4;
+                e.score = e.score * (1.0 - SEMANTIC_WEIGHT) + r.score * SEMANTIC_WEIGHT;
+                e.matched_chunk_ids = std::mem::take(&mut r.matched_chunk_ids);
+                e.matched_chunk_locators = std::mem::take(&mut r.matched_chunk_locators);
             })
             .or_insert_with(|| {
-                r.score *= 0.4;
+                r.score *= SEMANTIC_WEIGHT;
                 r
             });
     }
@@ -76,3 +290,74 @@ fn fuse_results(
     results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
     results
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn semantic_result(path: &str, distance: f32) -> SearchResult {
+        SearchResult {
+            score: distance,
+            source: SearchSource::Semantic,
+            file_info: FileInfo {
+                path: path.to_string(),
+                ..Default::default()
+            },
+            matched_keywords: Vec::new(),
+            matched_chunk_ids: Vec::new(),
+            matched_chunk_locators: HashMap::new(),
+            workspace: String::new(),
+        }
+    }
+
+    fn paths(results: &[SearchResult]) -> Vec<&str> {
+        results
+            .iter()
+            .map(|result| result.file_info.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn merge_semantic_results_nearest_first() {
+        let results = merge_workspace_results(
+            vec![(
+                "default".to_string(),
+                vec![
+                    semantic_result("/a/near", 0.2),
+                    semantic_result("/a/far", 0.7),
+                ],
+            )],
+            1.0,
+        );
+        assert_eq!(paths(&results), vec!["/a/near", "/a/far"]);
+
+        let results = merge_workspace_results(
+            vec![
+                (
+                    "work".to_string(),
+                    vec![
+                        semantic_result("/a/near", 0.2),
+                        semantic_result("/a/middle", 0.4),
+                        semantic_result("/a/far", 0.6),
+                    ],
+                ),
+                (
+                    "home".to_string(),
+                    vec![
+                        semantic_result("/b/near", 0.1),
+                        semantic_result("/b/far", 0.9),
+                    ],
+                ),
+            ],
+            1.0,
+        );
+        assert_eq!(
+            paths(&results),
+            vec!["/a/near", "/b/near", "/a/middle", "/a/far", "/b/far"]
+        );
+        assert_eq!(results[0].workspace, "work");
+        assert_eq!(results[1].workspace, "home");
+        assert_eq!(results[0].score, 1.0);
+        assert_eq!(results.last().unwrap().score, 0.0);
+    }
+}
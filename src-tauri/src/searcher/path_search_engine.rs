@@ -2,18 +2,26 @@ use crate::entities::FileInfo;
 use crate::enums::{FileCategory, SearchSource};
 use crate::global::{PATHS_CACHE, PATHS_CACHE_BUILD_TIME};
 use crate::repositories::file_info_repo;
+use crate::structs::search_filter::SearchFilter;
 use crate::structs::search_result::SearchResult;
-use crate::utils::file_util;
+use crate::utils::{file_util, path_util};
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
 use anyhow::{Result, anyhow};
 use chrono::Local;
 use rayon::prelude::*;
 use smallvec::SmallVec;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 const LIMIT: usize = 20;
-pub async fn search(query: &str) -> Vec<SearchResult> {
+pub async fn search(query: &str, filter: &SearchFilter) -> Vec<SearchResult> {
+    let paths_cache = PATHS_CACHE.read().await;
+    search_paths(&paths_cache, query, filter)
+}
+
+/// Match the keywords of the query in the paths, the id of a result is the index of its path
+pub fn search_paths(paths: &[String], query: &str, filter: &SearchFilter) -> Vec<SearchResult> {
     let start = Instant::now();
     let keywords: Vec<&str> = query.split_whitespace().collect();
     let Ok(automaton) = create_automaton(&keywords) else {
@@ -24,52 +32,10 @@ pub async fn search(query: &str) -> Vec<SearchResult> {
     } else {
         LIMIT * 10
     };
-    let paths_cache = PATHS_CACHE.read().await;
-    let mut result: Vec<SearchResult> = (*paths_cache)
+    let mut result: Vec<SearchResult> = paths
         .par_iter()
         .enumerate()
-        .filter_map(|(line_num, line)| {
-            if line.is_empty() {
-                return None;
-            }
-            //Scan for all keywords in the line
-            let matches: SmallVec<[usize; 5]> = automaton
-                .find_iter(line)
-                .map(|m| m.pattern().as_usize())
-                .collect();
-
-            if matches.is_empty() {
-                None
-            } else {
-                let (file_name, ext) = file_util::get_name_ext(line);
-                let match_keywords = keywords
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(i, k)| {
-                        if matches.contains(&i) {
-                            Some(k.to_string())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-                let result = SearchResult {
-                    file_info: FileInfo {
-                        id: line_num as i64,
-                        path: line.to_string(),
-                        name: file_name,
-                        category: FileCategory::from_ext(ext.as_str()).value(),
-                        file_ext: ext,
-                        ..Default::default()
-                    },
-                    score: matches.len() as f32,
-                    source: SearchSource::Path,
-                    matched_keywords: match_keywords,
-                    matched_chunk_ids: Vec::new(),
-                };
-                Some(result)
-            }
-        })
+        .filter_map(|(line_num, line)| match_path(&automaton, &keywords, line_num, line, filter))
         .take_any(take_num)
         .collect();
     result.sort_by(|a, b| {
@@ -83,11 +49,87 @@ pub async fn search(query: &str) -> Vec<SearchResult> {
     result
 }
 
+/// The number of cached paths matching the query, without the limit of `search`
+pub async fn count(query: &str, filter: &SearchFilter) -> usize {
+    let keywords: Vec<&str> = query.split_whitespace().collect();
+    let Ok(automaton) = create_automaton(&keywords) else {
+        return 0;
+    };
+    let paths_cache = PATHS_CACHE.read().await;
+    paths_cache
+        .par_iter()
+        .enumerate()
+        .filter(|(line_num, line)| {
+            match_path(&automaton, &keywords, *line_num, line, filter).is_some()
+        })
+        .count()
+}
+
+/// Whether the path contains a keyword of the query, as matched by `search`
+pub fn matches_query(query: &str, path: &str) -> bool {
+    let keywords: Vec<&str> = query.split_whitespace().collect();
+    create_automaton(&keywords).is_ok_and(|automaton| automaton.is_match(path))
+}
+
+fn match_path(
+    automaton: &AhoCorasick,
+    keywords: &[&str],
+    line_num: usize,
+    line: &str,
+    filter: &SearchFilter,
+) -> Option<SearchResult> {
+    if line.is_empty() {
+        return None;
+    }
+    //Scan for all keywords in the line
+    let matches: SmallVec<[usize; 5]> = automaton
+        .find_iter(line)
+        .map(|m| m.pattern().as_usize())
+        .collect();
+
+    if matches.is_empty() {
+        None
+    } else {
+        let (file_name, ext) = file_util::get_name_ext(line);
+        let match_keywords = keywords
+            .iter()
+            .enumerate()
+            .filter_map(|(i, k)| {
+                if matches.contains(&i) {
+                    Some(k.to_string())
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let result = SearchResult {
+            file_info: FileInfo {
+                id: line_num as i64,
+                path: line.to_string(),
+                name: file_name,
+                category: FileCategory::from_ext(ext.as_str()).value(),
+                file_ext: ext,
+                ..Default::default()
+            },
+            score: matches.len() as f32,
+            source: SearchSource::Path,
+            matched_keywords: match_keywords,
+            matched_chunk_ids: Vec::new(),
+            matched_chunk_locators: HashMap::new(),
+            workspace: String::new(),
+        };
+        if !filter.matches(&result.file_info) {
+            return None;
+        }
+        Some(result)
+    }
+}
+
 pub async fn init() {
     build_index().await;
     tokio::spawn(paths_index_timer());
 }
-async fn build_index() {
+pub async fn build_index() {
     let size = 1000;
     {
         let mut cache = PATHS_CACHE.write().await;
@@ -171,7 +213,9 @@ pub async fn push_to_index() {
 pub async fn remove_from_index(path: &str, is_file: bool) {
     let mut paths = PATHS_CACHE.write().await;
     if is_file {
-        (*paths).retain(|item| item != path);
+        // Members of a container file are removed with it
+        let members = path_util::to_virtual_path(path, "");
+        (*paths).retain(|item| item != path && !item.starts_with(members.as_str()));
     } else {
         let directory = format!("{}{}", path, std::path::MAIN_SEPARATOR);
         (*paths).retain(|item| !item.starts_with(directory.as_str()));
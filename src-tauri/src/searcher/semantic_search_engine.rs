@@ -2,14 +2,22 @@ use crate::embedding_service_manager::get_manager;
 use crate::entities::{FileContentEmbedding, FileInfo, FileMetaEmbedding};
 use crate::enums::SearchSource;
 use crate::errors::AppError;
+use crate::global::WORKSPACE_DB_PATH;
 use crate::repositories::{
-    file_content_embedding_repo, file_info_repo, file_metadata_embedding_repo,
+    file_content_chunk_repo, file_content_embedding_repo, file_info_repo,
+    file_metadata_embedding_repo,
 };
+use crate::structs::search_filter::SearchFilter;
 use crate::structs::search_result::SearchResult;
+use crate::utils::app_util::get_db_path;
 use std::collections::HashMap;
 use std::time::Instant;
 use tokio::{task, try_join};
 
+const LIMIT: usize = 10;
+// More candidates are needed when part of them will be dropped by the filter
+const FILTERED_LIMIT: usize = 100;
+
 #[derive(Debug, Clone)]
 struct SearchTmp {
     file_id: i64,
@@ -23,7 +31,7 @@ pub async fn warmup_embedding_service() -> Result<(), AppError> {
     Ok(())
 }
 
-pub async fn search(query: &str) -> Vec<SearchResult> {
+pub async fn search(query: &str, filter: &SearchFilter) -> Vec<SearchResult> {
     let start = Instant::now();
     let embedding = {
         let mut manager = get_manager().write().await;
@@ -32,22 +40,49 @@ pub async fn search(query: &str) -> Vec<SearchResult> {
     if embedding.is_empty() {
         return Vec::new();
     }
+    let limit = if filter.is_empty() {
+        LIMIT
+    } else {
+        FILTERED_LIMIT
+    };
+    // Only the photos in the taken range and geo bounds are searched, rather than the nearest candidates
+    let file_ids: Option<Vec<i64>> = filter
+        .photo_ids
+        .as_ref()
+        .map(|photos| photos.values().copied().collect());
+    if file_ids.as_ref().is_some_and(|ids| ids.is_empty()) {
+        return Vec::new();
+    }
     let checkpoint1 = start.elapsed();
     println!("checkpoint1 {:?}", checkpoint1);
+    // The blocking threads search the database of the caller, which may be another workspace
+    let db_path = get_db_path();
     let (content_result, meta_result) = try_join!(
         task::spawn_blocking({
             let embedding = embedding.clone();
-            move || file_content_embedding_repo::search(&embedding, 0.7).unwrap_or_default()
+            let db_path = db_path.clone();
+            let file_ids = file_ids.clone();
+            move || {
+                WORKSPACE_DB_PATH.sync_scope(db_path, || {
+                    file_content_embedding_repo::search(&embedding, 0.7, limit, file_ids.as_deref())
+                        .unwrap_or_default()
+                })
+            }
         }),
         task::spawn_blocking({
             let embedding = embedding.clone();
-            move || file_metadata_embedding_repo::search(&embedding, 0.7).unwrap_or_default()
+            move || {
+                WORKSPACE_DB_PATH.sync_scope(db_path, || {
+                    file_metadata_embedding_repo::search(&embedding, 0.7, file_ids.as_deref())
+                        .unwrap_or_default()
+                })
+            }
         }),
     )
     .unwrap_or_default();
     let checkpoint2 = start.elapsed();
     println!("checkpoint2: {:?}", checkpoint2 - checkpoint1);
-    let result = merge_and_filter_results(content_result, meta_result);
+    let result = merge_and_filter_results(content_result, meta_result, filter);
     let checkpoint3 = start.elapsed();
     println!("checkpoint3: {:?}", checkpoint3 - checkpoint2);
     result
@@ -57,6 +92,7 @@ pub async fn search(query: &str) -> Vec<SearchResult> {
 fn merge_and_filter_results(
     content_result: Vec<FileContentEmbedding>,
     meta_result: Vec<FileMetaEmbedding>,
+    filter: &SearchFilter,
 ) -> Vec<SearchResult> {
     if content_result.is_empty() && meta_result.is_empty() {
         return Vec::new();
@@ -103,8 +139,18 @@ fn merge_and_filter_results(
         return Vec::new();
     }
 
-    let file_map: HashMap<i64, FileInfo> =
-        file_infos.into_iter().map(|info| (info.id, info)).collect();
+    let file_map: HashMap<i64, FileInfo> = file_infos
+        .into_iter()
+        .filter(|info| filter.matches(info))
+        .map(|info| (info.id, info))
+        .collect();
+    let chunk_ids: Vec<i64> = tmps
+        .iter()
+        .filter(|tmp| file_map.contains_key(&tmp.file_id))
+        .flat_map(|tmp| tmp.chunk_ids.iter().copied())
+        .collect();
+    let mut chunk_locators =
+        file_content_chunk_repo::map_by_embedding_ids(&chunk_ids).unwrap_or_default();
     // Return results in the sorted order
     tmps.into_iter()
         .filter_map(|tmp| {
@@ -113,12 +159,19 @@ fn merge_and_filter_results(
             if info.is_none() {
                 return None;
             }
+            let matched_chunk_locators = tmp
+                .chunk_ids
+                .iter()
+                .filter_map(|id| chunk_locators.remove(id).map(|locator| (*id, locator)))
+                .collect();
             Some(SearchResult {
                 file_info: info.unwrap_or_default(),
                 score: tmp.distance,
                 source: SearchSource::Semantic,
                 matched_keywords: Vec::new(),
                 matched_chunk_ids: tmp.chunk_ids,
+                matched_chunk_locators,
+                workspace: String::new(),
             })
         })
         .collect()
@@ -1,8 +1,19 @@
+pub mod chunk_locator;
 pub mod command_result;
+pub mod document_chunk;
 pub mod embedding_progress;
 pub mod file_metadata;
 pub mod fs_watcher_setting;
+pub mod index_bundle;
 pub mod indexer_setting;
 pub mod indexing_summary;
+pub mod local_model;
+pub mod model_pricing;
 pub mod proxy_setting;
+pub mod search_filter;
+pub mod search_history_setting;
 pub mod search_result;
+pub mod table;
+pub mod usage_report;
+pub mod virtual_member;
+pub mod workspace;
@@ -0,0 +1,77 @@
+use crate::utils::subtitle_util;
+use serde::{Deserialize, Serialize};
+
+/// Where a chunk comes from inside the file, e.g. the function and line range of a code chunk.
+/// Only the fields that make sense for the file type are set.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct ChunkLocator {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>, // Heading breadcrumb of a markdown chunk, e.g. `Guide > Install`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>, // 1-based, inclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>, // 1-based, inclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_row: Option<usize>, // 1-based, inclusive, row number in the file/sheet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_row: Option<usize>, // 1-based, inclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_cell: Option<usize>, // 1-based, inclusive, cell number in a notebook
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_cell: Option<usize>, // 1-based, inclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<f64>, // Seconds from the beginning of the video, start of the first subtitle cue
+}
+
+impl ChunkLocator {
+    pub fn is_empty(&self) -> bool {
+        self == &ChunkLocator::default()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self).unwrap_or("{}".to_string())
+    }
+
+    pub fn from_json(json: &str) -> Self {
+        serde_json::from_str(json).unwrap_or_default()
+    }
+
+    /// Readable description, embedded together with the chunk text
+    pub fn to_text(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(language) = &self.language {
+            parts.push(format!("language:{}", language));
+        }
+        if let Some(symbol) = &self.symbol {
+            parts.push(format!("symbol:{}", symbol));
+        }
+        if let Some(heading) = &self.heading {
+            parts.push(format!("section:{}", heading));
+        }
+        if let (Some(start), Some(end)) = (self.start_line, self.end_line) {
+            parts.push(format!("lines:{}-{}", start, end));
+        }
+        if let Some(sheet) = &self.sheet {
+            parts.push(format!("sheet:{}", sheet));
+        }
+        if let (Some(start), Some(end)) = (self.start_row, self.end_row) {
+            parts.push(format!("rows:{}-{}", start, end));
+        }
+        if let (Some(start), Some(end)) = (self.start_cell, self.end_cell) {
+            parts.push(format!("cells:{}-{}", start, end));
+        }
+        if let Some(start_time) = self.start_time {
+            parts.push(format!(
+                "time:{}",
+                subtitle_util::format_timestamp(start_time)
+            ));
+        }
+        parts.join(",")
+    }
+}
@@ -0,0 +1,25 @@
+use crate::structs::chunk_locator::ChunkLocator;
+
+#[derive(Debug, Clone, Default)]
+pub struct DocumentChunk {
+    pub text: String,
+    pub locator: ChunkLocator,
+}
+
+impl DocumentChunk {
+    pub fn from_text(text: String) -> Self {
+        Self {
+            text,
+            locator: ChunkLocator::default(),
+        }
+    }
+
+    /// The text sent to the embedding model, the locator is prepended so that
+    /// names like the function name take part in the semantic search.
+    pub fn embedding_text(&self) -> String {
+        if self.locator.is_empty() {
+            return self.text.clone();
+        }
+        format!("{}\n{}", self.locator.to_text(), self.text)
+    }
+}
@@ -2,6 +2,7 @@ use crate::utils::datetime_util;
 use crate::utils::file_util::get_file_attributes_desc;
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FileMetadata {
@@ -15,6 +16,179 @@ pub struct FileMetadata {
     #[serde(with = "datetime_util")]
     pub modified: DateTime<Local>,
     pub author: String,
+    // Document properties stored inside the file, e.g. OOXML core properties or PDF Info
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subject: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u32>, // Pages of a document or slides of a presentation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<EmailMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<AudioMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video: Option<VideoMetadata>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub video_path: String, // Video sharing the stem of a subtitle file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>, // User tags, read from the tag tables when the metadata is embedded
+}
+
+/// Headers of an email message
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct EmailMetadata {
+    pub subject: String,
+    pub from: String,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub date: String, // Local time in DEFAULT_DATETIME_FORMAT, empty if unknown
+    pub message_id: String,
+}
+
+impl EmailMetadata {
+    pub fn to_text(&self) -> String {
+        format!(
+            "subject:{},from:{},to:{},cc:{},date:{}",
+            self.subject,
+            self.from,
+            self.to.join("; "),
+            self.cc.join("; "),
+            self.date
+        )
+    }
+}
+
+/// Capture details of a photo from EXIF/XMP
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct ImageMetadata {
+    pub taken: String, // Capture time in DEFAULT_DATETIME_FORMAT as recorded by the camera, empty if unknown
+    pub camera: String,
+    pub lens: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub orientation: Option<u32>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub altitude: Option<f64>, // In meters, negative if below sea level
+}
+
+impl ImageMetadata {
+    pub fn is_empty(&self) -> bool {
+        self == &ImageMetadata::default()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.taken.is_empty() {
+            parts.push(format!("taken time:{}", self.taken));
+        }
+        if !self.camera.is_empty() {
+            parts.push(format!("camera:{}", self.camera));
+        }
+        if !self.lens.is_empty() {
+            parts.push(format!("lens:{}", self.lens));
+        }
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            parts.push(format!("dimensions:{}x{}", width, height));
+        }
+        if let (Some(latitude), Some(longitude)) = (self.latitude, self.longitude) {
+            parts.push(format!("gps:{:.6},{:.6}", latitude, longitude));
+        }
+        parts.join(",")
+    }
+}
+
+/// Tags and stream properties of an audio file
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct AudioMetadata {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub year: Option<i32>,
+    pub genre: String,
+    pub duration: Option<f64>, // In seconds
+    pub bitrate: Option<u32>,  // Average bitrate in kbps
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub lyrics: String, // Indexed as content, not part of the metadata text
+}
+
+impl AudioMetadata {
+    pub fn is_empty(&self) -> bool {
+        self == &AudioMetadata::default()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut parts = Vec::new();
+        for (name, value) in [
+            ("title", &self.title),
+            ("artist", &self.artist),
+            ("album", &self.album),
+            ("genre", &self.genre),
+        ] {
+            if !value.is_empty() {
+                parts.push(format!("{}:{}", name, value));
+            }
+        }
+        if let Some(year) = self.year {
+            parts.push(format!("year:{}", year));
+        }
+        if let Some(duration) = self.duration {
+            parts.push(format!("duration:{}", format_duration(duration)));
+        }
+        if let Some(bitrate) = self.bitrate {
+            parts.push(format!("bitrate:{} kbps", bitrate));
+        }
+        parts.join(",")
+    }
+}
+
+/// Stream properties from the container headers of a video file
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct VideoMetadata {
+    pub duration: Option<f64>, // In seconds
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String, // Guessed by the text model from the title, not the content of the video
+}
+
+impl VideoMetadata {
+    pub fn is_empty(&self) -> bool {
+        self == &VideoMetadata::default()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(duration) = self.duration {
+            parts.push(format!("duration:{}", format_duration(duration)));
+        }
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            parts.push(format!("resolution:{}x{}", width, height));
+        }
+        if !self.description.is_empty() {
+            parts.push(format!("description:{}", self.description));
+        }
+        parts.join(",")
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour
+pub fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    if seconds >= 3600 {
+        return format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        );
+    }
+    format!("{}:{:02}", seconds / 60, seconds % 60)
 }
 
 impl FileMetadata {
@@ -28,6 +202,16 @@ impl FileMetadata {
             created: DateTime::default(),
             modified: DateTime::default(),
             author: String::new(),
+            title: String::new(),
+            subject: String::new(),
+            keywords: Vec::new(),
+            page_count: None,
+            email: None,
+            image: None,
+            audio: None,
+            video: None,
+            video_path: String::new(),
+            tags: Vec::new(),
         }
     }
 
@@ -41,7 +225,7 @@ impl FileMetadata {
 
     pub fn to_text(&self) -> String {
         let attribute = get_file_attributes_desc(self.attributes);
-        return format_args!(
+        let mut text = format_args!(
             r#"file name:{},file extension:{},file category:{},size:{} bytes,creation time:{},last write time:{},author:{},file attributes:{}"#,
             self.name,
             self.extension,
@@ -52,5 +236,36 @@ impl FileMetadata {
             self.author,
             attribute.join(", ")
         ).to_string();
+        if !self.title.is_empty() {
+            text.push_str(&format!(",title:{}", self.title));
+        }
+        if !self.subject.is_empty() {
+            text.push_str(&format!(",subject:{}", self.subject));
+        }
+        if !self.keywords.is_empty() {
+            text.push_str(&format!(",keywords:{}", self.keywords.join(", ")));
+        }
+        if let Some(page_count) = self.page_count {
+            text.push_str(&format!(",pages:{}", page_count));
+        }
+        if let Some(email) = &self.email {
+            text.push_str(&format!(",{}", email.to_text()));
+        }
+        if let Some(image) = self.image.as_ref().filter(|image| !image.is_empty()) {
+            text.push_str(&format!(",{}", image.to_text()));
+        }
+        if let Some(audio) = self.audio.as_ref().filter(|audio| !audio.is_empty()) {
+            text.push_str(&format!(",{}", audio.to_text()));
+        }
+        if let Some(video) = self.video.as_ref().filter(|video| !video.is_empty()) {
+            text.push_str(&format!(",{}", video.to_text()));
+        }
+        if let Some(video_name) = Path::new(&self.video_path).file_name() {
+            text.push_str(&format!(",video:{}", video_name.to_string_lossy()));
+        }
+        if !self.tags.is_empty() {
+            text.push_str(&format!(",tags:{}", self.tags.join(", ")));
+        }
+        text
     }
 }
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// The manifest of an exported index bundle, checked before the snapshot is imported
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct IndexBundleManifest {
+    pub format_version: i32,
+    pub app_version: String,
+    pub db_version: i32,         // Older snapshots are migrated after the import
+    pub embedding_model: String, // The embeddings are only usable with the same model
+    pub root_paths: Vec<String>, // Indexed directories or files, the prefixes to remap on another machine
+    pub file_count: i64,
+    pub create_time: String,
+}
+
+/// Rewrite the paths under a root path of the bundle to a local one
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PathMapping {
+    pub from: String,
+    pub to: String,
+}
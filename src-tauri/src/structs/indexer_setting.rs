@@ -1,4 +1,4 @@
-use crate::enums::{FileCategory, FileContentLanguage};
+use crate::enums::{BudgetPeriod, FileCategory, FileContentLanguage};
 use serde::{Deserialize, Serialize};
 
 /// @see enums.rs FileCategory
@@ -33,6 +33,13 @@ impl SaveParsedContent {
     }
 }
 
+/// Cloud analysis is paused once the cost of the period reaches the limit
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct UsageBudget {
+    pub limit: f64, // 0: unlimited, in the currency of the model pricing
+    pub period: BudgetPeriod,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct IndexerSetting {
     pub is_private: bool,
@@ -42,6 +49,14 @@ pub struct IndexerSetting {
     pub ignore_files: Vec<String>, // File absolute path
     #[serde(default)]
     pub save_parsed_content: SaveParsedContent,
+    #[serde(default)]
+    pub budget: UsageBudget,
+    #[serde(default)]
+    pub notebook_include_outputs: bool, // Outputs of notebook code cells are noisy, not indexed by default
+    #[serde(default)]
+    pub video_describe_by_title: bool, // A video without subtitles is described by the text model from its title, off by default
+    #[serde(default)]
+    pub ollama_embedding_model: String, // Embedded by this model of the Ollama platform if set, instead of the local model
 }
 
 impl Default for IndexerSetting {
@@ -58,6 +73,10 @@ impl Default for IndexerSetting {
                 video: true,
                 audio: true,
             },
+            budget: UsageBudget::default(),
+            notebook_include_outputs: false,
+            video_describe_by_title: false,
+            ollama_embedding_model: String::new(),
         }
     }
 }
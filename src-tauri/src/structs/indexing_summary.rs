@@ -8,23 +8,40 @@ pub struct IndexingSummary {
     pub task_id: i64,
     pub start_time: DateTime<Local>,
     pub end_time: DateTime<Local>,
-    // Indexing time in seconds = Files scanning duration + document embedding duration + image embedding duration + audio embedding duration
+    // Indexing time in seconds = Files scanning duration + document embedding duration + image embedding duration + audio embedding duration + video embedding duration
     pub duration: i64,
     pub total: i64, // Total files count
     pub document: EmbeddingProgress,
     pub image: EmbeddingProgress,
     pub audio: EmbeddingProgress,
+    #[serde(default)]
+    pub video: EmbeddingProgress,
 }
 
 impl IndexingSummary {
     pub fn calculate_all_embedding(&self) -> EmbeddingProgress {
         EmbeddingProgress {
-            total: self.document.total + self.image.total + self.audio.total,
-            processed: self.document.processed + self.image.processed + self.audio.processed,
-            success: self.document.success + self.image.success + self.audio.success,
-            failed: self.document.failed + self.image.failed + self.audio.failed,
-            skipped: self.document.skipped + self.image.skipped + self.audio.skipped,
-            duration: self.document.duration + self.image.duration + self.audio.duration,
+            total: self.document.total + self.image.total + self.audio.total + self.video.total,
+            processed: self.document.processed
+                + self.image.processed
+                + self.audio.processed
+                + self.video.processed,
+            success: self.document.success
+                + self.image.success
+                + self.audio.success
+                + self.video.success,
+            failed: self.document.failed
+                + self.image.failed
+                + self.audio.failed
+                + self.video.failed,
+            skipped: self.document.skipped
+                + self.image.skipped
+                + self.audio.skipped
+                + self.video.skipped,
+            duration: self.document.duration
+                + self.image.duration
+                + self.audio.duration
+                + self.video.duration,
         }
     }
 
@@ -33,6 +50,7 @@ impl IndexingSummary {
             FileCategory::Document => &mut self.document,
             FileCategory::Image => &mut self.image,
             FileCategory::Audio => &mut self.audio,
+            FileCategory::Video => &mut self.video,
             _ => {
                 println!("Unknown support file category");
                 &mut self.document
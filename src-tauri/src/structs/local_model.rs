@@ -0,0 +1,25 @@
+use crate::enums::LocalModelKind;
+use serde::{Deserialize, Serialize};
+
+/// A model run locally by ONNX Runtime, listed in a manifest of the model registry
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LocalModel {
+    pub id: String,
+    pub name: String,
+    pub kind: LocalModelKind,
+    #[serde(default)]
+    pub description: String,
+    pub files: Vec<LocalModelFile>,
+    #[serde(default)]
+    pub is_installed: bool, // Checked when listed, not part of the manifest
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LocalModelFile {
+    pub path: String, // Relative to the model directory
+    pub url: String,
+    #[serde(default)]
+    pub size: u64, // 0 if unknown, the size reported by the server is checked anyway
+    #[serde(default)]
+    pub sha256: String, // Hex digest verified after the download, empty to skip
+}
@@ -0,0 +1,54 @@
+use crate::entities::ModelUsage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Prices of a model, kept as `pricing` in `ai_model.properties`, e.g.
+/// `{"pricing":{"input_per_million":0.15,"output_per_million":0.6,"per_audio_minute":0.006,"per_image":0.0}}`
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ModelPricing {
+    pub input_per_million: f64,  // Per million prompt tokens
+    pub output_per_million: f64, // Per million completion tokens
+    pub per_audio_minute: f64,
+    pub per_image: f64,
+}
+
+impl ModelPricing {
+    /// Free if the properties have no pricing
+    pub fn from_properties(properties: &str) -> Self {
+        serde_json::from_str::<Value>(properties)
+            .ok()
+            .and_then(|properties| serde_json::from_value(properties["pricing"].clone()).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn cost(&self, usage: &ModelUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.input_per_million
+            + usage.completion_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+            + usage.audio_seconds / 60.0 * self.per_audio_minute
+            + usage.image_count as f64 * self.per_image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cost_by_pricing() {
+        let pricing = ModelPricing::from_properties(
+            r#"{"dimension":1536,"pricing":{"input_per_million":0.15,"output_per_million":0.6,"per_audio_minute":0.006}}"#,
+        );
+        let usage = ModelUsage {
+            prompt_tokens: 2_000_000,
+            completion_tokens: 500_000,
+            audio_seconds: 90.0,
+            image_count: 3,
+            ..Default::default()
+        };
+        assert!((pricing.cost(&usage) - 0.609).abs() < 1e-9);
+        assert_eq!(ModelPricing::from_properties("{}"), ModelPricing::default());
+        assert_eq!(ModelPricing::from_properties("not json").cost(&usage), 0.0);
+    }
+}
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct ProxyInfo {
-    pub protocal: String, // http、socks4、socks5
-    pub host: String,
+    pub protocal: String, // http、https、socks4、socks5、socks5h(host names resolved by the proxy)
+    pub host: String, // Empty to use the proxy of the environment variables(HTTPS_PROXY, ALL_PROXY, NO_PROXY)
     pub port: u16,
+    pub username: String,
+    pub password: String,      // Encrypted by secret_util, masked in the UI
+    pub no_proxy: Vec<String>, // Hosts, domains(with subdomains) or IP ranges connected directly, e.g. a local Ollama
 }
 
 impl Default for ProxyInfo {
@@ -13,6 +17,13 @@ impl Default for ProxyInfo {
             protocal: String::from("http"),
             host: String::new(),
             port: 0,
+            username: String::new(),
+            password: String::new(),
+            no_proxy: vec![
+                String::from("localhost"),
+                String::from("127.0.0.1"),
+                String::from("::1"),
+            ],
         }
     }
 }
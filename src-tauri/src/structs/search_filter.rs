@@ -0,0 +1,339 @@
+use crate::entities::FileInfo;
+use crate::enums::{CodeLanguage, FileCategory};
+use crate::global::CODE_EXTS;
+use crate::repositories::{file_info_repo, tag_repo};
+use std::collections::{HashMap, HashSet};
+
+/// Filters written inline in the query, e.g. `type:code lang:python retry http request`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilter {
+    pub category: Option<FileCategory>,
+    pub code_only: bool,
+    pub language: Option<CodeLanguage>,
+    // Capture time range of photos, inclusive date prefixes like `2023`, `2023-06` or `2023-06-01`
+    pub taken_from: Option<String>,
+    pub taken_to: Option<String>,
+    pub geo_bounds: Option<GeoBounds>,
+    pub photo_ids: Option<HashMap<String, i64>>, // Set by `load_photo_ids`, path to id of the photos in the range and bounds
+    // `tag:work tag:2024`, the files must have all the tags
+    pub tags: Vec<String>,
+    pub tagged_paths: Option<HashSet<String>>, // Set by `load_tagged_paths`, a result is matched by its path
+}
+
+/// GPS bounding box written as `geo:south,west,north,east`,
+/// west may be greater than east if the box crosses the antimeridian
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoBounds {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+impl GeoBounds {
+    fn parse(value: &str) -> Option<Self> {
+        let values: Vec<f64> = value
+            .split(',')
+            .map(|v| v.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .ok()?;
+        let [south, west, north, east] = values[..] else {
+            return None;
+        };
+        let valid_lat = |lat: f64| (-90.0..=90.0).contains(&lat);
+        let valid_lon = |lon: f64| (-180.0..=180.0).contains(&lon);
+        if !valid_lat(south) || !valid_lat(north) || !valid_lon(west) || !valid_lon(east) {
+            return None;
+        }
+        Some(Self {
+            south: south.min(north),
+            west,
+            north: south.max(north),
+            east,
+        })
+    }
+
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        if latitude < self.south || latitude > self.north {
+            return false;
+        }
+        match self.west <= self.east {
+            true => longitude >= self.west && longitude <= self.east,
+            false => longitude >= self.west || longitude <= self.east,
+        }
+    }
+}
+
+/// `2023`, `2023-06` or `2023-06-01`
+fn is_date_prefix(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let lens = [4, 2, 2];
+    parts.len() <= lens.len()
+        && parts
+            .iter()
+            .zip(lens)
+            .all(|(part, len)| part.len() == len && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl SearchFilter {
+    pub fn is_empty(&self) -> bool {
+        self == &SearchFilter::default()
+    }
+
+    /// Whether the filter needs the photo metadata, `taken:` or `geo:`
+    pub fn has_photo_range(&self) -> bool {
+        self.taken_from.is_some() || self.taken_to.is_some() || self.geo_bounds.is_some()
+    }
+
+    /// Try to consume a `key:value` query token, return false if it is not a filter
+    pub fn apply_token(&mut self, token: &str) -> bool {
+        let Some((key, value)) = token.split_once(':') else {
+            return false;
+        };
+        let value = value.to_lowercase();
+        match key.to_lowercase().as_str() {
+            "type" => {
+                if value == "code" {
+                    self.code_only = true;
+                    return true;
+                }
+                match FileCategory::from(value.as_str()) {
+                    FileCategory::Other => false,
+                    category => {
+                        self.category = Some(category);
+                        true
+                    }
+                }
+            }
+            "lang" | "language" => match CodeLanguage::from_name(&value) {
+                Some(language) => {
+                    self.language = Some(language);
+                    true
+                }
+                None => false,
+            },
+            // `taken:2023`, `taken:2023-01..2023-06-15`, `taken:2023-06..` or `taken:..2022`
+            "taken" => {
+                let (from, to) = value.split_once("..").unwrap_or((&value, &value));
+                let bounds = [from, to].map(|bound| match bound.is_empty() {
+                    true => Some(None),
+                    false => is_date_prefix(bound).then(|| Some(bound.to_string())),
+                });
+                match bounds {
+                    [Some(from), Some(to)] if from.is_some() || to.is_some() => {
+                        self.taken_from = from;
+                        self.taken_to = to;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            "tag" => {
+                if value.is_empty() {
+                    return false;
+                }
+                if !self.tags.contains(&value) {
+                    self.tags.push(value);
+                }
+                true
+            }
+            "geo" => match GeoBounds::parse(&value) {
+                Some(bounds) => {
+                    self.geo_bounds = Some(bounds);
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Load the paths of the files with the tags of the filter, no file matches if it fails
+    pub fn load_tagged_paths(&mut self) {
+        if self.tags.is_empty() {
+            return;
+        }
+        let paths = tag_repo::list_paths_by_names(&self.tags).unwrap_or_else(|e| {
+            println!("Failed to load the tagged paths: {}", e);
+            HashSet::new()
+        });
+        self.tagged_paths = Some(paths);
+    }
+
+    /// Load the photos in the taken range and geo bounds from the metadata column,
+    /// so the results without metadata can be matched too. No file matches if it fails
+    pub fn load_photo_ids(&mut self) {
+        if !self.has_photo_range() {
+            return;
+        }
+        let photos = file_info_repo::list_photo_ids(
+            self.taken_from.as_deref(),
+            self.taken_to.as_deref(),
+            self.geo_bounds.as_ref(),
+        )
+        .unwrap_or_else(|e| {
+            println!("Failed to load the photos in the range: {}", e);
+            Vec::new()
+        });
+        self.photo_ids = Some(photos.into_iter().map(|(id, path)| (path, id)).collect());
+    }
+
+    pub fn matches(&self, file_info: &FileInfo) -> bool {
+        let ext = file_info.file_ext.to_lowercase();
+        if let Some(category) = self.category {
+            if FileCategory::from_ext(&ext) != category {
+                return false;
+            }
+        }
+        if self.code_only && !CODE_EXTS.contains(&ext.as_str()) {
+            return false;
+        }
+        if let Some(language) = self.language {
+            if !language.exts().contains(&ext.as_str()) {
+                return false;
+            }
+        }
+        if !self.tags.is_empty()
+            && !self
+                .tagged_paths
+                .as_ref()
+                .is_some_and(|paths| paths.contains(&file_info.path))
+        {
+            return false;
+        }
+        // Results without metadata(e.g. from the path search) are looked up in the loaded photos
+        if file_info.metadata.image.is_none() && self.has_photo_range() {
+            return self
+                .photo_ids
+                .as_ref()
+                .is_some_and(|photos| photos.contains_key(&file_info.path));
+        }
+        if self.taken_from.is_some() || self.taken_to.is_some() {
+            let taken = file_info
+                .metadata
+                .image
+                .as_ref()
+                .map_or("", |image| image.taken.as_str());
+            if taken.is_empty() {
+                return false;
+            }
+            if self
+                .taken_from
+                .as_ref()
+                .is_some_and(|from| taken < from.as_str())
+            {
+                return false;
+            }
+            // Compare the prefix so the whole period of the upper bound is included
+            if self
+                .taken_to
+                .as_ref()
+                .is_some_and(|to| &taken[..to.len().min(taken.len())] > to.as_str())
+            {
+                return false;
+            }
+        }
+        if let Some(bounds) = self.geo_bounds {
+            let location = file_info
+                .metadata
+                .image
+                .as_ref()
+                .and_then(|image| image.latitude.zip(image.longitude));
+            match location {
+                Some((latitude, longitude)) if bounds.contains(latitude, longitude) => (),
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::file_metadata::ImageMetadata;
+
+    fn photo(taken: &str, location: Option<(f64, f64)>) -> FileInfo {
+        let mut file_info = FileInfo {
+            file_ext: "jpg".to_string(),
+            ..Default::default()
+        };
+        file_info.metadata.image = Some(ImageMetadata {
+            taken: taken.to_string(),
+            latitude: location.map(|l| l.0),
+            longitude: location.map(|l| l.1),
+            ..Default::default()
+        });
+        file_info
+    }
+
+    #[test]
+    fn taken_range() {
+        let mut filter = SearchFilter::default();
+        assert!(filter.apply_token("taken:2023-01..2023-06"));
+        assert!(filter.matches(&photo("2023-06-30 23:59:59", None)));
+        assert!(!filter.matches(&photo("2023-07-01 00:00:00", None)));
+        assert!(!filter.matches(&photo("", None)));
+
+        let mut filter = SearchFilter::default();
+        assert!(filter.apply_token("taken:..2022"));
+        assert!(filter.matches(&photo("2022-12-31 10:00:00", None)));
+        assert!(!filter.matches(&photo("2023-01-01 10:00:00", None)));
+
+        assert!(!SearchFilter::default().apply_token("taken:last-week"));
+        assert!(!SearchFilter::default().apply_token("taken:.."));
+    }
+
+    #[test]
+    fn tags() {
+        let mut filter = SearchFilter::default();
+        assert!(filter.apply_token("tag:Work"));
+        assert!(filter.apply_token("tag:work"));
+        assert!(!filter.apply_token("tag:"));
+        assert_eq!(filter.tags, vec!["work".to_string()]);
+
+        let file_info = FileInfo {
+            path: "/notes/plan.md".to_string(),
+            ..Default::default()
+        };
+        // Not loaded yet
+        assert!(!filter.matches(&file_info));
+        filter.tagged_paths = Some(HashSet::from([file_info.path.clone()]));
+        assert!(filter.matches(&file_info));
+    }
+
+    #[test]
+    fn geo_bounds() {
+        let mut filter = SearchFilter::default();
+        assert!(filter.apply_token("geo:48.8,2.2,48.9,2.5"));
+        assert!(filter.matches(&photo("", Some((48.8566, 2.3522)))));
+        assert!(!filter.matches(&photo("", Some((51.5072, -0.1276)))));
+        assert!(!filter.matches(&photo("", None)));
+
+        // Crossing the antimeridian
+        let mut filter = SearchFilter::default();
+        assert!(filter.apply_token("geo:-20,170,-10,-170"));
+        assert!(filter.matches(&photo("", Some((-17.7, 178.0)))));
+        assert!(!filter.matches(&photo("", Some((-17.7, 0.0)))));
+
+        assert!(!SearchFilter::default().apply_token("geo:100,0,0,0"));
+    }
+
+    #[test]
+    fn photo_ids() {
+        let mut filter = SearchFilter::default();
+        assert!(filter.apply_token("taken:2023"));
+        let path_result = FileInfo {
+            path: "/photos/paris.jpg".to_string(),
+            file_ext: "jpg".to_string(),
+            ..Default::default()
+        };
+        // Not loaded yet
+        assert!(!filter.matches(&path_result));
+        filter.photo_ids = Some(HashMap::from([(path_result.path.clone(), 1)]));
+        assert!(filter.matches(&path_result));
+
+        assert!(filter.apply_token("tag:trip"));
+        assert!(!filter.matches(&path_result));
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// Searches are recorded locally unless disabled, entries older than the max days or beyond the max entries are evicted
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SearchHistorySetting {
+    pub enabled: bool,
+    pub max_days: i64,    // 0: kept until evicted by the max entries
+    pub max_entries: i64, // 0: unlimited
+}
+
+impl Default for SearchHistorySetting {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_days: 90,
+            max_entries: 1000,
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::entities::FileInfo;
 use crate::enums::SearchSource;
+use crate::structs::chunk_locator::ChunkLocator;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SearchResult {
@@ -9,4 +11,7 @@ pub struct SearchResult {
     pub file_info: FileInfo,
     pub matched_keywords: Vec<String>, // For path search
     pub matched_chunk_ids: Vec<i64>,   // For semantic search
+    pub matched_chunk_locators: HashMap<i64, ChunkLocator>, // Key: chunk id, for semantic search
+    #[serde(default)]
+    pub workspace: String, // Set by the search across workspaces
 }
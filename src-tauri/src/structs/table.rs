@@ -0,0 +1,15 @@
+/// Rows of a csv file or a spreadsheet, the first non-empty row is used as the header
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    pub sheet: Option<String>,
+    pub header: Vec<String>,
+    pub rows: Vec<TableRow>,
+    // Rows or columns are dropped because the table is too big
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TableRow {
+    pub number: usize, // 1-based row number in the file/sheet
+    pub cells: Vec<String>,
+}
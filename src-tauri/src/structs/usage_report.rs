@@ -0,0 +1,24 @@
+use crate::structs::indexer_setting::UsageBudget;
+use serde::{Deserialize, Serialize};
+
+/// Usage of a model within the report range
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct UsageReportItem {
+    pub platform: String,
+    pub model: String,
+    pub calls: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub audio_seconds: f64,
+    pub image_count: i64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct UsageReport {
+    pub items: Vec<UsageReportItem>,
+    pub total_cost: f64,
+    pub budget: UsageBudget,
+    pub budget_used: f64, // Cost of the current budget period
+    pub is_budget_exceeded: bool,
+}
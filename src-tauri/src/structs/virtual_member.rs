@@ -0,0 +1,11 @@
+use crate::structs::file_metadata::FileMetadata;
+
+/// A file inside a container file(e.g. a message of a mailbox), indexed as a separate item
+/// under the virtual path `<container path>#<id>` of a mailbox or `<container path>!/<id>` of an archive
+#[derive(Debug, Clone)]
+pub struct VirtualMember {
+    pub id: String,  // Unique inside the container
+    pub ext: String, // Lowercase extension, selects the loader to split the content
+    pub content: String,
+    pub metadata: FileMetadata, // name, extension and size are always set
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// A named index with its own database, switched to at runtime
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Workspace {
+    pub name: String,
+    pub is_active: bool,
+    pub is_initialized: bool, // The database is created when the workspace is switched to for the first time
+}
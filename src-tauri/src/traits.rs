@@ -3,4 +3,5 @@ pub mod chat_capable;
 pub mod document_loader;
 pub mod image_analyzer;
 pub mod indexing_template;
+pub mod video_describer;
 pub mod with_platform_config;
@@ -1,29 +1,40 @@
-use crate::entities::AiModel;
+use crate::entities::{AiModel, ModelUsage};
+use crate::enums::ModelOperation;
 use crate::errors::AppError;
+use crate::global::{AUDIO_SEGMENT_SECONDS, AUDIO_UPLOAD_MAX_SIZE};
 use crate::traits::with_platform_config::WithPlatformConfig;
-use crate::utils::audio_util::is_supported_audio_file;
-use crate::utils::llm_client_util::create_client;
-use async_openai::types::{AudioResponseFormat, CreateTranscriptionRequestArgs};
+use crate::utils::audio_util::{AudioSegmenter, is_supported_audio_file, load_audio_metadata};
+use crate::utils::llm_client_util::{api_url, create_http_client};
+use crate::utils::subtitle_util::Cue;
+use crate::utils::usage_util::usage_from_response;
+use crate::utils::{remote_call_util, subtitle_util, usage_util};
 use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
 use rusqlite::Result;
+use serde_json::Value;
+use std::path::Path;
 
 #[async_trait]
 pub trait AudioAnalyzer: WithPlatformConfig + Send + Sync {
     /// Analyze audio file and convert it to text using speech recognition model.
     ///
+    /// Files bigger than the upload limit or longer than a segment are cut into segments
+    /// which are transcribed one by one, each request is retried on 429/5xx by `remote_call_util`.
+    ///
     /// # Arguments
     /// * `ai_model` - The AI model for audio processing
     /// * `audio_path` - Path to the audio file to be analyzed
     ///
     /// # Returns
-    /// * `Result<String, Box<dyn Error>>` - The transcribed text or an error if:
+    /// * `Result<String, AppError>` - The transcript, one line per segment prefixed by its start time
+    ///   (see `subtitle_util::cues_to_transcript`), or an error if:
     ///   - The audio format is not supported
-    ///   - The transcription request fails
-    ///   - The file cannot be accessed
+    ///   - A segment still fails after the retries
+    ///   - The file cannot be accessed or decoded
     ///
     /// # Example
     /// ```rust
-    /// let result = analyzer.audio_analyze(&model, "path/to/audio.mp3").await?;
+    /// let result = analyzer.analyze_audio(&model, "path/to/audio.mp3").await?;
     /// println!("Transcribed text: {}", result);
     /// ```
     async fn analyze_audio(
@@ -34,15 +45,119 @@ pub trait AudioAnalyzer: WithPlatformConfig + Send + Sync {
         if !is_supported_audio_file(audio_path)? {
             return Err(AppError::UnsupportedAudioFormat(audio_path.to_string()));
         }
-        let client = create_client(self.platform(), self.proxy())?;
-        let request = CreateTranscriptionRequestArgs::default()
-            .file(audio_path)
-            .model(ai_model.name.clone())
-            .response_format(AudioResponseFormat::Json)
-            .build()?;
+        let path = Path::new(audio_path);
+        let file_size = path.metadata()?.len();
+        let duration = load_audio_metadata(path)
+            .ok()
+            .and_then(|audio| audio.duration)
+            .unwrap_or(0.0);
+        if file_size <= AUDIO_UPLOAD_MAX_SIZE && duration <= AUDIO_SEGMENT_SECONDS as f64 {
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let cues = self
+                .transcribe(ai_model, &file_name, &std::fs::read(path)?, duration)
+                .await?;
+            return Ok(subtitle_util::cues_to_transcript(&cues));
+        }
+
+        println!(
+            "Transcribe {} in segments, size: {}, duration: {:.0}s",
+            audio_path, file_size, duration
+        );
+        let mut segmenter = AudioSegmenter::open(path, AUDIO_SEGMENT_SECONDS)?;
+        let mut cues = Vec::new();
+        let mut index = 0;
+        while let Some(segment) = segmenter.next_segment()? {
+            index += 1;
+            let file_name = format!("segment-{}.wav", index);
+            let segment_cues = self
+                .transcribe(ai_model, &file_name, &segment.wav, segment.duration)
+                .await?;
+            cues.extend(segment_cues.into_iter().map(|cue| Cue {
+                start: cue.start + segment.start,
+                text: cue.text,
+            }));
+        }
+        Ok(subtitle_util::cues_to_transcript(&cues))
+    }
+
+    /// Transcribe with segment timestamps, the offsets are relative to the start of the input.
+    ///
+    /// The usage is recorded by the `duration` of the response, or the given `seconds` if the platform leaves it out.
+    /// Sent through `remote_call_util`, a rate limited or busy platform is retried.
+    async fn transcribe(
+        &self,
+        ai_model: &AiModel,
+        file_name: &str,
+        data: &[u8],
+        seconds: f64,
+    ) -> Result<Vec<Cue>, AppError> {
+        let client = create_http_client(self.platform(), self.proxy())?;
+        let url = api_url(self.platform(), "audio/transcriptions");
+        let response = remote_call_util::send(self.platform(), || {
+            // A multipart form is consumed by the request, built again for each attempt
+            let form = Form::new()
+                .part(
+                    "file",
+                    Part::bytes(data.to_vec()).file_name(file_name.to_string()),
+                )
+                .text("model", ai_model.name.clone())
+                .text("response_format", "verbose_json")
+                .text("timestamp_granularities[]", "segment");
+            Ok(client.post(&url).multipart(form))
+        })
+        .await?;
+
+        // Parsed by hand, the compatible platforms often leave out fields like `language` or `duration`
+        let response: Value = serde_json::from_slice(&response.bytes().await?)
+            .map_err(|error| AppError::AnalyzeAudioError(error.to_string()))?;
+        let usage = usage_from_response(&response);
+        let audio_seconds = if usage.audio_seconds > 0.0 {
+            usage.audio_seconds
+        } else {
+            seconds
+        };
+        usage_util::record(
+            &self.platform().name,
+            ai_model,
+            ModelOperation::Audio,
+            ModelUsage {
+                audio_seconds,
+                ..usage
+            },
+        );
+        Ok(response_to_cues(&response))
+    }
+}
 
-        let response = client.audio().transcribe(request).await?;
-        println!("{}", response.text);
-        Ok(response.text)
+/// Segments of a verbose response, the whole text as one cue if the platform has no segments
+fn response_to_cues(response: &Value) -> Vec<Cue> {
+    let segments = response["segments"].as_array().map(|segments| {
+        segments
+            .iter()
+            .filter_map(|segment| {
+                let text = segment["text"].as_str()?.trim();
+                (!text.is_empty()).then(|| Cue {
+                    start: segment["start"].as_f64().unwrap_or(0.0),
+                    text: text.to_string(),
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+    match segments {
+        Some(segments) if !segments.is_empty() => segments,
+        _ => response["text"]
+            .as_str()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(|text| {
+                vec![Cue {
+                    start: 0.0,
+                    text: text.to_string(),
+                }]
+            })
+            .unwrap_or_default(),
     }
 }
@@ -1,6 +1,8 @@
 use crate::entities::AiModel;
+use crate::enums::ModelOperation;
 use crate::traits::with_platform_config::WithPlatformConfig;
 use crate::utils::llm_client_util::create_client;
+use crate::utils::usage_util;
 use async_openai::types::{
     ChatCompletionRequestUserMessageArgs, ChatCompletionStreamOptions,
     CreateChatCompletionRequestArgs,
@@ -25,12 +27,20 @@ pub trait ChatCapable: WithPlatformConfig {
 
         let mut result = String::from("");
         let response = client.chat().create(request).await?;
+        if let Some(ref usage) = response.usage {
+            usage_util::record(
+                &self.platform().name,
+                ai_model,
+                ModelOperation::Chat,
+                usage_util::usage_from_completion(usage),
+            );
+        }
         for choice in response.choices {
             if let Some(ref content) = choice.message.content {
                 result.push_str(content);
             }
         }
-        return Ok("".to_string());
+        return Ok(result);
     }
 
     async fn chat_stream<F>(
@@ -69,7 +79,12 @@ pub trait ChatCapable: WithPlatformConfig {
                         });
                     } else if response.usage.is_some() {
                         response.usage.iter().for_each(|usage| {
-                            println!("usage: {}", usage.total_tokens);
+                            usage_util::record(
+                                &self.platform().name,
+                                ai_model,
+                                ModelOperation::Chat,
+                                usage_util::usage_from_completion(usage),
+                            );
                         })
                     }
                 }
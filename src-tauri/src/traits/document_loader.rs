@@ -1,5 +1,11 @@
+use crate::structs::document_chunk::DocumentChunk;
+use crate::structs::file_metadata::FileMetadata;
+use crate::structs::virtual_member::VirtualMember;
+use crate::utils::text_util;
+use std::error::Error;
 use std::io;
 use std::path::Path;
+use tokenizers::Tokenizer;
 
 pub trait DocumentLoader {
     fn get_exts(&self) -> &[String];
@@ -24,6 +30,58 @@ pub trait DocumentLoader {
     /// ```
     fn load_max(&self, path: &Path, max_load_chars: usize) -> io::Result<String>;
     fn load_file_max(&self, file: &std::fs::File, max_load_chars: usize) -> io::Result<String>;
+
+    /// Splits the loaded content into chunks for embedding.
+    ///
+    /// Loaders that know the structure of their content(e.g. source code) override it
+    /// to split at meaningful boundaries and fill in the chunk locator.
+    fn split(
+        &self,
+        _path: &Path,
+        content: &str,
+        tokenizer: &Tokenizer,
+    ) -> Result<Vec<DocumentChunk>, Box<dyn Error>> {
+        let chunks = text_util::split_text(content, tokenizer)?;
+        Ok(chunks.into_iter().map(DocumentChunk::from_text).collect())
+    }
+
+    /// Whether the loaded content should be kept as is instead of collapsing the blank lines,
+    /// e.g. to keep the line numbers of source code.
+    fn keep_layout(&self) -> bool {
+        false
+    }
+
+    /// Fills in the metadata stored inside the file, e.g. the subject and sender of an email.
+    fn load_metadata(&self, _path: &Path, _metadata: &mut FileMetadata) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Loads the content up to the max chars and fills in the metadata stored inside the file.
+    ///
+    /// Loaders that parse the whole file for both(e.g. PDF) override it to parse the file once.
+    fn load_with_metadata(
+        &self,
+        path: &Path,
+        max_load_chars: usize,
+        metadata: &mut FileMetadata,
+    ) -> io::Result<String> {
+        let content = self.load_max(path, max_load_chars);
+        if let Err(error) = self.load_metadata(path, metadata) {
+            println!("Load metadata error: {}, path: {}", error, path.display());
+        }
+        content
+    }
+
+    /// Loads the members of a container file(e.g. the messages of a mailbox),
+    /// each member is indexed as a separate item. Returns None if the file is not a container.
+    /// Called on a blocking thread.
+    fn load_members(
+        &self,
+        _path: &Path,
+        _max_load_chars: usize,
+    ) -> io::Result<Option<Vec<VirtualMember>>> {
+        Ok(None)
+    }
 }
 
 pub trait OpenOfficeDoc {
@@ -1,7 +1,13 @@
+use crate::enums::ModelOperation;
 use crate::traits::with_platform_config::WithPlatformConfig;
 use crate::utils::image_util::image_to_data_uri;
-use crate::utils::llm_client_util::create_client;
-use crate::{entities::AiModel, errors::AppError};
+use crate::utils::llm_client_util::{api_url, create_http_client};
+use crate::utils::usage_util::usage_from_response;
+use crate::utils::{remote_call_util, usage_util};
+use crate::{
+    entities::{AiModel, ModelUsage},
+    errors::AppError,
+};
 use async_openai::types::{
     ChatCompletionRequestMessageContentPartImageArgs,
     ChatCompletionRequestMessageContentPartTextArgs, ChatCompletionRequestUserMessageArgs,
@@ -10,12 +16,15 @@ use async_openai::types::{
 use async_trait::async_trait;
 use rusqlite::Result;
 use rust_i18n::t;
+use serde_json::Value;
 
 #[async_trait]
 pub trait ImageAnalyzer: WithPlatformConfig + Send + Sync {
     fn is_stream(&self) -> bool;
 
     /// Analyze image and return the result as a string.
+    ///
+    /// Sent through `remote_call_util`, a rate limited or busy platform is retried.
     async fn analyze_image(
         &self,
         ai_model: &AiModel,
@@ -33,7 +42,6 @@ pub trait ImageAnalyzer: WithPlatformConfig + Send + Sync {
                 .build()?,
         );
 
-        let client = create_client(self.platform(), self.proxy())?;
         let request = CreateChatCompletionRequestArgs::default()
             .model(ai_model.name.clone())
             .messages([ChatCompletionRequestUserMessageArgs::default()
@@ -42,12 +50,26 @@ pub trait ImageAnalyzer: WithPlatformConfig + Send + Sync {
                 .into()])
             .stream(self.is_stream())
             .build()?;
-        let response = client.chat().create(request).await?;
+        let client = create_http_client(self.platform(), self.proxy())?;
+        let url = api_url(self.platform(), "chat/completions");
+        let response: Value =
+            remote_call_util::send(self.platform(), || Ok(client.post(&url).json(&request)))
+                .await?
+                .json()
+                .await?;
+        usage_util::record(
+            &self.platform().name,
+            ai_model,
+            ModelOperation::Image,
+            ModelUsage {
+                image_count: 1,
+                ..usage_from_response(&response)
+            },
+        );
 
-        Ok(response.choices[0]
-            .message
-            .content
-            .clone()
-            .unwrap_or_default())
+        Ok(response["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string())
     }
 }
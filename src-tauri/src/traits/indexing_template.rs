@@ -4,10 +4,14 @@ use crate::enums::{FileCategory, FileIndexStatus, IndexingEvent};
 use crate::errors::{AppError, IndexingError};
 use crate::global::{INDEXER_SETTING, STOP_INDEX_SIGNAL};
 use crate::repositories::{
-    file_content_embedding_repo, file_info_repo, file_metadata_embedding_repo,
+    RepositoryError, file_content_chunk_repo, file_content_embedding_repo, file_info_repo,
+    file_metadata_embedding_repo, tag_repo,
 };
+use crate::structs::document_chunk::DocumentChunk;
 use crate::structs::file_metadata::FileMetadata;
-use crate::utils::{file_util, frontend_util, indexing_task_util, text_util};
+use crate::structs::virtual_member::VirtualMember;
+use crate::utils::{file_util, frontend_util, indexing_task_util, path_util, text_util};
+use futures::{TryStreamExt, stream};
 use rust_i18n::t;
 use std::path::Path;
 use std::sync::Arc;
@@ -48,6 +52,19 @@ pub trait IndexingTemplate {
                 }
                 break;
             }
+            if self.is_paused().await {
+                println!("Indexing of {} paused by the usage budget", self.category());
+                if let Some(event) = on_event.as_ref() {
+                    frontend_util::send_to_frontend(
+                        event,
+                        IndexingEvent::Embed {
+                            task_id: task.id,
+                            msg: t!("message.indexing-paused-by-budget").to_string(),
+                        },
+                    );
+                }
+                break;
+            }
             println!(
                 "list_unindexed_files by min_id: {},category:{}",
                 min_id,
@@ -67,47 +84,88 @@ pub trait IndexingTemplate {
                 .map(|info| info.id)
                 .max()
                 .unwrap_or(min_id + 1000);
-            for file_info in file_infos {
-                if STOP_INDEX_SIGNAL.load(Ordering::SeqCst) {
-                    println!("Indexing process interrupted by stop signal");
-                    break 'outer;
-                }
-                indexing_task_util::processed_incr(self.category(), 1).await;
-                if !Path::new(&file_info.path).exists() {
-                    println!("File not exist: {}", file_info.path);
-                    indexing_task_util::failed_incr(self.category(), 1).await;
-                    file_info_repo::delete_by_id(file_info.id)?;
-                    file_content_embedding_repo::delete_by_file_id(file_info.id)?;
-                    file_metadata_embedding_repo::delete_by_file_id(file_info.id)?;
-                    continue;
-                }
-                if let Some(event) = on_event.as_ref() {
-                    frontend_util::send_to_frontend(
-                        event,
-                        IndexingEvent::Embed {
-                            task_id: task.id,
-                            msg: format!("Embedding path: {}", &file_info.path),
-                        },
-                    );
-                }
-                if let Err(error) = self.embedding_one_file(&file_info).await {
-                    println!("Embedding failed: {}", error.to_string());
-                    indexing_task_util::failed_incr(self.category(), 1).await;
-                }
+            // Files are dispatched in parallel up to the limit of the model platform
+            let this = &*self;
+            stream::iter(file_infos.into_iter().map(Ok))
+                .try_for_each_concurrent(self.concurrency().max(1), |file_info| {
+                    this.index_one_file(&task, on_event.as_ref(), file_info)
+                })
+                .await?;
+            if STOP_INDEX_SIGNAL.load(Ordering::SeqCst) {
+                println!("Indexing process interrupted by stop signal");
+                break 'outer;
             }
         }
         Ok(())
     }
 
+    /// Files indexed at the same time, more than one if the content is analyzed by a remote model platform
+    fn concurrency(&self) -> usize {
+        1
+    }
+
+    /// Stop analyzing files of this category, the files are left unindexed for a later task.
+    /// Indexers backed by a cloud model platform pause once the usage budget is exceeded.
+    async fn is_paused(&self) -> bool {
+        false
+    }
+
+    async fn index_one_file(
+        &self,
+        task: &IndexingTask,
+        on_event: Option<&Arc<Channel<IndexingEvent>>>,
+        file_info: FileInfo,
+    ) -> Result<(), IndexingError> {
+        if STOP_INDEX_SIGNAL.load(Ordering::SeqCst) || self.is_paused().await {
+            return Ok(());
+        }
+        indexing_task_util::processed_incr(self.category(), 1).await;
+        let (real_path, member_id) = path_util::split_virtual_path(&file_info.path);
+        if !Path::new(real_path).exists() {
+            println!("File not exist: {}", file_info.path);
+            indexing_task_util::failed_incr(self.category(), 1).await;
+            file_info_repo::delete_by_id(file_info.id)?;
+            file_content_embedding_repo::delete_by_file_id(file_info.id)?;
+            file_content_chunk_repo::delete_by_file_id(file_info.id)?;
+            file_metadata_embedding_repo::delete_by_file_id(file_info.id)?;
+            return Ok(());
+        }
+        if member_id.is_some() {
+            // Members are indexed together with their container file
+            return Ok(());
+        }
+        if let Some(event) = on_event {
+            frontend_util::send_to_frontend(
+                event,
+                IndexingEvent::Embed {
+                    task_id: task.id,
+                    msg: format!("Embedding path: {}", &file_info.path),
+                },
+            );
+        }
+        if let Err(error) = self.embedding_one_file(&file_info).await {
+            println!("Embedding failed: {}", error.to_string());
+            indexing_task_util::failed_incr(self.category(), 1).await;
+        }
+        Ok(())
+    }
+
     async fn embedding_one_file(&self, file_info: &FileInfo) -> Result<(), IndexingError> {
-        let filtered_content = {
-            let content = self.load_content(&file_info).await;
-            text_util::collapse_newlines(&content)
-        };
+        if let Some(members) = self.load_members(&file_info).await {
+            return self.embedding_members(&file_info, members).await;
+        }
         let file_id = file_info.id;
         let path_str = file_info.path.as_str();
         let path = Path::new(path_str);
-        let file_meta = file_util::get_meta_by_record(path, &file_info).await?;
+        let mut file_meta = file_util::get_meta_by_record(path, &file_info).await?;
+        let filtered_content = {
+            let content = self.load_content_metadata(&file_info, &mut file_meta).await;
+            if self.keep_layout(&file_info).await {
+                content
+            } else {
+                text_util::collapse_newlines(&content)
+            }
+        };
 
         let save_parsed_content = INDEXER_SETTING
             .read()
@@ -130,6 +188,7 @@ pub trait IndexingTemplate {
 
         //Remove old index
         file_content_embedding_repo::delete_by_file_id(file_id)?;
+        file_content_chunk_repo::delete_by_file_id(file_id)?;
         file_metadata_embedding_repo::delete_by_file_id(file_id)?;
 
         embedding_metadata(file_id, &file_meta).await?;
@@ -142,7 +201,11 @@ pub trait IndexingTemplate {
             println!("Skip empty content: {}", path_str);
             indexing_task_util::skipped_incr(self.category(), 1).await;
         } else {
-            match embedding_content(file_id, &filtered_content).await {
+            let result = match self.split_content(&file_info, &filtered_content).await {
+                Ok(chunks) => embedding_content(file_id, chunks).await,
+                Err(error) => Err(error),
+            };
+            match result {
                 Ok(_) => {
                     indexing_task_util::success_incr(self.category(), 1).await;
                 }
@@ -155,28 +218,172 @@ pub trait IndexingTemplate {
         return Ok(());
     }
 
+    /// Index every member of a container file as a separate item,
+    /// only the metadata of the container itself is indexed.
+    async fn embedding_members(
+        &self,
+        file_info: &FileInfo,
+        members: Vec<VirtualMember>,
+    ) -> Result<(), IndexingError> {
+        let file_id = file_info.id;
+        let path_str = file_info.path.as_str();
+        let file_meta = file_util::get_meta_by_record(Path::new(path_str), &file_info).await?;
+        let _ = file_info_repo::update_content_meta(file_id, "", &file_meta.to_json())?;
+
+        //Remove old index
+        file_content_embedding_repo::delete_by_file_id(file_id)?;
+        file_content_chunk_repo::delete_by_file_id(file_id)?;
+        file_metadata_embedding_repo::delete_by_file_id(file_id)?;
+        remove_members_index(path_str)?;
+
+        embedding_metadata(file_id, &file_meta).await?;
+        let save_parsed_content = INDEXER_SETTING
+            .read()
+            .await
+            .save_parsed_content
+            .need_store(self.category());
+        println!("Embedding {} members of {}", members.len(), path_str);
+        for member in members {
+            if STOP_INDEX_SIGNAL.load(Ordering::SeqCst) {
+                // The container stays unindexed and is indexed again next time
+                println!("Indexing members interrupted by stop signal");
+                return Ok(());
+            }
+            let mut metadata = member.metadata;
+            metadata.category = self.category().to_text().to_string();
+            let mut member_info = FileInfo {
+                name: metadata.name.clone(),
+                category: self.category().value(),
+                path: path_util::to_virtual_path(path_str, &member.id),
+                metadata: metadata.clone(),
+                file_ext: member.ext,
+                file_size: metadata.size,
+                // Members belong to this version of the container
+                md5: file_info.md5.clone(),
+                file_create_time: metadata.created,
+                file_update_time: metadata.modified,
+                ..Default::default()
+            };
+            // Members are loaded, kept and split like a file of their own extension
+            let content = if self.keep_layout(&member_info).await {
+                member.content
+            } else {
+                text_util::collapse_newlines(&member.content)
+            };
+            if save_parsed_content {
+                member_info.content = content.clone();
+            }
+            let Some(member_info) = file_info_repo::insert(&member_info)? else {
+                continue;
+            };
+            // Keep the member out of the unindexed list, it is indexed right here
+            file_info_repo::update_content_index_status(
+                member_info.id,
+                FileIndexStatus::Indexing.value(),
+                "",
+            )?;
+            embedding_metadata(member_info.id, &metadata).await?;
+            if content.is_empty() {
+                let _ = file_info_repo::update_content_index_status(
+                    member_info.id,
+                    FileIndexStatus::Indexed.value(),
+                    t!("message.indexing-skip-empty-content").as_ref(),
+                );
+                continue;
+            }
+            let result = match self.split_content(&member_info, &content).await {
+                Ok(chunks) => embedding_content(member_info.id, chunks).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                println!("Embedding member error: {}", error.to_string());
+                let _ = file_info_repo::update_content_index_status(
+                    member_info.id,
+                    FileIndexStatus::IndexFailed.value(),
+                    error.to_string().as_str(),
+                );
+            }
+        }
+        file_info_repo::update_content_index_status(
+            file_id,
+            FileIndexStatus::Indexed.value(),
+            "success",
+        )?;
+        indexing_task_util::success_incr(self.category(), 1).await;
+        return Ok(());
+    }
+
     async fn load_content(&self, file_info: &FileInfo) -> String;
     fn category(&self) -> &FileCategory;
+
+    /// Whether the loaded content is indexed without collapsing blank lines
+    async fn keep_layout(&self, _file_info: &FileInfo) -> bool {
+        false
+    }
+
+    /// Fills in the metadata stored inside the file
+    async fn load_metadata(&self, _file_info: &FileInfo, _file_meta: &mut FileMetadata) {}
+
+    /// Loads the content and fills in the metadata, indexers that read both at once override it
+    async fn load_content_metadata(
+        &self,
+        file_info: &FileInfo,
+        file_meta: &mut FileMetadata,
+    ) -> String {
+        let content = self.load_content(file_info).await;
+        self.load_metadata(file_info, file_meta).await;
+        content
+    }
+
+    /// Members of a container file, None if the file is not a container
+    async fn load_members(&self, _file_info: &FileInfo) -> Option<Vec<VirtualMember>> {
+        None
+    }
+
+    async fn split_content(
+        &self,
+        _file_info: &FileInfo,
+        content: &str,
+    ) -> Result<Vec<DocumentChunk>, IndexingError> {
+        split_text_content(content).await
+    }
+}
+
+/// Remove the index of all members of a container file
+pub fn remove_members_index(container_path: &str) -> Result<(), RepositoryError> {
+    for member_id in file_info_repo::list_member_ids(container_path)? {
+        file_content_embedding_repo::delete_by_file_id(member_id)?;
+        file_content_chunk_repo::delete_by_file_id(member_id)?;
+        file_metadata_embedding_repo::delete_by_file_id(member_id)?;
+        file_info_repo::delete_by_id(member_id)?;
+    }
+    Ok(())
 }
 
-pub async fn embedding_content(file_id: i64, content: &str) -> Result<(), IndexingError> {
-    if content.is_empty() {
+/// Split content as plain text
+pub async fn split_text_content(content: &str) -> Result<Vec<DocumentChunk>, IndexingError> {
+    let mut manager = get_manager().write().await;
+    let embedding_service = manager.service().await?;
+    let chunks = text_util::split_text(content, &embedding_service.tokenizer)
+        .map_err(|op| AppError::DocumentSplitterError(op.to_string()))?;
+    Ok(chunks.into_iter().map(DocumentChunk::from_text).collect())
+}
+
+pub async fn embedding_content(
+    file_id: i64,
+    chunks: Vec<DocumentChunk>,
+) -> Result<(), IndexingError> {
+    if chunks.is_empty() {
         return Err(IndexingError::EmptyContent);
     }
 
     // Embedding content
-    let chunks = {
-        let mut manager = get_manager().write().await;
-        let embedding_service = manager.service().await?;
-        text_util::split_text(&content, &embedding_service.tokenizer)
-            .map_err(|op| AppError::DocumentSplitterError(op.to_string()))?
-    };
-    for (chunk_index, chunk_text) in chunks.into_iter().enumerate() {
-        println!("Chunk text: {}", chunk_text.len());
+    for (chunk_index, chunk) in chunks.into_iter().enumerate() {
+        println!("Chunk text: {}", chunk.text.len());
         let mut keep_run = true;
         let chunk_embedding = {
             let mut manager = get_manager().write().await;
-            match manager.embed(&chunk_text).await {
+            match manager.embed(&chunk.embedding_text()).await {
                 Ok(embedding) => embedding,
                 Err(op) => {
                     drop(manager);
@@ -209,16 +416,26 @@ pub async fn embedding_content(file_id: i64, content: &str) -> Result<(), Indexi
         if !keep_run {
             continue;
         }
-        file_content_embedding_repo::insert(
+        let inserted = file_content_embedding_repo::insert(
             &(FileContentEmbedding {
                 id: 0,
                 file_id,
                 embedding: content_array,
                 chunk_index: chunk_index as i64,
-                chunk_text,
+                chunk_text: chunk.text,
                 distance: -0.1,
             }),
         )?;
+        if let Some(inserted) = inserted {
+            if !chunk.locator.is_empty() {
+                file_content_chunk_repo::insert(
+                    inserted.id,
+                    file_id,
+                    chunk_index as i64,
+                    &chunk.locator,
+                )?;
+            }
+        }
         let _ = file_info_repo::update_content_index_status(
             file_id,
             FileIndexStatus::Indexed.value(),
@@ -233,14 +450,9 @@ pub async fn embedding_metadata(
     file_meta: &FileMetadata,
 ) -> Result<(), IndexingError> {
     // File meta embedding
-    let mut guard = get_manager().write().await;
-    let meta_embedding = match guard.embed(file_meta.to_text().as_str()).await {
-        Ok(embedding) => {
-            drop(guard);
-            embedding
-        }
+    let meta_array = match embed_metadata(file_id, file_meta).await {
+        Ok(embedding) => embedding,
         Err(op) => {
-            drop(guard);
             println!("embedding meta error:{}", op.to_string());
             file_info_repo::update_meta_index_status(
                 file_id,
@@ -250,7 +462,6 @@ pub async fn embedding_metadata(
             return Ok(());
         }
     };
-    let meta_array: [f32; 384] = meta_embedding.try_into().unwrap_or([0.0; 384]);
     file_metadata_embedding_repo::insert(
         &(FileMetaEmbedding {
             id: 0,
@@ -262,3 +473,19 @@ pub async fn embedding_metadata(
     file_info_repo::update_meta_index_status(file_id, FileIndexStatus::Indexed.value(), "success")?;
     return Ok(());
 }
+
+/// The embedding of the metadata text, the tags of the user are part of the text
+/// but are kept out of the stored metadata
+pub async fn embed_metadata(
+    file_id: i64,
+    file_meta: &FileMetadata,
+) -> Result<[f32; 384], AppError> {
+    let mut file_meta = file_meta.clone();
+    file_meta.tags = tag_repo::list_names_by_file_id(file_id).unwrap_or_default();
+    let meta_embedding = get_manager()
+        .write()
+        .await
+        .embed(file_meta.to_text().as_str())
+        .await?;
+    Ok(meta_embedding.try_into().unwrap_or([0.0; 384]))
+}
@@ -0,0 +1,44 @@
+use crate::enums::ModelOperation;
+use crate::traits::with_platform_config::WithPlatformConfig;
+use crate::utils::llm_client_util::create_client;
+use crate::utils::usage_util;
+use crate::{entities::AiModel, errors::AppError};
+use async_openai::types::{ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequestArgs};
+use async_trait::async_trait;
+use rusqlite::Result;
+use rust_i18n::t;
+
+#[async_trait]
+pub trait VideoDescriber: WithPlatformConfig + Send + Sync {
+    /// Describe a video from its title with a text model, the video stream is not uploaded.
+    ///
+    /// Return an empty string if the model knows nothing relevant about the title.
+    async fn describe_video(&self, ai_model: &AiModel, title: &str) -> Result<String, AppError> {
+        let client = create_client(self.platform(), self.proxy())?;
+        let request = CreateChatCompletionRequestArgs::default()
+            .model(ai_model.name.clone())
+            .messages([ChatCompletionRequestUserMessageArgs::default()
+                .content(t!("prompt.video-introduce", title = title).to_string())
+                .build()?
+                .into()])
+            .build()?;
+        let response = client.chat().create(request).await?;
+        if let Some(ref usage) = response.usage {
+            usage_util::record(
+                &self.platform().name,
+                ai_model,
+                ModelOperation::Chat,
+                usage_util::usage_from_completion(usage),
+            );
+        }
+
+        let Some(choice) = response.choices.first() else {
+            return Err(AppError::AnalyzeVideoError(title.to_string()));
+        };
+        let description = choice.message.content.clone().unwrap_or_default();
+        if description.contains(t!("prompt.video-no-content").as_ref()) {
+            return Ok(String::new());
+        }
+        Ok(description)
+    }
+}
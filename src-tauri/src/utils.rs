@@ -1,14 +1,35 @@
 pub mod aes_util;
+pub mod analysis_cache_util;
 pub mod app_util;
 pub mod audio_util;
 pub mod base64_util;
+pub mod code_util;
 pub mod datetime_util;
+pub mod doc_props_util;
 pub mod download_util;
+pub mod exif_util;
 pub mod file_util;
 pub mod frontend_util;
+pub mod http_client_util;
 pub mod image_util;
+pub mod index_bundle_util;
 pub mod indexing_task_util;
 pub mod llm_client_util;
+pub mod markdown_util;
+#[cfg(test)]
+pub mod mock_server_util;
+pub mod model_platform_util;
+pub mod model_registry_util;
 pub mod path_util;
+pub mod remote_call_util;
+pub mod saved_search_util;
+pub mod search_history_util;
 pub mod search_util;
+pub mod secret_util;
+pub mod subtitle_util;
+pub mod table_util;
+pub mod tag_util;
 pub mod text_util;
+pub mod usage_util;
+pub mod video_util;
+pub mod workspace_util;
@@ -6,7 +6,8 @@ type Aes128CbcDec = Decryptor<aes::Aes128>;
 
 pub fn aes_encrypt(plaintext: &[u8], key: &[u8], iv: &[u8]) -> Vec<u8> {
     let pt_len = plaintext.len();
-    let mut buffer = vec![0u8; pt_len];
+    // Room for the padding, up to one block
+    let mut buffer = vec![0u8; pt_len + 16];
     buffer[..pt_len].copy_from_slice(plaintext);
     let encryptor = match Aes128CbcEnc::new_from_slices(key, iv) {
         Ok(enc) => enc,
@@ -29,9 +30,14 @@ pub fn aes_decrypt(ciphertext: &[u8], key: &[u8], iv: &[u8]) -> Vec<u8> {
     let mut buffer = vec![0u8; ciphertext.len()];
     let decrypted_text = match Aes128CbcDec::new_from_slices(key, iv) {
         Ok(decryptor) => {
-            buffer.copy_from_slice(&ciphertext);
-            let decrypted = decryptor.decrypt_padded_mut::<Pkcs7>(&mut buffer).unwrap();
-            decrypted
+            buffer.copy_from_slice(ciphertext);
+            match decryptor.decrypt_padded_mut::<Pkcs7>(&mut buffer) {
+                Ok(decrypted) => decrypted,
+                Err(e) => {
+                    eprintln!("Decryption failed: {}", e);
+                    return vec![];
+                }
+            }
         }
         Err(e) => {
             eprintln!("Failed to create decryptor: {}", e);
@@ -0,0 +1,61 @@
+use crate::entities::{AiModel, FileInfo, ModelPlatform};
+use crate::errors::AppError;
+use crate::global::{ANALYSIS_CACHE_MAX_DAYS, ANALYSIS_CACHE_MAX_ENTRIES};
+use crate::repositories::analysis_cache_repo;
+
+/// The analysis of the file content by the model, reused from the cache if the same content
+/// has been analyzed with the prompt version before, otherwise `analyze` is called and its result cached.
+///
+/// Cache failures are only logged, the analysis itself is what matters.
+pub async fn load_or_analyze<F, Fut>(
+    file_info: &FileInfo,
+    platform: &ModelPlatform,
+    ai_model: &AiModel,
+    prompt_version: &str,
+    analyze: F,
+) -> Result<String, AppError>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<String, AppError>>,
+{
+    if file_info.md5.is_empty() {
+        return analyze().await;
+    }
+    match analysis_cache_repo::get(
+        &file_info.md5,
+        &platform.name,
+        &ai_model.name,
+        prompt_version,
+    ) {
+        Ok(Some(content)) => {
+            println!("Analysis cache hit: {}", file_info.path);
+            return Ok(content);
+        }
+        Ok(None) => (),
+        Err(e) => println!("Failed to load analysis cache: {}", e),
+    }
+    let content = analyze().await?;
+    // An empty result is often a refusal or a hiccup of the platform, it is tried again next time
+    if content.trim().is_empty() {
+        return Ok(content);
+    }
+    if let Err(e) = analysis_cache_repo::save(
+        &file_info.md5,
+        &platform.name,
+        &ai_model.name,
+        prompt_version,
+        &content,
+    ) {
+        println!("Failed to save analysis cache: {}", e);
+    }
+    Ok(content)
+}
+
+/// Evict the entries unused for long or beyond the max entries, called when an indexing task is done
+pub fn evict() {
+    match analysis_cache_repo::evict(ANALYSIS_CACHE_MAX_DAYS, ANALYSIS_CACHE_MAX_ENTRIES) {
+        Ok(0) => (),
+        Ok(count) => println!("Evicted {} analysis cache entries", count),
+        Err(e) => println!("Failed to evict analysis cache: {}", e),
+    }
+}
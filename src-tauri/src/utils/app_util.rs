@@ -1,10 +1,11 @@
 use crate::enums::TrayMenuItem;
 use crate::global::{
-    APP_DATA_PATH, DB_PATH, DOWNLOADING, EN_EMBEDDING_PATH, EN_TOKENIZER_PATH, EXIT_APP_SIGNAL,
-    HOME_PATH, INDEXING, MULTI_LANG_EMBEDDING_PATH, MULTI_LANG_TOKENIZER_PATH, SCANNING,
-    STOP_INDEX_SIGNAL, STORAGE_PATH, TMP_PATH, TRAY_ID,
+    ACTIVE_WORKSPACE, APP_DATA_PATH, DB_PATH, DOWNLOADING, EN_EMBEDDING_PATH, EN_TOKENIZER_PATH,
+    EXIT_APP_SIGNAL, HOME_PATH, INDEXING, MODEL_PATH, MULTI_LANG_EMBEDDING_PATH,
+    MULTI_LANG_TOKENIZER_PATH, SCANNING, SECRET_KEY_PATH, STOP_INDEX_SIGNAL, STORAGE_PATH,
+    TMP_PATH, TRAY_ID, WORKSPACE_DB_PATH,
 };
-use crate::utils::file_util;
+use crate::utils::{file_util, workspace_util};
 use log::{error, info, warn};
 use rust_i18n::t;
 use std::env;
@@ -145,6 +146,20 @@ pub async fn set_data_path(
             file_util::copy_file(&old_db, &new_db)
                 .map_err(|e| format!("Failed to copy db file: {}", e))?;
         }
+        // The API keys in the db can not be decrypted without it
+        let old_secret_key = old_path_buf.join("storage").join("secret.key");
+        if old_secret_key.exists() {
+            let new_secret_key = ndp.join("storage").join("secret.key");
+            file_util::copy_file(&old_secret_key, &new_secret_key)
+                .map_err(|e| format!("Failed to copy secret key file: {}", e))?;
+        }
+        // The databases of the other workspaces, and which one is active
+        let old_storage = old_path_buf.join("storage");
+        for (old_file, relative_path) in workspace_util::list_files(&old_storage) {
+            let new_file = ndp.join("storage").join(relative_path);
+            file_util::copy_file(&old_file, &new_file)
+                .map_err(|e| format!("Failed to copy workspace file: {}", e))?;
+        }
         let old_model = old_path_buf.join("model").join("model.onnx");
         if old_model.exists() {
             let new_model = ndp.join("model").join("model.onnx");
@@ -240,14 +255,15 @@ pub async fn init_paths(app: &AppHandle) {
         "Storage directory: {}",
         STORAGE_PATH.get().unwrap_or(&String::new())
     );
-    let db_path = Path::new(&data_path).join("storage").join("mango-desk.db");
-    DB_PATH
-        .set(db_path.to_string_lossy().into_owned())
-        .unwrap_or_else(|error| error!("Failed to set DB_PATH: {}", error));
-    info!(
-        "Database path: {}",
-        DB_PATH.get().unwrap_or(&String::new()).to_string()
-    );
+    // The database of the workspace active when the app was closed
+    let workspace = workspace_util::read_active_record();
+    set_db_path(&workspace_util::db_path(&workspace));
+    *ACTIVE_WORKSPACE.write().await = workspace;
+    info!("Database path: {}", get_db_path());
+    let secret_key_path = Path::new(&data_path).join("storage").join("secret.key");
+    SECRET_KEY_PATH
+        .set(secret_key_path.to_string_lossy().into_owned())
+        .unwrap_or_else(|error| error!("Failed to set SECRET_KEY_PATH: {}", error));
     // For download models
     let model_path = Path::new(&data_path).join("model");
     if !model_path.exists() {
@@ -255,6 +271,9 @@ pub async fn init_paths(app: &AppHandle) {
             error!("Failed to create models directory: {}", error);
         });
     }
+    MODEL_PATH
+        .set(model_path.to_string_lossy().into_owned())
+        .unwrap_or_else(|e| error!("Failed to set MODEL_PATH: {}", e));
     let multilingual_embedding_path = model_path.join("model.onnx").to_string_lossy().into_owned();
     MULTI_LANG_EMBEDDING_PATH
         .set(multilingual_embedding_path)
@@ -366,14 +385,36 @@ fn init_en_embedding_model_path(app_handle: &AppHandle) {
     );
 }
 
+/// The database of the active workspace, or of the workspace of the `WORKSPACE_DB_PATH` scope
 pub fn get_db_path() -> String {
-    DB_PATH.get().unwrap_or(&String::new()).to_string()
+    WORKSPACE_DB_PATH
+        .try_with(|path| path.clone())
+        .unwrap_or_else(|_| DB_PATH.read().map(|path| path.clone()).unwrap_or_default())
+}
+
+pub fn set_db_path(path: &str) {
+    match DB_PATH.write() {
+        Ok(mut guard) => *guard = path.to_string(),
+        Err(e) => error!("Failed to set DB_PATH: {}", e),
+    }
+}
+
+pub fn get_storage_path() -> String {
+    STORAGE_PATH.get().unwrap_or(&String::new()).to_string()
+}
+
+pub fn get_secret_key_path() -> String {
+    SECRET_KEY_PATH.get().unwrap_or(&String::new()).to_string()
 }
 
 pub fn get_assets_tmp_path() -> String {
     TMP_PATH.get().unwrap_or(&String::new()).to_string()
 }
 
+pub fn get_model_path() -> String {
+    MODEL_PATH.get().unwrap_or(&String::new()).to_string()
+}
+
 pub fn get_multilingual_embedding_path() -> String {
     MULTI_LANG_EMBEDDING_PATH
         .get()
@@ -1,6 +1,19 @@
 use crate::errors::AppError;
-use crate::global::SUPPORTED_AUDIO_EXTS;
+use crate::global::{AUDIO_SEGMENT_SAMPLE_RATE, SUPPORTED_AUDIO_EXTS};
+use crate::structs::file_metadata::AudioMetadata;
 use crate::utils::base64_util::file_to_data_uri;
+use std::fs::File;
+use std::io;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, StandardTagKey, Tag};
+use symphonia::core::probe::{Hint, ProbeResult};
+use symphonia::core::units::TimeBase;
 
 pub fn is_supported_audio_ext(ext: &str) -> bool {
     SUPPORTED_AUDIO_EXTS.contains(&ext)
@@ -28,3 +41,288 @@ pub fn audio_to_data_uri(path: &str) -> Result<String, AppError> {
     let data_uri = file_to_data_uri(path)?;
     Ok(data_uri)
 }
+
+/// Tags(ID3, Vorbis comments, MP4 atoms, RIFF INFO) and stream properties of an audio file,
+/// the file is only probed, no audio is decoded
+pub fn load_audio_metadata(path: &Path) -> io::Result<AudioMetadata> {
+    let file_size = path.metadata()?.len();
+    let mut probed = probe(path)?;
+
+    let mut audio = AudioMetadata::default();
+    // Tags in front of the container(e.g. ID3v2 of mp3) come first
+    if let Some(revision) = probed
+        .metadata
+        .get()
+        .as_ref()
+        .and_then(|metadata| metadata.current())
+    {
+        apply_tags(revision.tags(), &mut audio);
+    }
+    if let Some(revision) = probed.format.metadata().current() {
+        apply_tags(revision.tags(), &mut audio);
+    }
+
+    if let Some(track) = probed.format.default_track() {
+        let params = &track.codec_params;
+        let time_base = params
+            .time_base
+            .or(params
+                .sample_rate
+                .filter(|rate| *rate > 0)
+                .map(|rate| TimeBase::new(1, rate)))
+            .filter(|time_base| time_base.denom > 0);
+        if let (Some(time_base), Some(n_frames)) = (time_base, params.n_frames) {
+            audio.duration =
+                Some(n_frames as f64 * time_base.numer as f64 / time_base.denom as f64);
+        }
+    }
+    if let Some(duration) = audio.duration.filter(|duration| *duration > 0.0) {
+        audio.bitrate = Some((file_size as f64 * 8.0 / duration / 1000.0).round() as u32);
+    }
+    Ok(audio)
+}
+
+fn probe(path: &Path) -> io::Result<ProbeResult> {
+    let file = File::open(path)?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|error| Error::new(ErrorKind::InvalidData, error))
+}
+
+/// A piece of an audio file, start is the offset from the beginning of the file in seconds
+pub struct AudioSegment {
+    pub start: f64,
+    pub duration: f64,
+    pub wav: Vec<u8>,
+}
+
+/// Decodes an audio file and cuts it into WAV segments(16 kHz, mono, 16-bit) that fit in an upload,
+/// the segments are decoded one by one to keep the memory usage low
+pub struct AudioSegmenter {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    sample_rate: u32,
+    segment_seconds: usize,
+    // Decoded mono samples at the source sample rate not yet returned
+    pending: Vec<f32>,
+    // Source samples already returned
+    consumed: usize,
+    finished: bool,
+}
+
+impl AudioSegmenter {
+    pub fn open(path: &Path, segment_seconds: usize) -> io::Result<AudioSegmenter> {
+        let format = probe(path)?.format;
+        let track = format
+            .default_track()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "No audio track"))?;
+        let sample_rate = track
+            .codec_params
+            .sample_rate
+            .filter(|rate| *rate > 0)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Unknown sample rate"))?;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+        Ok(AudioSegmenter {
+            track_id: track.id,
+            format,
+            decoder,
+            sample_rate,
+            segment_seconds,
+            pending: Vec::new(),
+            consumed: 0,
+            finished: false,
+        })
+    }
+
+    /// The next segment, None at the end of the file
+    pub fn next_segment(&mut self) -> io::Result<Option<AudioSegment>> {
+        let segment_len = self.segment_seconds * self.sample_rate as usize;
+        while !self.finished && self.pending.len() < segment_len {
+            self.decode_packet()?;
+        }
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+        let len = segment_len.min(self.pending.len());
+        let samples: Vec<f32> = self.pending.drain(..len).collect();
+        let start = self.consumed as f64 / self.sample_rate as f64;
+        self.consumed += len;
+        let samples = resample(&samples, self.sample_rate, AUDIO_SEGMENT_SAMPLE_RATE);
+        Ok(Some(AudioSegment {
+            start,
+            duration: len as f64 / self.sample_rate as f64,
+            wav: encode_wav(&samples, AUDIO_SEGMENT_SAMPLE_RATE),
+        }))
+    }
+
+    fn decode_packet(&mut self) -> io::Result<()> {
+        let packet = match self.format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(error)) if error.kind() == ErrorKind::UnexpectedEof => {
+                self.finished = true;
+                return Ok(());
+            }
+            Err(SymphoniaError::ResetRequired) => {
+                // Chained streams with new parameters, the first one is enough
+                self.finished = true;
+                return Ok(());
+            }
+            Err(error) => return Err(Error::new(ErrorKind::InvalidData, error)),
+        };
+        if packet.track_id() != self.track_id {
+            return Ok(());
+        }
+        match self.decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                let channels = spec.channels.count().max(1);
+                let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                buffer.copy_interleaved_ref(decoded);
+                // Down mix to mono
+                self.pending.extend(
+                    buffer
+                        .samples()
+                        .chunks(channels)
+                        .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+                );
+                Ok(())
+            }
+            // A corrupted packet is skipped
+            Err(SymphoniaError::DecodeError(error)) => {
+                println!("Decode audio packet error: {}", error);
+                Ok(())
+            }
+            Err(error) => Err(Error::new(ErrorKind::InvalidData, error)),
+        }
+    }
+}
+
+/// Linear interpolation, good enough for speech recognition
+fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = from_rate as f64 / to_rate as f64;
+    let len = (samples.len() as f64 / ratio) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let index = pos as usize;
+            let next = samples.get(index + 1).unwrap_or(&samples[index]);
+            let fraction = (pos - index as f64) as f32;
+            samples[index] + (next - samples[index]) * fraction
+        })
+        .collect()
+}
+
+/// 16-bit PCM WAV file of mono samples
+fn encode_wav(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // Byte rate
+    wav.extend_from_slice(&2u16.to_le_bytes()); // Block align
+    wav.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&value.to_le_bytes());
+    }
+    wav
+}
+
+/// Tags already set are kept
+pub fn apply_tags(tags: &[Tag], audio: &mut AudioMetadata) {
+    for tag in tags {
+        let Some(key) = tag.std_key else {
+            continue;
+        };
+        let value = tag.value.to_string().trim().to_string();
+        if value.is_empty() {
+            continue;
+        }
+        let field = match key {
+            StandardTagKey::TrackTitle => &mut audio.title,
+            StandardTagKey::Artist => &mut audio.artist,
+            StandardTagKey::Album => &mut audio.album,
+            StandardTagKey::Genre => &mut audio.genre,
+            StandardTagKey::Lyrics => &mut audio.lyrics,
+            StandardTagKey::Date | StandardTagKey::ReleaseDate | StandardTagKey::OriginalDate => {
+                // Dates are `2023`, `2023-06-01` or `2023-06-01T10:00:00`
+                if audio.year.is_none() {
+                    audio.year = value.get(..4).and_then(|year| year.parse().ok());
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        if field.is_empty() {
+            *field = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use symphonia::core::meta::Value;
+
+    #[test]
+    fn read_tags() {
+        let tags = [
+            (StandardTagKey::TrackTitle, "Mango Song"),
+            (StandardTagKey::Artist, "Alice"),
+            (StandardTagKey::Date, "2021-06-01"),
+            (StandardTagKey::Lyrics, "la la la"),
+            (StandardTagKey::Artist, "Bob"),
+        ]
+        .map(|(key, value)| Tag::new(Some(key), "", Value::from(value)));
+        let mut audio = AudioMetadata::default();
+        apply_tags(&tags, &mut audio);
+        assert_eq!(audio.title, "Mango Song");
+        assert_eq!(audio.artist, "Alice");
+        assert_eq!(audio.year, Some(2021));
+        assert_eq!(audio.lyrics, "la la la");
+    }
+
+    #[test]
+    fn split_segments() {
+        let path = Path::new("assets/test_file/test.mp3");
+        let mut segmenter = AudioSegmenter::open(path, 2).unwrap();
+        let mut starts = Vec::new();
+        while let Some(segment) = segmenter.next_segment().unwrap() {
+            assert!(segment.wav.starts_with(b"RIFF"));
+            starts.push(segment.start);
+        }
+        // 4.4 seconds in segments of 2 seconds
+        assert_eq!(starts, vec![0.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn read_duration() {
+        let audio = load_audio_metadata(Path::new("assets/test_file/test.mp3")).unwrap();
+        println!("{:?}", audio);
+        assert!(audio.duration.is_some_and(|duration| duration > 0.0));
+        assert!(audio.bitrate.is_some());
+    }
+}
@@ -0,0 +1,902 @@
+use crate::enums::CodeLanguage;
+use crate::structs::chunk_locator::ChunkLocator;
+use crate::structs::document_chunk::DocumentChunk;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use text_splitter::ChunkSizer;
+
+// Nested blocks(e.g. methods in a class) are split at most this deep
+const MAX_NESTING: usize = 3;
+
+static CONTROL_KEYWORDS: &[&str] = &[
+    "if",
+    "else",
+    "for",
+    "foreach",
+    "while",
+    "do",
+    "switch",
+    "case",
+    "catch",
+    "try",
+    "return",
+    "throw",
+    "new",
+    "with",
+    "using",
+    "lock",
+    "synchronized",
+    "sizeof",
+    "typeof",
+    "await",
+    "yield",
+    "delete",
+    "function",
+    "elif",
+    "except",
+    "match",
+];
+
+static SYMBOL_PATTERNS: LazyLock<HashMap<CodeLanguage, Vec<Regex>>> = LazyLock::new(|| {
+    let js_patterns = vec![
+        r"^(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:async\s+)?function\s*\*?\s*(?P<name>[\w$]+)",
+        r"^(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:class|interface|enum|type|namespace|module)\s+(?P<name>[\w$]+)",
+        r"^(?:export\s+)?(?:const|let|var)\s+(?P<name>[\w$]+)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:function\b|\(|[\w$]+\s*=>)",
+        r"^(?:(?:public|private|protected|static|async|readonly|override|abstract|get|set)\s+)*(?P<name>[\w$]+)\s*(?:<[^>]*>)?\s*\([^;]*$",
+    ];
+    let oo_patterns = vec![
+        r"^(?:[\w@]+(?:\([^)]*\))?\s+)*?(?:class|interface|enum|record|struct|object|trait|protocol|extension|namespace)\s+(?P<name>[\w$.]+)",
+        r"^(?:[\w@<>\[\],?]+\s+)*?(?:fun|func|function)\s+(?:<[^>]*>\s*)?(?:[\w.]+\.)?(?P<name>[\w$]+)",
+        r"^(?:[\w@<>\[\],.?]+\s+)+(?P<name>[\w$]+)\s*\([^;]*$",
+    ];
+    let c_patterns = vec![
+        r"^(?:template\s*<.*>\s*)?(?:typedef\s+)?(?:class|struct|union|enum(?:\s+class)?|namespace)\s+(?P<name>\w+)",
+        r"^#define\s+(?P<name>\w+)",
+        r"^(?:[\w:*&<>,~]+\s+[*&]*)+(?P<name>[\w:~]+)\s*\([^;]*$",
+    ];
+    let patterns: Vec<(CodeLanguage, Vec<&str>)> = vec![
+        (
+            CodeLanguage::Rust,
+            vec![
+                r#"^(?:pub(?:\([^)]*\))?\s+)?(?:(?:default|async|const|unsafe|extern\s*(?:"[^"]*")?)\s+)*(?:fn|struct|enum|union|trait|mod|type|static|const)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)"#,
+                r"^macro_rules!\s*(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+                r"^(?:unsafe\s+)?impl(?:\s*<[^{]*?>)?\s+(?P<name>[^{]+?)\s*(?:where\b.*)?\{?\s*$",
+            ],
+        ),
+        (
+            CodeLanguage::Python,
+            vec![r"^(?:async\s+)?(?:def|class)\s+(?P<name>[A-Za-z_]\w*)"],
+        ),
+        (CodeLanguage::JavaScript, js_patterns.clone()),
+        (CodeLanguage::TypeScript, js_patterns),
+        (
+            CodeLanguage::Go,
+            vec![
+                r"^func\s*(?:\(\s*\w*\s*\*?\s*(?P<owner>\w+)(?:\[[^\]]*\])?\s*\)\s*)?(?P<name>\w+)",
+                r"^type\s+(?P<name>\w+)",
+                r"^(?:var|const)\s+(?P<name>\w+)",
+            ],
+        ),
+        (CodeLanguage::Java, oo_patterns.clone()),
+        (CodeLanguage::Kotlin, oo_patterns.clone()),
+        (CodeLanguage::CSharp, oo_patterns.clone()),
+        (CodeLanguage::Swift, oo_patterns.clone()),
+        (CodeLanguage::Php, oo_patterns),
+        (CodeLanguage::C, c_patterns.clone()),
+        (CodeLanguage::Cpp, c_patterns),
+        (
+            CodeLanguage::Sql,
+            vec![
+                r#"(?i)^(?:create|alter)\s+(?:or\s+replace\s+)?(?:(?:temporary|temp|unique|materialized|virtual)\s+)*(?P<kind>table|view|function|procedure|index|trigger|type|sequence|schema)\s+(?:if\s+not\s+exists\s+)?(?P<name>[\w."`\[\]]+)"#,
+            ],
+        ),
+        (
+            CodeLanguage::Shell,
+            vec![
+                r"^function\s+(?P<name>[\w.:-]+)",
+                r"^(?P<name>[\w.:-]+)\s*\(\s*\)",
+            ],
+        ),
+    ];
+    patterns
+        .into_iter()
+        .map(|(language, patterns)| {
+            let regexes = patterns
+                .into_iter()
+                .filter_map(|pattern| Regex::new(pattern).ok())
+                .collect();
+            (language, regexes)
+        })
+        .collect()
+});
+
+static PYTHON_BLOCK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:async\s+)?(?:def|class)\b").unwrap());
+
+// Declarations whose members are split into separate chunks when they are too big
+static CONTAINER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:class|impl|trait|interface|struct|enum|namespace|module|object|protocol|extension|record|mod)\b").unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Family {
+    // Blocks are delimited by braces
+    Brace,
+    // Blocks are delimited by indentation
+    Indent,
+    // Statements end with semicolon
+    Statement,
+}
+
+struct Syntax {
+    family: Family,
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    brackets: &'static [(char, char)],
+    quotes: &'static [char],
+    // Whether strings with these quotes may span lines
+    multiline_quotes: &'static [char],
+}
+
+impl Syntax {
+    fn of(language: CodeLanguage) -> Self {
+        let c_like = Syntax {
+            family: Family::Brace,
+            line_comments: &["//"],
+            block_comment: Some(("/*", "*/")),
+            brackets: &[('{', '}')],
+            quotes: &['"', '\'', '`'],
+            multiline_quotes: &['`'],
+        };
+        match language {
+            CodeLanguage::Python => Syntax {
+                family: Family::Indent,
+                line_comments: &["#"],
+                block_comment: None,
+                brackets: &[('(', ')'), ('[', ']'), ('{', '}')],
+                quotes: &['"', '\''],
+                multiline_quotes: &[],
+            },
+            CodeLanguage::Sql => Syntax {
+                family: Family::Statement,
+                line_comments: &["--"],
+                block_comment: Some(("/*", "*/")),
+                brackets: &[('(', ')')],
+                quotes: &['\'', '"', '`'],
+                multiline_quotes: &['\''],
+            },
+            CodeLanguage::Shell => Syntax {
+                line_comments: &["#"],
+                block_comment: None,
+                quotes: &['"', '\''],
+                multiline_quotes: &['"', '\''],
+                ..c_like
+            },
+            CodeLanguage::Php => Syntax {
+                line_comments: &["//", "#"],
+                ..c_like
+            },
+            _ => c_like,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LexState {
+    Normal,
+    BlockComment,
+    Str {
+        end: String,
+        escape: bool,
+        multiline: bool,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+struct LineInfo {
+    depth_start: usize,
+    depth_end: usize,
+    max_depth: usize,
+    closes: bool,
+    has_code: bool,
+    // Starts inside an unclosed bracket or string, or after a line continuation
+    continuation: bool,
+    indent: usize,
+    last_code_char: Option<char>,
+}
+
+#[derive(Debug, Clone)]
+struct Piece {
+    start: usize,
+    end: usize,
+    symbols: Vec<String>,
+}
+
+/// Split source code into chunks at function/class boundaries.
+///
+/// Top-level blocks are detected with a lightweight lexer(comments, strings, brackets and indentation),
+/// blocks bigger than `capacity` are split into their nested blocks(e.g. methods of a class),
+/// small adjacent blocks are packed together. Every chunk records its symbols and 1-based line range.
+pub fn split_code<S: ChunkSizer>(
+    content: &str,
+    language: CodeLanguage,
+    sizer: &S,
+    capacity: usize,
+) -> Vec<DocumentChunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let syntax = Syntax::of(language);
+    let infos = scan(&lines, &syntax, language);
+    let splitter = CodeSplitter {
+        lines: &lines,
+        infos: &infos,
+        syntax: &syntax,
+        language,
+        sizer,
+        capacity,
+    };
+    let top_units = splitter.units(0, lines.len() - 1, 0);
+    let mut pieces = Vec::new();
+    for (start, end) in top_units {
+        splitter.split_unit(start, end, 0, None, 0, &mut pieces);
+    }
+    splitter.pack(pieces)
+}
+
+fn scan(lines: &[&str], syntax: &Syntax, language: CodeLanguage) -> Vec<LineInfo> {
+    let mut infos = Vec::with_capacity(lines.len());
+    let mut state = LexState::Normal;
+    let mut depth = 0usize;
+    let mut backslash_continuation = false;
+    for line in lines {
+        let mut info = LineInfo {
+            depth_start: depth,
+            max_depth: depth,
+            continuation: state != LexState::Normal
+                || backslash_continuation
+                || (syntax.family == Family::Indent && depth > 0),
+            indent: line
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum(),
+            ..Default::default()
+        };
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            let c = rest.chars().next().unwrap_or(' ');
+            match &state {
+                LexState::BlockComment => {
+                    let end = syntax.block_comment.map(|(_, end)| end).unwrap_or("*/");
+                    if rest.starts_with(end) {
+                        state = LexState::Normal;
+                        i += end.len();
+                        continue;
+                    }
+                }
+                LexState::Str { end, escape, .. } => {
+                    info.has_code = true;
+                    if *escape && c == '\\' {
+                        i += 1;
+                        i += line[i..].chars().next().map(|c| c.len_utf8()).unwrap_or(0);
+                        continue;
+                    }
+                    if rest.starts_with(end.as_str()) {
+                        i += end.len();
+                        state = LexState::Normal;
+                        continue;
+                    }
+                }
+                LexState::Normal => {
+                    if c.is_whitespace() {
+                        i += c.len_utf8();
+                        continue;
+                    }
+                    let at_word_start = i == 0 || line[..i].ends_with(char::is_whitespace);
+                    if syntax.line_comments.iter().any(|comment| {
+                        rest.starts_with(comment) && (*comment != "#" || at_word_start)
+                    }) {
+                        break;
+                    }
+                    if let Some((start, _)) = syntax.block_comment {
+                        if rest.starts_with(start) {
+                            state = LexState::BlockComment;
+                            i += start.len();
+                            continue;
+                        }
+                    }
+                    info.has_code = true;
+                    if let Some((lexed, next_state)) = lex_string_start(rest, syntax, language) {
+                        i += lexed;
+                        state = next_state;
+                        info.last_code_char = Some(c);
+                        continue;
+                    }
+                    if syntax.brackets.iter().any(|(open, _)| *open == c) {
+                        depth += 1;
+                        info.max_depth = info.max_depth.max(depth);
+                    } else if syntax.brackets.iter().any(|(_, close)| *close == c) {
+                        depth = depth.saturating_sub(1);
+                        info.closes = true;
+                    }
+                    info.last_code_char = Some(c);
+                }
+            }
+            i += c.len_utf8();
+        }
+        // Single line strings can not span lines, reset the state to recover from unknown syntax
+        if let LexState::Str {
+            multiline: false, ..
+        } = state
+        {
+            state = LexState::Normal;
+        }
+        backslash_continuation = state == LexState::Normal && line.trim_end().ends_with('\\');
+        info.depth_end = depth;
+        infos.push(info);
+    }
+    infos
+}
+
+/// Check whether a string literal starts at the beginning of `rest`,
+/// return the length of the opening delimiter and the new lexer state
+fn lex_string_start(
+    rest: &str,
+    syntax: &Syntax,
+    language: CodeLanguage,
+) -> Option<(usize, LexState)> {
+    let c = rest.chars().next()?;
+    match language {
+        CodeLanguage::Python => {
+            for quote in ["\"\"\"", "'''"] {
+                if rest.starts_with(quote) {
+                    return Some((3, string_state(quote, true, true)));
+                }
+            }
+        }
+        CodeLanguage::Rust => {
+            // Raw string: r"..." or r#"..."#
+            if c == 'r' {
+                let hashes = rest[1..].chars().take_while(|c| *c == '#').count();
+                if rest[1 + hashes..].starts_with('"') {
+                    let end = format!("\"{}", "#".repeat(hashes));
+                    return Some((2 + hashes, string_state(&end, false, true)));
+                }
+            }
+            // Lifetime('a) is not a char literal
+            if c == '\'' {
+                let is_char = rest[1..].starts_with('\\') || rest[1..].chars().nth(1) == Some('\'');
+                if !is_char {
+                    return None;
+                }
+            }
+            if c == '"' {
+                return Some((1, string_state("\"", true, true)));
+            }
+        }
+        CodeLanguage::Sql => {
+            // Dollar quoted string of PostgreSQL: $$...$$ or $tag$...$tag$
+            if c == '$' {
+                let tag_len = rest[1..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .map(|c| c.len_utf8())
+                    .sum::<usize>();
+                if rest[1 + tag_len..].starts_with('$') {
+                    let tag = &rest[..tag_len + 2];
+                    return Some((tag.len(), string_state(tag, false, true)));
+                }
+            }
+        }
+        _ => (),
+    }
+    if syntax.quotes.contains(&c) {
+        let quote = c.to_string();
+        let escape =
+            !(language == CodeLanguage::Shell && c == '\'') && language != CodeLanguage::Sql;
+        let multiline = syntax.multiline_quotes.contains(&c);
+        return Some((1, string_state(&quote, escape, multiline)));
+    }
+    None
+}
+
+fn string_state(end: &str, escape: bool, multiline: bool) -> LexState {
+    LexState::Str {
+        end: end.to_string(),
+        escape,
+        multiline,
+    }
+}
+
+struct CodeSplitter<'a, S: ChunkSizer> {
+    lines: &'a [&'a str],
+    infos: &'a [LineInfo],
+    syntax: &'a Syntax,
+    language: CodeLanguage,
+    sizer: &'a S,
+    capacity: usize,
+}
+
+impl<S: ChunkSizer> CodeSplitter<'_, S> {
+    fn is_blank(&self, index: usize) -> bool {
+        self.lines[index].trim().is_empty()
+    }
+
+    fn text(&self, start: usize, end: usize) -> String {
+        self.lines[start..=end].join("\n")
+    }
+
+    fn size(&self, start: usize, end: usize) -> usize {
+        self.sizer.size(&self.text(start, end))
+    }
+
+    /// Split lines [start, end] into units at the given nesting level,
+    /// `level` is the bracket depth for brace/statement languages and the indentation for Python
+    fn units(&self, start: usize, end: usize, level: usize) -> Vec<(usize, usize)> {
+        match self.syntax.family {
+            Family::Indent => self.indent_units(start, end, level),
+            _ => self.brace_units(start, end, level),
+        }
+    }
+
+    fn brace_units(&self, start: usize, end: usize, depth: usize) -> Vec<(usize, usize)> {
+        let mut units = Vec::new();
+        let mut unit_start: Option<usize> = None;
+        for i in start..=end {
+            if unit_start.is_none() {
+                if self.is_blank(i) {
+                    continue;
+                }
+                unit_start = Some(i);
+            }
+            let info = &self.infos[i];
+            let at_level = info.depth_end <= depth;
+            // Brackets of SQL are only parentheses which never end a statement
+            let closed_block =
+                self.syntax.family != Family::Statement && info.closes && info.max_depth > depth;
+            let next_blank = i == end || self.is_blank(i + 1);
+            let statement_end =
+                self.syntax.family == Family::Statement && info.last_code_char == Some(';');
+            if at_level && (closed_block || next_blank || statement_end) {
+                units.push((unit_start.unwrap_or(i), i));
+                unit_start = None;
+            }
+        }
+        if let Some(unit_start) = unit_start {
+            units.push((unit_start, self.trim_end(unit_start, end)));
+        }
+        units
+    }
+
+    fn indent_units(&self, start: usize, end: usize, indent: usize) -> Vec<(usize, usize)> {
+        let mut unit_starts = Vec::new();
+        let mut is_block = false;
+        let mut body_seen = false;
+        let mut prev_decorator = false;
+        for i in start..=end {
+            let info = &self.infos[i];
+            if !info.has_code || info.continuation {
+                continue;
+            }
+            let trimmed = self.lines[i].trim_start();
+            if info.indent > indent {
+                body_seen = true;
+                continue;
+            }
+            let is_decorator = trimmed.starts_with('@');
+            let is_block_start = is_decorator || PYTHON_BLOCK_RE.is_match(trimmed);
+            let new_unit = unit_starts.is_empty()
+                || (is_block_start && !prev_decorator)
+                || (is_block && body_seen && !prev_decorator);
+            if new_unit {
+                unit_starts.push(i);
+                is_block = is_block_start;
+                body_seen = false;
+            }
+            // One line block, e.g. `def f(): return 1`
+            if is_block_start && !is_decorator && self.header_end(i) == i && !trimmed.ends_with(':')
+            {
+                body_seen = true;
+            }
+            prev_decorator = is_decorator;
+        }
+        if unit_starts.is_empty() {
+            return Vec::new();
+        }
+        // Attach the comments right above a unit to it
+        for k in 1..unit_starts.len() {
+            let mut s = unit_starts[k];
+            while s > unit_starts[k - 1] + 1 && !self.infos[s - 1].has_code && !self.is_blank(s - 1)
+            {
+                s -= 1;
+            }
+            unit_starts[k] = s;
+        }
+        unit_starts[0] = (start..=unit_starts[0])
+            .find(|i| !self.is_blank(*i))
+            .unwrap_or(unit_starts[0]);
+        let mut units = Vec::new();
+        for (k, unit_start) in unit_starts.iter().enumerate() {
+            let unit_end = unit_starts.get(k + 1).map(|s| s - 1).unwrap_or(end);
+            units.push((*unit_start, self.trim_end(*unit_start, unit_end)));
+        }
+        units
+    }
+
+    fn trim_end(&self, start: usize, mut end: usize) -> usize {
+        while end > start && self.is_blank(end) {
+            end -= 1;
+        }
+        end
+    }
+
+    /// The last line of a Python block header which may span lines, e.g. `def f(\n a,\n b):`
+    fn header_end(&self, start: usize) -> usize {
+        let mut i = start;
+        while i + 1 < self.lines.len() && self.infos[i + 1].continuation {
+            i += 1;
+        }
+        i
+    }
+
+    fn split_unit(
+        &self,
+        start: usize,
+        end: usize,
+        level: usize,
+        parent: Option<&str>,
+        nesting: usize,
+        pieces: &mut Vec<Piece>,
+    ) {
+        let own_symbols = self.symbols(start, end, level);
+        let symbols: Vec<String> = own_symbols
+            .iter()
+            .map(|symbol| match parent {
+                Some(parent) => format!("{}{}{}", parent, self.separator(), symbol),
+                None => symbol.to_string(),
+            })
+            .collect();
+        if self.size(start, end) <= self.capacity {
+            pieces.push(Piece {
+                start,
+                end,
+                symbols,
+            });
+            return;
+        }
+        if nesting < MAX_NESTING && self.is_container(start, end, level) {
+            if let Some((body_start, body_end, body_level)) = self.body(start, end, level) {
+                let inner_units = self.units(body_start, body_end, body_level);
+                if inner_units.len() > 1 {
+                    let owner = own_symbols.first().map(|symbol| owner_name(symbol));
+                    let owner = match (parent, owner) {
+                        (Some(parent), Some(owner)) => {
+                            Some(format!("{}{}{}", parent, self.separator(), owner))
+                        }
+                        (None, Some(owner)) => Some(owner),
+                        (parent, None) => parent.map(|p| p.to_string()),
+                    };
+                    let last = inner_units.len() - 1;
+                    for (k, (inner_start, inner_end)) in inner_units.into_iter().enumerate() {
+                        // Header lines go with the first nested unit, closing lines with the last one
+                        let inner_start = if k == 0 { start } else { inner_start };
+                        let inner_end = if k == last { end } else { inner_end };
+                        self.split_unit(
+                            inner_start,
+                            inner_end,
+                            body_level,
+                            owner.as_deref(),
+                            nesting + 1,
+                            pieces,
+                        );
+                    }
+                    return;
+                }
+            }
+        }
+        // No nested blocks to split, fall back to line windows
+        let mut window_start = start;
+        for i in start..=end {
+            if i > window_start && self.size(window_start, i) > self.capacity {
+                pieces.push(Piece {
+                    start: window_start,
+                    end: i - 1,
+                    symbols: symbols.clone(),
+                });
+                window_start = i;
+            }
+        }
+        pieces.push(Piece {
+            start: window_start,
+            end,
+            symbols,
+        });
+    }
+
+    /// Whether the block declares a class-like container, function bodies are never split into statements
+    fn is_container(&self, start: usize, end: usize, level: usize) -> bool {
+        if self.syntax.family == Family::Statement {
+            return false;
+        }
+        (start..=end)
+            .find(|i| {
+                let info = &self.infos[*i];
+                let trimmed = self.lines[*i].trim_start();
+                let at_level = match self.syntax.family {
+                    Family::Indent => info.indent == level,
+                    _ => info.depth_start == level,
+                };
+                info.has_code && at_level && !trimmed.starts_with('@') && !trimmed.starts_with("#[")
+            })
+            .is_some_and(|i| CONTAINER_RE.is_match(self.lines[i]))
+    }
+
+    /// Find the body of a block, return (body start line, body end line, body level)
+    fn body(&self, start: usize, end: usize, level: usize) -> Option<(usize, usize, usize)> {
+        match self.syntax.family {
+            Family::Indent => {
+                let header = (start..=end).find(|i| {
+                    let trimmed = self.lines[*i].trim_start();
+                    self.infos[*i].indent == level && PYTHON_BLOCK_RE.is_match(trimmed)
+                })?;
+                let body_start = self.header_end(header) + 1;
+                if body_start > end {
+                    return None;
+                }
+                let body_level = (body_start..=end)
+                    .find(|i| self.infos[*i].has_code && !self.infos[*i].continuation)
+                    .map(|i| self.infos[i].indent)?;
+                if body_level <= level {
+                    return None;
+                }
+                Some((body_start, end, body_level))
+            }
+            _ => {
+                let open = (start..=end).find(|i| self.infos[*i].depth_end > level)?;
+                let close = (open + 1..=end)
+                    .rev()
+                    .find(|i| self.infos[*i].depth_end <= level)
+                    .unwrap_or(end + 1);
+                if open + 1 > close.saturating_sub(1) || close > end + 1 {
+                    return None;
+                }
+                Some((open + 1, close - 1, level + 1))
+            }
+        }
+    }
+
+    fn symbols(&self, start: usize, end: usize, level: usize) -> Vec<String> {
+        let Some(patterns) = SYMBOL_PATTERNS.get(&self.language) else {
+            return Vec::new();
+        };
+        let mut symbols: Vec<String> = Vec::new();
+        for i in start..=end {
+            let info = &self.infos[i];
+            let is_declaration = match self.syntax.family {
+                Family::Indent => info.indent == level && !info.continuation,
+                _ => info.depth_start == level,
+            };
+            if !info.has_code || !is_declaration {
+                continue;
+            }
+            let trimmed = self.lines[i].trim();
+            if let Some(symbol) = match_symbol(patterns, trimmed) {
+                if !symbols.contains(&symbol) {
+                    symbols.push(symbol);
+                }
+            }
+            // Only the first statement names a SQL chunk
+            if self.syntax.family == Family::Statement && !symbols.is_empty() {
+                break;
+            }
+        }
+        symbols
+    }
+
+    fn separator(&self) -> &'static str {
+        match self.language {
+            CodeLanguage::Rust | CodeLanguage::Cpp | CodeLanguage::Php => "::",
+            _ => ".",
+        }
+    }
+
+    /// Pack small adjacent pieces into chunks up to the capacity
+    fn pack(&self, pieces: Vec<Piece>) -> Vec<DocumentChunk> {
+        let mut packed: Vec<Piece> = Vec::new();
+        for piece in pieces {
+            if let Some(last) = packed.last_mut() {
+                if last.end < piece.start && self.size(last.start, piece.end) <= self.capacity {
+                    last.end = piece.end;
+                    for symbol in piece.symbols {
+                        if !last.symbols.contains(&symbol) {
+                            last.symbols.push(symbol);
+                        }
+                    }
+                    continue;
+                }
+            }
+            packed.push(piece);
+        }
+        packed
+            .into_iter()
+            .map(|piece| DocumentChunk {
+                text: self.text(piece.start, piece.end),
+                locator: ChunkLocator {
+                    language: Some(self.language.text().to_string()),
+                    symbol: if piece.symbols.is_empty() {
+                        None
+                    } else {
+                        Some(piece.symbols.join(", "))
+                    },
+                    start_line: Some(piece.start + 1),
+                    end_line: Some(piece.end + 1),
+                    ..Default::default()
+                },
+            })
+            .collect()
+    }
+}
+
+fn match_symbol(patterns: &[Regex], line: &str) -> Option<String> {
+    let first_word = line
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default();
+    if CONTROL_KEYWORDS.contains(&first_word) && first_word != "function" {
+        return None;
+    }
+    for pattern in patterns {
+        let Some(captures) = pattern.captures(line) else {
+            continue;
+        };
+        let Some(name) = captures.name("name") else {
+            continue;
+        };
+        let name = name.as_str().trim();
+        if CONTROL_KEYWORDS.contains(&name) {
+            continue;
+        }
+        let symbol = match (captures.name("owner"), captures.name("kind")) {
+            (Some(owner), _) => format!("{}.{}", owner.as_str(), name),
+            (None, Some(kind)) => format!("{} {}", kind.as_str().to_lowercase(), name),
+            (None, None) => name.to_string(),
+        };
+        return Some(symbol);
+    }
+    None
+}
+
+/// Name used as the prefix of nested symbols, e.g. `Display for Foo<T>` => `Foo`
+fn owner_name(symbol: &str) -> String {
+    let name = symbol.rsplit(" for ").next().unwrap_or(symbol);
+    let name = name.split('<').next().unwrap_or(name);
+    name.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::global::CODE_EXTS;
+    use text_splitter::Characters;
+
+    #[test]
+    fn code_exts_match_languages() {
+        for ext in CODE_EXTS {
+            assert!(
+                CodeLanguage::from_ext(ext).is_some(),
+                "unknown ext: {}",
+                ext
+            );
+        }
+        for language in CodeLanguage::ALL {
+            for ext in language.exts() {
+                assert!(CODE_EXTS.contains(ext), "missing ext: {}", ext);
+            }
+        }
+    }
+
+    #[test]
+    fn split_rust() {
+        let code = r#"use std::time::Duration;
+
+/// Retry the request with backoff
+pub async fn retry_request(url: &str) -> Result<String, Error> {
+    let s = "}{";
+    for attempt in 0..3 {
+        if let Ok(body) = fetch(url).await {
+            return Ok(body);
+        }
+    }
+    Err(Error::Timeout)
+}
+
+impl<'a> Display for Client<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "client")
+    }
+
+    fn name(&self) -> &str {
+        "client"
+    }
+}
+"#;
+        let chunks = split_code(code, CodeLanguage::Rust, &Characters, 300);
+        for chunk in &chunks {
+            println!("{:?}\n{}", chunk.locator, chunk.text);
+        }
+        let retry = chunks
+            .iter()
+            .find(|c| c.locator.symbol.as_deref() == Some("retry_request"))
+            .unwrap();
+        assert_eq!(retry.locator.end_line, Some(12));
+        assert_eq!(chunks.last().unwrap().locator.end_line, Some(22));
+
+        // The impl block doesn't fit, its methods are split apart
+        let chunks = split_code(code, CodeLanguage::Rust, &Characters, 100);
+        for symbol in ["Client::fmt", "Client::name"] {
+            assert!(chunks.iter().any(|c| {
+                c.locator
+                    .symbol
+                    .as_deref()
+                    .is_some_and(|s| s.contains(symbol))
+            }));
+        }
+    }
+
+    #[test]
+    fn split_python() {
+        let code = r#"import requests
+
+
+class Client:
+    """HTTP client"""
+
+    def __init__(self, url):
+        self.url = url
+
+    # Retry on connection errors
+    def get_with_retry(self, path,
+                       retries=3):
+        for _ in range(retries):
+            try:
+                return requests.get(self.url + path)
+            except requests.ConnectionError:
+                pass
+
+
+def main():
+    Client("http://localhost").get_with_retry("/")
+"#;
+        let chunks = split_code(code, CodeLanguage::Python, &Characters, 300);
+        for chunk in &chunks {
+            println!("{:?}\n{}", chunk.locator, chunk.text);
+        }
+        let retry = chunks
+            .iter()
+            .find(|c| c.text.contains("def get_with_retry"))
+            .unwrap();
+        assert_eq!(
+            retry.locator.symbol.as_deref(),
+            Some("Client.get_with_retry")
+        );
+        assert_eq!(retry.locator.start_line, Some(10));
+        assert_eq!(retry.locator.end_line, Some(17));
+        let main = chunks.iter().find(|c| c.text.contains("def main")).unwrap();
+        assert!(main.locator.symbol.as_deref().unwrap().contains("main"));
+    }
+
+    #[test]
+    fn split_sql() {
+        let code = "CREATE TABLE users (id int, name text);\n\nCREATE OR REPLACE FUNCTION touch() RETURNS trigger AS $$\nBEGIN\n  NEW.updated = now();\n  RETURN NEW;\nEND;\n$$ LANGUAGE plpgsql;\n";
+        let chunks = split_code(code, CodeLanguage::Sql, &Characters, 140);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].locator.symbol.as_deref(), Some("table users"));
+        assert_eq!(chunks[1].locator.symbol.as_deref(), Some("function touch"));
+        assert_eq!(chunks[1].locator.start_line, Some(3));
+        assert_eq!(chunks[1].locator.end_line, Some(8));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::structs::file_metadata::FileMetadata;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+/// Core(`docProps/core.xml`) and extended(`docProps/app.xml`) properties of OOXML documents(docx, pptx, xlsx)
+pub fn load_ooxml_properties(path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    for name in ["docProps/core.xml", "docProps/app.xml"] {
+        if let Some(xml) = read_entry(&mut archive, name)? {
+            apply_xml_properties(&xml, metadata);
+        }
+    }
+    Ok(())
+}
+
+/// Properties in `meta.xml` of OpenDocument files(odt, odp, ods)
+pub fn load_odf_properties(path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    if let Some(xml) = read_entry(&mut archive, "meta.xml")? {
+        apply_xml_properties(&xml, metadata);
+    }
+    Ok(())
+}
+
+pub fn read_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> io::Result<Option<String>> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+    let mut xml = String::new();
+    entry.read_to_string(&mut xml)?;
+    Ok(Some(xml))
+}
+
+/// Fill in the properties by the local names of the elements, the namespaces differ between formats:
+/// `dc:title`, `dc:subject`, `dc:creator`, `meta:initial-creator`, `cp:keywords`, `meta:keyword`,
+/// `Pages`/`Slides` and `meta:document-statistic/@meta:page-count`.
+/// Properties already set are kept.
+pub fn apply_xml_properties(xml: &str, metadata: &mut FileMetadata) {
+    let mut reader = Reader::from_str(xml);
+    let mut current: Vec<u8> = Vec::new();
+    let mut text = String::new();
+    let mut creator = String::new();
+    let mut initial_creator = String::new();
+    let mut subjects: Vec<String> = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                current = e.local_name().as_ref().to_vec();
+                text.clear();
+            }
+            Ok(Event::Empty(e)) => {
+                if e.local_name().as_ref() == b"document-statistic" {
+                    for attr in e.attributes().flatten() {
+                        if attr.key.local_name().as_ref() == b"page-count" {
+                            let value = String::from_utf8_lossy(&attr.value).to_string();
+                            if let Ok(page_count) = value.parse::<u32>() {
+                                metadata.page_count.get_or_insert(page_count);
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(Event::Text(e)) => {
+                if let Ok(decoded) = e.decode() {
+                    text.push_str(&decoded);
+                }
+            }
+            Ok(Event::CData(e)) => {
+                if let Ok(decoded) = e.decode() {
+                    text.push_str(&decoded);
+                }
+            }
+            Ok(Event::GeneralRef(e)) => {
+                if let Ok(Some(c)) = e.resolve_char_ref() {
+                    text.push(c);
+                } else if let Ok(name) = e.decode() {
+                    text.push_str(resolve_predefined_entity(&name).unwrap_or_default());
+                }
+            }
+            Ok(Event::End(_)) => {
+                let value = text.trim();
+                if !value.is_empty() {
+                    match current.as_slice() {
+                        b"title" if metadata.title.is_empty() => metadata.title = value.to_string(),
+                        b"subject" => subjects.push(value.to_string()),
+                        b"creator" if creator.is_empty() => creator = value.to_string(),
+                        b"initial-creator" => initial_creator = value.to_string(),
+                        b"keywords" => metadata.keywords.extend(
+                            value
+                                .split([',', ';'])
+                                .map(|keyword| keyword.trim().to_string())
+                                .filter(|keyword| !keyword.is_empty()),
+                        ),
+                        b"keyword" => metadata.keywords.push(value.to_string()),
+                        b"Pages" | b"Slides" => {
+                            if let Ok(page_count) = value.parse::<u32>() {
+                                metadata.page_count.get_or_insert(page_count);
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                current.clear();
+                text.clear();
+            }
+            Ok(Event::Eof) => break,
+            Err(error) => {
+                println!("Read document properties error: {}", error);
+                break;
+            }
+            _ => (),
+        }
+    }
+    if metadata.subject.is_empty() {
+        metadata.subject = subjects.join("; ");
+    }
+    // The initial creator is the author of an OpenDocument file, creator is the last editor
+    let author = match initial_creator.is_empty() {
+        true => creator,
+        false => initial_creator,
+    };
+    if metadata.author.is_empty() {
+        metadata.author = author;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_ooxml_core() {
+        let mut metadata = FileMetadata::default();
+        apply_xml_properties(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:title>Budget &amp; plan</dc:title><dc:subject>Finance</dc:subject><dc:creator>Alice</dc:creator>
+<cp:keywords>budget; 2024, plan</cp:keywords></cp:coreProperties>"#,
+            &mut metadata,
+        );
+        apply_xml_properties(
+            "<Properties><Pages>12</Pages><Company>Mango</Company></Properties>",
+            &mut metadata,
+        );
+        assert_eq!(metadata.title, "Budget & plan");
+        assert_eq!(metadata.subject, "Finance");
+        assert_eq!(metadata.author, "Alice");
+        assert_eq!(metadata.keywords, vec!["budget", "2024", "plan"]);
+        assert_eq!(metadata.page_count, Some(12));
+    }
+
+    #[test]
+    fn apply_odf_meta() {
+        let mut metadata = FileMetadata::default();
+        apply_xml_properties(
+            r#"<office:document-meta><office:meta><dc:title>Notes</dc:title>
+<meta:initial-creator>Bob</meta:initial-creator><dc:creator>Carol</dc:creator>
+<meta:keyword>mango</meta:keyword><meta:keyword>desk</meta:keyword>
+<meta:document-statistic meta:page-count="3" meta:word-count="100"/></office:meta></office:document-meta>"#,
+            &mut metadata,
+        );
+        assert_eq!(metadata.title, "Notes");
+        assert_eq!(metadata.author, "Bob");
+        assert_eq!(metadata.keywords, vec!["mango", "desk"]);
+        assert_eq!(metadata.page_count, Some(3));
+    }
+}
@@ -1,12 +1,11 @@
-use crate::enums::{DownloadEvent, Locale};
+use crate::enums::DownloadEvent;
 use crate::global::{
-    ACTIVE_LOCALE, DOWNLOADING, EXIT_APP_SIGNAL, HUGGINFACE_MIRROR, HUGGINFACE_WEBSITE,
-    MULTI_LANG_MODEL_URL, MULTI_LANG_TOKENIZER_URL, PROXY,
+    DOWNLOAD_CANCEL_SIGNAL, DOWNLOADING, EXIT_APP_SIGNAL, MULTI_LANG_MODEL_ID, PROXY,
 };
+use crate::structs::local_model::LocalModel;
 use crate::structs::proxy_setting::ProxyInfo;
-use crate::utils::app_util;
-use crate::utils::app_util::{get_multilingual_embedding_path, get_multilingual_tokenizer_path};
 use crate::utils::path_util::check_and_move;
+use crate::utils::{app_util, http_client_util, model_registry_util};
 use anyhow::{Context, Result, anyhow};
 use futures::StreamExt;
 use log::{error, info};
@@ -31,58 +30,93 @@ pub async fn download_multilingual_model(
     on_event: &Channel<DownloadEvent>,
 ) -> Result<()> {
     info!("Downloading multilingual model");
-    if DOWNLOADING.load(Ordering::SeqCst) {
+    let model = model_registry_util::get_model(MULTI_LANG_MODEL_ID)?;
+    let prefer_mirror = model_registry_util::prefer_mirror(None).await;
+    install_local_model(&model, enable_proxy, prefer_mirror, on_event).await
+}
+
+/// Download the files of a model of the registry into the tmp directory, verify and move them into the model directory.
+///
+/// The preferred source(HuggingFace or its mirror) is tried first and the other one on failure,
+/// the partial files are kept so a cancelled or failed download resumes where it stopped.
+pub async fn install_local_model(
+    model: &LocalModel,
+    enable_proxy: bool,
+    prefer_mirror: bool,
+    on_event: &Channel<DownloadEvent>,
+) -> Result<()> {
+    info!("Installing local model: {}", model.id);
+    // Claimed up front so that two installs can not both pass the check
+    if DOWNLOADING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
         return Err(anyhow!("Downloading is in progress"));
     }
-    let tmp_path = app_util::get_assets_tmp_path();
-    let mut model_url = MULTI_LANG_MODEL_URL.to_string();
-    let mut tokenizer_url = MULTI_LANG_TOKENIZER_URL.to_string();
-    // Use mirror first when zh-CN locale is active
-    if *ACTIVE_LOCALE.read().await == Locale::ZhCn.text() {
-        model_url = model_url.replace(HUGGINFACE_WEBSITE, HUGGINFACE_MIRROR);
-        tokenizer_url = tokenizer_url.replace(HUGGINFACE_WEBSITE, HUGGINFACE_MIRROR);
-    }
-    let file_urls = vec![model_url.clone(), tokenizer_url];
-    let tmp_paths = vec![
-        format!("{}/model.onnx", tmp_path).to_string(),
-        format!("{}/tokenizer.json", tmp_path).to_string(),
-    ];
-    match download_files(&file_urls, &tmp_paths, enable_proxy, on_event).await {
-        Ok(_) => {}
-        Err(error) => {
-            error!("Failed to download model: {error}");
-            if model_url.contains(HUGGINFACE_MIRROR) {
-                error!("Failed to download from mirror, trying to download from original source");
-                let file_urls = vec![
-                    MULTI_LANG_MODEL_URL.to_string(),
-                    MULTI_LANG_TOKENIZER_URL.to_string(),
-                ];
-                download_files(&file_urls, &tmp_paths, enable_proxy, on_event).await?;
-            } else {
-                error!("Failed to download from original source, trying to download from mirror");
-                let file_urls = vec![
-                    MULTI_LANG_MODEL_URL
-                        .to_string()
-                        .replace(HUGGINFACE_WEBSITE, HUGGINFACE_MIRROR),
-                    MULTI_LANG_TOKENIZER_URL
-                        .to_string()
-                        .replace(HUGGINFACE_WEBSITE, HUGGINFACE_MIRROR),
-                ];
-                download_files(&file_urls, &tmp_paths, enable_proxy, on_event).await?;
+    let result = download_and_install(model, enable_proxy, prefer_mirror, on_event).await;
+    DOWNLOADING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn download_and_install(
+    model: &LocalModel,
+    enable_proxy: bool,
+    prefer_mirror: bool,
+    on_event: &Channel<DownloadEvent>,
+) -> Result<()> {
+    DOWNLOAD_CANCEL_SIGNAL.store(false, Ordering::SeqCst);
+    let tmp_dir = Path::new(&app_util::get_assets_tmp_path()).join(&model.id);
+    let tmp_paths: Vec<String> = model
+        .files
+        .iter()
+        .map(|file| tmp_dir.join(&file.path).to_string_lossy().into_owned())
+        .collect();
+    for use_mirror in [prefer_mirror, !prefer_mirror] {
+        let file_urls: Vec<String> = model
+            .files
+            .iter()
+            .map(|file| model_registry_util::source_url(&file.url, use_mirror))
+            .collect();
+        match download_files(&file_urls, &tmp_paths, enable_proxy, on_event).await {
+            Ok(_) => break,
+            Err(error) if is_cancelled() || use_mirror != prefer_mirror => return Err(error),
+            Err(error) => {
+                error!("Failed to download model: {error}");
+                error!(
+                    "Trying to download from {}",
+                    if use_mirror {
+                        "original source"
+                    } else {
+                        "mirror"
+                    }
+                );
             }
         }
     }
-    check_and_move(
-        tmp_paths[0].as_str(),
-        get_multilingual_embedding_path().as_str(),
-    )?;
-    check_and_move(
-        tmp_paths[1].as_str(),
-        get_multilingual_tokenizer_path().as_str(),
-    )?;
+    for (file, tmp_path) in model.files.iter().zip(tmp_paths.iter()) {
+        if let Err(error) = model_registry_util::verify_file(Path::new(tmp_path), file) {
+            // Downloaded again from scratch next time
+            fs::remove_file(tmp_path).ok();
+            return Err(error.into());
+        }
+    }
+    for (file, tmp_path) in model.files.iter().zip(tmp_paths.iter()) {
+        check_and_move(
+            tmp_path,
+            model_registry_util::file_path(file)
+                .to_string_lossy()
+                .as_ref(),
+        )?;
+    }
+    info!("Local model installed: {}", model.id);
     Ok(())
 }
 
+/// Cancelled by the user or the app is exiting
+fn is_cancelled() -> bool {
+    EXIT_APP_SIGNAL.load(Ordering::SeqCst) || DOWNLOAD_CANCEL_SIGNAL.load(Ordering::SeqCst)
+}
+
 pub async fn download_files(
     file_urls: &Vec<String>,
     local_paths: &Vec<String>,
@@ -90,8 +124,8 @@ pub async fn download_files(
     on_event: &Channel<DownloadEvent>,
 ) -> Result<()> {
     info!("Downloading files");
-    DOWNLOADING.store(true, Ordering::SeqCst);
-    let mut builder = Client::builder()
+    let proxy_info: ProxyInfo = (*PROXY.read().await).clone();
+    let builder = http_client_util::client_builder(enable_proxy.then_some(&proxy_info))?
         .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
         .redirect(reqwest::redirect::Policy::limited(10))
         .timeout(Duration::from_secs(30))
@@ -107,29 +141,15 @@ pub async fn download_files(
             );
             headers
         });
-    if enable_proxy {
-        let proxy_info: ProxyInfo = (*PROXY.read().await).clone();
-        builder = builder.proxy(reqwest::Proxy::http(format!(
-            "{}://{}:{}",
-            proxy_info.protocal, proxy_info.host, proxy_info.port
-        ))?);
-    }
     let client = builder.build()?;
     for (file_url, local_path) in file_urls.iter().zip(local_paths.iter()) {
         info!("Downloading file:{}", file_url);
-        if EXIT_APP_SIGNAL.load(Ordering::SeqCst) {
-            DOWNLOADING.store(false, Ordering::SeqCst);
-            return Err(anyhow!("Download interrupted by exit signal"));
+        if is_cancelled() {
+            return Err(anyhow!("Download cancelled"));
         }
-        let file_size = get_remote_file_size(&client, &file_url)
-            .await
-            .or_else(|error| {
-                DOWNLOADING.store(false, Ordering::SeqCst);
-                Err(error)
-            })?;
+        let file_size = get_remote_file_size(&client, &file_url).await?;
         info!("Remote file size: {}", file_size);
         if file_size == 0 {
-            DOWNLOADING.store(false, Ordering::SeqCst);
             error!("File size is 0,url:{}", file_url);
             continue;
         }
@@ -159,7 +179,6 @@ pub async fn download_files(
         )
         .await
         .or_else(|error| {
-            DOWNLOADING.store(false, Ordering::SeqCst);
             let _ = on_event.send(DownloadEvent::Error {
                 download_id,
                 error: format!("Download file failed:{}", error.to_string()),
@@ -167,7 +186,6 @@ pub async fn download_files(
             Err(error)
         })?;
     }
-    DOWNLOADING.store(false, Ordering::SeqCst);
     Ok(())
 }
 
@@ -239,6 +257,9 @@ pub async fn download_file(
             }
             Err(e) => {
                 retries += 1;
+                if is_cancelled() {
+                    return Err(e);
+                }
                 if retries > max_retries {
                     return Err(e).context(format!(
                         "Exceeded max retries times ({}) Exceeded max retries",
@@ -323,9 +344,8 @@ async fn try_download_range(
     let mut bytes_downloaded = 0u64;
     let mut stream = response.bytes_stream();
     while let Some(chunk) = stream.next().await {
-        if EXIT_APP_SIGNAL.load(Ordering::SeqCst) {
-            DOWNLOADING.store(false, Ordering::SeqCst);
-            return Err(anyhow!("Download interrupted by exit signal"));
+        if is_cancelled() {
+            return Err(anyhow!("Download cancelled"));
         }
         let chunk = chunk?;
         file.write_all(&chunk)?;
@@ -0,0 +1,321 @@
+use crate::global::DEFAULT_DATETIME_FORMAT;
+use crate::structs::file_metadata::{FileMetadata, ImageMetadata};
+use chrono::{NaiveDate, NaiveDateTime};
+use exif::{Exif, In, Tag, Value};
+use quick_xml::encoding::Decoder;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+const XMP_START: &[u8] = b"<x:xmpmeta";
+const XMP_END: &[u8] = b"</x:xmpmeta>";
+// Container elements of XMP arrays, their text belongs to the parent property
+const XMP_ARRAY_ELEMENTS: [&[u8]; 4] = [b"Alt", b"Bag", b"Seq", b"li"];
+
+/// Fill in the capture details of an image, EXIF is read first and
+/// XMP supplies the fields EXIF lacks along with the title, creator, description and keywords.
+pub fn load_image_metadata(path: &Path, metadata: &mut FileMetadata) -> io::Result<()> {
+    let mut image = metadata.image.take().unwrap_or_default();
+    let file = File::open(path)?;
+    match exif::Reader::new().read_from_container(&mut BufReader::new(&file)) {
+        Ok(exif) => apply_exif(&exif, &mut image),
+        Err(exif::Error::Io(error)) => return Err(error),
+        Err(error) => println!("No EXIF in {}: {}", path.display(), error),
+    }
+
+    let mut data = Vec::new();
+    File::open(path)?.read_to_end(&mut data)?;
+    if let Some(xmp) = find_xmp_packet(&data) {
+        apply_xmp(&xmp, &mut image, metadata);
+    }
+    if !image.is_empty() {
+        metadata.image = Some(image);
+    }
+    Ok(())
+}
+
+pub fn apply_exif(exif: &Exif, image: &mut ImageMetadata) {
+    let field = |tag: Tag| exif.get_field(tag, In::PRIMARY);
+    let ascii = |tag: Tag| match field(tag).map(|field| &field.value) {
+        Some(Value::Ascii(values)) => values
+            .first()
+            .map(|value| String::from_utf8_lossy(value).trim().to_string())
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+    let uint = |tag: Tag| field(tag).and_then(|field| field.value.get_uint(0));
+
+    for tag in [Tag::DateTimeOriginal, Tag::DateTimeDigitized, Tag::DateTime] {
+        if !image.taken.is_empty() {
+            break;
+        }
+        let taken = match field(tag).map(|field| &field.value) {
+            Some(Value::Ascii(values)) => values
+                .first()
+                .and_then(|value| exif::DateTime::from_ascii(value).ok()),
+            _ => None,
+        };
+        if let Some(taken) = taken {
+            image.taken = format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                taken.year, taken.month, taken.day, taken.hour, taken.minute, taken.second
+            );
+        }
+    }
+    if image.camera.is_empty() {
+        image.camera = camera_name(&ascii(Tag::Make), &ascii(Tag::Model));
+    }
+    if image.lens.is_empty() {
+        image.lens = ascii(Tag::LensModel);
+    }
+    image.width = image
+        .width
+        .or(uint(Tag::PixelXDimension))
+        .or(uint(Tag::ImageWidth));
+    image.height = image
+        .height
+        .or(uint(Tag::PixelYDimension))
+        .or(uint(Tag::ImageLength));
+    image.orientation = image.orientation.or(uint(Tag::Orientation));
+
+    let coordinate = |tag: Tag, ref_tag: Tag, negative_ref: &str| {
+        let Some(Value::Rational(dms)) = field(tag).map(|field| &field.value) else {
+            return None;
+        };
+        let degrees = dms
+            .iter()
+            .zip([1.0, 60.0, 3600.0])
+            .map(|(value, divisor)| value.to_f64() / divisor)
+            .sum::<f64>();
+        if !degrees.is_finite() {
+            return None;
+        }
+        match ascii(ref_tag).eq_ignore_ascii_case(negative_ref) {
+            true => Some(-degrees),
+            false => Some(degrees),
+        }
+    };
+    if image.latitude.is_none() || image.longitude.is_none() {
+        let latitude = coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, "S");
+        let longitude = coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, "W");
+        if let (Some(latitude), Some(longitude)) = (latitude, longitude) {
+            image.latitude = Some(latitude);
+            image.longitude = Some(longitude);
+        }
+    }
+    let altitude = field(Tag::GPSAltitude).map(|field| &field.value);
+    if let (None, Some(Value::Rational(values))) = (image.altitude, altitude) {
+        // GPSAltitudeRef is 1 if the altitude is below sea level
+        let below = uint(Tag::GPSAltitudeRef) == Some(1);
+        image.altitude = values
+            .first()
+            .map(|value| value.to_f64())
+            .filter(|value| value.is_finite())
+            .map(|value| if below { -value } else { value });
+    }
+}
+
+/// The make is dropped if the model already starts with it, e.g. "Canon" + "Canon EOS R5"
+fn camera_name(make: &str, model: &str) -> String {
+    if make.is_empty() || model.to_lowercase().starts_with(&make.to_lowercase()) {
+        return model.to_string();
+    }
+    if model.is_empty() {
+        return make.to_string();
+    }
+    format!("{} {}", make, model)
+}
+
+fn find_xmp_packet(data: &[u8]) -> Option<String> {
+    let start = data
+        .windows(XMP_START.len())
+        .position(|window| window == XMP_START)?;
+    let end = data[start..]
+        .windows(XMP_END.len())
+        .position(|window| window == XMP_END)?;
+    let packet = &data[start..start + end + XMP_END.len()];
+    Some(String::from_utf8_lossy(packet).to_string())
+}
+
+/// XMP properties are written either as attributes of `rdf:Description` or as child elements,
+/// array values(`rdf:Alt/Bag/Seq`) are attributed to the enclosing property.
+pub fn apply_xmp(xmp: &str, image: &mut ImageMetadata, metadata: &mut FileMetadata) {
+    let mut reader = Reader::from_str(xmp);
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut properties: Vec<(Vec<u8>, String)> = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                collect_attributes(&e, reader.decoder(), &mut properties);
+                stack.push(e.local_name().as_ref().to_vec());
+            }
+            Ok(Event::Empty(e)) => collect_attributes(&e, reader.decoder(), &mut properties),
+            Ok(Event::Text(e)) => {
+                let property = stack
+                    .iter()
+                    .rev()
+                    .find(|name| !XMP_ARRAY_ELEMENTS.contains(&name.as_slice()));
+                let text = e.decode().ok().filter(|text| !text.trim().is_empty());
+                if let (Some(property), Some(text)) = (property, text) {
+                    properties.push((property.clone(), text.trim().to_string()));
+                }
+            }
+            Ok(Event::End(_)) => {
+                stack.pop();
+            }
+            Ok(Event::Eof) => break,
+            Err(error) => {
+                println!("Read XMP error: {}", error);
+                break;
+            }
+            _ => (),
+        }
+    }
+
+    let mut make = String::new();
+    let mut model = String::new();
+    let mut latitude = None;
+    let mut longitude = None;
+    for (name, value) in properties {
+        match name.as_slice() {
+            b"DateTimeOriginal" | b"DateCreated" | b"CreateDate" if image.taken.is_empty() => {
+                image.taken = parse_xmp_date(&value).unwrap_or_default()
+            }
+            b"Make" => make = value,
+            b"Model" => model = value,
+            b"LensModel" | b"Lens" if image.lens.is_empty() => image.lens = value,
+            b"PixelXDimension" => image.width = image.width.or(value.parse().ok()),
+            b"PixelYDimension" => image.height = image.height.or(value.parse().ok()),
+            b"Orientation" => image.orientation = image.orientation.or(value.parse().ok()),
+            b"GPSLatitude" => latitude = parse_xmp_coordinate(&value),
+            b"GPSLongitude" => longitude = parse_xmp_coordinate(&value),
+            b"title" if metadata.title.is_empty() => metadata.title = value,
+            b"description" if metadata.subject.is_empty() => metadata.subject = value,
+            b"creator" if metadata.author.is_empty() => metadata.author = value,
+            b"subject" => metadata.keywords.push(value),
+            _ => (),
+        }
+    }
+    if image.camera.is_empty() {
+        image.camera = camera_name(&make, &model);
+    }
+    let location = latitude.zip(longitude);
+    if let (None, Some((latitude, longitude))) = (image.latitude.zip(image.longitude), location) {
+        image.latitude = Some(latitude);
+        image.longitude = Some(longitude);
+    }
+}
+
+fn collect_attributes(e: &BytesStart, decoder: Decoder, properties: &mut Vec<(Vec<u8>, String)>) {
+    for attr in e.attributes().flatten() {
+        if let Ok(value) = attr.decode_and_unescape_value(decoder) {
+            properties.push((attr.key.local_name().as_ref().to_vec(), value.to_string()));
+        }
+    }
+}
+
+/// XMP dates are ISO 8601 with optional time and zone, e.g. `2023-06-01T10:20:30+02:00`,
+/// the local time as written is kept like EXIF
+fn parse_xmp_date(value: &str) -> Option<String> {
+    let local: String = value.chars().take(19).collect();
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(&local, "%Y-%m-%dT%H:%M:%S") {
+        return Some(datetime.format(DEFAULT_DATETIME_FORMAT).to_string());
+    }
+    let date: String = value.chars().take(10).collect();
+    NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.format(DEFAULT_DATETIME_FORMAT).to_string())
+}
+
+/// XMP coordinates are `DDD,MM.mmk` or `DDD,MM,SSk` where k is N/S/E/W
+fn parse_xmp_coordinate(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let direction = value.chars().last()?.to_ascii_uppercase();
+    let degrees = value[..value.len() - direction.len_utf8()]
+        .split(',')
+        .zip([1.0, 60.0, 3600.0])
+        .map(|(part, divisor)| part.trim().parse::<f64>().map(|part| part / divisor))
+        .sum::<Result<f64, _>>()
+        .ok()?;
+    match direction {
+        'S' | 'W' => Some(-degrees),
+        'N' | 'E' => Some(degrees),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exif::experimental::Writer;
+    use exif::{Field, Rational};
+    use std::io::Cursor;
+
+    #[test]
+    fn apply_exif_fields() {
+        let rational = |num: u32, denom: u32| Rational { num, denom };
+        let fields = [
+            (Tag::Make, Value::Ascii(vec![b"Canon".to_vec()])),
+            (Tag::Model, Value::Ascii(vec![b"Canon EOS R5".to_vec()])),
+            (
+                Tag::DateTimeOriginal,
+                Value::Ascii(vec![b"2023:06:01 10:20:30".to_vec()]),
+            ),
+            (Tag::Orientation, Value::Short(vec![6])),
+            (Tag::GPSLatitudeRef, Value::Ascii(vec![b"N".to_vec()])),
+            (
+                Tag::GPSLatitude,
+                Value::Rational(vec![rational(48, 1), rational(51, 1), rational(36, 1)]),
+            ),
+            (Tag::GPSLongitudeRef, Value::Ascii(vec![b"W".to_vec()])),
+            (
+                Tag::GPSLongitude,
+                Value::Rational(vec![rational(2, 1), rational(30, 1), rational(0, 1)]),
+            ),
+        ]
+        .map(|(tag, value)| Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value,
+        });
+        let mut writer = Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut buf = Cursor::new(Vec::new());
+        writer.write(&mut buf, false).unwrap();
+        let exif = exif::Reader::new().read_raw(buf.into_inner()).unwrap();
+
+        let mut image = ImageMetadata::default();
+        apply_exif(&exif, &mut image);
+        assert_eq!(image.taken, "2023-06-01 10:20:30");
+        assert_eq!(image.camera, "Canon EOS R5");
+        assert_eq!(image.orientation, Some(6));
+        assert!((image.latitude.unwrap() - 48.86).abs() < 1e-9);
+        assert!((image.longitude.unwrap() + 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn apply_xmp_packet() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:exif="http://ns.adobe.com/exif/1.0/" xmlns:tiff="http://ns.adobe.com/tiff/1.0/"
+ xmlns:dc="http://purl.org/dc/elements/1.1/" exif:DateTimeOriginal="2021-12-24T18:30:00+01:00"
+ exif:GPSLatitude="35,40.5N" exif:GPSLongitude="139,45,36E" tiff:Make="FUJIFILM" tiff:Model="X-T4">
+<dc:title><rdf:Alt><rdf:li xml:lang="x-default">Tokyo at night</rdf:li></rdf:Alt></dc:title>
+<dc:subject><rdf:Bag><rdf:li>travel</rdf:li><rdf:li>city</rdf:li></rdf:Bag></dc:subject>
+</rdf:Description></rdf:RDF></x:xmpmeta>"#;
+        let mut image = ImageMetadata::default();
+        let mut metadata = FileMetadata::default();
+        apply_xmp(xmp, &mut image, &mut metadata);
+        assert_eq!(image.taken, "2021-12-24 18:30:00");
+        assert_eq!(image.camera, "FUJIFILM X-T4");
+        assert!((image.latitude.unwrap() - 35.675).abs() < 1e-9);
+        assert!((image.longitude.unwrap() - 139.76).abs() < 1e-9);
+        assert_eq!(metadata.title, "Tokyo at night");
+        assert_eq!(metadata.keywords, vec!["travel", "city"]);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::errors::AppError;
+use crate::structs::proxy_setting::ProxyInfo;
+use crate::utils::secret_util;
+use log::debug;
+use reqwest::{ClientBuilder, NoProxy, Proxy, Url};
+
+const PROXY_PROTOCALS: [&str; 6] = ["http", "https", "socks4", "socks4a", "socks5", "socks5h"];
+
+/// Builder of every HTTP client sent to the model platforms or downloading models, so they share the proxy logic.
+///
+/// * `None`(the proxy is disabled) or a proxy without host - the proxy of the environment variables
+///   (HTTP_PROXY, HTTPS_PROXY, ALL_PROXY, NO_PROXY) if any, otherwise connect directly
+/// * Otherwise the configured proxy with its credentials, the `no_proxy` hosts are connected directly
+pub fn client_builder(proxy: Option<&ProxyInfo>) -> Result<ClientBuilder, AppError> {
+    let builder = reqwest::Client::builder();
+    match proxy {
+        Some(proxy) if !proxy.host.trim().is_empty() => Ok(builder.proxy(build_proxy(proxy)?)),
+        _ => Ok(builder),
+    }
+}
+
+pub fn build_proxy(proxy: &ProxyInfo) -> Result<Proxy, AppError> {
+    let url = proxy_url(proxy)?;
+    debug!(
+        "Using proxy: {}://{}:{}",
+        url.scheme(),
+        proxy.host.trim(),
+        proxy.port
+    );
+    let no_proxy = NoProxy::from_string(&proxy.no_proxy.join(","));
+    Ok(Proxy::all(url)?.no_proxy(no_proxy))
+}
+
+/// The proxy URL with the decrypted credentials, never logged
+fn proxy_url(proxy: &ProxyInfo) -> Result<Url, AppError> {
+    let protocal = match proxy.protocal.trim().to_lowercase() {
+        protocal if protocal.is_empty() => "http".to_string(),
+        protocal if PROXY_PROTOCALS.contains(&protocal.as_str()) => protocal,
+        protocal => {
+            return Err(AppError::InvalidParameter(format!(
+                "Unsupported proxy protocol: {}",
+                protocal
+            )));
+        }
+    };
+    if proxy.port == 0 {
+        return Err(AppError::InvalidParameter(
+            "Proxy port is required".to_string(),
+        ));
+    }
+    let mut url = Url::parse(&format!(
+        "{}://{}:{}",
+        protocal,
+        proxy.host.trim(),
+        proxy.port
+    ))
+    .map_err(|e| AppError::InvalidParameter(format!("Invalid proxy host: {}", e)))?;
+    if !proxy.username.is_empty() {
+        // Percent-encoded by the URL, a password may contain `@` or `:`
+        let password = secret_util::decrypt_secret(&proxy.password)?;
+        url.set_username(&proxy.username)
+            .and_then(|_| url.set_password(Some(&password)))
+            .map_err(|_| AppError::InvalidParameter("Invalid proxy credentials".to_string()))?;
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn socks5_proxy_with_auth() {
+        let proxy = ProxyInfo {
+            protocal: "SOCKS5".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 1080,
+            username: "user".to_string(),
+            password: "p@ss:word".to_string(),
+            ..Default::default()
+        };
+        let url = proxy_url(&proxy).unwrap();
+        assert_eq!(url.scheme(), "socks5");
+        assert_eq!(url.username(), "user");
+        assert_eq!(url.password(), Some("p%40ss%3Aword"));
+        assert!(client_builder(Some(&proxy)).unwrap().build().is_ok());
+
+        let invalid = [
+            ProxyInfo {
+                protocal: "ftp".to_string(),
+                ..proxy.clone()
+            },
+            ProxyInfo {
+                port: 0,
+                ..proxy.clone()
+            },
+        ];
+        for proxy in invalid {
+            assert!(proxy_url(&proxy).is_err());
+        }
+    }
+}
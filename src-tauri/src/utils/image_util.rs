@@ -1,5 +1,5 @@
 use crate::errors::AppError;
-use crate::global::SUPPORTED_IMAGE_EXTS;
+use crate::global::{SUPPORTED_IMAGE_EXTS, VISION_IMAGE_EXTS};
 use crate::utils::base64_util::file_to_data_uri;
 
 fn is_supported_image_ext(ext: &str) -> bool {
@@ -11,7 +11,7 @@ fn is_supported_image_ext(ext: &str) -> bool {
 pub fn is_supported_image_file(path: &str) -> Result<bool, AppError> {
     if let Some(kind) = infer::get_from_path(path)? {
         let ext = kind.extension();
-        if SUPPORTED_IMAGE_EXTS.contains(&ext) {
+        if VISION_IMAGE_EXTS.contains(&ext) {
             return Ok(true);
         }
     }
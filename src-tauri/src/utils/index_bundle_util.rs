@@ -0,0 +1,282 @@
+use crate::db_initializer;
+use crate::embedding_service::EmbeddingService;
+use crate::embedding_service_manager;
+use crate::entities::Config;
+use crate::errors::AppError;
+use crate::global::{
+    CONFIG_NAME_ACTIVE_LOCALE, CONFIG_NAME_CLIENT_ID, CONFIG_NAME_INDEXER_SETTING,
+    CONFIG_NAME_PROXY, CONFIG_NAME_WATCHER_SETTING, DB_VERSION, EN_EMBEDDING_MODEL_ID,
+    INDEX_BUNDLE_DB_NAME, INDEX_BUNDLE_FORMAT_VERSION, INDEX_BUNDLE_MANIFEST_NAME, INDEXER_SETTING,
+    INDEXING, MULTI_LANG_MODEL_ID, SCANNING, WORKSPACE_DB_PATH,
+};
+use crate::initializer;
+use crate::repositories::{backup_repo, config_repo, file_info_repo, indexing_task_repo};
+use crate::searcher::path_search_engine;
+use crate::structs::index_bundle::{IndexBundleManifest, PathMapping};
+use crate::utils::app_util::{get_assets_tmp_path, get_db_path};
+use crate::utils::{datetime_util, model_registry_util};
+use chrono::Local;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{MAIN_SEPARATOR, Path, PathBuf};
+use std::sync::atomic::Ordering;
+use zip::ZipArchive;
+use zip::write::{SimpleFileOptions, ZipWriter};
+
+// Local to this machine, kept as they are when a bundle is imported
+const LOCAL_CONFIG_NAMES: [&str; 4] = [
+    CONFIG_NAME_CLIENT_ID,
+    CONFIG_NAME_PROXY,
+    CONFIG_NAME_WATCHER_SETTING,
+    CONFIG_NAME_ACTIVE_LOCALE,
+];
+
+/// Write a snapshot of the index(files, chunks, embeddings, tasks and settings without secrets)
+/// and its manifest into a zip file
+pub async fn export(path: &str) -> Result<IndexBundleManifest, AppError> {
+    let embedding_model = match indexing_task_repo::list(1, 1, "id", "desc")?.first() {
+        Some(task) if !task.embedding_model.is_empty() => task.embedding_model.clone(),
+        _ => EmbeddingService::model_name().await,
+    };
+    let manifest = IndexBundleManifest {
+        format_version: INDEX_BUNDLE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        db_version: DB_VERSION,
+        embedding_model,
+        root_paths: root_paths(&indexing_task_repo::list_paths()?),
+        file_count: file_info_repo::count()?,
+        create_time: datetime_util::datetime_to_str(&Local::now()),
+    };
+    let bundle_path = PathBuf::from(path);
+    let bundle_manifest = manifest.clone();
+    tokio::task::spawn_blocking(move || write_bundle(&bundle_path, &bundle_manifest)).await??;
+    println!("Index exported to {}: {:?}", path, manifest);
+    Ok(manifest)
+}
+
+pub fn read_manifest(path: &str) -> Result<IndexBundleManifest, AppError> {
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(bundle_error)?;
+    let entry = archive
+        .by_name(INDEX_BUNDLE_MANIFEST_NAME)
+        .map_err(bundle_error)?;
+    Ok(serde_json::from_reader(entry)?)
+}
+
+/// Replace the index by the one of the bundle, the paths under the `from` of a mapping are moved to its `to`.
+///
+/// The bundle database is prepared apart and then copied over the current one in a single transaction.
+/// The current database is kept next to it as a `.bak` file and put back if the copy fails,
+/// the API keys and the local settings survive the import.
+pub async fn import(
+    path: &str,
+    path_mappings: &[PathMapping],
+) -> Result<IndexBundleManifest, AppError> {
+    if SCANNING.load(Ordering::SeqCst) || INDEXING.load(Ordering::SeqCst) {
+        return Err(AppError::OperationFailed(
+            "Indexing is in progress".to_string(),
+        ));
+    }
+    let manifest = read_manifest(path)?;
+    check_manifest(&manifest)?;
+
+    let bundle_path = PathBuf::from(path);
+    let path_mappings = path_mappings.to_vec();
+    let db_path = get_db_path();
+    tokio::task::spawn_blocking(move || {
+        let bundle_db_path = extract_db(&bundle_path)?;
+        let result = restore(&db_path, &bundle_db_path, &path_mappings);
+        fs::remove_file(&bundle_db_path)
+            .unwrap_or_else(|e| println!("Failed to remove {:?}: {}", bundle_db_path, e));
+        result
+    })
+    .await??;
+
+    // The snapshot comes with its indexer setting, and maybe another embedding model
+    initializer::init_setting(CONFIG_NAME_INDEXER_SETTING, String::new, &INDEXER_SETTING).await;
+    initializer::init_doc_loaders().await;
+    initializer::init_active_platform().await;
+    embedding_service_manager::get_manager()
+        .write()
+        .await
+        .clear();
+    path_search_engine::build_index().await;
+    println!("Index imported from {}: {:?}", path, manifest);
+    Ok(manifest)
+}
+
+fn write_bundle(path: &Path, manifest: &IndexBundleManifest) -> Result<(), AppError> {
+    let db_path = tmp_db_path("export");
+    if db_path.exists() {
+        fs::remove_file(&db_path)?;
+    }
+    let result = backup_repo::backup_to(&db_path)
+        .and_then(|_| backup_repo::strip_secrets(&db_path))
+        .map_err(AppError::from)
+        .and_then(|_| {
+            let mut writer = ZipWriter::new(File::create(path)?);
+            let options = SimpleFileOptions::default().large_file(true);
+            writer
+                .start_file(INDEX_BUNDLE_MANIFEST_NAME, options)
+                .map_err(bundle_error)?;
+            writer.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+            writer
+                .start_file(INDEX_BUNDLE_DB_NAME, options)
+                .map_err(bundle_error)?;
+            io::copy(&mut File::open(&db_path)?, &mut writer)?;
+            writer.finish().map_err(bundle_error)?;
+            Ok(())
+        });
+    fs::remove_file(&db_path).unwrap_or_else(|e| println!("Failed to remove {:?}: {}", db_path, e));
+    result
+}
+
+fn extract_db(path: &Path) -> Result<PathBuf, AppError> {
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(bundle_error)?;
+    let mut entry = archive
+        .by_name(INDEX_BUNDLE_DB_NAME)
+        .map_err(bundle_error)?;
+    let db_path = tmp_db_path("import");
+    io::copy(&mut entry, &mut File::create(&db_path)?)?;
+    if !backup_repo::is_intact(&db_path)? {
+        fs::remove_file(&db_path)?;
+        return Err(AppError::IndexBundleError(
+            "The database of the bundle is corrupted".to_string(),
+        ));
+    }
+    Ok(db_path)
+}
+
+fn restore(
+    db_path: &str,
+    bundle_db_path: &Path,
+    path_mappings: &[PathMapping],
+) -> Result<(), AppError> {
+    let (local_configs, api_keys) = WORKSPACE_DB_PATH.sync_scope(db_path.to_string(), || {
+        let mut local_configs = Vec::new();
+        for name in LOCAL_CONFIG_NAMES {
+            if let Some(config) = config_repo::get_one(name)? {
+                local_configs.push(config);
+            }
+        }
+        Ok::<_, AppError>((local_configs, backup_repo::list_api_keys()?))
+    })?;
+
+    // The current database is untouched until the bundle one is ready
+    WORKSPACE_DB_PATH.sync_scope(bundle_db_path.to_string_lossy().into_owned(), || {
+        prepare_bundle_db(local_configs, &api_keys, path_mappings)
+    })?;
+
+    WORKSPACE_DB_PATH.sync_scope(db_path.to_string(), || {
+        let bak_path = PathBuf::from(format!("{}.bak", db_path));
+        backup_repo::backup_to(&bak_path)?;
+        if let Err(e) = backup_repo::restore_from(bundle_db_path) {
+            backup_repo::restore_from(&bak_path)
+                .unwrap_or_else(|e| println!("Failed to roll back from {:?}: {}", bak_path, e));
+            return Err(e.into());
+        }
+        Ok(())
+    })
+}
+
+/// Migrate a snapshot of an older version, keep the local settings and API keys and move the mapped paths,
+/// in the database of the `WORKSPACE_DB_PATH` scope
+fn prepare_bundle_db(
+    local_configs: Vec<Config>,
+    api_keys: &[(String, String)],
+    path_mappings: &[PathMapping],
+) -> Result<(), AppError> {
+    db_initializer::init().map_err(|e| AppError::IndexBundleError(e.to_string()))?;
+    for config in local_configs {
+        config_repo::insert_or_ignore(&config.name, &config.value)?;
+        config_repo::update_by_name(&config.name, &config.value)?;
+    }
+    backup_repo::restore_api_keys(api_keys)?;
+    for mapping in path_mappings {
+        let from = mapping.from.trim_end_matches(MAIN_SEPARATOR);
+        let to = mapping.to.trim_end_matches(MAIN_SEPARATOR);
+        if from.is_empty() || to.is_empty() || from == to {
+            continue;
+        }
+        file_info_repo::replace_directory_prefix_path(from, to)?;
+        indexing_task_repo::replace_directory_prefix_path(from, to)?;
+    }
+    Ok(())
+}
+
+/// The embeddings of the bundle are only searchable by the model which made them
+fn check_manifest(manifest: &IndexBundleManifest) -> Result<(), AppError> {
+    if manifest.format_version > INDEX_BUNDLE_FORMAT_VERSION || manifest.db_version > DB_VERSION {
+        return Err(AppError::IndexBundleError(format!(
+            "The bundle is made by a newer version: {}",
+            manifest.app_version
+        )));
+    }
+    match manifest.embedding_model.as_str() {
+        EN_EMBEDDING_MODEL_ID => Ok(()),
+        MULTI_LANG_MODEL_ID => {
+            let model = model_registry_util::get_model(MULTI_LANG_MODEL_ID)?;
+            if model_registry_util::is_installed(&model) {
+                Ok(())
+            } else {
+                Err(AppError::IndexBundleError(format!(
+                    "The embedding model is not installed: {}",
+                    MULTI_LANG_MODEL_ID
+                )))
+            }
+        }
+        other => Err(AppError::IndexBundleError(format!(
+            "Unsupported embedding model: {}",
+            other
+        ))),
+    }
+}
+
+/// The indexed paths of the tasks without the ones under another
+fn root_paths(task_paths: &[String]) -> Vec<String> {
+    let mut paths: Vec<&str> = task_paths
+        .iter()
+        .flat_map(|paths| paths.split(','))
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .collect();
+    paths.sort();
+    paths.dedup();
+    let mut roots: Vec<String> = Vec::new();
+    for path in paths {
+        let is_nested = roots.iter().any(|root| {
+            path.strip_prefix(root.trim_end_matches(MAIN_SEPARATOR))
+                .is_some_and(|rest| rest.starts_with(MAIN_SEPARATOR))
+        });
+        if !is_nested {
+            roots.push(path.to_string());
+        }
+    }
+    roots
+}
+
+fn tmp_db_path(name: &str) -> PathBuf {
+    Path::new(&get_assets_tmp_path()).join(format!("index-bundle-{}.db", name))
+}
+
+fn bundle_error(e: zip::result::ZipError) -> AppError {
+    AppError::IndexBundleError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduce_root_paths() {
+        let sep = MAIN_SEPARATOR;
+        let docs = format!("{sep}home{sep}me{sep}docs");
+        let nested = format!("{docs}{sep}work");
+        let sibling = format!("{docs}-old");
+        let task_paths = vec![
+            format!("{nested},{sibling}"),
+            format!("{docs}, {nested}"),
+            String::new(),
+        ];
+        assert_eq!(root_paths(&task_paths), vec![docs, sibling]);
+    }
+}
@@ -1,8 +1,9 @@
 use crate::enums::{FileCategory, IndexingTaskStatus};
-use crate::global::INDEXING_SUMMARY;
+use crate::global::{INDEXING_SUMMARY, INDEXING_TASK_ID};
 use crate::repositories::indexing_task_repo;
 use crate::structs::indexing_summary::IndexingSummary;
 use chrono::Local;
+use std::sync::atomic::Ordering;
 
 pub async fn task_new(
     paths: &Vec<String>,
@@ -20,6 +21,7 @@ pub async fn task_new(
     summary.task_id = task.id;
     summary.start_time = now;
     *INDEXING_SUMMARY.write().await = summary;
+    INDEXING_TASK_ID.store(task.id, Ordering::SeqCst);
 
     Ok(task)
 }
@@ -31,6 +33,7 @@ pub async fn task_done() -> Result<usize, String> {
         summary.duration = summary.end_time.timestamp() - summary.start_time.timestamp();
         summary.task_id
     };
+    INDEXING_TASK_ID.store(0, Ordering::SeqCst);
     let mut task = indexing_task_repo::get(task_id)?;
 
     let summary = INDEXING_SUMMARY.read().await;
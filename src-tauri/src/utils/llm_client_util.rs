@@ -3,8 +3,10 @@ use crate::errors::AppError;
 use crate::global::PROXY;
 use crate::repositories::model_platform_repo;
 use crate::structs::proxy_setting::ProxyInfo;
+use crate::utils::{http_client_util, secret_util};
 use async_openai::Client;
-use async_openai::config::OpenAIConfig;
+use async_openai::config::{OPENAI_API_BASE, OpenAIConfig};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
 
 pub async fn init_service(
     platform_name: &str,
@@ -24,6 +26,34 @@ pub async fn init_service(
     (platform, PROXY.read().await.clone())
 }
 
+/// Full URL of an OpenAI compatible endpoint, e.g. `chat/completions`
+pub fn api_url(platform: &ModelPlatform, path: &str) -> String {
+    let base_url = match platform.base_url.trim_end_matches('/') {
+        "" => OPENAI_API_BASE,
+        base_url => base_url,
+    };
+    format!("{}/{}", base_url, path)
+}
+
+/// HTTP client for the requests sent through `remote_call_util`,
+/// the API key(if any) is sent as the bearer token
+pub fn create_http_client(
+    platform: &ModelPlatform,
+    proxy: &ProxyInfo,
+) -> Result<reqwest::Client, AppError> {
+    let mut headers = HeaderMap::new();
+    let api_key = secret_util::decrypt_secret(&platform.api_key)?;
+    if !api_key.is_empty() {
+        let mut value = HeaderValue::from_str(&format!("Bearer {}", api_key))
+            .map_err(|e| AppError::InternalError(format!("Invalid API key: {}", e)))?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+    Ok(platform_client_builder(platform, proxy)?
+        .default_headers(headers)
+        .build()?)
+}
+
 pub fn create_client(
     platform: &ModelPlatform,
     proxy: &ProxyInfo,
@@ -32,37 +62,24 @@ pub fn create_client(
         return Err(AppError::InternalError("API key is required".to_string()));
     }
 
-    if platform.is_proxy_enable {
-        if proxy.host.is_empty() || proxy.port == 0 {
-            return Err(AppError::InternalError(
-                "Invalid proxy configuration".to_string(),
-            ));
-        }
-        println!(
-            "Using proxy: {}://{}:{}",
-            proxy.protocal, proxy.host, proxy.port
-        );
-    }
-
+    let api_key = secret_util::decrypt_secret(&platform.api_key)?;
     let open_ai_config = if platform.base_url.is_empty() {
-        OpenAIConfig::new().with_api_key(platform.api_key.clone())
+        OpenAIConfig::new().with_api_key(api_key)
     } else {
         OpenAIConfig::new()
-            .with_api_key(platform.api_key.clone())
+            .with_api_key(api_key)
             .with_api_base(platform.base_url.clone())
     };
 
-    let client = if platform.is_proxy_enable && !proxy.host.is_empty() {
-        let request_proxy = reqwest::Proxy::http(format!(
-            "{}://{}:{}",
-            proxy.protocal, proxy.host, proxy.port
-        ))?;
-        println!("request_proxy: {:?}", request_proxy);
-        Client::with_config(open_ai_config)
-            .with_http_client(reqwest::Client::builder().proxy(request_proxy).build()?)
-    } else {
-        Client::with_config(open_ai_config)
-    };
-
+    let client = Client::with_config(open_ai_config)
+        .with_http_client(platform_client_builder(platform, proxy)?.build()?);
     Ok(client)
 }
+
+/// The proxy applies if enabled for the platform
+fn platform_client_builder(
+    platform: &ModelPlatform,
+    proxy: &ProxyInfo,
+) -> Result<reqwest::ClientBuilder, AppError> {
+    http_client_util::client_builder(platform.is_proxy_enable.then_some(proxy))
+}
@@ -0,0 +1,180 @@
+use crate::structs::chunk_locator::ChunkLocator;
+use crate::structs::document_chunk::DocumentChunk;
+use text_splitter::{ChunkConfig, ChunkSizer, MarkdownSplitter};
+
+pub const BREADCRUMB_SEPARATOR: &str = " > ";
+
+/// A heading of a markdown document, `offset` is the byte offset of its first line
+#[derive(Debug, PartialEq)]
+pub struct Heading {
+    pub offset: usize,
+    pub level: usize,
+    pub title: String,
+}
+
+/// ATX(`## Title`) and setext(`Title` + `===`/`---`) headings, fenced code blocks and front matter are skipped
+pub fn parse_headings(text: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut in_front_matter = text.starts_with("---\n") || text.starts_with("---\r\n");
+    // Previous line that may be the title of a setext heading
+    let mut previous: Option<(usize, &str)> = None;
+    let mut offset = 0;
+    for (i, raw_line) in text.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += raw_line.len();
+        let line = raw_line.trim_end();
+        let trimmed = line.trim_start();
+
+        if in_front_matter {
+            if i > 0 && line == "---" {
+                in_front_matter = false;
+            }
+            continue;
+        }
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            previous = None;
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let is_atx = (1..=6).contains(&level)
+            && trimmed[level..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace());
+        if is_atx {
+            let title = trimmed[level..].trim().trim_end_matches('#').trim();
+            if !title.is_empty() {
+                headings.push(Heading {
+                    offset: line_offset,
+                    level,
+                    title: title.to_string(),
+                });
+            }
+            previous = None;
+            continue;
+        }
+
+        let underline = if !trimmed.is_empty() && trimmed.chars().all(|c| c == '=') {
+            Some(1)
+        } else if trimmed.len() >= 2 && trimmed.chars().all(|c| c == '-') {
+            Some(2)
+        } else {
+            None
+        };
+        if let (Some(level), Some((title_offset, title))) = (underline, previous) {
+            headings.push(Heading {
+                offset: title_offset,
+                level,
+                title: title.to_string(),
+            });
+            previous = None;
+            continue;
+        }
+        // Only a paragraph line can be the title of a setext heading
+        previous = match trimmed.is_empty() || line.starts_with("    ") {
+            true => None,
+            false => Some((line_offset, trimmed)),
+        };
+    }
+    headings
+}
+
+/// Split markdown at the structure boundaries(headings, paragraphs, code blocks...),
+/// the heading breadcrumb of every chunk(e.g. `Guide > Install > Linux`) is recorded in the locator.
+pub fn split_markdown<S: ChunkSizer>(text: &str, sizer: &S, capacity: usize) -> Vec<DocumentChunk> {
+    let headings = parse_headings(text);
+    let splitter = MarkdownSplitter::new(ChunkConfig::new(capacity).with_sizer(sizer));
+    let mut stack: Vec<&Heading> = Vec::new();
+    let mut next_heading = 0;
+    let mut chunks = Vec::new();
+    for (offset, chunk) in splitter.chunk_indices(text) {
+        // Headings before the chunk or at its start
+        while next_heading < headings.len() && headings[next_heading].offset <= offset {
+            let heading = &headings[next_heading];
+            while stack.last().is_some_and(|top| top.level >= heading.level) {
+                stack.pop();
+            }
+            stack.push(heading);
+            next_heading += 1;
+        }
+        let heading = match stack.is_empty() {
+            true => None,
+            false => Some(
+                stack
+                    .iter()
+                    .map(|heading| heading.title.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(BREADCRUMB_SEPARATOR),
+            ),
+        };
+        chunks.push(DocumentChunk {
+            text: chunk.to_string(),
+            locator: ChunkLocator {
+                heading,
+                ..Default::default()
+            },
+        });
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text_splitter::Characters;
+
+    const GUIDE: &str = "---\ntitle: Guide\n---\n# Guide\n\nIntro text.\n\n## Install\n\nDownload the package.\n\n### Linux\n\n```sh\n# not a heading\napt install mango\n```\n\nMac\n---\n\nUse the dmg file.\n";
+
+    #[test]
+    fn parse() {
+        let titles: Vec<(usize, String)> = parse_headings(GUIDE)
+            .into_iter()
+            .map(|heading| (heading.level, heading.title))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                (1, "Guide".to_string()),
+                (2, "Install".to_string()),
+                (3, "Linux".to_string()),
+                (2, "Mac".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_with_breadcrumb() {
+        let chunks = split_markdown(GUIDE, &Characters, 50);
+        for chunk in &chunks {
+            assert!(chunk.text.chars().count() <= 50);
+            assert!(!chunk.text.trim().is_empty());
+            // The comment in the code block is not a heading
+            if let Some(heading) = &chunk.locator.heading {
+                assert!(heading.starts_with("Guide"));
+                assert!(!heading.contains("not a heading"));
+            }
+        }
+        let linux = chunks
+            .iter()
+            .find(|chunk| chunk.text.contains("apt install"))
+            .unwrap();
+        assert_eq!(
+            linux.locator.heading.as_deref(),
+            Some("Guide > Install > Linux")
+        );
+        let mac = chunks
+            .iter()
+            .find(|chunk| chunk.text.contains("dmg"))
+            .unwrap();
+        assert_eq!(mac.locator.heading.as_deref(), Some("Guide > Mac"));
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A local HTTP server for the tests of the remote calls, one request per connection
+pub struct MockServer {
+    pub url: String,
+    pub requests: Arc<AtomicUsize>,
+    pub max_in_flight: Arc<AtomicUsize>,
+}
+
+/// Answer each request with the status line(and extra headers) and the body returned by `respond`,
+/// called with the 0-based request index and the request path.
+/// Each response is delayed to let the concurrent requests overlap
+pub async fn start<F>(delay: Duration, respond: F) -> MockServer
+where
+    F: Fn(usize, &str) -> (String, String) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let (counter, peak) = (requests.clone(), max_in_flight.clone());
+    let respond = Arc::new(respond);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let index = counter.fetch_add(1, Ordering::SeqCst);
+            let (in_flight, peak, respond) = (in_flight.clone(), peak.clone(), respond.clone());
+            tokio::spawn(async move {
+                let path = read_request_path(&mut stream).await;
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(delay).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                let (head, body) = respond(index, &path);
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    head,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    MockServer {
+        url,
+        requests,
+        max_in_flight,
+    }
+}
+
+/// Read the headers and the body announced by Content-Length, returns the request path
+async fn read_request_path(stream: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let len = stream.read(&mut buffer).await.unwrap_or(0);
+        request.extend_from_slice(&buffer[..len]);
+        let text = String::from_utf8_lossy(&request);
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let content_length = text[..header_end]
+                .lines()
+                .find_map(|line| {
+                    line.to_ascii_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(|value| value.trim().parse::<usize>().unwrap_or(0))
+                })
+                .unwrap_or(0);
+            if request.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+        if len == 0 {
+            break;
+        }
+    }
+    let text = String::from_utf8_lossy(&request);
+    text.split_whitespace().nth(1).unwrap_or("").to_string()
+}
@@ -0,0 +1,201 @@
+use crate::entities::{AiModel, ModelPlatform};
+use crate::enums::{ModelPlatformName, ModelType};
+use crate::errors::AppError;
+
+const MODEL_TYPES: [ModelType; 7] = [
+    ModelType::Text,
+    ModelType::Image,
+    ModelType::Vision,
+    ModelType::Embedding,
+    ModelType::Rerank,
+    ModelType::Asr,
+    ModelType::Tts,
+];
+const INPUT_TYPES: [&str; 4] = ["text", "image", "audio", "video"];
+
+/// Check and tidy up a user-defined platform before it is saved.
+///
+/// The name is the key referenced by the models and the `active_model_platform` config,
+/// so only lowercase letters, digits, `-` and `_` are accepted and the built-in names are reserved.
+pub fn normalize_custom_platform(platform: &mut ModelPlatform) -> Result<(), AppError> {
+    platform.name = platform.name.trim().to_string();
+    platform.title = platform.title.trim().to_string();
+    platform.base_url = platform.base_url.trim().trim_end_matches('/').to_string();
+    platform.api_key = platform.api_key.trim().to_string();
+
+    if platform.name.is_empty() {
+        return Err(AppError::InvalidParameter(
+            "platform name is empty".to_string(),
+        ));
+    }
+    if !platform
+        .name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(AppError::InvalidParameter(format!(
+            "platform name {} may only contain a-z, 0-9, - and _",
+            platform.name
+        )));
+    }
+    if ModelPlatformName::is_builtin(&platform.name) {
+        return Err(AppError::InvalidParameter(format!(
+            "platform name {} is reserved",
+            platform.name
+        )));
+    }
+    if !platform.base_url.starts_with("http://") && !platform.base_url.starts_with("https://") {
+        return Err(AppError::InvalidParameter(format!(
+            "invalid base url: {}",
+            platform.base_url
+        )));
+    }
+    if platform.max_concurrency < 1 || platform.requests_per_minute < 0 {
+        return Err(AppError::InvalidParameter(
+            "max concurrency must be at least 1 and requests per minute not negative".to_string(),
+        ));
+    }
+    if platform.title.is_empty() {
+        platform.title = platform.name.clone();
+    }
+    platform.is_openai_api_compatible = true;
+    Ok(())
+}
+
+/// Check and tidy up a model before it is saved, `model_types` and `input_types` are comma separated lists
+pub fn normalize_model(model: &mut AiModel) -> Result<(), AppError> {
+    model.name = model.name.trim().to_string();
+    model.title = model.title.trim().to_string();
+    if model.name.is_empty() {
+        return Err(AppError::InvalidParameter(
+            "model name is empty".to_string(),
+        ));
+    }
+    if model.title.is_empty() {
+        model.title = model.name.clone();
+    }
+
+    let model_types = normalize_list(&model.model_types);
+    if model_types.is_empty() {
+        return Err(AppError::InvalidParameter(
+            "model types are empty".to_string(),
+        ));
+    }
+    if let Some(unknown) = model_types
+        .iter()
+        .find(|one| !MODEL_TYPES.iter().any(|t| <&str>::from(*t) == one.as_str()))
+    {
+        return Err(AppError::InvalidParameter(format!(
+            "unknown model type: {}",
+            unknown
+        )));
+    }
+    model.model_types = model_types.join(",");
+
+    let input_types = normalize_list(&model.input_types);
+    if let Some(unknown) = input_types
+        .iter()
+        .find(|one| !INPUT_TYPES.contains(&one.as_str()))
+    {
+        return Err(AppError::InvalidParameter(format!(
+            "unknown input type: {}",
+            unknown
+        )));
+    }
+    model.input_types = if input_types.is_empty() {
+        "text".to_string()
+    } else {
+        input_types.join(",")
+    };
+
+    if model.context_window < 0 || model.max_input_tokens < 0 || model.max_output_tokens < 0 {
+        return Err(AppError::InvalidParameter(
+            "token limits can not be negative".to_string(),
+        ));
+    }
+    if model.context_window > 0
+        && (model.max_input_tokens > model.context_window
+            || model.max_output_tokens > model.context_window)
+    {
+        return Err(AppError::InvalidParameter(
+            "token limits exceed the context window".to_string(),
+        ));
+    }
+    if model.properties.trim().is_empty() {
+        model.properties = "{}".to_string();
+    }
+    serde_json::from_str::<serde_json::Value>(&model.properties)?;
+    Ok(())
+}
+
+/// Split a comma separated list, lowercase and deduplicated in the original order
+fn normalize_list(value: &str) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for item in value.split(',').map(|s| s.trim().to_lowercase()) {
+        if !item.is_empty() && !items.contains(&item) {
+            items.push(item);
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_platform() {
+        let mut platform = ModelPlatform {
+            name: " lm-studio ".to_string(),
+            base_url: "http://127.0.0.1:1234/v1/".to_string(),
+            ..Default::default()
+        };
+        normalize_custom_platform(&mut platform).unwrap();
+        assert_eq!(platform.name, "lm-studio");
+        assert_eq!(platform.title, "lm-studio");
+        assert_eq!(platform.base_url, "http://127.0.0.1:1234/v1");
+        assert!(platform.is_openai_api_compatible);
+        // a local server is used without an API key
+        assert!(platform.api_key.is_empty());
+        assert!(platform.is_available());
+        platform.is_enable = false;
+        assert!(!platform.is_available());
+        platform.is_enable = true;
+        // the built-in remote platforms still require it
+        let openai = ModelPlatform {
+            name: "openai".to_string(),
+            ..Default::default()
+        };
+        assert!(!openai.is_available());
+
+        platform.name = "openai".to_string();
+        assert!(normalize_custom_platform(&mut platform).is_err());
+        platform.name = "My Platform".to_string();
+        assert!(normalize_custom_platform(&mut platform).is_err());
+        platform.name = "vllm".to_string();
+        platform.base_url = "127.0.0.1:8000".to_string();
+        assert!(normalize_custom_platform(&mut platform).is_err());
+    }
+
+    #[test]
+    fn model_types() {
+        let mut model = AiModel {
+            name: "qwen2.5-vl".to_string(),
+            model_types: "Text, vision,text".to_string(),
+            input_types: "text,image".to_string(),
+            context_window: 32768,
+            max_output_tokens: 8192,
+            properties: "".to_string(),
+            ..Default::default()
+        };
+        normalize_model(&mut model).unwrap();
+        assert_eq!(model.model_types, "text,vision");
+        assert_eq!(model.properties, "{}");
+
+        model.model_types = "text,speech".to_string();
+        assert!(normalize_model(&mut model).is_err());
+        model.model_types = "text".to_string();
+        model.max_output_tokens = 65536;
+        assert!(normalize_model(&mut model).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use crate::enums::Locale;
+use crate::errors::AppError;
+use crate::global::{
+    ACTIVE_LOCALE, HUGGINFACE_MIRROR, HUGGINFACE_WEBSITE, LOCAL_MODEL_MANIFEST_NAME,
+};
+use crate::structs::local_model::{LocalModel, LocalModelFile};
+use crate::utils::app_util::get_model_path;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+const BUILTIN_MANIFEST: &str = include_str!("../../assets/local_models.json");
+
+/// The built-in models followed by the ones of the custom manifest in the model directory,
+/// a custom model with the id of a built-in one replaces it
+pub fn load_manifest() -> Result<Vec<LocalModel>, AppError> {
+    let mut models = parse_manifest(BUILTIN_MANIFEST)?;
+    let custom_path = Path::new(&get_model_path()).join(LOCAL_MODEL_MANIFEST_NAME);
+    if custom_path.exists() {
+        for model in parse_manifest(&fs::read_to_string(&custom_path)?)? {
+            models.retain(|builtin| builtin.id != model.id);
+            models.push(model);
+        }
+    }
+    Ok(models)
+}
+
+/// The models of the manifest with their install state
+pub fn list_models() -> Result<Vec<LocalModel>, AppError> {
+    Ok(load_manifest()?
+        .into_iter()
+        .map(|mut model| {
+            model.is_installed = is_installed(&model);
+            model
+        })
+        .collect())
+}
+
+pub fn get_model(id: &str) -> Result<LocalModel, AppError> {
+    load_manifest()?
+        .into_iter()
+        .find(|model| model.id == id)
+        .ok_or_else(|| AppError::LocalModelNotFound(id.to_string()))
+}
+
+/// Where a file of a model is installed
+pub fn file_path(file: &LocalModelFile) -> PathBuf {
+    Path::new(&get_model_path()).join(&file.path)
+}
+
+pub fn is_installed(model: &LocalModel) -> bool {
+    model.files.iter().all(|file| {
+        fs::metadata(file_path(file))
+            .map(|metadata| {
+                // An interrupted download may leave an empty file behind
+                metadata.len() > 0 && (file.size == 0 || metadata.len() == file.size)
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// Delete the files of a model, and the directories left empty
+pub fn remove_model(model: &LocalModel) -> Result<(), AppError> {
+    let model_dir = PathBuf::from(get_model_path());
+    for file in &model.files {
+        let path = file_path(file);
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        let mut dir = path.parent();
+        while let Some(parent) = dir.filter(|parent| *parent != model_dir) {
+            // Fails if not empty, which ends the clean up
+            if fs::remove_dir(parent).is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+    }
+    Ok(())
+}
+
+/// HuggingFace mirror is preferred if asked, or by default when the zh-CN locale is active
+pub async fn prefer_mirror(mirror: Option<bool>) -> bool {
+    match mirror {
+        Some(mirror) => mirror,
+        None => *ACTIVE_LOCALE.read().await == Locale::ZhCn.text(),
+    }
+}
+
+/// The url of a file on HuggingFace or its mirror, other hosts are kept as is
+pub fn source_url(url: &str, use_mirror: bool) -> String {
+    if use_mirror {
+        url.replacen(HUGGINFACE_WEBSITE, HUGGINFACE_MIRROR, 1)
+    } else {
+        url.replacen(HUGGINFACE_MIRROR, HUGGINFACE_WEBSITE, 1)
+    }
+}
+
+/// Check the size and SHA-256 checksum(if set in the manifest) of a downloaded file
+pub fn verify_file(path: &Path, file: &LocalModelFile) -> Result<(), AppError> {
+    let size = fs::metadata(path)?.len();
+    if file.size > 0 && size != file.size {
+        return Err(AppError::DownloadError(format!(
+            "Size mismatch of {}: expected {}, got {}",
+            file.path, file.size, size
+        )));
+    }
+    if file.sha256.is_empty() {
+        return Ok(());
+    }
+    let checksum = sha256_file(path)?;
+    if !checksum.eq_ignore_ascii_case(&file.sha256) {
+        return Err(AppError::DownloadError(format!(
+            "Checksum mismatch of {}: expected {}, got {}",
+            file.path, file.sha256, checksum
+        )));
+    }
+    Ok(())
+}
+
+fn sha256_file(path: &Path) -> Result<String, AppError> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        match file.read(&mut buffer)? {
+            0 => break,
+            n => hasher.update(&buffer[..n]),
+        }
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn parse_manifest(content: &str) -> Result<Vec<LocalModel>, AppError> {
+    let models: Vec<LocalModel> = serde_json::from_str(content)
+        .map_err(|e| AppError::InvalidParameter(format!("Invalid model manifest: {}", e)))?;
+    for model in &models {
+        if model.id.trim().is_empty() || model.files.is_empty() {
+            return Err(AppError::InvalidParameter(format!(
+                "Model without id or files in manifest: {}",
+                model.name
+            )));
+        }
+        // A manifest must not write outside of the model directory
+        if let Some(file) = model
+            .files
+            .iter()
+            .find(|file| !is_relative_path(&file.path))
+        {
+            return Err(AppError::InvalidParameter(format!(
+                "Invalid file path of model {}: {}",
+                model.id, file.path
+            )));
+        }
+    }
+    Ok(models)
+}
+
+fn is_relative_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_manifest() {
+        let models = parse_manifest(BUILTIN_MANIFEST).unwrap();
+        assert!(
+            models
+                .iter()
+                .any(|model| model.id == crate::global::MULTI_LANG_MODEL_ID)
+        );
+
+        let invalid = r#"[{"id":"evil","name":"evil","kind":"ocr","files":[{"path":"../../evil.onnx","url":"https://example.com/evil.onnx"}]}]"#;
+        assert!(parse_manifest(invalid).is_err());
+        assert!(!is_relative_path("/tmp/model.onnx"));
+        assert!(is_relative_path("whisper-tiny/tokenizer.json"));
+
+        let url = "https://huggingface.co/Xenova/whisper-tiny/resolve/main/tokenizer.json";
+        assert_eq!(
+            source_url(url, true),
+            "https://hf-mirror.com/Xenova/whisper-tiny/resolve/main/tokenizer.json"
+        );
+        assert_eq!(source_url(&source_url(url, true), false), url);
+    }
+
+    #[test]
+    fn builtin_files_are_pinned() {
+        // Still to be pinned, the embedding service needs it so it can not be dropped
+        let unpinned = [crate::global::MULTI_LANG_MODEL_ID];
+        for model in parse_manifest(BUILTIN_MANIFEST).unwrap() {
+            if unpinned.contains(&model.id.as_str()) {
+                continue;
+            }
+            for file in &model.files {
+                // A checksum only holds for the files of a fixed revision
+                assert!(
+                    !file.url.contains("/resolve/main/"),
+                    "{} is not pinned to a revision",
+                    file.path
+                );
+                assert!(file.size > 0, "{} has no size", file.path);
+                assert!(
+                    file.sha256.len() == 64 && file.sha256.chars().all(|c| c.is_ascii_hexdigit()),
+                    "{} has no sha256",
+                    file.path
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn empty_file_is_not_installed() {
+        let path = std::env::temp_dir().join("mango-desk-installed-model.bin");
+        fs::write(&path, b"").unwrap();
+        let mut model = parse_manifest(BUILTIN_MANIFEST).unwrap().remove(0);
+        model.files = vec![LocalModelFile {
+            path: path.to_string_lossy().to_string(),
+            url: String::new(),
+            size: 0,
+            sha256: String::new(),
+        }];
+        assert!(!is_installed(&model));
+        fs::write(&path, b"hello").unwrap();
+        assert!(is_installed(&model));
+        model.files[0].size = 6;
+        assert!(!is_installed(&model));
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn verify_checksum() {
+        let path = std::env::temp_dir().join("mango-desk-verify-checksum.bin");
+        fs::write(&path, b"hello").unwrap();
+        let mut file = LocalModelFile {
+            path: "hello.bin".to_string(),
+            url: String::new(),
+            size: 5,
+            sha256: "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824".to_string(),
+        };
+        assert!(verify_file(&path, &file).is_ok());
+        file.sha256 = file.sha256.replace('2', "3");
+        assert!(verify_file(&path, &file).is_err());
+        file.sha256.clear();
+        file.size = 6;
+        assert!(verify_file(&path, &file).is_err());
+        fs::remove_file(&path).ok();
+    }
+}
@@ -1,3 +1,4 @@
+use crate::global::{ARCHIVE_MEMBER_SEPARATOR, MAILBOX_MEMBER_SEPARATOR, VIRTUAL_CONTAINER_EXTS};
 use anyhow::{Result, anyhow};
 use log::error;
 use std::fs;
@@ -33,6 +34,53 @@ pub fn append_seperator(path: &mut String) {
         path.push(std::path::MAIN_SEPARATOR);
     }
 }
+
+/// Path of a member inside a container file, e.g. `/mail/archive.mbox#<message-id>`
+/// or `/docs/bundle.zip!/specs/api.pdf`
+pub fn to_virtual_path(container_path: &str, member_id: &str) -> String {
+    format!(
+        "{}{}{}",
+        container_path,
+        virtual_path_separator(container_path),
+        member_id
+    )
+}
+
+/// The separator between a container path and its member ids, by the extension of the container
+pub fn virtual_path_separator(container_path: &str) -> &'static str {
+    if container_ext(container_path) == "mbox" {
+        MAILBOX_MEMBER_SEPARATOR
+    } else {
+        ARCHIVE_MEMBER_SEPARATOR
+    }
+}
+
+/// Split a path into the container path and the member id, the member id is None for a normal path
+pub fn split_virtual_path(path: &str) -> (&str, Option<&str>) {
+    let mut candidates: Vec<(usize, &str)> = path
+        .match_indices(MAILBOX_MEMBER_SEPARATOR)
+        .chain(path.match_indices(ARCHIVE_MEMBER_SEPARATOR))
+        .collect();
+    candidates.sort();
+    for (index, separator) in candidates {
+        let container_path = &path[..index];
+        if VIRTUAL_CONTAINER_EXTS.contains(&container_ext(container_path).as_str())
+            && virtual_path_separator(container_path) == separator
+        {
+            return (container_path, Some(&path[index + separator.len()..]));
+        }
+    }
+    (path, None)
+}
+
+fn container_ext(container_path: &str) -> String {
+    Path::new(container_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
 mod test {
     use super::*;
     use crate::utils::app_util;
@@ -63,4 +111,33 @@ mod test {
         let normalized = to_absolute_path("../target/debug").unwrap();
         assert!(normalized.is_absolute());
     }
+
+    #[test]
+    fn test_split_virtual_path() {
+        let path = to_virtual_path("/mail/C#/archive.mbox", "abc#1@example.com");
+        assert_eq!(
+            split_virtual_path(&path),
+            ("/mail/C#/archive.mbox", Some("abc#1@example.com"))
+        );
+        assert_eq!(split_virtual_path("/notes/C#.txt"), ("/notes/C#.txt", None));
+    }
+
+    #[test]
+    fn test_split_archive_virtual_path() {
+        let path = to_virtual_path("/docs/a!/bundle.zip", "inner.tar.gz!/C#/spec.pdf");
+        assert_eq!(path, "/docs/a!/bundle.zip!/inner.tar.gz!/C#/spec.pdf");
+        assert_eq!(
+            split_virtual_path(&path),
+            ("/docs/a!/bundle.zip", Some("inner.tar.gz!/C#/spec.pdf"))
+        );
+        // A mailbox inside an archive is a member of the archive
+        assert_eq!(
+            split_virtual_path("/docs/mail.zip!/old.mbox#abc@example.com"),
+            ("/docs/mail.zip", Some("old.mbox#abc@example.com"))
+        );
+        assert_eq!(
+            split_virtual_path("/notes/a!/b.txt"),
+            ("/notes/a!/b.txt", None)
+        );
+    }
 }
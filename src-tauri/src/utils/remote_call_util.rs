@@ -0,0 +1,290 @@
+use crate::entities::ModelPlatform;
+use crate::errors::AppError;
+use crate::global::{REMOTE_CALL_BASE_DELAY_MS, REMOTE_CALL_MAX_DELAY_SECS, REMOTE_CALL_RETRIES};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::sync::{Mutex as AsyncMutex, Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// One limiter per platform, shared by all the indexers and services calling it
+static LIMITERS: LazyLock<Mutex<HashMap<String, Arc<PlatformLimiter>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: REMOTE_CALL_RETRIES,
+            base_delay: Duration::from_millis(REMOTE_CALL_BASE_DELAY_MS),
+            max_delay: Duration::from_secs(REMOTE_CALL_MAX_DELAY_SECS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+}
+
+/// Bounds the requests in flight and the request rate of a platform
+struct PlatformLimiter {
+    concurrency: usize,
+    requests_per_minute: u32,
+    semaphore: Semaphore,
+    // The earliest time the next request may be sent
+    next_slot: AsyncMutex<Instant>,
+}
+
+impl PlatformLimiter {
+    fn new(concurrency: usize, requests_per_minute: u32) -> Self {
+        Self {
+            concurrency,
+            requests_per_minute,
+            semaphore: Semaphore::new(concurrency),
+            next_slot: AsyncMutex::new(Instant::now()),
+        }
+    }
+
+    async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = self.semaphore.acquire().await.ok();
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval();
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+        permit
+    }
+
+    /// Hold back every caller of the platform, e.g. the platform asked to retry after a while
+    async fn pause_until(&self, until: Instant) {
+        let mut next_slot = self.next_slot.lock().await;
+        *next_slot = (*next_slot).max(until);
+    }
+
+    fn interval(&self) -> Duration {
+        if self.requests_per_minute == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(60) / self.requests_per_minute
+        }
+    }
+}
+
+fn limiter(platform: &ModelPlatform) -> Arc<PlatformLimiter> {
+    let concurrency = platform.max_concurrency.max(1) as usize;
+    let requests_per_minute = platform.requests_per_minute.max(0) as u32;
+    let mut limiters = LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+    match limiters.get(&platform.name) {
+        Some(limiter)
+            if limiter.concurrency == concurrency
+                && limiter.requests_per_minute == requests_per_minute =>
+        {
+            limiter.clone()
+        }
+        // New platform or the limits are changed
+        _ => {
+            let limiter = Arc::new(PlatformLimiter::new(concurrency, requests_per_minute));
+            limiters.insert(platform.name.clone(), limiter.clone());
+            limiter
+        }
+    }
+}
+
+/// Send a request to a model platform within its concurrency and rate limits.
+///
+/// 429 and 5xx responses, timeouts and connection errors are retried with exponential backoff,
+/// `build` is called again for each attempt as a request(e.g. a multipart upload) can not be reused.
+///
+/// # Returns
+/// * `Result<Response, AppError>` - The successful response, or the last error when the retries run out
+pub async fn send<F>(platform: &ModelPlatform, build: F) -> Result<Response, AppError>
+where
+    F: Fn() -> Result<RequestBuilder, AppError>,
+{
+    send_with_policy(platform, &RetryPolicy::default(), build).await
+}
+
+pub async fn send_with_policy<F>(
+    platform: &ModelPlatform,
+    policy: &RetryPolicy,
+    build: F,
+) -> Result<Response, AppError>
+where
+    F: Fn() -> Result<RequestBuilder, AppError>,
+{
+    let limiter = limiter(platform);
+    let mut attempt = 0;
+    loop {
+        let permit = limiter.acquire().await;
+        let result = build()?.send().await;
+        drop(permit);
+        let delay = match result {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) if is_retryable(response.status()) && attempt < policy.retries => {
+                match retry_after(response.headers()) {
+                    Some(retry_after) => {
+                        let delay = retry_after.min(policy.max_delay);
+                        limiter.pause_until(Instant::now() + delay).await;
+                        delay
+                    }
+                    None => policy.backoff(attempt),
+                }
+            }
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(AppError::RemoteCallError(format!(
+                    "{} {}",
+                    status,
+                    body.chars().take(500).collect::<String>()
+                )));
+            }
+            Err(error)
+                if (error.is_timeout() || error.is_connect()) && attempt < policy.retries =>
+            {
+                policy.backoff(attempt)
+            }
+            Err(error) => return Err(error.into()),
+        };
+        attempt += 1;
+        println!(
+            "Remote call to {} failed, retry {}/{} in {:?}",
+            platform.name, attempt, policy.retries, delay
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// `Retry-After` in seconds or as an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_server_util::{self, MockServer};
+    use std::sync::atomic::Ordering;
+
+    /// Answer the requests with the scripted status lines and headers in order(the last one repeats)
+    async fn mock_server(responses: Vec<&'static str>, delay: Duration) -> MockServer {
+        mock_server_util::start(delay, move |index, _| {
+            let head = responses[index.min(responses.len() - 1)];
+            (head.to_string(), "ok".to_string())
+        })
+        .await
+    }
+
+    fn platform(name: &str, max_concurrency: i32) -> ModelPlatform {
+        ModelPlatform {
+            name: name.to_string(),
+            max_concurrency,
+            ..Default::default()
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            retries: 3,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_rate_limited_and_server_errors() {
+        let server = mock_server(
+            vec![
+                "429 Too Many Requests\r\nRetry-After: 1",
+                "503 Service Unavailable",
+                "200 OK",
+            ],
+            Duration::ZERO,
+        )
+        .await;
+        let client = reqwest::Client::new();
+        let start = std::time::Instant::now();
+        let response = send_with_policy(&platform("retry-test", 1), &policy(), || {
+            Ok(client.get(&server.url))
+        })
+        .await
+        .unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+        assert_eq!(server.requests.load(Ordering::SeqCst), 3);
+        // Waited as long as the Retry-After header asked
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        // Client errors are not retried
+        let server = mock_server(vec!["401 Unauthorized"], Duration::ZERO).await;
+        let result = send_with_policy(&platform("retry-test", 1), &policy(), || {
+            Ok(client.get(&server.url))
+        })
+        .await;
+        assert!(matches!(result, Err(AppError::RemoteCallError(_))));
+        assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+
+        // Gives up after the retries
+        let server = mock_server(vec!["500 Internal Server Error"], Duration::ZERO).await;
+        let result = send_with_policy(&platform("retry-test", 1), &policy(), || {
+            Ok(client.get(&server.url))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(server.requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn bounded_concurrency() {
+        let server = mock_server(vec!["200 OK"], Duration::from_millis(100)).await;
+        let client = reqwest::Client::new();
+        let (platform, policy) = (platform("concurrency-test", 2), policy());
+        let calls =
+            (0..6).map(|_| send_with_policy(&platform, &policy, || Ok(client.get(&server.url))));
+        let results = futures::future::join_all(calls).await;
+        assert!(results.iter().all(|result| result.is_ok()));
+        assert_eq!(server.requests.load(Ordering::SeqCst), 6);
+        assert_eq!(server.max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+        headers.insert(RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+}
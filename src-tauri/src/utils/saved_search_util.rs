@@ -0,0 +1,198 @@
+use crate::entities::SavedSearch;
+use crate::enums::{SearchMode, SmartFolderEvent};
+use crate::errors::AppError;
+use crate::global::{
+    SAVED_SEARCH_NAME_MAX_CHARS, SMART_FOLDER_CHANNEL, SMART_FOLDER_REFRESH_DELAY_SECS,
+    SMART_FOLDER_REFRESH_GENERATION,
+};
+use crate::repositories::saved_search_repo;
+use crate::searcher;
+use crate::structs::search_filter::SearchFilter;
+use crate::structs::search_result::SearchResult;
+use crate::utils::{frontend_util, search_history_util};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::ipc::Channel;
+
+/// Insert the saved search if its id is 0, update it otherwise, a smart folder is refreshed at once
+pub async fn save(saved_search: SavedSearch) -> Result<SavedSearch, AppError> {
+    let saved_search = check(saved_search)?;
+    let id = if saved_search.id == 0 {
+        saved_search_repo::insert(&saved_search).map_err(|_| already_exists(&saved_search.name))?
+    } else {
+        get(saved_search.id)?;
+        saved_search_repo::update(&saved_search).map_err(|_| already_exists(&saved_search.name))?;
+        saved_search.id
+    };
+    let saved_search = get(id)?;
+    if saved_search.is_smart_folder {
+        refresh(&saved_search).await;
+        return get(id);
+    }
+    Ok(saved_search)
+}
+
+pub fn delete(id: i64) -> Result<(), AppError> {
+    saved_search_repo::delete_by_id(id)?;
+    Ok(())
+}
+
+/// Run a saved search again, it is recorded in the search history like a typed one
+pub async fn run_by_id(id: i64) -> Result<Vec<SearchResult>, AppError> {
+    let saved_search = get(id)?;
+    let results = run(&saved_search).await;
+    let result_count = searcher::count(&full_query(&saved_search), saved_search.mode).await;
+    saved_search_repo::update_result_count(id, result_count as i64)?;
+    search_history_util::record(&full_query(&saved_search), saved_search.mode, results.len()).await;
+    Ok(results)
+}
+
+/// The channel the refreshed result counts of the smart folders are sent to, replacing the previous one
+pub fn subscribe(on_event: Channel<SmartFolderEvent>) {
+    match SMART_FOLDER_CHANNEL.write() {
+        Ok(mut guard) => *guard = Some(on_event),
+        Err(e) => println!("Failed to subscribe to smart folders: {}", e),
+    }
+}
+
+/// Refresh the smart folders once no other refresh has been requested for the delay,
+/// the watcher requests one for each indexed or removed file
+pub fn refresh_smart_folders_later() {
+    let generation = SMART_FOLDER_REFRESH_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(SMART_FOLDER_REFRESH_DELAY_SECS)).await;
+        if SMART_FOLDER_REFRESH_GENERATION.load(Ordering::SeqCst) == generation {
+            refresh_smart_folders().await;
+        }
+    });
+}
+
+pub async fn refresh_smart_folders() {
+    let smart_folders = saved_search_repo::list_smart_folders().unwrap_or_else(|e| {
+        println!("Failed to load smart folders: {}", e);
+        Vec::new()
+    });
+    for smart_folder in smart_folders {
+        refresh(&smart_folder).await;
+    }
+}
+
+/// The filters followed by the query, as it would be typed in the search box
+pub fn full_query(saved_search: &SavedSearch) -> String {
+    format!("{} {}", saved_search.filters, saved_search.query)
+        .trim()
+        .to_string()
+}
+
+async fn run(saved_search: &SavedSearch) -> Vec<SearchResult> {
+    let query = full_query(saved_search);
+    match saved_search.mode {
+        SearchMode::Intent => searcher::search_with_intent(&query).await,
+        SearchMode::Path => searcher::path_search(&query).await,
+        SearchMode::Semantic => searcher::semantic_search(&query).await,
+    }
+}
+
+async fn refresh(saved_search: &SavedSearch) {
+    // Not the number of results of a run, which is capped for display
+    let result_count = searcher::count(&full_query(saved_search), saved_search.mode).await as i64;
+    if let Err(e) = saved_search_repo::update_result_count(saved_search.id, result_count) {
+        println!(
+            "Failed to refresh smart folder {}: {}",
+            saved_search.name, e
+        );
+        return;
+    }
+    let guard = match SMART_FOLDER_CHANNEL.read() {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+    if let Some(channel) = guard.as_ref() {
+        frontend_util::send_to_frontend(
+            channel,
+            SmartFolderEvent::Refresh {
+                saved_search_id: saved_search.id,
+                result_count,
+            },
+        );
+    }
+}
+
+fn get(id: i64) -> Result<SavedSearch, AppError> {
+    saved_search_repo::get_by_id(id)?
+        .ok_or_else(|| AppError::InvalidParameter(format!("Saved search not found: {}", id)))
+}
+
+fn already_exists(name: &str) -> AppError {
+    AppError::InvalidParameter(format!("Saved search already exists: {}", name))
+}
+
+/// The name is required, the filters are inline filters only and the search is not empty.
+/// A search of filters only, e.g. `tag:trip`, lists the files matched by them
+fn check(mut saved_search: SavedSearch) -> Result<SavedSearch, AppError> {
+    saved_search.name = saved_search.name.trim().to_string();
+    if saved_search.name.is_empty()
+        || saved_search.name.chars().count() > SAVED_SEARCH_NAME_MAX_CHARS
+    {
+        return Err(AppError::InvalidParameter(format!(
+            "Invalid saved search name: {}",
+            saved_search.name
+        )));
+    }
+    let mut filter = SearchFilter::default();
+    if let Some(token) = saved_search
+        .filters
+        .split_whitespace()
+        .find(|token| !filter.apply_token(token))
+    {
+        return Err(AppError::InvalidParameter(format!(
+            "Invalid search filter: {}",
+            token
+        )));
+    }
+    saved_search.filters = saved_search
+        .filters
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    saved_search.query = saved_search.query.trim().to_string();
+    if saved_search.query.is_empty() && saved_search.filters.is_empty() {
+        return Err(AppError::InvalidParameter(
+            "Saved search has no query".to_string(),
+        ));
+    }
+    Ok(saved_search)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_search(name: &str, query: &str, filters: &str) -> SavedSearch {
+        SavedSearch {
+            name: name.to_string(),
+            query: query.to_string(),
+            filters: filters.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn check_saved_search() {
+        let checked = check(saved_search(
+            " Trips ",
+            " beach ",
+            " type:image   tag:trip ",
+        ))
+        .unwrap();
+        assert_eq!(checked.name, "Trips");
+        assert_eq!(full_query(&checked), "type:image tag:trip beach");
+
+        assert!(check(saved_search("", "beach", "")).is_err());
+        assert!(check(saved_search("Trips", "beach", "sunset")).is_err());
+        assert!(check(saved_search("Trips", " ", " ")).is_err());
+        let tagged = check(saved_search("Trips", "", "tag:trip")).unwrap();
+        assert_eq!(full_query(&tagged), "tag:trip");
+        assert!(check(saved_search("Trips", "tag:trip", "")).is_ok());
+    }
+}
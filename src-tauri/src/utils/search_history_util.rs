@@ -0,0 +1,68 @@
+use crate::enums::SearchMode;
+use crate::errors::AppError;
+use crate::global::{
+    CONFIG_NAME_SEARCH_HISTORY_SETTING, SEARCH_HISTORY_MERGE_SECS, SEARCH_HISTORY_SETTING,
+};
+use crate::repositories::{RepositoryError, config_repo, search_history_repo};
+use crate::structs::search_history_setting::SearchHistorySetting;
+
+/// Record a search unless the history is disabled, a failure is logged as the search itself succeeded
+pub async fn record(query: &str, mode: SearchMode, result_count: usize) {
+    let setting = SEARCH_HISTORY_SETTING.read().await.clone();
+    let query = query.trim();
+    if !setting.enabled || query.is_empty() {
+        return;
+    }
+    if let Err(e) = save(query, mode, result_count as i64, &setting) {
+        println!("Failed to record the search: {}", e);
+    }
+}
+
+/// Save the setting, the entries beyond the new retention are evicted at once
+pub async fn update_setting(setting: SearchHistorySetting) -> Result<usize, AppError> {
+    let json = serde_json::to_string(&setting)?;
+    config_repo::insert_or_ignore(CONFIG_NAME_SEARCH_HISTORY_SETTING, &json)?;
+    let result = config_repo::update_by_name(CONFIG_NAME_SEARCH_HISTORY_SETTING, &json)?;
+    search_history_repo::evict(setting.max_days, setting.max_entries)?;
+    *SEARCH_HISTORY_SETTING.write().await = setting;
+    Ok(result)
+}
+
+fn save(
+    query: &str,
+    mode: SearchMode,
+    result_count: i64,
+    setting: &SearchHistorySetting,
+) -> Result<(), RepositoryError> {
+    match search_history_repo::get_latest_within(SEARCH_HISTORY_MERGE_SECS)? {
+        Some(latest) if latest.mode == mode && is_refinement(&latest.query, query) => {
+            search_history_repo::update(latest.id, query, result_count)?;
+        }
+        _ => {
+            search_history_repo::insert(query, mode, result_count)?;
+        }
+    }
+    search_history_repo::evict(setting.max_days, setting.max_entries)?;
+    Ok(())
+}
+
+/// The query is typed on from the previous one, or back to a part of it
+fn is_refinement(previous: &str, query: &str) -> bool {
+    let previous = previous.to_lowercase();
+    let query = query.to_lowercase();
+    query.starts_with(&previous) || previous.starts_with(&query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refinement() {
+        assert!(is_refinement("inv", "Invoice 2024"));
+        assert!(is_refinement("invoice 2024", "invoice"));
+        assert!(is_refinement("invoice", "invoice"));
+        assert!(!is_refinement("invoice", "receipt"));
+        assert!(!is_refinement("invoice 2024", "invoice 2023"));
+    }
+}
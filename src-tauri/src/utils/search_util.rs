@@ -1,4 +1,19 @@
 use crate::enums::QueryIntent;
+use crate::structs::search_filter::SearchFilter;
+
+/// Split the inline filters(e.g. `type:code`, `lang:rust`, `tag:work`) from the query text,
+/// the paths of the tagged files and the photos in the `taken:`/`geo:` ranges are loaded
+pub fn parse_query(query: &str) -> (String, SearchFilter) {
+    let mut filter = SearchFilter::default();
+    let text = query
+        .split_whitespace()
+        .filter(|token| !filter.apply_token(token))
+        .collect::<Vec<&str>>()
+        .join(" ");
+    filter.load_tagged_paths();
+    filter.load_photo_ids();
+    (text, filter)
+}
 
 pub fn detect_intent(query: &str) -> QueryIntent {
     let q = query.trim();
@@ -30,3 +45,21 @@ pub fn detect_intent(query: &str) -> QueryIntent {
 
     QueryIntent::Hybrid
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::CodeLanguage;
+
+    #[test]
+    fn parse_query_filters() {
+        let (text, filter) = parse_query("where do we retry http requests type:code lang:py");
+        assert_eq!(text, "where do we retry http requests");
+        assert!(filter.code_only);
+        assert_eq!(filter.language, Some(CodeLanguage::Python));
+
+        let (text, filter) = parse_query("meeting notes at 10:30");
+        assert_eq!(text, "meeting notes at 10:30");
+        assert!(filter.is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::errors::AppError;
+use crate::utils::app_util::get_secret_key_path;
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rand::RngCore;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Stored secrets are `enc:` + base64(nonce + ciphertext + tag)
+const ENCRYPTED_PREFIX: &str = "enc:";
+/// Returned to the UI in place of a configured API key, sending it back keeps the stored key
+pub const API_KEY_MASK: &str = "********";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Loaded once, the lock also keeps two first-use callers from racing on the key file
+static SECRET_KEY: Mutex<Option<[u8; KEY_LEN]>> = Mutex::new(None);
+
+/// Encrypt a secret with the per-install key, an empty value is returned as is.
+/// A plaintext that happens to start with the prefix is encrypted too
+pub fn encrypt_secret(plaintext: &str) -> Result<String, AppError> {
+    if plaintext.is_empty() {
+        return Ok(plaintext.to_string());
+    }
+    encrypt_with_key(plaintext, &secret_key()?)
+}
+
+/// Decrypt a secret stored by `encrypt_secret`, values without the prefix are legacy plaintext.
+/// A malformed value with the prefix is an error, it is never used as the plaintext
+pub fn decrypt_secret(stored: &str) -> Result<String, AppError> {
+    if !is_encrypted(stored) {
+        return Ok(stored.to_string());
+    }
+    decrypt_with_key(stored, &secret_key()?)
+}
+
+fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// What the UI sees of a secret: nothing if not set, the mask otherwise
+pub fn mask_secret(value: &str) -> String {
+    if value.is_empty() {
+        String::new()
+    } else {
+        API_KEY_MASK.to_string()
+    }
+}
+
+fn encrypt_with_key(plaintext: &str, key: &[u8; KEY_LEN]) -> Result<String, AppError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rng().fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(key.into())
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| AppError::InternalError("Failed to encrypt secret".to_string()))?;
+    let mut data = nonce.to_vec();
+    data.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(data)))
+}
+
+fn decrypt_with_key(stored: &str, key: &[u8; KEY_LEN]) -> Result<String, AppError> {
+    let data = STANDARD
+        .decode(&stored[ENCRYPTED_PREFIX.len()..])
+        .map_err(|e| AppError::InternalError(format!("Invalid encrypted secret: {}", e)))?;
+    if data.len() <= NONCE_LEN {
+        return Err(AppError::InternalError(
+            "Invalid encrypted secret".to_string(),
+        ));
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    // Fails on a tampered value or a different key file, e.g. the db was copied without it
+    let plaintext = Aes256Gcm::new(key.into())
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AppError::InternalError("Failed to decrypt secret".to_string()))?;
+    String::from_utf8(plaintext).map_err(|e| AppError::InternalError(e.to_string()))
+}
+
+fn secret_key() -> Result<[u8; KEY_LEN], AppError> {
+    let mut cached = SECRET_KEY
+        .lock()
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    if let Some(key) = *cached {
+        return Ok(key);
+    }
+    let key = load_or_create_key(Path::new(&get_secret_key_path()))?;
+    *cached = Some(key);
+    Ok(key)
+}
+
+/// The AES-256 key is the random bytes of the key file, which is created on first use
+fn load_or_create_key(path: &Path) -> io::Result<[u8; KEY_LEN]> {
+    if path.as_os_str().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "secret key path is not initialized",
+        ));
+    }
+    match create_key_file(path) {
+        Ok(key) => return Ok(key),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e),
+    }
+    fs::read(path)?.try_into().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("secret key file must hold {} bytes", KEY_LEN),
+        )
+    })
+}
+
+/// `create_new` fails if another caller created the file first, so only one key is ever written.
+/// The file is readable by the owner only
+fn create_key_file(path: &Path) -> io::Result<[u8; KEY_LEN]> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    let mut key = [0u8; KEY_LEN];
+    rand::rng().fill_bytes(&mut key);
+    if let Err(e) = file.write_all(&key).and_then(|_| file.sync_all()) {
+        drop(file);
+        let _ = fs::remove_file(path);
+        return Err(e);
+    }
+    println!("Created secret key file: {}", path.display());
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secret.key");
+        let key = load_or_create_key(&path).unwrap();
+        assert_eq!(load_or_create_key(&path).unwrap(), key);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let api_key = "sk-proj-0123456789abcdefghijklmnopqrstuvwxyz";
+        let stored = encrypt_with_key(api_key, &key).unwrap();
+        assert!(is_encrypted(&stored));
+        assert!(!stored.contains(api_key));
+        assert_ne!(stored, encrypt_with_key(api_key, &key).unwrap());
+        assert_eq!(decrypt_with_key(&stored, &key).unwrap(), api_key);
+
+        let other_key = load_or_create_key(&dir.path().join("other.key")).unwrap();
+        assert_ne!(
+            decrypt_with_key(&stored, &other_key).ok().as_deref(),
+            Some(api_key)
+        );
+        assert!(decrypt_with_key("enc:not base64!", &key).is_err());
+        assert!(decrypt_with_key("enc:AAAA", &key).is_err());
+
+        let mut tampered = STANDARD.decode(&stored[ENCRYPTED_PREFIX.len()..]).unwrap();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        let tampered = format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(tampered));
+        assert!(decrypt_with_key(&tampered, &key).is_err());
+
+        let prefixed = "enc:sk-0123456789";
+        let stored = encrypt_with_key(prefixed, &key).unwrap();
+        assert_ne!(stored, prefixed);
+        assert_eq!(decrypt_with_key(&stored, &key).unwrap(), prefixed);
+
+        assert_eq!(mask_secret(&stored), API_KEY_MASK);
+        assert_eq!(mask_secret(""), "");
+    }
+}
@@ -0,0 +1,358 @@
+use crate::global::{SUBTITLE_EXTS, SUPPORTED_VIDEO_EXTS};
+use crate::structs::chunk_locator::ChunkLocator;
+use crate::structs::document_chunk::DocumentChunk;
+use encoding_rs::Encoding;
+use regex::Regex;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use text_splitter::{ChunkConfig, ChunkSizer, TextSplitter};
+
+// Formatting tags of srt/vtt(e.g. `<i>`, `<font color="red">`, `<c.yellow>`, `<00:01.000>`)
+static TAG_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+// Override blocks of ass/ssa, e.g. `{\an8}` or `{\i1}`
+static ASS_OVERRIDE_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{[^}]*\}").unwrap());
+
+/// A subtitle cue, start is the offset from the beginning of the video in seconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start: f64,
+    pub text: String,
+}
+
+/// Parse srt, vtt and ass/ssa subtitles by the file extension, formatting tags are stripped.
+/// ass/ssa is also detected by the `[Events]` section if the extension is unknown.
+pub fn parse_subtitle(content: &str, ext: &str) -> Vec<Cue> {
+    let content = content.trim_start_matches('\u{feff}');
+    match ext.to_lowercase().as_str() {
+        "ass" | "ssa" => parse_ass(content),
+        "" if content.contains("[Events]") => parse_ass(content),
+        // vtt differs from srt in the header, blocks and the millisecond separator only
+        _ => parse_srt(content),
+    }
+}
+
+fn parse_srt(content: &str) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    let content = content.replace("\r\n", "\n");
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+        let Some(timing) = lines.next() else {
+            // Index only, vtt header, NOTE/STYLE/REGION blocks
+            continue;
+        };
+        let Some(start) = timing.split("-->").next().and_then(parse_timestamp) else {
+            continue;
+        };
+        let text = lines
+            .map(|line| clean_text(&TAG_PATTERN.replace_all(line, "")))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !text.is_empty() {
+            cues.push(Cue { start, text });
+        }
+    }
+    cues
+}
+
+fn parse_ass(content: &str) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    // Field order of the Dialogue lines, the text is always the last field and may contain commas
+    let mut fields: Vec<String> = Vec::new();
+    let mut in_events = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_events = line.eq_ignore_ascii_case("[events]");
+            continue;
+        }
+        if !in_events {
+            continue;
+        }
+        if let Some(format) = line.strip_prefix("Format:") {
+            fields = format
+                .split(',')
+                .map(|field| field.trim().to_lowercase())
+                .collect();
+            continue;
+        }
+        let Some(dialogue) = line.strip_prefix("Dialogue:") else {
+            continue;
+        };
+        if fields.is_empty() {
+            continue;
+        }
+        let values: Vec<&str> = dialogue.splitn(fields.len(), ',').collect();
+        let value = |name: &str| {
+            fields
+                .iter()
+                .position(|field| field == name)
+                .and_then(|index| values.get(index))
+                .map(|value| value.trim())
+        };
+        let (Some(start), Some(text)) = (value("start").and_then(parse_timestamp), value("text"))
+        else {
+            continue;
+        };
+        let text = ASS_OVERRIDE_PATTERN
+            .replace_all(text, "")
+            .replace("\\N", "\n")
+            .replace("\\n", "\n")
+            .replace("\\h", " ");
+        let text = text
+            .lines()
+            .map(clean_text)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !text.is_empty() {
+            cues.push(Cue { start, text });
+        }
+    }
+    cues.sort_by(|a, b| a.start.total_cmp(&b.start));
+    cues
+}
+
+fn clean_text(line: &str) -> String {
+    line.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// `00:01:02,345`(srt), `01:02.345`(vtt) or `0:01:02.34`(ass) to seconds
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let value = value.trim().replace(',', ".");
+    let mut seconds = 0.0;
+    for part in value.split(':') {
+        seconds = seconds * 60.0 + part.trim().parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+/// `00:14:32`, hours are always shown as the media players do
+pub fn format_timestamp(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Text of the cues, repeated lines of rolling captions are merged
+pub fn cues_to_text(cues: &[Cue]) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for cue in cues {
+        for line in cue.text.lines() {
+            if lines.last() != Some(&line) {
+                lines.push(line);
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// One cue per line prefixed by its start time, e.g. `[00:14:32] Welcome back`,
+/// the format of the transcripts stored as the content of audio files
+pub fn cues_to_transcript(cues: &[Cue]) -> String {
+    cues.iter()
+        .map(|cue| {
+            format!(
+                "[{}] {}",
+                format_timestamp(cue.start),
+                cue.text.replace('\n', " ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Cues of a transcript made by `cues_to_transcript`, lines without a start time belong to the previous cue
+pub fn parse_transcript(transcript: &str) -> Vec<Cue> {
+    let mut cues: Vec<Cue> = Vec::new();
+    for line in transcript.lines() {
+        let timed = line
+            .strip_prefix('[')
+            .and_then(|line| line.split_once("] "))
+            .and_then(|(time, text)| Some((parse_timestamp(time)?, text)));
+        match (timed, cues.last_mut()) {
+            (Some((start, text)), _) => cues.push(Cue {
+                start,
+                text: text.trim().to_string(),
+            }),
+            (None, Some(cue)) if !line.trim().is_empty() => {
+                cue.text.push('\n');
+                cue.text.push_str(line.trim());
+            }
+            _ => (),
+        }
+    }
+    cues
+}
+
+/// Pack consecutive cues into chunks, repeated lines of rolling captions are merged
+pub fn split_cues<S: ChunkSizer>(cues: &[Cue], sizer: &S, capacity: usize) -> Vec<DocumentChunk> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut size = 0;
+    for (i, cue) in cues.iter().enumerate() {
+        let cue_size = sizer.size(&cue.text) + 1;
+        if i > start && size + cue_size > capacity {
+            chunks.extend(cue_chunks(&cues[start..i], sizer, capacity));
+            start = i;
+            size = 0;
+        }
+        size += cue_size;
+    }
+    if start < cues.len() {
+        chunks.extend(cue_chunks(&cues[start..], sizer, capacity));
+    }
+    chunks
+}
+
+/// A chunk of the cues, a single cue bigger than the capacity is split with the same start time
+fn cue_chunks<S: ChunkSizer>(cues: &[Cue], sizer: &S, capacity: usize) -> Vec<DocumentChunk> {
+    let locator = ChunkLocator {
+        start_time: cues.first().map(|cue| cue.start),
+        ..Default::default()
+    };
+    let text = cues_to_text(cues);
+    if cues.len() > 1 || sizer.size(&text) <= capacity {
+        return vec![DocumentChunk { text, locator }];
+    }
+    let splitter = TextSplitter::new(ChunkConfig::new(capacity).with_sizer(sizer));
+    splitter
+        .chunks(&text)
+        .map(|part| DocumentChunk {
+            text: part.to_string(),
+            locator: locator.clone(),
+        })
+        .collect()
+}
+
+/// Read a subtitle file, UTF-16 is detected by the BOM and UTF-8 is assumed otherwise
+pub fn read_subtitle(path: &Path) -> io::Result<String> {
+    Ok(decode_subtitle(&fs::read(path)?))
+}
+
+pub fn decode_subtitle(data: &[u8]) -> String {
+    match Encoding::for_bom(data) {
+        Some((encoding, bom_len)) => encoding
+            .decode_without_bom_handling(&data[bom_len..])
+            .0
+            .to_string(),
+        None => String::from_utf8_lossy(data).to_string(),
+    }
+}
+
+/// Subtitle files next to a video sharing its stem, e.g. `movie.srt` and `movie.en.vtt` for `movie.mp4`
+pub fn find_sidecar_subtitles(video_path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(stem)) = (
+        video_path.parent(),
+        video_path.file_stem().and_then(|stem| stem.to_str()),
+    ) else {
+        return Vec::new();
+    };
+    let stem = stem.to_lowercase();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut subtitles: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let ext = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("")
+                .to_lowercase();
+            let sub_stem = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("")
+                .to_lowercase();
+            SUBTITLE_EXTS.contains(&ext.as_str())
+                && (sub_stem == stem || sub_stem.starts_with(&format!("{}.", stem)))
+        })
+        .collect();
+    subtitles.sort();
+    subtitles
+}
+
+/// The video a subtitle file belongs to, e.g. `movie.mp4` for `movie.srt` or `movie.en.srt`
+pub fn find_sibling_video(subtitle_path: &Path) -> Option<PathBuf> {
+    let dir = subtitle_path.parent()?;
+    let mut stem = subtitle_path.file_stem()?.to_str()?;
+    loop {
+        for ext in SUPPORTED_VIDEO_EXTS {
+            for ext in [ext.to_string(), ext.to_uppercase()] {
+                let video_path = dir.join(format!("{}.{}", stem, ext));
+                if video_path.is_file() {
+                    return Some(video_path);
+                }
+            }
+        }
+        // Strip the language or track suffix and try again
+        stem = &stem[..stem.rfind('.')?];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_srt_vtt() {
+        let srt = "1\r\n00:00:01,500 --> 00:00:03,000\r\n<i>Hello</i> world\r\n\r\n2\r\n00:14:32,000 --> 00:14:35,000\r\nSecond &amp; last\r\n";
+        let cues = parse_subtitle(srt, "srt");
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].start, 1.5);
+        assert_eq!(cues[0].text, "Hello world");
+        assert_eq!(cues[1].start, 872.0);
+        assert_eq!(cues[1].text, "Second & last");
+
+        let vtt = "WEBVTT\n\nNOTE a comment\n\n01:02.250 --> 01:04.000 align:start\n<c.yellow>Rolling</c>\n\n01:04.000 --> 01:06.000\nRolling\ncaption\n";
+        let cues = parse_subtitle(vtt, "vtt");
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].start, 62.25);
+        assert_eq!(cues_to_text(&cues), "Rolling\ncaption");
+    }
+
+    #[test]
+    fn parse_ass_dialogue() {
+        let ass = "[Script Info]\nTitle: test\n\n[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:05.00,0:00:07.00,Default,,0,0,0,,{\\i1}Well,{\\i0} hello\\Nthere\nDialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,First\n";
+        let cues = parse_subtitle(ass, "ass");
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].text, "First");
+        assert_eq!(cues[1].start, 5.0);
+        assert_eq!(cues[1].text, "Well, hello\nthere");
+        assert_eq!(parse_subtitle(ass, "").len(), 2);
+        assert_eq!(format_timestamp(cues[1].start + 872.0), "00:14:37");
+    }
+
+    #[test]
+    fn transcript_round_trip() {
+        let cues = vec![
+            Cue {
+                start: 0.0,
+                text: "Hello".to_string(),
+            },
+            Cue {
+                start: 3725.4,
+                text: "Two\nlines".to_string(),
+            },
+        ];
+        let transcript = cues_to_transcript(&cues);
+        assert_eq!(transcript, "[00:00:00] Hello\n[01:02:05] Two lines");
+        let parsed = parse_transcript(&transcript);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].start, 3725.0);
+        assert_eq!(parsed[1].text, "Two lines");
+    }
+}
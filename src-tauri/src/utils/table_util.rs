@@ -0,0 +1,446 @@
+use crate::global::{TABLE_MAX_CELL_CHARS, TABLE_MAX_COLUMNS, TABLE_MAX_ROWS};
+use crate::structs::chunk_locator::ChunkLocator;
+use crate::structs::document_chunk::DocumentChunk;
+use crate::structs::table::{Table, TableRow};
+use text_splitter::ChunkSizer;
+
+/// Build a table from raw rows with their 1-based row numbers, the first non-empty row is the header.
+///
+/// Cells are trimmed and cut to `TABLE_MAX_CELL_CHARS`, only the first `TABLE_MAX_COLUMNS` columns
+/// and `TABLE_MAX_ROWS` rows are kept. Return None if all rows are empty.
+pub fn build_table<I>(sheet: Option<String>, rows: I) -> Option<Table>
+where
+    I: IntoIterator<Item = (usize, Vec<String>)>,
+{
+    let mut table = Table {
+        sheet,
+        ..Default::default()
+    };
+    let mut header_found = false;
+    for (number, row) in rows {
+        if row.len() > TABLE_MAX_COLUMNS {
+            table.truncated = true;
+        }
+        let cells: Vec<String> = row
+            .iter()
+            .take(TABLE_MAX_COLUMNS)
+            .map(|cell| normalize_cell(cell))
+            .collect();
+        if cells.iter().all(|cell| cell.is_empty()) {
+            continue;
+        }
+        if !header_found {
+            table.header = cells
+                .into_iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if cell.is_empty() {
+                        format!("Column {}", i + 1)
+                    } else {
+                        cell
+                    }
+                })
+                .collect();
+            header_found = true;
+            continue;
+        }
+        if table.rows.len() >= TABLE_MAX_ROWS {
+            table.truncated = true;
+            break;
+        }
+        table.rows.push(TableRow { number, cells });
+    }
+    if !header_found {
+        return None;
+    }
+    Some(table)
+}
+
+/// Render a row as `header: value` pairs, empty cells are skipped
+pub fn render_row(header: &[String], row: &TableRow) -> String {
+    row_pairs(header, row).join("; ")
+}
+
+/// Render tables as text, one row per line
+pub fn render_tables(tables: &[Table], max_load_chars: usize) -> String {
+    let mut txt = String::new();
+    for table in cap_tables(tables, max_load_chars) {
+        for line in header_lines(&table) {
+            txt.push_str(&line);
+            txt.push('\n');
+        }
+        for row in &table.rows {
+            txt.push_str(&row_line(&table.header, row));
+            txt.push('\n');
+        }
+        txt.push('\n');
+    }
+    txt.trim_end().to_string()
+}
+
+/// The tables with the rows rendered by `render_tables` within the max load chars(0 for no limit),
+/// the tables after the row reaching the limit are dropped
+pub fn cap_tables(tables: &[Table], max_load_chars: usize) -> Vec<Table> {
+    let mut capped = Vec::new();
+    let mut total_chars = 0;
+    for table in tables {
+        total_chars += header_lines(table)
+            .iter()
+            .map(|line| line.chars().count() + 1)
+            .sum::<usize>();
+        let mut kept = Table {
+            sheet: table.sheet.clone(),
+            header: table.header.clone(),
+            rows: Vec::new(),
+            truncated: table.truncated,
+        };
+        let mut is_full = false;
+        for row in &table.rows {
+            total_chars += row_line(&table.header, row).chars().count() + 1;
+            kept.rows.push(row.clone());
+            if max_load_chars > 0 && total_chars > max_load_chars {
+                kept.truncated = true;
+                is_full = true;
+                break;
+            }
+        }
+        capped.push(kept);
+        if is_full {
+            break;
+        }
+    }
+    capped
+}
+
+/// Split tables into chunks of rows, the header is repeated in every chunk.
+///
+/// A row that doesn't fit in one chunk is split into several parts by columns.
+/// The sheet name and row range are recorded in the chunk locator.
+pub fn split_tables<S: ChunkSizer>(
+    tables: &[Table],
+    sizer: &S,
+    capacity: usize,
+) -> Vec<DocumentChunk> {
+    let tables: Vec<TableLines> = tables
+        .iter()
+        .map(|table| TableLines {
+            sheet: table.sheet.clone(),
+            header: header_lines(table),
+            rows: table
+                .rows
+                .iter()
+                .map(|row| (row.number, row_pairs(&table.header, row)))
+                .collect(),
+        })
+        .collect();
+    split_table_lines(&tables, sizer, capacity)
+}
+
+/// Split the text rendered by `render_tables` like `split_tables`, the rows are read back by their `Row n:` label
+pub fn split_rendered_tables<S: ChunkSizer>(
+    text: &str,
+    sizer: &S,
+    capacity: usize,
+) -> Vec<DocumentChunk> {
+    let mut tables: Vec<TableLines> = Vec::new();
+    for line in text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+    {
+        // A header line after the rows of a table starts the next one
+        let row = parse_row_line(line);
+        if tables
+            .last()
+            .is_none_or(|table| row.is_none() && !table.rows.is_empty())
+        {
+            tables.push(TableLines::default());
+        }
+        let Some(table) = tables.last_mut() else {
+            continue;
+        };
+        match row {
+            Some((number, pairs)) => table
+                .rows
+                .push((number, pairs.split("; ").map(str::to_string).collect())),
+            None => {
+                if let Some(sheet) = line.strip_prefix("Sheet: ") {
+                    table.sheet = Some(sheet.to_string());
+                }
+                table.header.push(line.to_string());
+            }
+        }
+    }
+    split_table_lines(&tables, sizer, capacity)
+}
+
+/// The header lines and the `header: value` pairs of the rows of a table
+#[derive(Default)]
+struct TableLines {
+    sheet: Option<String>,
+    header: Vec<String>,
+    rows: Vec<(usize, Vec<String>)>,
+}
+
+fn split_table_lines<S: ChunkSizer>(
+    tables: &[TableLines],
+    sizer: &S,
+    capacity: usize,
+) -> Vec<DocumentChunk> {
+    let mut chunks = Vec::new();
+    for table in tables {
+        let mut prefix = table.header.clone();
+        // Drop the column list of very wide tables, every row has the column names anyway
+        if prefix
+            .last()
+            .is_some_and(|columns| sizer.size(columns) > capacity / 2)
+        {
+            prefix.pop();
+        }
+        let prefix = prefix.join("\n");
+        let prefix_size = sizer.size(&prefix);
+        let row_capacity = capacity.saturating_sub(prefix_size).max(1);
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut size = 0;
+        let mut rows: Option<(usize, usize)> = None;
+        for (number, pairs) in &table.rows {
+            for line in row_lines(*number, pairs, sizer, row_capacity) {
+                let line_size = sizer.size(&line) + 1;
+                if !lines.is_empty() && size + line_size > row_capacity {
+                    chunks.push(table_chunk(table, &prefix, &lines, rows));
+                    lines.clear();
+                    size = 0;
+                    rows = None;
+                }
+                size += line_size;
+                lines.push(line);
+                rows = Some(match rows {
+                    Some((start, _)) => (start, *number),
+                    None => (*number, *number),
+                });
+            }
+        }
+        if !lines.is_empty() {
+            chunks.push(table_chunk(table, &prefix, &lines, rows));
+        }
+    }
+    chunks
+}
+
+/// `Row 3: name: Alice; city: Paris` to the row number and the pairs
+fn parse_row_line(line: &str) -> Option<(usize, &str)> {
+    let (number, pairs) = line.strip_prefix("Row ")?.split_once(": ")?;
+    Some((number.parse().ok()?, pairs))
+}
+
+fn header_lines(table: &Table) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(sheet) = &table.sheet {
+        lines.push(format!("Sheet: {}", sheet));
+    }
+    lines.push(format!("Columns: {}", table.header.join(" | ")));
+    lines
+}
+
+fn row_line(header: &[String], row: &TableRow) -> String {
+    format!("Row {}: {}", row.number, render_row(header, row))
+}
+
+fn row_pairs(header: &[String], row: &TableRow) -> Vec<String> {
+    row.cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| !cell.is_empty())
+        .map(|(i, cell)| match header.get(i) {
+            Some(name) => format!("{}: {}", name, cell),
+            None => format!("Column {}: {}", i + 1, cell),
+        })
+        .collect()
+}
+
+/// Lines of a row, usually one, a wide row is split into several lines that fit the capacity
+fn row_lines<S: ChunkSizer>(
+    number: usize,
+    pairs: &[String],
+    sizer: &S,
+    capacity: usize,
+) -> Vec<String> {
+    let label = format!("Row {}:", number);
+    let line = format!("{} {}", label, pairs.join("; "));
+    if sizer.size(&line) < capacity {
+        return vec![line];
+    }
+    let mut lines = Vec::new();
+    let mut current = label.clone();
+    for pair in pairs {
+        let candidate = if current == label {
+            format!("{} {}", current, pair)
+        } else {
+            format!("{}; {}", current, pair)
+        };
+        if current != label && sizer.size(&candidate) >= capacity {
+            lines.push(current);
+            current = format!("{} {}", label, pair);
+        } else {
+            current = candidate;
+        }
+    }
+    lines.push(current);
+    lines
+}
+
+fn table_chunk(
+    table: &TableLines,
+    prefix: &str,
+    lines: &[String],
+    rows: Option<(usize, usize)>,
+) -> DocumentChunk {
+    DocumentChunk {
+        text: if prefix.is_empty() {
+            lines.join("\n")
+        } else {
+            format!("{}\n{}", prefix, lines.join("\n"))
+        },
+        locator: ChunkLocator {
+            sheet: table.sheet.clone(),
+            start_row: rows.map(|(start, _)| start),
+            end_row: rows.map(|(_, end)| end),
+            ..Default::default()
+        },
+    }
+}
+
+fn normalize_cell(cell: &str) -> String {
+    let cell = cell.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if cell.chars().count() <= TABLE_MAX_CELL_CHARS {
+        return cell;
+    }
+    let mut cut: String = cell.chars().take(TABLE_MAX_CELL_CHARS).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text_splitter::Characters;
+
+    fn rows(data: &[&[&str]]) -> Vec<(usize, Vec<String>)> {
+        data.iter()
+            .enumerate()
+            .map(|(i, row)| (i + 1, row.iter().map(|cell| cell.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn split_repeats_header() {
+        let table = build_table(
+            Some("Staff".to_string()),
+            rows(&[
+                &["", "", ""],
+                &["Name", "City", ""],
+                &["Alice", "Paris", "x"],
+                &["Bob", "", "y"],
+                &["Carol", "Berlin", "z"],
+            ]),
+        )
+        .unwrap();
+        assert_eq!(table.header, vec!["Name", "City", "Column 3"]);
+        assert_eq!(table.rows[0].number, 3);
+
+        let chunks = split_tables(&[table], &Characters, 120);
+        for chunk in &chunks {
+            println!("{:?}\n{}", chunk.locator, chunk.text);
+        }
+        assert_eq!(chunks.len(), 2);
+        for chunk in &chunks {
+            assert!(
+                chunk
+                    .text
+                    .starts_with("Sheet: Staff\nColumns: Name | City | Column 3\n")
+            );
+        }
+        assert!(chunks[0].text.contains("Row 4: Name: Bob; Column 3: y"));
+        assert_eq!(chunks[0].locator.start_row, Some(3));
+        assert_eq!(chunks[0].locator.end_row, Some(4));
+        assert_eq!(chunks[1].locator.start_row, Some(5));
+        assert_eq!(chunks[1].locator.sheet.as_deref(), Some("Staff"));
+    }
+
+    #[test]
+    fn split_wide_row() {
+        let header: Vec<String> = (1..=20).map(|i| format!("field{}", i)).collect();
+        let values: Vec<String> = (1..=20).map(|i| format!("value{}", i)).collect();
+        let table = build_table(None, vec![(1, header), (2, values)]).unwrap();
+        let chunks = split_tables(&[table], &Characters, 200);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.text.chars().count() <= 200);
+            assert_eq!(chunk.locator.start_row, Some(2));
+        }
+        assert!(chunks.last().unwrap().text.contains("field20: value20"));
+    }
+
+    #[test]
+    fn split_rendered_text() {
+        let staff = build_table(
+            Some("Staff".to_string()),
+            rows(&[
+                &["Name", "City"],
+                &["Alice; Bob", "Paris"],
+                &["Carol", "Berlin"],
+            ]),
+        )
+        .unwrap();
+        let teams =
+            build_table(Some("Teams".to_string()), rows(&[&["Team"], &["Search"]])).unwrap();
+        let tables = [staff, teams];
+        // Blank lines may be collapsed from the loaded text
+        let text = render_tables(&tables, 0).replace("\n\n", "\n");
+        for capacity in [40, 1000] {
+            let expected = split_tables(&tables, &Characters, capacity);
+            let chunks = split_rendered_tables(&text, &Characters, capacity);
+            assert_eq!(chunks.len(), expected.len());
+            for (chunk, expected) in chunks.iter().zip(expected.iter()) {
+                assert_eq!(chunk.text, expected.text);
+                assert_eq!(chunk.locator.sheet, expected.locator.sheet);
+                assert_eq!(chunk.locator.start_row, expected.locator.start_row);
+                assert_eq!(chunk.locator.end_row, expected.locator.end_row);
+            }
+        }
+    }
+
+    #[test]
+    fn cap_rows() {
+        let staff = build_table(
+            Some("Staff".to_string()),
+            rows(&[
+                &["Name", "City"],
+                &["Alice; Bob", "Paris"],
+                &["Carol", "Berlin"],
+            ]),
+        )
+        .unwrap();
+        let teams =
+            build_table(Some("Teams".to_string()), rows(&[&["Team"], &["Search"]])).unwrap();
+        let tables = [staff, teams];
+        let chunks = split_tables(&cap_tables(&tables, 0), &Characters, 1000);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            chunks[0].text,
+            "Sheet: Staff\nColumns: Name | City\nRow 2: Name: Alice; Bob; City: Paris\nRow 3: Name: Carol; City: Berlin"
+        );
+        assert_eq!(chunks[1].locator.sheet.as_deref(), Some("Teams"));
+
+        // Rows beyond the max load chars are neither loaded nor chunked
+        let capped = cap_tables(&tables, 60);
+        assert_eq!(capped.len(), 1);
+        assert_eq!(capped[0].rows.len(), 1);
+        assert!(capped[0].truncated);
+        assert_eq!(
+            render_tables(&tables, 60),
+            "Sheet: Staff\nColumns: Name | City\nRow 2: Name: Alice; Bob; City: Paris"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::entities::Tag;
+use crate::enums::FileIndexStatus;
+use crate::errors::AppError;
+use crate::global::{TAG_NAME_MAX_CHARS, WORKSPACE_DB_PATH};
+use crate::repositories::{file_info_repo, file_metadata_embedding_repo, tag_repo};
+use crate::traits::indexing_template::embed_metadata;
+use crate::utils::app_util::get_db_path;
+
+pub fn create(name: &str) -> Result<Tag, AppError> {
+    Ok(tag_repo::insert_or_get(&check_name(name)?)?)
+}
+
+pub fn rename(id: i64, name: &str) -> Result<Tag, AppError> {
+    let name = check_name(name)?;
+    let tag = get(id)?;
+    if tag.name == name {
+        return Ok(tag);
+    }
+    tag_repo::rename(id, &name)
+        .map_err(|_| AppError::InvalidParameter(format!("Tag already exists: {}", name)))?;
+    refresh_in_background(list_file_ids(id)?);
+    get(id)
+}
+
+pub fn delete(id: i64) -> Result<(), AppError> {
+    let file_ids = list_file_ids(id)?;
+    tag_repo::delete_by_id(id)?;
+    refresh_in_background(file_ids);
+    Ok(())
+}
+
+/// Tag the files(e.g. the results of a search) with the tags, which are created if missing
+pub fn tag_files(paths: &[String], names: &[String]) -> Result<usize, AppError> {
+    let file_ids = file_ids_by_paths(paths)?;
+    let mut affected = 0;
+    for name in names {
+        let tag = create(name)?;
+        affected += tag_repo::link(&file_ids, tag.id)?;
+    }
+    refresh_in_background(file_ids);
+    Ok(affected)
+}
+
+pub fn untag_files(paths: &[String], tag_id: i64) -> Result<usize, AppError> {
+    let file_ids = file_ids_by_paths(paths)?;
+    let affected = tag_repo::unlink(&file_ids, tag_id)?;
+    refresh_in_background(file_ids);
+    Ok(affected)
+}
+
+/// Embed the metadata of the files again, so the text of the embedding has their current tags
+pub async fn refresh_metadata_embeddings(file_ids: Vec<i64>) {
+    for file_id in file_ids {
+        let file_info = match file_info_repo::get_by_id(file_id) {
+            Ok(Some(file_info)) => file_info,
+            Ok(None) => continue,
+            Err(e) => {
+                println!("Failed to load file {}: {}", file_id, e);
+                continue;
+            }
+        };
+        // Not indexed yet, the tags are embedded when it is
+        if file_info.meta_index_status != FileIndexStatus::Indexed.value() {
+            continue;
+        }
+        // The previous embedding is kept if the new one fails
+        let embedding = match embed_metadata(file_id, &file_info.metadata).await {
+            Ok(embedding) => embedding,
+            Err(e) => {
+                println!("Failed to embed metadata of {}: {}", file_id, e);
+                continue;
+            }
+        };
+        if let Err(e) = file_metadata_embedding_repo::replace_by_file_id(file_id, &embedding) {
+            println!("Failed to replace metadata embedding of {}: {}", file_id, e);
+        }
+    }
+}
+
+/// The spawned task leaves the `WORKSPACE_DB_PATH` scope, the database of the caller is kept for it
+fn refresh_in_background(file_ids: Vec<i64>) {
+    if file_ids.is_empty() {
+        return;
+    }
+    let db_path = get_db_path();
+    tokio::spawn(WORKSPACE_DB_PATH.scope(db_path, refresh_metadata_embeddings(file_ids)));
+}
+
+fn get(id: i64) -> Result<Tag, AppError> {
+    tag_repo::get_by_id(id)?
+        .ok_or_else(|| AppError::InvalidParameter(format!("Tag not found: {}", id)))
+}
+
+fn list_file_ids(tag_id: i64) -> Result<Vec<i64>, AppError> {
+    Ok(tag_repo::list_file_ids(tag_id, 1, i64::MAX)?)
+}
+
+fn file_ids_by_paths(paths: &[String]) -> Result<Vec<i64>, AppError> {
+    let mut file_ids = Vec::new();
+    for path in paths {
+        match file_info_repo::get_by_path(path)? {
+            Some(file_info) => file_ids.push(file_info.id),
+            None => println!("File not indexed, not tagged: {}", path),
+        }
+    }
+    Ok(file_ids)
+}
+
+/// A name is written as `tag:name` in a query, so it has no whitespace
+fn check_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty()
+        || name.chars().count() > TAG_NAME_MAX_CHARS
+        || name.chars().any(char::is_whitespace)
+    {
+        return Err(AppError::InvalidParameter(format!(
+            "Invalid tag name: {}",
+            name
+        )));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_name() {
+        assert_eq!(check_name(" 2024-旅行 ").unwrap(), "2024-旅行");
+        assert!(check_name("").is_err());
+        assert!(check_name("two words").is_err());
+        assert!(check_name(&"x".repeat(TAG_NAME_MAX_CHARS + 1)).is_err());
+    }
+}
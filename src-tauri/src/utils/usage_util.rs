@@ -0,0 +1,124 @@
+use crate::entities::{AiModel, ModelPlatform, ModelUsage};
+use crate::enums::{BudgetPeriod, ModelOperation, ModelPlatformName};
+use crate::global::{INDEXER_SETTING, INDEXING_TASK_ID};
+use crate::repositories::model_usage_repo;
+use crate::structs::indexer_setting::UsageBudget;
+use crate::structs::model_pricing::ModelPricing;
+use async_openai::types::CompletionUsage;
+use chrono::{DateTime, Datelike, Local, TimeZone};
+use serde_json::Value;
+use std::sync::atomic::Ordering;
+
+/// Record the usage of a remote call, linked to the running indexing task(if any).
+///
+/// The cost is calculated by the pricing of the model, a failure is only logged
+/// as the call itself has succeeded.
+pub fn record(platform: &str, ai_model: &AiModel, operation: ModelOperation, usage: ModelUsage) {
+    let mut usage = ModelUsage {
+        task_id: INDEXING_TASK_ID.load(Ordering::SeqCst),
+        platform: platform.to_string(),
+        model: ai_model.name.clone(),
+        operation: operation.text().to_string(),
+        ..usage
+    };
+    usage.cost = ModelPricing::from_properties(&ai_model.properties).cost(&usage);
+    if let Err(e) = model_usage_repo::insert(&usage) {
+        println!("Failed to record model usage: {}", e);
+    }
+}
+
+/// Usage in an OpenAI compatible response, `prompt_tokens`/`completion_tokens` of chat completions,
+/// `input_tokens`/`output_tokens` or the `duration` in seconds of transcriptions
+pub fn usage_from_response(response: &Value) -> ModelUsage {
+    let usage = &response["usage"];
+    let tokens = |names: [&str; 2]| {
+        names
+            .iter()
+            .find_map(|name| usage[name].as_i64())
+            .unwrap_or(0)
+    };
+    ModelUsage {
+        prompt_tokens: tokens(["prompt_tokens", "input_tokens"]),
+        completion_tokens: tokens(["completion_tokens", "output_tokens"]),
+        audio_seconds: response["duration"]
+            .as_f64()
+            .or_else(|| usage["seconds"].as_f64())
+            .unwrap_or(0.0),
+        ..Default::default()
+    }
+}
+
+/// Token counts of a chat completion by `async_openai`
+pub fn usage_from_completion(usage: &CompletionUsage) -> ModelUsage {
+    ModelUsage {
+        prompt_tokens: usage.prompt_tokens as i64,
+        completion_tokens: usage.completion_tokens as i64,
+        ..Default::default()
+    }
+}
+
+/// Cost of the current budget period
+pub fn budget_used(budget: &UsageBudget) -> f64 {
+    let since = period_start(budget.period, Local::now());
+    model_usage_repo::sum_cost_since(&since).unwrap_or_else(|e| {
+        println!("Failed to sum model usage cost: {}", e);
+        0.0
+    })
+}
+
+/// Cloud analysis is paused once the budget(if set) of the current period is used up
+pub async fn is_budget_exceeded() -> bool {
+    let budget = INDEXER_SETTING.read().await.budget.clone();
+    budget.limit > 0.0 && budget_used(&budget) >= budget.limit
+}
+
+/// Analysis by the platform is paused by the budget, local platforms like Ollama cost nothing and keep running
+pub async fn is_paused(platform: &ModelPlatform) -> bool {
+    ModelPlatformName::from(platform.name.as_str()) != ModelPlatformName::Ollama
+        && is_budget_exceeded().await
+}
+
+/// Start of the period in the format of `create_time`, e.g. `2025-05-01 00:00:00` for a monthly budget
+pub fn period_start(period: BudgetPeriod, now: DateTime<Local>) -> String {
+    let date = match period {
+        BudgetPeriod::Daily => now.date_naive(),
+        BudgetPeriod::Monthly => now.date_naive().with_day(1).unwrap_or(now.date_naive()),
+    };
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .unwrap_or(now)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_usage() {
+        let chat = json!({"choices":[],"usage":{"prompt_tokens":812,"completion_tokens":96,"total_tokens":908}});
+        let usage = usage_from_response(&chat);
+        assert_eq!((usage.prompt_tokens, usage.completion_tokens), (812, 96));
+
+        let transcription = json!({"text":"hello","duration":42.5,"segments":[]});
+        assert_eq!(usage_from_response(&transcription).audio_seconds, 42.5);
+        let transcription = json!({"text":"hello","usage":{"type":"duration","seconds":12}});
+        assert_eq!(usage_from_response(&transcription).audio_seconds, 12.0);
+    }
+
+    #[test]
+    fn budget_period_start() {
+        let now = Local.with_ymd_and_hms(2025, 5, 17, 15, 30, 0).unwrap();
+        assert_eq!(
+            period_start(BudgetPeriod::Daily, now),
+            "2025-05-17 00:00:00"
+        );
+        assert_eq!(
+            period_start(BudgetPeriod::Monthly, now),
+            "2025-05-01 00:00:00"
+        );
+    }
+}
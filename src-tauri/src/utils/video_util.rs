@@ -0,0 +1,483 @@
+use crate::structs::file_metadata::VideoMetadata;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// Header elements are read into memory, larger ones are treated as corrupted
+const MAX_HEADER_SIZE: u64 = 64 * 1024 * 1024;
+
+// Matroska element ids
+const MKV_EBML: u32 = 0x1A45_DFA3;
+const MKV_SEGMENT: u32 = 0x1853_8067;
+const MKV_INFO: u32 = 0x1549_A966;
+const MKV_TIMESTAMP_SCALE: u32 = 0x2A_D7B1;
+const MKV_DURATION: u32 = 0x4489;
+const MKV_TITLE: u32 = 0x7BA9;
+const MKV_TRACKS: u32 = 0x1654_AE6B;
+const MKV_TRACK_ENTRY: u32 = 0xAE;
+const MKV_VIDEO: u32 = 0xE0;
+const MKV_PIXEL_WIDTH: u32 = 0xB0;
+const MKV_PIXEL_HEIGHT: u32 = 0xBA;
+const MKV_CLUSTER: u32 = 0x1F43_B675;
+
+/// Duration, resolution and title from the container headers of mp4/mov, mkv/webm and avi files,
+/// the video stream itself is not decoded
+pub fn load_video_metadata(path: &Path) -> io::Result<(VideoMetadata, String)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut magic = [0u8; 12];
+    let len = reader.read(&mut magic)?;
+    reader.seek(SeekFrom::Start(0))?;
+    let magic = &magic[..len];
+    if magic.get(4..8) == Some(b"ftyp") {
+        read_mp4(&mut reader)
+    } else if magic.starts_with(&MKV_EBML.to_be_bytes()) {
+        read_mkv(&mut reader)
+    } else if magic.starts_with(b"RIFF") && magic.get(8..12) == Some(b"AVI ") {
+        read_avi(&mut reader)
+    } else {
+        Err(invalid_data("Unsupported video container"))
+    }
+}
+
+/// ISO base media file(mp4/mov), the metadata is in the `moov` box
+fn read_mp4<R: Read + Seek>(reader: &mut R) -> io::Result<(VideoMetadata, String)> {
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut pos = 0;
+    while pos + 8 <= end {
+        let (box_type, header_len, size) = read_box_header(reader, end - pos)?;
+        if box_type == *b"moov" {
+            let moov = read_exact_vec(reader, size - header_len)?;
+            return Ok(parse_moov(&moov));
+        }
+        pos += size;
+        reader.seek(SeekFrom::Start(pos))?;
+    }
+    Err(invalid_data("moov box not found"))
+}
+
+/// Return the box type, header length and box size(header included)
+fn read_box_header<R: Read>(reader: &mut R, remaining: u64) -> io::Result<([u8; 4], u64, u64)> {
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header)?;
+    let box_type = [header[4], header[5], header[6], header[7]];
+    let (header_len, size) = match u32::from_be_bytes([header[0], header[1], header[2], header[3]])
+    {
+        // Extends to the end of file
+        0 => (8, remaining),
+        1 => {
+            let mut large = [0u8; 8];
+            reader.read_exact(&mut large)?;
+            (16, u64::from_be_bytes(large))
+        }
+        size => (8, size as u64),
+    };
+    if size < header_len || size > remaining {
+        return Err(invalid_data("Invalid box size"));
+    }
+    Ok((box_type, header_len, size))
+}
+
+/// Iterate the child boxes in memory, yield the type and the payload
+fn boxes(data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let header = data.get(pos..pos + 8)?;
+        let box_type = [header[4], header[5], header[6], header[7]];
+        let (header_len, size) =
+            match u32::from_be_bytes([header[0], header[1], header[2], header[3]]) {
+                0 => (8, data.len() - pos),
+                1 => (16, read_u64_be(data, pos + 8)? as usize),
+                size => (8, size as usize),
+            };
+        let payload = data.get(pos + header_len..pos.checked_add(size)?)?;
+        pos += size.max(header_len);
+        Some((box_type, payload))
+    })
+}
+
+fn parse_moov(moov: &[u8]) -> (VideoMetadata, String) {
+    let mut video = VideoMetadata::default();
+    let mut title = String::new();
+    for (box_type, payload) in boxes(moov) {
+        match &box_type {
+            b"mvhd" => {
+                // version 1 uses 64-bit times and duration
+                let (timescale, duration) = match payload.first() {
+                    Some(1) => (read_u32_be(payload, 20), read_u64_be(payload, 24)),
+                    _ => (
+                        read_u32_be(payload, 12),
+                        read_u32_be(payload, 16).map(u64::from),
+                    ),
+                };
+                // All bits set if the duration is unknown
+                let timescale = timescale.filter(|timescale| *timescale > 0);
+                let duration = duration.filter(|d| *d != u64::MAX && *d != u32::MAX as u64);
+                if let (Some(timescale), Some(duration)) = (timescale, duration) {
+                    video.duration = Some(duration as f64 / timescale as f64);
+                }
+            }
+            b"trak" => {
+                let Some((_, tkhd)) = boxes(payload).find(|(box_type, _)| box_type == b"tkhd")
+                else {
+                    continue;
+                };
+                // Width and height are 16.16 fixed-point at the end of tkhd, zero for audio tracks
+                let offset = match tkhd.first() {
+                    Some(1) => 88,
+                    _ => 76,
+                };
+                let width = read_u32_be(tkhd, offset)
+                    .map(|width| width >> 16)
+                    .filter(|width| *width > 0);
+                let height = read_u32_be(tkhd, offset + 4)
+                    .map(|height| height >> 16)
+                    .filter(|height| *height > 0);
+                if let (None, Some(width), Some(height)) = (video.width, width, height) {
+                    video.width = Some(width);
+                    video.height = Some(height);
+                }
+            }
+            b"udta" => {
+                if let Some(value) = find_mp4_title(payload) {
+                    title = value;
+                }
+            }
+            _ => (),
+        }
+    }
+    (video, title)
+}
+
+/// `udta/©nam`(QuickTime) or `udta/meta/ilst/©nam/data`(iTunes style)
+fn find_mp4_title(udta: &[u8]) -> Option<String> {
+    const NAME: [u8; 4] = [0xA9, b'n', b'a', b'm'];
+    for (box_type, payload) in boxes(udta) {
+        if box_type == NAME {
+            // 16-bit text length and language code before the text
+            let len = u16::from_be_bytes([*payload.first()?, *payload.get(1)?]) as usize;
+            return payload.get(4..4 + len).map(decode_utf8);
+        }
+        if box_type == *b"meta" {
+            // meta is a full box in mp4 but a plain box in QuickTime
+            let children = match payload.get(4..8) {
+                Some(b"hdlr") => payload,
+                _ => payload.get(4..)?,
+            };
+            let (_, ilst) = boxes(children).find(|(box_type, _)| box_type == b"ilst")?;
+            let (_, item) = boxes(ilst).find(|(box_type, _)| *box_type == NAME)?;
+            let (_, data) = boxes(item).find(|(box_type, _)| box_type == b"data")?;
+            // Type indicator and locale before the value
+            return data.get(8..).map(decode_utf8);
+        }
+    }
+    None
+}
+
+/// Matroska/WebM, the metadata is in the Info and Tracks elements in front of the clusters
+fn read_mkv<R: Read + Seek>(reader: &mut R) -> io::Result<(VideoMetadata, String)> {
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut video = VideoMetadata::default();
+    let mut title = String::new();
+    // Elements from the start of the segment
+    let mut segment_end = end;
+    let mut pos = 0;
+    while pos < segment_end {
+        let (id, size) = read_element_header(reader)?;
+        let data_pos = reader.stream_position()?;
+        // A truncated or corrupt header may run past the end of the segment
+        let remaining = segment_end
+            .checked_sub(data_pos)
+            .ok_or_else(|| invalid_data("Element header beyond the end of the segment"))?;
+        let size = size.unwrap_or(remaining).min(remaining);
+        match id {
+            MKV_SEGMENT => {
+                // Descend into the segment
+                segment_end = data_pos + size;
+                pos = data_pos;
+                continue;
+            }
+            MKV_INFO => {
+                let info = read_exact_vec(reader, size)?;
+                let mut scale = 1_000_000u64;
+                let mut duration = None;
+                for (id, value) in elements(&info) {
+                    match id {
+                        MKV_TIMESTAMP_SCALE => scale = read_uint(value),
+                        MKV_DURATION => duration = read_float(value),
+                        MKV_TITLE => title = decode_utf8(value),
+                        _ => (),
+                    }
+                }
+                // Duration is in units of the timestamp scale(nanoseconds)
+                video.duration = duration.map(|duration| duration * scale as f64 / 1e9);
+            }
+            MKV_TRACKS => {
+                let tracks = read_exact_vec(reader, size)?;
+                for (id, entry) in elements(&tracks) {
+                    if id != MKV_TRACK_ENTRY || video.width.is_some() {
+                        continue;
+                    }
+                    let Some((_, settings)) = elements(entry).find(|(id, _)| *id == MKV_VIDEO)
+                    else {
+                        continue;
+                    };
+                    for (id, value) in elements(settings) {
+                        match id {
+                            MKV_PIXEL_WIDTH => video.width = Some(read_uint(value) as u32),
+                            MKV_PIXEL_HEIGHT => video.height = Some(read_uint(value) as u32),
+                            _ => (),
+                        }
+                    }
+                }
+            }
+            // The headers are written before the media data
+            MKV_CLUSTER => break,
+            _ => (),
+        }
+        pos = data_pos + size;
+        reader.seek(SeekFrom::Start(pos))?;
+    }
+    Ok((video, title))
+}
+
+/// Return the element id(marker kept) and the data size, None if the size is unknown
+fn read_element_header<R: Read>(reader: &mut R) -> io::Result<(u32, Option<u64>)> {
+    let (id, id_len) = read_vint(reader, 4)?;
+    // Restore the length marker, ids are written with it
+    let id = (id | (1 << (7 * id_len))) as u32;
+    let (size, size_len) = read_vint(reader, 8)?;
+    let unknown = size == (1 << (7 * size_len)) - 1;
+    Ok((id, (!unknown).then_some(size)))
+}
+
+/// Read an EBML variable length integer, return the value without the marker and the length
+fn read_vint<R: Read>(reader: &mut R, max_len: u32) -> io::Result<(u64, u32)> {
+    let mut first = [0u8; 1];
+    reader.read_exact(&mut first)?;
+    let len = first[0].leading_zeros() + 1;
+    if len > max_len {
+        return Err(invalid_data("Invalid EBML variable length integer"));
+    }
+    let mut value = (first[0] as u64) & ((1 << (8 - len)) - 1);
+    for _ in 1..len {
+        reader.read_exact(&mut first)?;
+        value = (value << 8) | first[0] as u64;
+    }
+    Ok((value, len))
+}
+
+/// Iterate the child elements in memory, yield the id and the data
+fn elements(data: &[u8]) -> impl Iterator<Item = (u32, &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let mut cursor = data.get(pos..)?;
+        let start_len = cursor.len();
+        let (id, size) = read_element_header(&mut cursor).ok()?;
+        let data_pos = pos + start_len - cursor.len();
+        let size = size.map_or(data.len() - data_pos, |size| size as usize);
+        let value = data.get(data_pos..data_pos.checked_add(size)?)?;
+        pos = data_pos + size;
+        Some((id, value))
+    })
+}
+
+fn read_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .take(8)
+        .fold(0, |value, byte| (value << 8) | *byte as u64)
+}
+
+fn read_float(data: &[u8]) -> Option<f64> {
+    match data.len() {
+        4 => Some(f32::from_be_bytes(data.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(data.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+/// AVI(RIFF), the main header is in `LIST hdrl/avih` and the title in `LIST INFO/INAM`
+fn read_avi<R: Read + Seek>(reader: &mut R) -> io::Result<(VideoMetadata, String)> {
+    let end = reader.seek(SeekFrom::End(0))?;
+    // Skip "RIFF", size and "AVI "
+    let mut pos = 12;
+    let mut video = VideoMetadata::default();
+    let mut title = String::new();
+    while pos + 8 <= end {
+        reader.seek(SeekFrom::Start(pos))?;
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header[..8])?;
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+        if &header[..4] == b"LIST" && size >= 4 {
+            reader.read_exact(&mut header[8..12])?;
+            match &header[8..12] {
+                b"hdrl" => {
+                    let hdrl = read_exact_vec(reader, size - 4)?;
+                    if let Some(avih) =
+                        riff_chunks(&hdrl).find_map(|(id, data)| (id == *b"avih").then_some(data))
+                    {
+                        let us_per_frame = read_u32_le(avih, 0).unwrap_or(0);
+                        let total_frames = read_u32_le(avih, 16).unwrap_or(0);
+                        if us_per_frame > 0 && total_frames > 0 {
+                            video.duration = Some(total_frames as f64 * us_per_frame as f64 / 1e6);
+                        }
+                        video.width = read_u32_le(avih, 32).filter(|width| *width > 0);
+                        video.height = read_u32_le(avih, 36).filter(|height| *height > 0);
+                    }
+                }
+                b"INFO" => {
+                    let info = read_exact_vec(reader, size - 4)?;
+                    if let Some(name) =
+                        riff_chunks(&info).find_map(|(id, data)| (id == *b"INAM").then_some(data))
+                    {
+                        title = decode_utf8(name);
+                    }
+                }
+                _ => (),
+            }
+        }
+        // Chunks are word aligned
+        pos += 8 + size + (size & 1);
+    }
+    Ok((video, title))
+}
+
+fn riff_chunks(data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let id: [u8; 4] = data.get(pos..pos + 4)?.try_into().ok()?;
+        let size = read_u32_le(data, pos + 4)? as usize;
+        let chunk = data.get(pos + 8..(pos + 8).checked_add(size)?)?;
+        pos += 8 + size + (size & 1);
+        Some((id, chunk))
+    })
+}
+
+fn read_exact_vec<R: Read>(reader: &mut R, size: u64) -> io::Result<Vec<u8>> {
+    if size > MAX_HEADER_SIZE {
+        return Err(invalid_data("Header is too large"));
+    }
+    let mut data = vec![0u8; size as usize];
+    reader.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// Null terminated or padded strings are common in the headers
+fn decode_utf8(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .trim_end_matches('\0')
+        .trim()
+        .to_string()
+}
+
+fn read_u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64_be(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn invalid_data(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn mp4_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut data = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(box_type);
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn read_mp4_moov() {
+        let mut mvhd = vec![0u8; 100];
+        mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&90_500u32.to_be_bytes());
+        let mut tkhd = vec![0u8; 84];
+        tkhd[76..80].copy_from_slice(&(1920u32 << 16).to_be_bytes());
+        tkhd[80..84].copy_from_slice(&(1080u32 << 16).to_be_bytes());
+        let mut name = vec![0, 7, 0x55, 0xC4];
+        name.extend_from_slice(b"Holiday");
+        let moov = [
+            mp4_box(b"mvhd", &mvhd),
+            mp4_box(b"trak", &mp4_box(b"tkhd", &tkhd)),
+            mp4_box(b"udta", &mp4_box(&[0xA9, b'n', b'a', b'm'], &name)),
+        ]
+        .concat();
+        let file = [
+            mp4_box(b"ftyp", b"isom"),
+            mp4_box(b"mdat", &[0u8; 32]),
+            mp4_box(b"moov", &moov),
+        ]
+        .concat();
+
+        let (video, title) = read_mp4(&mut Cursor::new(file)).unwrap();
+        assert_eq!(video.duration, Some(90.5));
+        assert_eq!((video.width, video.height), (Some(1920), Some(1080)));
+        assert_eq!(title, "Holiday");
+    }
+
+    #[test]
+    fn read_mkv_headers() {
+        fn element(id: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut element = id.to_vec();
+            element.push(0x80 | data.len() as u8);
+            element.extend_from_slice(data);
+            element
+        }
+        let info = [
+            element(&[0x2A, 0xD7, 0xB1], &1_000_000u32.to_be_bytes()),
+            element(&[0x44, 0x89], &125_000f64.to_be_bytes()),
+            element(&[0x7B, 0xA9], b"Lecture 03"),
+        ]
+        .concat();
+        let video = [
+            element(&[0xB0], &[0x05, 0x00]),
+            element(&[0xBA], &[0x02, 0xD0]),
+        ]
+        .concat();
+        let tracks = element(&[0xAE], &element(&[0xE0], &video));
+        let segment = [
+            element(&[0x15, 0x49, 0xA9, 0x66], &info),
+            element(&[0x16, 0x54, 0xAE, 0x6B], &tracks),
+        ]
+        .concat();
+        // Segment of unknown size followed by a cluster
+        let mut file = element(
+            &[0x1A, 0x45, 0xDF, 0xA3],
+            &[0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'],
+        );
+        file.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0xFF]);
+        file.extend_from_slice(&segment);
+        file.extend_from_slice(&element(&[0x1F, 0x43, 0xB6, 0x75], &[0u8; 16]));
+
+        let (video, title) = read_mkv(&mut Cursor::new(file)).unwrap();
+        assert_eq!(video.duration, Some(125.0));
+        assert_eq!((video.width, video.height), (Some(1280), Some(720)));
+        assert_eq!(title, "Lecture 03");
+
+        // The segment ends in the middle of the header of its first element
+        let mut file = vec![0x18, 0x53, 0x80, 0x67, 0x82];
+        file.extend_from_slice(&element(&[0x15, 0x49, 0xA9, 0x66], &info));
+        let error = read_mkv(&mut Cursor::new(file)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
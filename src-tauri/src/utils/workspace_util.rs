@@ -0,0 +1,297 @@
+use crate::db_initializer;
+use crate::embedding_service::EmbeddingService;
+use crate::embedding_service_manager;
+use crate::entities::Config;
+use crate::errors::AppError;
+use crate::fs_watcher::watcher;
+use crate::global::{
+    ACTIVE_WORKSPACE, CONFIG_NAME_ACTIVE_LOCALE, CONFIG_NAME_CLIENT_ID,
+    CONFIG_NAME_INDEXER_SETTING, CONFIG_NAME_PROXY, DB_FILE_NAME, DEFAULT_WORKSPACE, INDEXING,
+    SCANNING, WORKSPACE_DB_PATH, WORKSPACE_DIR_NAME, WORKSPACE_NAME_MAX_CHARS,
+    WORKSPACE_RECORD_NAME,
+};
+use crate::initializer;
+use crate::repositories::{backup_repo, config_repo};
+use crate::searcher::path_search_engine;
+use crate::structs::indexer_setting::IndexerSetting;
+use crate::structs::workspace::Workspace;
+use crate::utils::app_util::{get_db_path, get_storage_path, set_db_path};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+// Settings of the machine rather than of an index, a new workspace starts with the ones of the active workspace
+const MACHINE_CONFIG_NAMES: [&str; 3] = [
+    CONFIG_NAME_CLIENT_ID,
+    CONFIG_NAME_PROXY,
+    CONFIG_NAME_ACTIVE_LOCALE,
+];
+
+/// The default workspace followed by the others by name
+pub async fn list() -> Result<Vec<Workspace>, AppError> {
+    let active = ACTIVE_WORKSPACE.read().await.clone();
+    let mut names = vec![DEFAULT_WORKSPACE.to_string()];
+    let mut others = list_names()?;
+    others.sort();
+    names.extend(others);
+    Ok(names
+        .into_iter()
+        .map(|name| Workspace {
+            is_active: name == active,
+            is_initialized: Path::new(&db_path(&name)).exists(),
+            name,
+        })
+        .collect())
+}
+
+/// The database of a workspace, not created until the workspace is switched to
+pub fn db_path(name: &str) -> String {
+    let storage_path = PathBuf::from(get_storage_path());
+    let path = if name == DEFAULT_WORKSPACE {
+        storage_path.join(DB_FILE_NAME)
+    } else {
+        storage_path
+            .join(WORKSPACE_DIR_NAME)
+            .join(name)
+            .join(DB_FILE_NAME)
+    };
+    path.to_string_lossy().into_owned()
+}
+
+pub fn exists(name: &str) -> bool {
+    // A checked name never leads out of the workspace directory
+    name == DEFAULT_WORKSPACE
+        || (check_name(name).is_ok_and(|checked| checked == name) && workspace_dir(name).is_dir())
+}
+
+pub fn create(name: &str) -> Result<Workspace, AppError> {
+    let name = check_name(name)?;
+    // Names differing by case are the same directory on Windows and macOS
+    if name.eq_ignore_ascii_case(DEFAULT_WORKSPACE)
+        || list_names()?
+            .iter()
+            .any(|other| other.to_lowercase() == name.to_lowercase())
+    {
+        return Err(AppError::InvalidParameter(format!(
+            "Workspace already exists: {}",
+            name
+        )));
+    }
+    fs::create_dir_all(workspace_dir(&name))?;
+    Ok(Workspace {
+        name,
+        is_active: false,
+        is_initialized: false,
+    })
+}
+
+/// Delete a workspace with its database, the default and the active workspace can not be deleted
+pub async fn delete(name: &str) -> Result<(), AppError> {
+    if name == DEFAULT_WORKSPACE || name == *ACTIVE_WORKSPACE.read().await {
+        return Err(AppError::OperationFailed(format!(
+            "Workspace in use can not be deleted: {}",
+            name
+        )));
+    }
+    if !exists(name) {
+        return Err(AppError::WorkspaceNotFound(name.to_string()));
+    }
+    fs::remove_dir_all(workspace_dir(name))?;
+    Ok(())
+}
+
+/// Make the workspace the active one: its database, settings, watched paths, paths cache
+/// and embedding model replace the ones of the current workspace
+pub async fn switch(name: &str) -> Result<(), AppError> {
+    if name == *ACTIVE_WORKSPACE.read().await {
+        return Ok(());
+    }
+    if SCANNING.load(Ordering::SeqCst) || INDEXING.load(Ordering::SeqCst) {
+        return Err(AppError::OperationFailed(
+            "Indexing is in progress".to_string(),
+        ));
+    }
+    if !exists(name) {
+        return Err(AppError::WorkspaceNotFound(name.to_string()));
+    }
+    let new_db_path = db_path(name);
+    let is_new = !Path::new(&new_db_path).exists();
+    let mut machine_configs = Vec::new();
+    let mut api_keys = Vec::new();
+    if is_new {
+        for config_name in MACHINE_CONFIG_NAMES {
+            if let Some(config) = config_repo::get_one(config_name)? {
+                machine_configs.push(config);
+            }
+        }
+        api_keys = backup_repo::list_api_keys()?;
+    }
+
+    // The database is ready before anything is switched, a failure leaves the current workspace active
+    let prepared = WORKSPACE_DB_PATH.sync_scope(new_db_path.clone(), || {
+        prepare_db(is_new, machine_configs, &api_keys)
+    });
+    if let Err(e) = prepared {
+        if is_new {
+            fs::remove_file(&new_db_path).ok();
+        }
+        return Err(e);
+    }
+
+    // The events of the current workspace are not processed into the new database
+    watcher::pause()
+        .await
+        .unwrap_or_else(|e| println!("Failed to unwatch the paths: {}", e));
+    set_db_path(&new_db_path);
+    *ACTIVE_WORKSPACE.write().await = name.to_string();
+    write_active_record(name);
+    initializer::process().await;
+    embedding_service_manager::get_manager()
+        .write()
+        .await
+        .clear();
+    path_search_engine::build_index().await;
+    watcher::resume()
+        .await
+        .unwrap_or_else(|e| println!("Failed to watch the paths: {}", e));
+    println!("Switched to workspace {}: {}", name, get_db_path());
+    Ok(())
+}
+
+/// Run a lookup in the database of a workspace, e.g. the one a search result came from.
+/// The active workspace is used if none is given
+pub async fn with_db<T>(
+    workspace: Option<&str>,
+    lookup: impl FnOnce() -> T,
+) -> Result<T, AppError> {
+    let workspace = match workspace {
+        Some(workspace) if !workspace.is_empty() && workspace != *ACTIVE_WORKSPACE.read().await => {
+            workspace
+        }
+        _ => return Ok(lookup()),
+    };
+    let db_path = db_path(workspace);
+    if !exists(workspace) || !Path::new(&db_path).exists() {
+        return Err(AppError::WorkspaceNotFound(workspace.to_string()));
+    }
+    Ok(WORKSPACE_DB_PATH.sync_scope(db_path, lookup))
+}
+
+/// Create or upgrade the database of the `WORKSPACE_DB_PATH` scope,
+/// a new one gets the machine settings and API keys of the current workspace
+fn prepare_db(
+    is_new: bool,
+    machine_configs: Vec<Config>,
+    api_keys: &[(String, String)],
+) -> Result<(), AppError> {
+    db_initializer::init().map_err(|e| AppError::InternalError(e.to_string()))?;
+    if is_new {
+        for config in machine_configs {
+            config_repo::insert_or_ignore(&config.name, &config.value)?;
+            config_repo::update_by_name(&config.name, &config.value)?;
+        }
+        backup_repo::restore_api_keys(api_keys)?;
+    }
+    Ok(())
+}
+
+/// The embedding model of the workspace of the current database
+pub fn embedding_model() -> String {
+    let setting: IndexerSetting = config_repo::get_one(CONFIG_NAME_INDEXER_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|config| serde_json::from_str(&config.value).ok())
+        .unwrap_or_default();
+    EmbeddingService::model_name_of(&setting)
+}
+
+/// The workspace active when the app was closed, the default one if it has been deleted since
+pub fn read_active_record() -> String {
+    let record_path = Path::new(&get_storage_path()).join(WORKSPACE_RECORD_NAME);
+    let name = fs::read_to_string(record_path)
+        .map(|content| content.trim().to_string())
+        .unwrap_or_default();
+    if name.is_empty() || !exists(&name) {
+        return DEFAULT_WORKSPACE.to_string();
+    }
+    name
+}
+
+/// The files of the workspaces other than the default database, with their path relative to the storage directory
+pub fn list_files(storage_path: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let mut relative_paths = vec![PathBuf::from(WORKSPACE_RECORD_NAME)];
+    if let Ok(entries) = fs::read_dir(storage_path.join(WORKSPACE_DIR_NAME)) {
+        for entry in entries.flatten() {
+            relative_paths.push(
+                PathBuf::from(WORKSPACE_DIR_NAME)
+                    .join(entry.file_name())
+                    .join(DB_FILE_NAME),
+            );
+        }
+    }
+    relative_paths
+        .into_iter()
+        .map(|relative_path| (storage_path.join(&relative_path), relative_path))
+        .filter(|(path, _)| path.is_file())
+        .collect()
+}
+
+fn write_active_record(name: &str) {
+    let record_path = Path::new(&get_storage_path()).join(WORKSPACE_RECORD_NAME);
+    if let Err(e) = fs::write(&record_path, name) {
+        println!("Failed to write the workspace record: {}", e);
+    }
+}
+
+fn list_names() -> Result<Vec<String>, AppError> {
+    let dir = Path::new(&get_storage_path()).join(WORKSPACE_DIR_NAME);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(names)
+}
+
+fn workspace_dir(name: &str) -> PathBuf {
+    Path::new(&get_storage_path())
+        .join(WORKSPACE_DIR_NAME)
+        .join(name)
+}
+
+/// A name is also a directory name: letters, digits, spaces, `-` and `_` only
+fn check_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty()
+        || name.chars().count() > WORKSPACE_NAME_MAX_CHARS
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+    {
+        return Err(AppError::InvalidParameter(format!(
+            "Invalid workspace name: {}",
+            name
+        )));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_name() {
+        assert_eq!(check_name(" 工作 notes_2 ").unwrap(), "工作 notes_2");
+        assert!(check_name("   ").is_err());
+        assert!(check_name("../evil").is_err());
+        assert!(check_name("a/b").is_err());
+        assert!(check_name(&"x".repeat(WORKSPACE_NAME_MAX_CHARS + 1)).is_err());
+        assert!(!exists(".."));
+    }
+}
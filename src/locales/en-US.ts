@@ -147,6 +147,12 @@ export default {
     host: 'Host',
     port: 'Port',
     setting: 'Proxy Setting',
+    username: 'Username',
+    password: 'Password',
+    noProxy: 'No Proxy Hosts',
+    envProxyTip: 'Leave the host empty to use the proxy of the environment variables (HTTPS_PROXY, ALL_PROXY)',
+    test: 'Test Connection',
+    testSuccess: 'Connected in {ms} ms',
   },
   model: {
     platform: 'Model Platform',
@@ -237,6 +243,10 @@ export default {
     saveImageParsedContent: 'Save Image Recognition Text',
     saveAudioParsedContent: 'Save Audio Recognition Text',
     saveVideoParsedContent: 'Save Video Recognition Text',
+    notebookIncludeOutputs: 'Index Outputs of Notebook Code Cells',
+    videoDescribeByTitle: 'Describe Videos without Subtitles from the Title',
+    ollamaEmbeddingModel: 'Ollama Embedding Model (384 dimensions)',
+    ollamaEmbeddingModelPlaceholder: 'Local model if empty, e.g. all-minilm',
     recognitionText: 'Recognition Text',
     parsedContent: 'Parsed Content',
     clearIndexConfirmation: 'Clear Index (not the files on disk)?',
@@ -148,6 +148,12 @@ export default {
     host: '地址',
     port: '端口',
     setting: '代理设置',
+    username: '用户名',
+    password: '密码',
+    noProxy: '不使用代理的地址',
+    envProxyTip: '地址留空则使用环境变量中的代理（HTTPS_PROXY、ALL_PROXY）',
+    test: '测试连接',
+    testSuccess: '连接成功，耗时 {ms} 毫秒',
   },
   model: {
     platform: '模型平台',
@@ -229,6 +235,10 @@ export default {
     saveImageParsedContent: '保存图片识别的内容',
     saveAudioParsedContent: '保存音频转录的文本',
     saveVideoParsedContent: '保存视频的解析内容',
+    notebookIncludeOutputs: '索引 Notebook 代码单元的输出',
+    videoDescribeByTitle: '根据标题描述无字幕的视频',
+    ollamaEmbeddingModel: 'Ollama 嵌入模型（384 维）',
+    ollamaEmbeddingModelPlaceholder: '为空时使用本地模型，例如 all-minilm',
     recognitionText: '已识别文本',
     parsedContent: '已解析内容',
     clearIndexConfirmation: '确认清除所有索引（不会删除磁盘上的文件）？',
@@ -22,20 +22,33 @@ interface ModelPlatform {
   api_key: string;
   remark: string;
   is_proxy_enable: boolean;
+  is_openai_api_compatible: boolean; // true: added by the user
+  is_enable: boolean;
+  max_concurrency: number; // requests in flight at the same time
+  requests_per_minute: number; // 0: unlimited
 }
 
 interface AiModel {
+  id: number;
   name: string;
   title: string;
   platform: string;
-  model_types: string; // text;image;vision;audio;video
+  model_types: string; // text,image,vision,embedding,rerank,asr,tts
+  input_types: string; // text,image,audio,video
+  context_window: number;
+  max_input_tokens: number;
+  max_output_tokens: number;
   remark: string;
+  is_enable: boolean;
 }
 
 interface ProxyInfo {
-  protocal: string;
-  host: string;
+  protocal: string; // http, https, socks4, socks5, socks5h
+  host: string; // empty: the proxy of the environment variables
   port: number;
+  username: string;
+  password: string; // masked once saved
+  no_proxy: string[];
 }
 
 interface SaveParsedContent {
@@ -52,6 +65,65 @@ interface IndexerSetting {
   ignore_exts: string[];
   ignore_files: string[];
   save_parsed_content: SaveParsedContent;
+  budget: UsageBudget;
+  notebook_include_outputs: boolean;
+  video_describe_by_title: boolean;
+  ollama_embedding_model: string; // Empty: the local embedding model
+}
+
+interface UsageBudget {
+  limit: number; // 0: unlimited
+  period: 'daily' | 'monthly';
+}
+
+interface UsageReportItem {
+  platform: string;
+  model: string;
+  calls: number;
+  prompt_tokens: number;
+  completion_tokens: number;
+  audio_seconds: number;
+  image_count: number;
+  cost: number;
+}
+
+interface UsageReport {
+  items: UsageReportItem[];
+  total_cost: number;
+  budget: UsageBudget;
+  budget_used: number;
+  is_budget_exceeded: boolean;
+}
+
+interface LocalModelFile {
+  path: string; // relative to the model directory
+  url: string;
+  size: number; // 0: unknown
+  sha256: string;
+}
+
+interface LocalModel {
+  id: string;
+  name: string;
+  kind: 'embedding' | 'reranker' | 'ocr' | 'asr';
+  description: string;
+  files: LocalModelFile[];
+  is_installed: boolean;
+}
+
+interface IndexBundleManifest {
+  format_version: number;
+  app_version: string;
+  db_version: number;
+  embedding_model: string;
+  root_paths: string[];
+  file_count: number;
+  create_time: string;
+}
+
+interface PathMapping {
+  from: string;
+  to: string;
 }
 
 interface DownloadState {
@@ -89,6 +161,7 @@ interface FileMetadata {
   created: string;
   modified: string;
   author: string;
+  tags?: string[];
 }
 
 interface FileInfo {
@@ -136,4 +209,46 @@ interface SearchResult {
   file_info: FileInfo,
   matched_keywords: string[],
   matched_chunk_ids: number[],
-}
\ No newline at end of file
+  workspace: string, // set by the search across workspaces
+}
+
+interface Workspace {
+  name: string;
+  is_active: boolean;
+  is_initialized: boolean;
+}
+
+interface Tag {
+  id: number;
+  name: string;
+  file_count: number;
+  create_time: string;
+}
+
+type SearchMode = 'intent' | 'path' | 'semantic';
+
+interface SearchHistory {
+  id: number;
+  query: string;
+  mode: SearchMode;
+  result_count: number;
+  create_time: string;
+}
+
+interface SearchHistorySetting {
+  enabled: boolean;
+  max_days: number; // 0: no limit by age
+  max_entries: number; // 0: unlimited
+}
+
+interface SavedSearch {
+  id: number; // 0: not saved yet
+  name: string;
+  query: string;
+  filters: string; // Inline filters, e.g. `type:image tag:trip`
+  mode: SearchMode;
+  is_smart_folder: boolean;
+  result_count: number;
+  refresh_time: string;
+  create_time: string;
+}
@@ -7,6 +7,10 @@ export function emptyModelPlatform() {
     api_key: '',
     remark: '',
     is_proxy_enable: false,
+    is_openai_api_compatible: false,
+    is_enable: true,
+    max_concurrency: 2,
+    requests_per_minute: 0,
   }
 }
 
@@ -15,6 +19,9 @@ export function emptyProxyInfo() {
     protocal: 'http',
     host: '',
     port: 0,
+    username: '',
+    password: '',
+    no_proxy: ['localhost', '127.0.0.1', '::1'],
   }
 }
 
@@ -31,6 +38,13 @@ export function emptyIndexerSetting() {
       video: true,
       audio: true,
     },
+    budget: {
+      limit: 0,
+      period: 'monthly' as UsageBudget['period'],
+    },
+    notebook_include_outputs: false,
+    video_describe_by_title: false,
+    ollama_embedding_model: '',
   }
 }
 